@@ -0,0 +1,336 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::next::NextCell;
+use super::options::BasicOptions;
+use super::{BasicLeaf, FlagAlign, LeafExt};
+#[cfg(feature = "rayon")]
+use crate::SkipList;
+use crate::options::{LeafSize, Monoid, TypedOptions};
+use crate::{
+    Interval, LeafNext, LeafRef, SharedLeafRef, Summarize, SyncLeafRef, This,
+};
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+
+/// Stores data of type `T`. <code>[Arc]\<[ArcLeaf]\<T>></code> implements
+/// [`LeafRef`] and can be used with [`SkipList`](crate::SkipList).
+///
+/// Unlike <code>[Rc]\<[RcLeaf]></code>, <code>[Arc]\<[ArcLeaf]\<T>></code>
+/// implements [`SyncLeafRef`] when `T` is [`Send`], so a list of these leaves
+/// can be wrapped in a [`SyncSkipList`] and guarded by a mutex. The link to
+/// the next item is stored in a [`Cell`], so <code>[Arc]\<[ArcLeaf]></code>
+/// is still neither [`Send`] nor [`Sync`]: leaves must be accessed only
+/// through the list (or while holding the lock that guards it), and the
+/// atomic reference count allows leaves to be dropped on any thread. When
+/// `T` is also [`Sync`], it implements [`SharedLeafRef`], so a
+/// [frozen](crate::SkipList::freeze) list can be read from multiple threads.
+///
+/// [Rc]: alloc::rc::Rc
+/// [RcLeaf]: super::RcLeaf
+/// [`SyncSkipList`]: crate::SyncSkipList
+/// [`Cell`]: core::cell::Cell
+///
+/// This type is `#[repr(C)]`, and the data of type `T` is stored at the start
+/// of it. Its alignment is at least the alignment of `Align`, which can be
+/// used to give each leaf extra alignment (e.g., to a cache line); see
+/// [`Self::with_align`]. Note that the list's internal nodes will also have
+/// this alignment.
+#[repr(C, align(2))]
+pub struct ArcLeaf<T, Align = ()> {
+    _align: [Align; 0],
+    data: T,
+    next: NextCell<Self>,
+}
+
+impl<T> ArcLeaf<T> {
+    /// Creates a new [`ArcLeaf<T>`].
+    pub fn new(data: T) -> Self {
+        Self::with_align(data)
+    }
+}
+
+impl<T, Align> ArcLeaf<T, Align> {
+    /// Creates a new [`ArcLeaf<T, Align>`](ArcLeaf), which is aligned to at
+    /// least the alignment of `Align`.
+    pub fn with_align(data: T) -> Self {
+        Self {
+            _align: [],
+            data,
+            next: NextCell::default(),
+        }
+    }
+
+    /// Takes ownership of the inner value of type `T`.
+    pub fn into_inner(this: Self) -> T {
+        this.data
+    }
+}
+
+impl<T: PartialEq, Align> PartialEq for ArcLeaf<T, Align> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.data, &self.next) == (&other.data, &other.next)
+    }
+}
+
+impl<T: Eq, Align> Eq for ArcLeaf<T, Align> {}
+
+impl<T: PartialOrd, Align> PartialOrd for ArcLeaf<T, Align> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (&self.data, &self.next).partial_cmp(&(&other.data, &other.next))
+    }
+}
+
+impl<T: Ord, Align> Ord for ArcLeaf<T, Align> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.data, &self.next).cmp(&(&other.data, &other.next))
+    }
+}
+
+impl<T> From<T> for ArcLeaf<T> {
+    fn from(data: T) -> Self {
+        Self::new(data)
+    }
+}
+
+impl<T, Align> Deref for ArcLeaf<T, Align> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T, Align> DerefMut for ArcLeaf<T, Align> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+/// Hashes only the inner value of type `T`.
+impl<T: Hash, Align> Hash for ArcLeaf<T, Align> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<T: Interval, Align> Interval for Arc<ArcLeaf<T, Align>> {
+    type Point = T::Point;
+
+    fn start(&self) -> T::Point {
+        self.data.start()
+    }
+
+    fn end(&self) -> T::Point {
+        self.data.end()
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<M, T, Align> Summarize<M> for Arc<ArcLeaf<T, Align>>
+where
+    M: Monoid,
+    T: Summarize<M>,
+{
+    fn summarize(&self) -> M::Value {
+        self.data.summarize()
+    }
+}
+
+impl<T: fmt::Debug, Align> fmt::Debug for ArcLeaf<T, Align> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ArcLeaf")
+            .field("addr", &(self as *const _))
+            .field("data", &self.data)
+            .field("next", &self.next.get())
+            .finish()
+    }
+}
+
+impl<T, Align: FlagAlign> LeafExt for ArcLeaf<T, Align> {
+    fn user_flag(&self) -> bool {
+        self.next.flag()
+    }
+
+    fn set_user_flag(&self, flag: bool) {
+        self.next.set_flag(flag);
+    }
+}
+
+// SAFETY:
+// * `Arc<ArcLeaf>` is not `Send` or `Sync`, as `ArcLeaf` contains a `Cell`
+//   and is therefore not `Sync`.
+// * `Self::next` will initially return `None` because `ArcLeaf::next` is
+//   initialized as `None`.
+// * `Self::set_next` stores its argument in `ArcLeaf::next` and is the only
+//   function that modifies that field. `Self::next` retrieves the value
+//   appropriately.
+// * Clones of `Arc` behave like the original pointer.
+unsafe impl<T: BasicLeaf, Align> LeafRef for Arc<ArcLeaf<T, Align>> {
+    type Options = TypedOptions<
+        <T::Options as BasicOptions>::SizeType,
+        <T::Options as BasicOptions>::StoreKeys,
+        <T::Options as BasicOptions>::Fanout,
+        ArcLeaf<T, Align>, /* Align */
+        <T::Options as BasicOptions>::SlabSize,
+        <T::Options as BasicOptions>::StoreHashes,
+        <T::Options as BasicOptions>::EmptyPolicy,
+        <T::Options as BasicOptions>::StoreMaxEnd,
+        <T::Options as BasicOptions>::Aggregate,
+    >;
+
+    fn next(&self) -> Option<LeafNext<Self>> {
+        let (ptr, tag) = self.next.get()?;
+        Some(match tag {
+            0 => LeafNext::Leaf({
+                // SAFETY: A tag of 0 corresponds to a pointer obtained from
+                // `Arc::into_raw` in `Self::set_next`. That reference is still
+                // owned by `self.next`, so it must not be dropped here.
+                let leaf =
+                    ManuallyDrop::new(unsafe { Arc::from_raw(ptr.as_ptr()) });
+                Arc::clone(&leaf)
+            }),
+            _ => LeafNext::Data(ptr.cast()),
+        })
+    }
+
+    fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
+        let old = this.next.replace(next.map(|n| match n {
+            LeafNext::Leaf(leaf) => (
+                // SAFETY: `Arc::into_raw` always returns non-null pointers.
+                unsafe { NonNull::new_unchecked(Arc::into_raw(leaf) as _) },
+                0,
+            ),
+            LeafNext::Data(data) => (data.cast(), 1),
+        }));
+        if let Some((ptr, 0)) = old {
+            // SAFETY: A tag of 0 corresponds to a pointer obtained from
+            // `Arc::into_raw`, and `self.next` no longer refers to it, so the
+            // reference it owned is released here.
+            drop(unsafe { Arc::from_raw(ptr.as_ptr()) });
+        }
+    }
+
+    fn size(&self) -> LeafSize<Self> {
+        self.data.size()
+    }
+
+    fn chunk_level(&self) -> usize {
+        self.data.chunk_level()
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T, Align> SkipList<Arc<ArcLeaf<T, Align>>>
+where
+    T: BasicLeaf + Send,
+{
+    /// Creates a new skip list containing a leaf for each item produced by
+    /// `items`, in order, building parts of the list in parallel with
+    /// [rayon].
+    ///
+    /// Each part of the input is turned into leaves and arranged into
+    /// internal nodes on its own thread, and the parts are then joined
+    /// together like [`Self::graft_after`].
+    ///
+    /// This method is available only when the crate feature `rayon` is
+    /// enabled.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*) total work, but with *p* threads, roughly Θ(*n* / *p* + *p* log
+    /// *n*) time.
+    pub fn from_par_iter<I>(items: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        // SAFETY: The leaves are created here, so no other clones exist.
+        unsafe {
+            Self::from_par_iter_unchecked(items, |data| {
+                Arc::new(ArcLeaf::with_align(data))
+            })
+        }
+    }
+}
+
+// SAFETY: `Arc` uses an atomic reference count and `T` is `Send`, so clones
+// can be used and dropped on different threads as long as accesses from
+// different threads don't overlap.
+unsafe impl<T: BasicLeaf + Send, Align> SyncLeafRef
+    for Arc<ArcLeaf<T, Align>>
+{
+}
+
+// SAFETY: `Arc` updates its reference count atomically, `next` is only read
+// (not written) when `set_next` isn't called, and `T` is `Send` and `Sync`,
+// so it can be accessed concurrently and dropped on any thread.
+unsafe impl<T: BasicLeaf + Send + Sync, Align> SharedLeafRef
+    for Arc<ArcLeaf<T, Align>>
+{
+}
+
+// SAFETY: Clones of an `Arc` point to the same allocation, and leaves that
+// exist at the same time have different addresses.
+unsafe impl<T: BasicLeaf, Align> crate::LeafId for Arc<ArcLeaf<T, Align>> {
+    fn id(&self) -> usize {
+        Arc::as_ptr(self) as usize
+    }
+}
+
+#[cfg(feature = "debug-graphviz")]
+impl<T, Align> crate::list::debug::LeafDebug for Arc<ArcLeaf<T, Align>>
+where
+    T: BasicLeaf + fmt::Debug,
+{
+    type Id = *const ArcLeaf<T, Align>;
+
+    fn id(&self) -> Self::Id {
+        Arc::as_ptr(self)
+    }
+
+    fn fmt_data(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.data)
+    }
+}
+
+#[cfg(any(doc, doctest))]
+/// <code>[Arc]\<[ArcLeaf]></code> cannot implement [`Send`] or [`Sync`], as
+/// this would make it unsound to implement [`LeafRef`].
+///
+/// ```
+/// use skippy::basic::ArcLeaf;
+/// struct Test<T = std::sync::Arc<ArcLeaf<u8>>>(T);
+/// ```
+///
+/// ```compile_fail
+/// use skippy::basic::ArcLeaf;
+/// struct Test<T: Send = std::sync::Arc<ArcLeaf<u8>>>(T);
+/// ```
+///
+/// ```compile_fail
+/// use skippy::basic::ArcLeaf;
+/// struct Test<T: Sync = std::sync::Arc<ArcLeaf<u8>>>(T);
+/// ```
+mod leaf_is_not_send_sync {}
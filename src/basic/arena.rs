@@ -0,0 +1,167 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! An arena for allocating [`RefLeaf`]s.
+//!
+//! Using <code>&[RefLeaf]</code> as the leaf type of a [`SkipList`] requires
+//! every leaf to be kept alive for as long as the list uses it. A
+//! [`LeafArena`] takes care of this: it allocates leaves in large chunks,
+//! hands out references that live as long as the arena, and frees every leaf
+//! at once when it is dropped.
+
+use super::{BasicLeaf, RefLeaf};
+use crate::SkipList;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp;
+use core::fmt;
+
+const MIN_CHUNK_CAPACITY: usize = 16;
+
+/// A bump-style arena that allocates [`RefLeaf`]s.
+///
+/// Leaves allocated with [`Self::alloc`] are never moved or freed until the
+/// arena is dropped, so the returned references can be used with a
+/// [`SkipList`] that lives no longer than the arena. See also
+/// [`SkipList::with_arena`].
+///
+/// Leaves are stored in chunks whose capacity doubles as the arena grows, so
+/// allocating *n* leaves performs only Θ(log *n*) heap allocations.
+pub struct LeafArena<'a, T, Align = ()> {
+    chunks: RefCell<Chunks<'a, T, Align>>,
+}
+
+struct Chunks<'a, T, Align> {
+    current: Vec<RefLeaf<'a, T, Align>>,
+    full: Vec<Vec<RefLeaf<'a, T, Align>>>,
+}
+
+impl<T> LeafArena<'_, T> {
+    /// Creates a new, empty arena.
+    pub fn new() -> Self {
+        Self::with_align()
+    }
+
+    /// Creates a new arena that can hold `capacity` leaves before it needs to
+    /// allocate more memory.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_align(capacity)
+    }
+}
+
+impl<'a, T, Align> LeafArena<'a, T, Align> {
+    /// Creates a new, empty arena whose leaves are aligned to at least the
+    /// alignment of `Align`; see [`RefLeaf::with_align`].
+    pub fn with_align() -> Self {
+        Self::with_capacity_and_align(0)
+    }
+
+    /// Like [`Self::with_capacity`], but the leaves are aligned to at least
+    /// the alignment of `Align`.
+    pub fn with_capacity_and_align(capacity: usize) -> Self {
+        Self {
+            chunks: RefCell::new(Chunks {
+                current: Vec::with_capacity(capacity),
+                full: Vec::new(),
+            }),
+        }
+    }
+
+    /// Allocates a new leaf containing `data` and returns a reference to it.
+    ///
+    /// The leaf is freed when the arena is dropped.
+    pub fn alloc(&'a self, data: T) -> &'a RefLeaf<'a, T, Align> {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.current.len() == chunks.current.capacity() {
+            let capacity = cmp::max(
+                chunks.current.capacity().saturating_mul(2),
+                MIN_CHUNK_CAPACITY,
+            );
+            let chunk = core::mem::replace(
+                &mut chunks.current,
+                Vec::with_capacity(capacity),
+            );
+            if chunk.capacity() > 0 {
+                chunks.full.push(chunk);
+            }
+        }
+        // Pushing won't reallocate because there is spare capacity.
+        chunks.current.push(RefLeaf::with_align(data));
+        let leaf: *const _ = chunks.current.last().unwrap();
+        // SAFETY: `leaf` points to an element of `chunks.current`, whose
+        // buffer is never reallocated (we push only when there is spare
+        // capacity) and whose elements are never removed. When the chunk is
+        // full, the `Vec` itself is moved into `chunks.full`, which doesn't
+        // move its buffer. The element therefore stays valid until the arena
+        // is dropped, which can't happen while `self` is borrowed for `'a`.
+        unsafe { &*leaf }
+    }
+
+    /// Allocates a leaf for each item in `iter`, returning references to
+    /// them in order.
+    pub fn alloc_from<I>(&'a self, iter: I) -> Vec<&'a RefLeaf<'a, T, Align>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter().map(|data| self.alloc(data)).collect()
+    }
+
+    /// Gets the number of leaves allocated in this arena.
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.full.iter().map(Vec::len).sum::<usize>() + chunks.current.len()
+    }
+
+    /// Returns whether no leaves have been allocated in this arena.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, Align> Default for LeafArena<'_, T, Align> {
+    fn default() -> Self {
+        Self::with_align()
+    }
+}
+
+impl<T, Align> fmt::Debug for LeafArena<'_, T, Align> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeafArena").field("len", &self.len()).finish()
+    }
+}
+
+impl<'a, T, Align> SkipList<&'a RefLeaf<'a, T, Align>>
+where
+    T: BasicLeaf,
+{
+    /// Creates a new skip list containing a leaf allocated in `arena` for
+    /// each item in `iter`, in order.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*m*), where *m* is the number of items in `iter`.
+    pub fn with_arena<I>(arena: &'a LeafArena<'a, T, Align>, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = Self::new();
+        list.push_back_from(iter.into_iter().map(|data| arena.alloc(data)));
+        list
+    }
+}
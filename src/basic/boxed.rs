@@ -0,0 +1,441 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::next::NextCell;
+use super::options::BasicOptions;
+use super::{BasicLeaf, FlagAlign, LeafExt};
+use crate::This;
+use crate::allocator::Allocator;
+use crate::iter::IntoIter;
+use crate::options::{LeafSize, Monoid, TypedOptions};
+use crate::{DropPolicy, Interval, LeafNext, LeafRef, SkipList};
+use crate::{Summarize, SyncLeafRef};
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+#[repr(C, align(2))]
+struct Node<T, Align> {
+    _align: [Align; 0],
+    data: T,
+    next: NextCell<Self>,
+}
+
+/// A pointer to data of type `T` stored in its own heap allocation.
+/// [`BoxLeaf`] implements [`LeafRef`] and can be used with [`SkipList`].
+///
+/// Unlike [`RefLeaf`](super::RefLeaf), the data doesn't need to be kept
+/// alive elsewhere, and unlike [`RcLeaf`](super::RcLeaf), there is no
+/// reference count: a [`BoxLeaf`] is a plain pointer, and the list that
+/// contains it owns the data. Lists of [`BoxLeaf`]s use
+/// [`DropPolicy::Release`], so the data of the items still in a list is
+/// freed when the list is dropped. The data can also be freed for an entire
+/// list with [`SkipList::into_values`], or individually with
+/// [`Self::into_inner`] once the leaf has been removed from its list.
+///
+/// Because the list needs clones of its leaves, [`BoxLeaf`] implements
+/// [`Clone`] (but not [`Copy`]), and a clone could outlive the data it
+/// points to. This is why creating a [`BoxLeaf`] is unsafe; see
+/// [`Self::new`].
+///
+/// [`DropPolicy::Release`]: crate::DropPolicy::Release
+///
+/// Its alignment is at least the alignment of `Align`, which can be used to
+/// give each leaf extra alignment (e.g., to a cache line); see
+/// [`Self::with_align`]. Note that the list's internal nodes will also have
+/// this alignment.
+pub struct BoxLeaf<T, Align = ()> {
+    ptr: NonNull<Node<T, Align>>,
+}
+
+impl<T> BoxLeaf<T> {
+    /// Allocates a new [`BoxLeaf<T>`] containing `data`.
+    ///
+    /// # Safety
+    ///
+    /// Neither the returned leaf nor any clone of it may be used after its
+    /// data is freed. The data is freed when the list that contains the leaf
+    /// is dropped (unless its [`DropPolicy`] has been changed), when the
+    /// leaf is yielded by [`SkipList::into_values`], and when it is passed
+    /// to [`Self::into_inner`].
+    ///
+    /// [`DropPolicy`]: crate::DropPolicy
+    pub unsafe fn new(data: T) -> Self {
+        // SAFETY: Checked by caller.
+        unsafe { Self::with_align(data) }
+    }
+}
+
+impl<T, Align> BoxLeaf<T, Align> {
+    /// Allocates a new [`BoxLeaf<T, Align>`](BoxLeaf) containing `data`,
+    /// which is aligned to at least the alignment of `Align`.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::new`].
+    pub unsafe fn with_align(data: T) -> Self {
+        let node = Box::new(Node {
+            _align: [],
+            data,
+            next: NextCell::default(),
+        });
+        Self {
+            ptr: NonNull::from(Box::leak(node)),
+        }
+    }
+
+    /// Frees the leaf and returns its data.
+    ///
+    /// # Safety
+    ///
+    /// The leaf must not be in a list, and neither `this` nor any clone of it
+    /// may be used after this function is called.
+    pub unsafe fn into_inner(this: Self) -> T {
+        // SAFETY: `this.ptr` was obtained from `Box::leak` in
+        // `Self::with_align`, and the caller guarantees it won't be used
+        // again.
+        unsafe { Box::from_raw(this.ptr.as_ptr()) }.data
+    }
+
+    fn node(&self) -> &Node<T, Align> {
+        // SAFETY: The caller of `Self::new` guaranteed that no clone of the
+        // leaf is used after the node is freed.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, Align> Clone for BoxLeaf<T, Align> {
+    fn clone(&self) -> Self {
+        Self {
+            ptr: self.ptr,
+        }
+    }
+}
+
+/// Compares the inner values of type `T`, then the addresses of the leaves.
+impl<T: PartialEq, Align> PartialEq for BoxLeaf<T, Align> {
+    fn eq(&self, other: &Self) -> bool {
+        (&**self, self.ptr) == (&**other, other.ptr)
+    }
+}
+
+impl<T: Eq, Align> Eq for BoxLeaf<T, Align> {}
+
+/// Compares the inner values of type `T`, then the addresses of the leaves.
+impl<T: PartialOrd, Align> PartialOrd for BoxLeaf<T, Align> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (&**self, self.ptr).partial_cmp(&(&**other, other.ptr))
+    }
+}
+
+/// Compares the inner values of type `T`, then the addresses of the leaves.
+impl<T: Ord, Align> Ord for BoxLeaf<T, Align> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&**self, self.ptr).cmp(&(&**other, other.ptr))
+    }
+}
+
+impl<T, Align> Deref for BoxLeaf<T, Align> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node().data
+    }
+}
+
+/// Hashes only the inner value of type `T`.
+impl<T: Hash, Align> Hash for BoxLeaf<T, Align> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<T: Interval, Align> Interval for BoxLeaf<T, Align> {
+    type Point = T::Point;
+
+    fn start(&self) -> T::Point {
+        (**self).start()
+    }
+
+    fn end(&self) -> T::Point {
+        (**self).end()
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<M, T, Align> Summarize<M> for BoxLeaf<T, Align>
+where
+    M: Monoid,
+    T: Summarize<M>,
+{
+    fn summarize(&self) -> M::Value {
+        (**self).summarize()
+    }
+}
+
+impl<T: fmt::Debug, Align> fmt::Debug for BoxLeaf<T, Align> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BoxLeaf")
+            .field("addr", &self.ptr)
+            .field("data", &**self)
+            .field("next", &self.node().next.get())
+            .finish()
+    }
+}
+
+impl<T, Align: FlagAlign> LeafExt for BoxLeaf<T, Align> {
+    fn user_flag(&self) -> bool {
+        self.node().next.flag()
+    }
+
+    fn set_user_flag(&self, flag: bool) {
+        self.node().next.set_flag(flag);
+    }
+}
+
+// SAFETY:
+// * `Self` is not `Send` or `Sync` because it contains a `NonNull`.
+// * `Self::next` will initially return `None` because `Node::next` is
+//   initialized as `None`.
+// * `Self::set_next` stores its argument in `Node::next` and is the only
+//   function that modifies that field. `Self::next` retrieves the value
+//   appropriately.
+// * Clones of `BoxLeaf` point to the same node, so they behave like the
+//   original.
+// * `Self::on_list_drop` frees the leaf, which the caller of `Self::new`
+//   allowed when the list containing it is dropped.
+unsafe impl<T: BasicLeaf, Align> LeafRef for BoxLeaf<T, Align> {
+    type Options = TypedOptions<
+        <T::Options as BasicOptions>::SizeType,
+        <T::Options as BasicOptions>::StoreKeys,
+        <T::Options as BasicOptions>::Fanout,
+        Align,
+        <T::Options as BasicOptions>::SlabSize,
+        <T::Options as BasicOptions>::StoreHashes,
+        <T::Options as BasicOptions>::EmptyPolicy,
+        <T::Options as BasicOptions>::StoreMaxEnd,
+        <T::Options as BasicOptions>::Aggregate,
+    >;
+
+    const DROP_POLICY: DropPolicy = DropPolicy::Release;
+
+    fn next(&self) -> Option<LeafNext<Self>> {
+        self.node().next.get().map(|p| match p {
+            (ptr, 0) => LeafNext::Leaf(Self {
+                ptr,
+            }),
+            (ptr, _) => LeafNext::Data(ptr.cast()),
+        })
+    }
+
+    fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
+        this.node().next.set(next.map(|n| match n {
+            LeafNext::Leaf(leaf) => (leaf.ptr, 0),
+            LeafNext::Data(data) => (data.cast(), 1),
+        }))
+    }
+
+    fn size(&self) -> LeafSize<Self> {
+        (**self).size()
+    }
+
+    fn chunk_level(&self) -> usize {
+        (**self).chunk_level()
+    }
+
+    /// Frees the leaf and its data.
+    fn on_list_drop(this: This<Self>) {
+        // SAFETY: The leaf has already been unlinked, and the caller of
+        // `Self::new` guaranteed that no clones of it are used after the
+        // list containing it is dropped.
+        drop(unsafe { Self::into_inner(This::into_inner(this)) });
+    }
+}
+
+// SAFETY: Clones of `BoxLeaf` point to the same heap allocation, which
+// isn't tied to any thread, and `T` is `Send`.
+unsafe impl<T: BasicLeaf + Send, Align> SyncLeafRef for BoxLeaf<T, Align> {}
+
+// SAFETY: Clones of `BoxLeaf` point to the same heap allocation, and leaves
+// that exist at the same time have different addresses.
+unsafe impl<T: BasicLeaf, Align> crate::LeafId for BoxLeaf<T, Align> {
+    fn id(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+}
+
+#[cfg(feature = "debug-graphviz")]
+impl<T, Align> crate::list::debug::LeafDebug for BoxLeaf<T, Align>
+where
+    T: BasicLeaf + fmt::Debug,
+{
+    type Id = NonNull<()>;
+
+    fn id(&self) -> Self::Id {
+        self.ptr.cast()
+    }
+
+    fn fmt_data(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", **self)
+    }
+}
+
+impl<T, Align, A> SkipList<BoxLeaf<T, Align>, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+    /// Returns an iterator that removes every item from the list, frees it,
+    /// and yields its data.
+    ///
+    /// Any items that aren't yielded are freed when the iterator is dropped.
+    /// As with dropping the list, no clone of a leaf in the list may be used
+    /// afterward (see [`BoxLeaf::new`]).
+    pub fn into_values(self) -> IntoValues<T, Align, A> {
+        IntoValues {
+            iter: self.into_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T, Align> SkipList<BoxLeaf<T, Align>>
+where
+    T: BasicLeaf + Send,
+{
+    /// Creates a new skip list containing a leaf for each item produced by
+    /// `items`, in order, building parts of the list in parallel with
+    /// [rayon].
+    ///
+    /// See <code>SkipList::<[Arc]\<[ArcLeaf]>>::[from_par_iter]</code>.
+    ///
+    /// This method is available only when the crate feature `rayon` is
+    /// enabled.
+    ///
+    /// # Safety
+    ///
+    /// The requirements of [`BoxLeaf::new`] apply to every leaf in the
+    /// returned list.
+    ///
+    /// [Arc]: alloc::sync::Arc
+    /// [ArcLeaf]: super::ArcLeaf
+    /// [from_par_iter]: SkipList::from_par_iter
+    pub unsafe fn from_par_iter<I>(items: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        // SAFETY: The leaves are created here, so no other clones exist, and
+        // the caller upholds the requirements of `BoxLeaf::with_align`.
+        unsafe {
+            Self::from_par_iter_unchecked(items, |data| {
+                BoxLeaf::with_align(data)
+            })
+        }
+    }
+}
+
+/// An iterator over the data of the items in a [`SkipList`] of [`BoxLeaf`]s.
+///
+/// This type is returned by [`SkipList::into_values`].
+pub struct IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+    iter: IntoIter<BoxLeaf<T, Align>, A>,
+}
+
+impl<T, Align, A> Iterator for IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        // SAFETY: The leaf has been removed from the list, and the caller of
+        // `BoxLeaf::new` guaranteed that no clone of it is used after it is
+        // yielded here.
+        self.iter.next().map(|leaf| unsafe { BoxLeaf::into_inner(leaf) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, Align, A> DoubleEndedIterator for IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<T> {
+        // SAFETY: See `Self::next`.
+        self.iter.next_back().map(|leaf| unsafe { BoxLeaf::into_inner(leaf) })
+    }
+}
+
+impl<T, Align, A> ExactSizeIterator for IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+}
+
+impl<T, Align, A> FusedIterator for IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+}
+
+impl<T, Align, A> Drop for IntoValues<T, Align, A>
+where
+    T: BasicLeaf,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(any(doc, doctest))]
+/// [`BoxLeaf`] cannot implement [`Send`] or [`Sync`], as this would make it
+/// unsound to implement [`LeafRef`].
+///
+/// ```
+/// use skippy::basic::BoxLeaf;
+/// struct Test<T = BoxLeaf<u8>>(T);
+/// ```
+///
+/// ```compile_fail
+/// use skippy::basic::BoxLeaf;
+/// struct Test<T: Send = BoxLeaf<u8>>(T);
+/// ```
+///
+/// ```compile_fail
+/// use skippy::basic::BoxLeaf;
+/// struct Test<T: Sync = BoxLeaf<u8>>(T);
+/// ```
+mod leaf_is_not_send_sync {}
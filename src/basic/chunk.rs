@@ -0,0 +1,384 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Leaves that each store a chunk of several elements.
+
+#[cfg(target_has_atomic = "ptr")]
+use super::ArcLeaf;
+use super::{BasicLeaf, RcLeaf};
+use crate::allocator::Allocator;
+use crate::options::ListOptions;
+use crate::{LeafRef, SkipList};
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::{Cell, Ref, RefCell, RefMut};
+use core::fmt;
+
+/// Stores up to `N` elements of type `T` in a single leaf.
+///
+/// Storing one element per leaf is wasteful when elements are small, as each
+/// leaf needs its own allocation and link. A [`ChunkLeaf`] groups adjacent
+/// elements together instead; its [size](BasicLeaf::size) is the number of
+/// elements it contains, so indices in the list count elements rather than
+/// leaves.
+///
+/// A [`ChunkLeaf`] is wrapped in one of the leaf types that implement
+/// [`ChunkRef`], such as
+/// <code>[Rc]\<[RcLeaf]\<[ChunkLeaf]\<T, N>>></code>. The elements can
+/// be read and modified in place with [`Self::items`] and
+/// [`Self::items_mut`], but the number of elements can be changed only by
+/// the methods of [`SkipList`] that take element indices, such as
+/// [`SkipList::insert_in_chunk`] and [`SkipList::remove_in_chunk`]. These
+/// split chunks that become full and merge chunks that become small, and
+/// keep the list's sizes up to date.
+///
+/// `N` must be at least 2.
+///
+/// [Rc]: alloc::rc::Rc
+pub struct ChunkLeaf<T, const N: usize> {
+    items: RefCell<Vec<T>>,
+    /// The number of elements, which is also the size of the leaf. This is
+    /// stored separately so that the size can be read while the elements are
+    /// borrowed.
+    len: Cell<usize>,
+}
+
+impl<T, const N: usize> ChunkLeaf<T, N> {
+    /// Creates a new, empty chunk.
+    ///
+    /// Chunks in a list should never be empty; see [`Self::from_vec`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `N` is less than 2.
+    pub fn new() -> Self {
+        Self::from_vec(Vec::new())
+    }
+
+    /// Creates a new chunk containing the elements in `items`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `N` is less than 2 or if `items` contains more
+    /// than `N` elements.
+    pub fn from_vec(mut items: Vec<T>) -> Self {
+        assert!(N >= 2, "chunk capacity must be at least 2");
+        assert!(items.len() <= N, "too many elements for chunk");
+        items.reserve_exact(N - items.len());
+        Self {
+            len: Cell::new(items.len()),
+            items: RefCell::new(items),
+        }
+    }
+
+    /// Gets the number of elements in the chunk.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Checks whether the chunk is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether the chunk contains `N` elements.
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// Borrows the elements of the chunk.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the elements are mutably borrowed.
+    pub fn items(&self) -> Ref<'_, [T]> {
+        Ref::map(self.items.borrow(), Vec::as_slice)
+    }
+
+    /// Mutably borrows the elements of the chunk.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the elements are already borrowed.
+    pub fn items_mut(&self) -> RefMut<'_, [T]> {
+        RefMut::map(self.items.borrow_mut(), Vec::as_mut_slice)
+    }
+
+    /// Modifies the underlying [`Vec`]. Must be called only within
+    /// [`SkipList::update`].
+    fn modify<R>(&self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        let mut items = self.items.borrow_mut();
+        let result = f(&mut items);
+        self.len.set(items.len());
+        result
+    }
+
+    /// Takes the elements of the chunk, leaving it empty. Must be called only
+    /// within [`SkipList::update`].
+    fn take(&self) -> Vec<T> {
+        self.modify(|items| core::mem::replace(items, Vec::with_capacity(N)))
+    }
+}
+
+impl<T, const N: usize> BasicLeaf for ChunkLeaf<T, N> {
+    type Options = super::Options<usize>;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, const N: usize> Default for ChunkLeaf<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ChunkLeaf<T, N> {
+    fn clone(&self) -> Self {
+        Self::from_vec(self.items().to_vec())
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ChunkLeaf<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.items().iter()).finish()
+    }
+}
+
+/// A [`LeafRef`] that stores a [`ChunkLeaf`] and can be created from one.
+///
+/// This trait is implemented by <code>[Rc]\<[RcLeaf]\<[ChunkLeaf]\<T,
+/// N>>></code> and <code>[Arc]\<[ArcLeaf]\<[ChunkLeaf]\<T, N>>></code>, and
+/// allows the methods of [`SkipList`] that operate on chunks (like
+/// [`SkipList::insert_in_chunk`]) to allocate new chunks when existing ones
+/// become full. Those methods also remove chunks from the list when they
+/// become empty, so the leaf type should free its data once the last
+/// reference to it is dropped. (This is why [`BoxLeaf`](super::BoxLeaf)
+/// doesn't implement this trait.)
+///
+/// [Rc]: alloc::rc::Rc
+/// [Arc]: alloc::sync::Arc
+pub trait ChunkRef<T, const N: usize>: LeafRef {
+    /// Gets the chunk stored in this leaf.
+    fn chunk(&self) -> &ChunkLeaf<T, N>;
+
+    /// Creates a new leaf that stores `chunk`.
+    fn from_chunk(chunk: ChunkLeaf<T, N>) -> Self;
+}
+
+impl<T, const N: usize, Align> ChunkRef<T, N>
+    for Rc<RcLeaf<ChunkLeaf<T, N>, Align>>
+{
+    fn chunk(&self) -> &ChunkLeaf<T, N> {
+        self
+    }
+
+    fn from_chunk(chunk: ChunkLeaf<T, N>) -> Self {
+        Rc::new(RcLeaf::with_align(chunk))
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize, Align> ChunkRef<T, N>
+    for Arc<ArcLeaf<ChunkLeaf<T, N>, Align>>
+{
+    fn chunk(&self) -> &ChunkLeaf<T, N> {
+        self
+    }
+
+    fn from_chunk(chunk: ChunkLeaf<T, N>) -> Self {
+        Arc::new(ArcLeaf::with_align(chunk))
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef,
+    L::Options: ListOptions<SizeType = usize>,
+    A: Allocator,
+{
+    /// Gets the chunk that contains the element at `index`, and the position
+    /// of that element within the chunk.
+    ///
+    /// Returns [`None`] if `index` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_in_chunk<T, const N: usize>(
+        &self,
+        index: usize,
+    ) -> Option<(L, usize)>
+    where
+        L: ChunkRef<T, N>,
+    {
+        if index >= self.size() {
+            return None;
+        }
+        let leaf = self.get(&index)?;
+        let start = SkipList::index(leaf.clone());
+        Some((leaf, index - start))
+    }
+
+    /// Inserts `value` so that it becomes the element at `index`.
+    ///
+    /// If the chunk that would contain `value` is full, the value is added to
+    /// the end of the previous chunk (if `value` would be the first element
+    /// and that chunk has room) or to a new chunk (if `value` would be the
+    /// last element in the list); otherwise the chunk is split in half.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is greater than the number of elements.
+    /// It may also panic if the list contains an empty chunk.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n* + *N*).
+    pub fn insert_in_chunk<T, const N: usize>(
+        &mut self,
+        index: usize,
+        value: T,
+    ) where
+        L: ChunkRef<T, N>,
+    {
+        assert!(index <= self.size(), "index is out of bounds");
+        let (leaf, offset) = match self.get_in_chunk(index) {
+            Some(found) => found,
+            None => match self.last() {
+                Some(last) => {
+                    let len = last.chunk().len();
+                    (last, len)
+                }
+                None => {
+                    let chunk = ChunkLeaf::from_vec(Vec::from([value]));
+                    self.push_back(L::from_chunk(chunk));
+                    return;
+                }
+            },
+        };
+        let chunk = leaf.chunk();
+        assert!(!chunk.is_empty(), "chunk is empty");
+        if !chunk.is_full() {
+            self.update(leaf.clone(), || {
+                chunk.modify(|items| items.insert(offset, value));
+            });
+            return;
+        }
+
+        // Appending to the last chunk or prepending to a chunk doesn't need
+        // to split it if the element can go in a neighboring chunk.
+        if offset == N {
+            self.insert_after(
+                leaf,
+                L::from_chunk(ChunkLeaf::from_vec(Vec::from([value]))),
+            );
+            return;
+        }
+        if offset == 0 {
+            if let Some(prev) = SkipList::previous(leaf.clone()) {
+                let chunk = prev.chunk();
+                if !chunk.is_full() {
+                    self.update(prev.clone(), || {
+                        chunk.modify(|items| items.push(value));
+                    });
+                    return;
+                }
+            }
+        }
+
+        let mid = N / 2;
+        let tail = self.update_with(leaf.clone(), || {
+            chunk.modify(|items| {
+                let mut tail = items.split_off(mid);
+                if offset <= mid {
+                    items.insert(offset, value);
+                } else {
+                    tail.insert(offset - mid, value);
+                }
+                tail
+            })
+        });
+        self.insert_after(leaf, L::from_chunk(ChunkLeaf::from_vec(tail)));
+    }
+
+    /// Removes and returns the element at `index`.
+    ///
+    /// If this leaves its chunk empty, the chunk is removed from the list. If
+    /// the chunk becomes less than half full and its elements fit in a
+    /// neighboring chunk, the two chunks are merged and the emptied chunk is
+    /// removed.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n* + *N*).
+    pub fn remove_in_chunk<T, const N: usize>(&mut self, index: usize) -> T
+    where
+        L: ChunkRef<T, N>,
+    {
+        let (leaf, offset) =
+            self.get_in_chunk(index).expect("index is out of bounds");
+        let chunk = leaf.chunk();
+        let value = self.update_with(leaf.clone(), || {
+            chunk.modify(|items| items.remove(offset))
+        });
+        if chunk.is_empty() {
+            self.remove(leaf);
+        } else if chunk.len() < N / 2 {
+            self.merge_chunk(leaf);
+        }
+        value
+    }
+
+    /// Merges `leaf` with one of its neighbors, if their elements fit in a
+    /// single chunk.
+    fn merge_chunk<T, const N: usize>(&mut self, leaf: L)
+    where
+        L: ChunkRef<T, N>,
+    {
+        let fits = |other: &L| leaf.chunk().len() + other.chunk().len() <= N;
+        let (first, second) = match SkipList::next(leaf.clone()) {
+            Some(next) if fits(&next) => (leaf, next),
+            _ => match SkipList::previous(leaf.clone()) {
+                Some(prev) if fits(&prev) => (prev, leaf),
+                _ => return,
+            },
+        };
+        let moved = self.update_with(second.clone(), || second.chunk().take());
+        self.remove(second);
+        let chunk = first.chunk();
+        self.update(first.clone(), || {
+            chunk.modify(|items| items.extend(moved));
+        });
+    }
+
+    /// Like [`Self::update`], but returns the result of `update`.
+    fn update_with<R>(&mut self, item: L, update: impl FnOnce() -> R) -> R {
+        let mut result = None;
+        self.update(item, || result = Some(update()));
+        result.unwrap()
+    }
+}
@@ -19,22 +19,51 @@
 
 //! “Basic” implementations of [`LeafRef`] that store data of a given type.
 //!
-//! This module provides two types that, when wrapped in the appropriate
+//! This module provides four types that, when wrapped in the appropriate
 //! reference-like type, implement [`LeafRef`]:
 //!
 //! * [`RefLeaf`], where <code>[&][r][RefLeaf]</code> implements [`LeafRef`].
 //! * [`RcLeaf`], where <code>[Rc]\<[RcLeaf]\></code> implements [`LeafRef`].
+//! * [`ArcLeaf`], where <code>[Arc]\<[ArcLeaf]\></code> implements
+//!   [`LeafRef`] and [`SyncLeafRef`].
+//! * [`BoxLeaf`], which implements [`LeafRef`] itself and whose data is owned
+//!   by the list and freed when the list is dropped.
+//!
+//! Leaves of type [`RefLeaf`] can be allocated in a [`LeafArena`], which keeps
+//! them alive for as long as the arena exists.
+//!
+//! When their `Align` parameter implements [`FlagAlign`] (e.g., [`UserFlag`]),
+//! these types also implement [`LeafExt`], which stores a user flag in a spare
+//! bit of the leaf's link to the next item.
+//!
+//! If the crate feature `serde` is enabled, lists of these leaves can be
+//! serialized as a sequence of their items and deserialized by bulk loading
+//! those items into a new list.
 //!
 //! [r]: prim@reference
 //! [Rc]: alloc::rc::Rc
+//! [Arc]: alloc::sync::Arc
 
 #[cfg(doc)]
-use crate::LeafRef;
+use crate::{LeafRef, SyncLeafRef};
 
+#[cfg(target_has_atomic = "ptr")]
+mod arc;
+pub mod arena;
+mod boxed;
+mod chunk;
+mod next;
 pub mod options;
 mod rc;
 mod reference;
+#[cfg(feature = "serde")]
+mod serde;
 
+#[cfg(target_has_atomic = "ptr")]
+pub use arc::ArcLeaf;
+pub use arena::LeafArena;
+pub use boxed::{BoxLeaf, IntoValues};
+pub use chunk::{ChunkLeaf, ChunkRef};
 pub use options::{BasicOptions, Options};
 pub use rc::RcLeaf;
 pub use reference::RefLeaf;
@@ -52,4 +81,53 @@ pub trait BasicLeaf {
     fn size(&self) -> <Self::Options as BasicOptions>::SizeType {
         Default::default()
     }
+
+    /// Gets the level of the node boundary that follows this item; see
+    /// [`LeafRef::chunk_level`].
+    ///
+    /// By default, this method returns 0.
+    fn chunk_level(&self) -> usize {
+        0
+    }
 }
+
+/// Per-item flags stored in the leaf types in this module.
+///
+/// The basic leaf types store a pointer to the next item. When the leaves are
+/// aligned to at least 4 bytes, which is opted into through their `Align`
+/// parameter (see [`FlagAlign`]), that pointer has a spare low bit that is
+/// available to the user (e.g., to mark an item as dirty or visited) without
+/// needing an extra field. The bit is masked out when the list traverses the
+/// leaves, so changing it never affects the list, and it is preserved when the
+/// list relinks the leaf.
+pub trait LeafExt {
+    /// Gets the user flag of this leaf. Leaves are created with the flag
+    /// cleared.
+    fn user_flag(&self) -> bool;
+
+    /// Sets the user flag of this leaf.
+    fn set_user_flag(&self, flag: bool);
+}
+
+/// Types that, when used as the `Align` parameter of the leaf types in this
+/// module, make those types implement [`LeafExt`].
+///
+/// The leaf types are aligned to 2 bytes by default, which leaves no spare bit
+/// for the user flag. Using an `Align` type with an alignment of at least 4
+/// bytes raises the leaves' alignment enough for the flag to fit.
+///
+/// # Safety
+///
+/// The alignment of `Self` must be at least 4.
+pub unsafe trait FlagAlign {}
+
+/// An `Align` type with an alignment of 4 bytes, the smallest that enables
+/// [`LeafExt`] (see [`FlagAlign`]).
+///
+/// For example, <code>[RefLeaf]\<T, [UserFlag]></code> implements
+/// [`LeafExt`], whereas <code>[RefLeaf]\<T></code> does not.
+#[repr(align(4))]
+pub struct UserFlag;
+
+// SAFETY: `UserFlag` has an alignment of 4.
+unsafe impl FlagAlign for UserFlag {}
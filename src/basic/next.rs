@@ -0,0 +1,152 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem::align_of;
+use core::ptr::NonNull;
+
+/// Tag bit set when the stored pointer is [`LeafNext::Data`].
+///
+/// [`LeafNext::Data`]: crate::LeafNext::Data
+const DATA: usize = 0b01;
+
+/// Tag bit that stores the user flag. It is available only when leaves are
+/// aligned to at least 4 bytes (see [`FlagAlign`](super::FlagAlign)).
+const FLAG: usize = 0b10;
+
+/// The stored next-pointer of a basic leaf type, which also holds the user
+/// flag exposed through [`LeafExt`](super::LeafExt).
+///
+/// Values are exchanged as `(ptr, kind)` pairs, where a `kind` of 0 means the
+/// pointer refers to a leaf and a `kind` of 1 means it is data from
+/// [`LeafNext::Data`](crate::LeafNext::Data). The user flag is masked out of
+/// these values and is preserved when the pointer changes.
+///
+/// The tag bits are stored in the low bits of the pointer itself. `T` must be
+/// aligned to at least 2 bytes; internal nodes are aligned to at least 4.
+pub struct NextCell<T>(Cell<NonNull<T>>);
+
+impl<T> NextCell<T> {
+    /// The tag bits that are available, given the alignment of `T`.
+    const MASK: usize = if align_of::<T>() >= 4 {
+        DATA | FLAG
+    } else {
+        DATA
+    };
+
+    /// The pointer stored when there is no next item. It can't be confused
+    /// with a real leaf or internal node because it refers to a static.
+    fn sentinel() -> NonNull<T> {
+        #[repr(align(4))]
+        struct Align4(#[allow(dead_code)] u32);
+
+        static SENTINEL: Align4 = Align4(0);
+        NonNull::from(&SENTINEL).cast()
+    }
+
+    fn pack(ptr: NonNull<T>, tag: usize) -> NonNull<T> {
+        debug_assert_eq!(ptr.as_ptr() as usize & Self::MASK, 0);
+        debug_assert_eq!(tag & !Self::MASK, 0);
+        let ptr = ptr.as_ptr().cast::<u8>().wrapping_add(tag);
+        // SAFETY: `ptr` was non-null and aligned to more than `tag`, so
+        // adding `tag` can't make it null.
+        unsafe { NonNull::new_unchecked(ptr.cast()) }
+    }
+
+    fn unpack(&self) -> (NonNull<T>, usize) {
+        let ptr = self.0.get().as_ptr();
+        let tag = ptr as usize & Self::MASK;
+        let ptr = ptr.cast::<u8>().wrapping_sub(tag);
+        // SAFETY: `ptr` was created by `Self::pack` from a non-null pointer
+        // and a tag of `tag`.
+        (unsafe { NonNull::new_unchecked(ptr.cast()) }, tag)
+    }
+
+    pub fn get(&self) -> Option<(NonNull<T>, usize)> {
+        let (ptr, tag) = self.unpack();
+        Some((ptr, tag & DATA)).filter(|_| ptr != Self::sentinel())
+    }
+
+    pub fn replace(
+        &self,
+        next: Option<(NonNull<T>, usize)>,
+    ) -> Option<(NonNull<T>, usize)> {
+        let old = self.get();
+        let flag = self.unpack().1 & FLAG;
+        let (ptr, kind) = next.unwrap_or((Self::sentinel(), 0));
+        self.0.set(Self::pack(ptr, (kind & DATA) | flag));
+        old
+    }
+
+    pub fn set(&self, next: Option<(NonNull<T>, usize)>) {
+        self.replace(next);
+    }
+
+    pub fn flag(&self) -> bool {
+        self.unpack().1 & FLAG != 0
+    }
+
+    pub fn set_flag(&self, flag: bool) {
+        assert!(Self::MASK & FLAG != 0, "leaf is not aligned to 4 bytes");
+        let (ptr, tag) = self.unpack();
+        self.0.set(Self::pack(
+            ptr,
+            (tag & !FLAG)
+                | if flag {
+                    FLAG
+                } else {
+                    0
+                },
+        ));
+    }
+}
+
+impl<T> Default for NextCell<T> {
+    fn default() -> Self {
+        Self(Cell::new(Self::sentinel()))
+    }
+}
+
+impl<T> PartialEq for NextCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.get() == other.0.get()
+    }
+}
+
+impl<T> Eq for NextCell<T> {}
+
+impl<T> PartialOrd for NextCell<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for NextCell<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.get().cmp(&other.0.get())
+    }
+}
+
+impl<T> fmt::Debug for NextCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.get())
+    }
+}
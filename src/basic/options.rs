@@ -21,7 +21,9 @@
 
 #[cfg(doc)]
 use super::BasicLeaf;
-use crate::options::{Fanout, NoSize, StoreKeys};
+use crate::options::{AggregateType, NoAggregate};
+use crate::options::{EmptyPolicyType, Fanout, NoSize};
+use crate::options::{SlabSize, StoreHashes, StoreKeys, StoreMaxEnd};
 use core::marker::PhantomData;
 use core::ops::{AddAssign, SubAssign};
 use integral_constant::{Bool, Usize};
@@ -54,6 +56,36 @@ pub trait BasicOptions: sealed::Sealed {
     ///
     /// See [`ListOptions::Fanout`](crate::ListOptions::Fanout).
     type Fanout: Fanout;
+
+    /// The number of internal nodes allocated together in a single block of
+    /// memory.
+    ///
+    /// See [`ListOptions::SlabSize`](crate::ListOptions::SlabSize).
+    type SlabSize: SlabSize;
+
+    /// Whether or not to cache a hash of the items in each internal node of
+    /// the list.
+    ///
+    /// See [`ListOptions::StoreHashes`](crate::ListOptions::StoreHashes).
+    type StoreHashes: StoreHashes;
+
+    /// Whether index queries for the size of the list return its last item
+    /// when that item has a size of 0. Queries on an empty list always return
+    /// [`None`].
+    ///
+    /// See [`ListOptions::EmptyPolicy`](crate::ListOptions::EmptyPolicy).
+    type EmptyPolicy: EmptyPolicyType;
+
+    /// Whether or not to cache the item whose interval ends last in each
+    /// internal node of the list.
+    ///
+    /// See [`ListOptions::StoreMaxEnd`](crate::ListOptions::StoreMaxEnd).
+    type StoreMaxEnd: StoreMaxEnd;
+
+    /// A user-defined summary of the items in each internal node's subtree.
+    ///
+    /// See [`ListOptions::Aggregate`](crate::ListOptions::Aggregate).
+    type Aggregate: AggregateType;
 }
 
 /// Options for [`BasicLeaf::Options`].
@@ -62,20 +94,35 @@ pub trait BasicOptions: sealed::Sealed {
 /// to associated types in [`BasicOptions`] as follows; see those associated
 /// types for documentation:
 ///
-/// Parameter    | Associated type
-/// ------------ | ---------------------------
-/// `SizeType`   | [`BasicOptions::SizeType`]
-/// `STORE_KEYS` | [`BasicOptions::StoreKeys`]
-/// `FANOUT`     | [`BasicOptions::Fanout`]
+/// Parameter       | Associated type
+/// --------------- | -----------------------------
+/// `SizeType`      | [`BasicOptions::SizeType`]
+/// `STORE_KEYS`    | [`BasicOptions::StoreKeys`]
+/// `FANOUT`        | [`BasicOptions::Fanout`]
+/// `SLAB_SIZE`     | [`BasicOptions::SlabSize`]
+/// `STORE_HASHES`  | [`BasicOptions::StoreHashes`]
+/// `RETURN_LAST`   | [`BasicOptions::EmptyPolicy`]
+/// `STORE_MAX_END` | [`BasicOptions::StoreMaxEnd`]
+/// `Aggregate`     | [`BasicOptions::Aggregate`]
 #[rustfmt::skip]
 pub type Options<
     SizeType = NoSize,
     const STORE_KEYS: bool = false,
     const FANOUT: usize = 8,
+    const SLAB_SIZE: usize = 1,
+    const STORE_HASHES: bool = false,
+    const RETURN_LAST: bool = true,
+    const STORE_MAX_END: bool = false,
+    Aggregate = NoAggregate,
 > = TypedOptions<
     SizeType,
     Bool<STORE_KEYS>,
     Usize<FANOUT>,
+    Usize<SLAB_SIZE>,
+    Bool<STORE_HASHES>,
+    Bool<RETURN_LAST>,
+    Bool<STORE_MAX_END>,
+    Aggregate,
 >;
 
 /// Like [`Options`], but uses types instead of const parameters.
@@ -87,10 +134,20 @@ pub struct TypedOptions<
     SizeType = NoSize,
     StoreKeys = Bool<false>,
     Fanout = Usize<8>,
+    SlabSize = Usize<1>,
+    StoreHashes = Bool<false>,
+    EmptyPolicy = Bool<true>,
+    StoreMaxEnd = Bool<false>,
+    Aggregate = NoAggregate,
 >(PhantomData<fn() -> (
     SizeType,
     StoreKeys,
     Fanout,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 )>);
 
 #[rustfmt::skip]
@@ -98,10 +155,20 @@ impl<
     SizeType,
     StoreKeys,
     Fanout,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > sealed::Sealed for TypedOptions<
     SizeType,
     StoreKeys,
     Fanout,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > {}
 
 #[rustfmt::skip]
@@ -109,12 +176,27 @@ impl<
     SizeType: Clone + Default + Eq + AddAssign + SubAssign,
     StoreKeys: self::StoreKeys,
     Fanout: self::Fanout,
+    SlabSize: self::SlabSize,
+    StoreHashes: self::StoreHashes,
+    EmptyPolicy: EmptyPolicyType,
+    StoreMaxEnd: self::StoreMaxEnd,
+    Aggregate: AggregateType,
 > BasicOptions for TypedOptions<
     SizeType,
     StoreKeys,
     Fanout,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > {
     type SizeType = SizeType;
     type StoreKeys = StoreKeys;
     type Fanout = Fanout;
+    type SlabSize = SlabSize;
+    type StoreHashes = StoreHashes;
+    type EmptyPolicy = EmptyPolicy;
+    type StoreMaxEnd = StoreMaxEnd;
+    type Aggregate = Aggregate;
 }
@@ -17,32 +17,49 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::BasicLeaf;
+use super::next::NextCell;
 use super::options::BasicOptions;
-use crate::options::{LeafSize, TypedOptions};
-use crate::{LeafNext, LeafRef, This};
+use super::{BasicLeaf, FlagAlign, LeafExt};
+use crate::options::{LeafSize, Monoid, TypedOptions};
+use crate::{Interval, LeafNext, LeafRef, Summarize, This};
 use alloc::rc::Rc;
-use core::cell::Cell;
+use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem::ManuallyDrop;
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
-use tagged_pointer::TaggedPtr;
 
 /// Stores data of type `T`. <code>[Rc]\<[RcLeaf]\<T>></code> implements
 /// [`LeafRef`] and can be used with [`SkipList`](crate::SkipList).
-#[repr(align(2))]
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-pub struct RcLeaf<T> {
+///
+/// This type is `#[repr(C)]`, and the data of type `T` is stored at the start
+/// of it. Its alignment is at least the alignment of `Align`, which can be
+/// used to give each leaf extra alignment (e.g., to a cache line); see
+/// [`Self::with_align`]. Note that the list's internal nodes will also have
+/// this alignment.
+#[repr(C, align(2))]
+pub struct RcLeaf<T, Align = ()> {
+    _align: [Align; 0],
     data: T,
-    next: Cell<Option<TaggedPtr<Self, 1>>>,
+    next: NextCell<Self>,
 }
 
 impl<T> RcLeaf<T> {
     /// Creates a new [`RcLeaf<T>`].
     pub fn new(data: T) -> Self {
+        Self::with_align(data)
+    }
+}
+
+impl<T, Align> RcLeaf<T, Align> {
+    /// Creates a new [`RcLeaf<T, Align>`](RcLeaf), which is aligned to at
+    /// least the alignment of `Align`.
+    pub fn with_align(data: T) -> Self {
         Self {
+            _align: [],
             data,
-            next: Cell::default(),
+            next: NextCell::default(),
         }
     }
 
@@ -52,13 +69,33 @@ impl<T> RcLeaf<T> {
     }
 }
 
+impl<T: PartialEq, Align> PartialEq for RcLeaf<T, Align> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.data, &self.next) == (&other.data, &other.next)
+    }
+}
+
+impl<T: Eq, Align> Eq for RcLeaf<T, Align> {}
+
+impl<T: PartialOrd, Align> PartialOrd for RcLeaf<T, Align> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (&self.data, &self.next).partial_cmp(&(&other.data, &other.next))
+    }
+}
+
+impl<T: Ord, Align> Ord for RcLeaf<T, Align> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.data, &self.next).cmp(&(&other.data, &other.next))
+    }
+}
+
 impl<T> From<T> for RcLeaf<T> {
     fn from(data: T) -> Self {
         Self::new(data)
     }
 }
 
-impl<T> Deref for RcLeaf<T> {
+impl<T, Align> Deref for RcLeaf<T, Align> {
     type Target = T;
 
     fn deref(&self) -> &T {
@@ -66,13 +103,44 @@ impl<T> Deref for RcLeaf<T> {
     }
 }
 
-impl<T> DerefMut for RcLeaf<T> {
+impl<T, Align> DerefMut for RcLeaf<T, Align> {
     fn deref_mut(&mut self) -> &mut T {
         &mut self.data
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for RcLeaf<T> {
+/// Hashes only the inner value of type `T`.
+impl<T: Hash, Align> Hash for RcLeaf<T, Align> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<T: Interval, Align> Interval for Rc<RcLeaf<T, Align>> {
+    type Point = T::Point;
+
+    fn start(&self) -> T::Point {
+        self.data.start()
+    }
+
+    fn end(&self) -> T::Point {
+        self.data.end()
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<M, T, Align> Summarize<M> for Rc<RcLeaf<T, Align>>
+where
+    M: Monoid,
+    T: Summarize<M>,
+{
+    fn summarize(&self) -> M::Value {
+        self.data.summarize()
+    }
+}
+
+impl<T: fmt::Debug, Align> fmt::Debug for RcLeaf<T, Align> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("RcLeaf")
             .field("addr", &(self as *const _))
@@ -82,6 +150,16 @@ impl<T: fmt::Debug> fmt::Debug for RcLeaf<T> {
     }
 }
 
+impl<T, Align: FlagAlign> LeafExt for RcLeaf<T, Align> {
+    fn user_flag(&self) -> bool {
+        self.next.flag()
+    }
+
+    fn set_user_flag(&self, flag: bool) {
+        self.next.set_flag(flag);
+    }
+}
+
 // SAFETY:
 // * `Rc` is not `Send` or `Sync`.
 // * `Self::next` will initially return `None` because `RcLeaf::next` is
@@ -90,45 +168,106 @@ impl<T: fmt::Debug> fmt::Debug for RcLeaf<T> {
 //   function that modifies that field. `Self::next` retrieves the value
 //   appropriately.
 // * Clones of `Rc` behave like the original pointer.
-unsafe impl<T: BasicLeaf> LeafRef for Rc<RcLeaf<T>> {
+unsafe impl<T: BasicLeaf, Align> LeafRef for Rc<RcLeaf<T, Align>> {
     type Options = TypedOptions<
         <T::Options as BasicOptions>::SizeType,
         <T::Options as BasicOptions>::StoreKeys,
         <T::Options as BasicOptions>::Fanout,
-        RcLeaf<T>, /* Align */
+        RcLeaf<T, Align>, /* Align */
+        <T::Options as BasicOptions>::SlabSize,
+        <T::Options as BasicOptions>::StoreHashes,
+        <T::Options as BasicOptions>::EmptyPolicy,
+        <T::Options as BasicOptions>::StoreMaxEnd,
+        <T::Options as BasicOptions>::Aggregate,
     >;
 
     fn next(&self) -> Option<LeafNext<Self>> {
-        let (ptr, tag) = self.next.get()?.get();
+        let (ptr, tag) = self.next.get()?;
         Some(match tag {
-            // SAFETY: A tag of 0 corresponds to a leaf pointer.
-            0 => LeafNext::Leaf(unsafe { Rc::from_raw(ptr.as_ptr()) }),
+            0 => LeafNext::Leaf({
+                // SAFETY: A tag of 0 corresponds to a pointer obtained from
+                // `Rc::into_raw` in `Self::set_next`. That reference is still
+                // owned by `self.next`, so it must not be dropped here.
+                let leaf =
+                    ManuallyDrop::new(unsafe { Rc::from_raw(ptr.as_ptr()) });
+                Rc::clone(&leaf)
+            }),
             _ => LeafNext::Data(ptr.cast()),
         })
     }
 
     fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
-        this.next.set(next.map(|n| match n {
-            LeafNext::Leaf(leaf) => TaggedPtr::new(
+        let old = this.next.replace(next.map(|n| match n {
+            LeafNext::Leaf(leaf) => (
                 // SAFETY: `Rc::into_raw` always returns non-null pointers.
                 unsafe { NonNull::new_unchecked(Rc::into_raw(leaf) as _) },
                 0,
             ),
-            LeafNext::Data(data) => TaggedPtr::new(data.cast(), 1),
-        }))
+            LeafNext::Data(data) => (data.cast(), 1),
+        }));
+        if let Some((ptr, 0)) = old {
+            // SAFETY: A tag of 0 corresponds to a pointer obtained from
+            // `Rc::into_raw`, and `self.next` no longer refers to it, so the
+            // reference it owned is released here.
+            drop(unsafe { Rc::from_raw(ptr.as_ptr()) });
+        }
     }
 
     fn size(&self) -> LeafSize<Self> {
         self.data.size()
     }
+
+    fn chunk_level(&self) -> usize {
+        self.data.chunk_level()
+    }
+}
+
+impl<T, Align, A> crate::SkipList<Rc<RcLeaf<T, Align>>, A>
+where
+    T: BasicLeaf,
+    A: crate::allocator::Allocator,
+{
+    /// Removes the items that aren't referenced outside of the list, and
+    /// returns how many items were removed.
+    ///
+    /// The list holds strong references to its items, so an item stays in
+    /// the list after all other [`Rc`]s that point to it are dropped. This
+    /// method finds and removes those items, which lets code that owns the
+    /// items remove them simply by dropping its [`Rc`]s and occasionally
+    /// calling this method. The removed items are dropped. References held
+    /// by the list itself, including those used for
+    /// [`handle`](crate::SkipList::handle), don't keep an item alive.
+    ///
+    /// Items are considered dead based on their [strong count]. The list
+    /// can't store [`Weak`](alloc::rc::Weak) references instead, because it
+    /// must still be able to follow a dead item's links (and return it as an
+    /// [`Rc`]) until the item is removed. Dead items are unlinked in place, so
+    /// this method never allocates, and the rest of the list is untouched.
+    ///
+    /// [strong count]: Rc::strong_count
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), plus Θ(log *n*) for each item removed.
+    pub fn purge_dead(&mut self) -> usize {
+        self.purge(|item, held| Rc::strong_count(item) <= held)
+    }
+}
+
+// SAFETY: Clones of an `Rc` point to the same allocation, and leaves that
+// exist at the same time have different addresses.
+unsafe impl<T: BasicLeaf, Align> crate::LeafId for Rc<RcLeaf<T, Align>> {
+    fn id(&self) -> usize {
+        Rc::as_ptr(self) as usize
+    }
 }
 
-#[cfg(skippy_debug)]
-impl<T> crate::list::debug::LeafDebug for Rc<RcLeaf<T>>
+#[cfg(feature = "debug-graphviz")]
+impl<T, Align> crate::list::debug::LeafDebug for Rc<RcLeaf<T, Align>>
 where
     T: BasicLeaf + fmt::Debug,
 {
-    type Id = *const RcLeaf<T>;
+    type Id = *const RcLeaf<T, Align>;
 
     fn id(&self) -> Self::Id {
         Rc::as_ptr(self)
@@ -17,16 +17,20 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::BasicLeaf;
+use super::next::NextCell;
 use super::options::BasicOptions;
-use crate::options::{LeafSize, TypedOptions};
-use crate::{LeafNext, LeafRef, This};
+use super::{BasicLeaf, FlagAlign, LeafExt};
+use crate::options::{LeafSize, Monoid, TypedOptions};
+use crate::{
+    Interval, LeafNext, LeafRef, SharedLeafRef, Summarize, SyncLeafRef, This,
+};
 use core::cell::Cell;
+use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 use core::ptr::NonNull;
-use tagged_pointer::TaggedPtr;
 
 /// Stores data of type `T`. <code>[&][r][RefLeaf]\<T></code> implements
 /// [`LeafRef`] and can be used with [`SkipList`](crate::SkipList).
@@ -34,21 +38,36 @@ use tagged_pointer::TaggedPtr;
 /// This type has a lifetime parameter because it can hold references to other
 /// [`RefLeaf`] objects.
 ///
+/// This type is `#[repr(C)]`, and the data of type `T` is stored at the start
+/// of it. Its alignment is at least the alignment of `Align`, which can be
+/// used to give each leaf extra alignment (e.g., to a cache line); see
+/// [`Self::with_align`]. Note that the list's internal nodes will also have
+/// this alignment.
+///
 /// [r]: reference
-#[repr(align(2))]
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-pub struct RefLeaf<'a, T> {
+#[repr(C, align(2))]
+pub struct RefLeaf<'a, T, Align = ()> {
+    _align: [Align; 0],
     data: T,
-    next: Cell<Option<TaggedPtr<Self, 1>>>,
+    next: NextCell<Self>,
     phantom: PhantomData<Cell<&'a Self>>,
 }
 
 impl<T> RefLeaf<'_, T> {
     /// Creates a new [`RefLeaf<'_, T>`](RefLeaf).
     pub fn new(data: T) -> Self {
+        Self::with_align(data)
+    }
+}
+
+impl<T, Align> RefLeaf<'_, T, Align> {
+    /// Creates a new [`RefLeaf<'_, T, Align>`](RefLeaf), which is aligned to
+    /// at least the alignment of `Align`.
+    pub fn with_align(data: T) -> Self {
         Self {
+            _align: [],
             data,
-            next: Cell::default(),
+            next: NextCell::default(),
             phantom: PhantomData,
         }
     }
@@ -65,7 +84,27 @@ impl<T> From<T> for RefLeaf<'_, T> {
     }
 }
 
-impl<T> Deref for RefLeaf<'_, T> {
+impl<T: PartialEq, Align> PartialEq for RefLeaf<'_, T, Align> {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.data, &self.next) == (&other.data, &other.next)
+    }
+}
+
+impl<T: Eq, Align> Eq for RefLeaf<'_, T, Align> {}
+
+impl<T: PartialOrd, Align> PartialOrd for RefLeaf<'_, T, Align> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (&self.data, &self.next).partial_cmp(&(&other.data, &other.next))
+    }
+}
+
+impl<T: Ord, Align> Ord for RefLeaf<'_, T, Align> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.data, &self.next).cmp(&(&other.data, &other.next))
+    }
+}
+
+impl<T, Align> Deref for RefLeaf<'_, T, Align> {
     type Target = T;
 
     fn deref(&self) -> &T {
@@ -73,13 +112,44 @@ impl<T> Deref for RefLeaf<'_, T> {
     }
 }
 
-impl<T> DerefMut for RefLeaf<'_, T> {
+impl<T, Align> DerefMut for RefLeaf<'_, T, Align> {
     fn deref_mut(&mut self) -> &mut T {
         &mut self.data
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for RefLeaf<'_, T> {
+/// Hashes only the inner value of type `T`.
+impl<T: Hash, Align> Hash for RefLeaf<'_, T, Align> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<T: Interval, Align> Interval for &RefLeaf<'_, T, Align> {
+    type Point = T::Point;
+
+    fn start(&self) -> T::Point {
+        self.data.start()
+    }
+
+    fn end(&self) -> T::Point {
+        self.data.end()
+    }
+}
+
+/// Forwards to the inner value of type `T`.
+impl<M, T, Align> Summarize<M> for &RefLeaf<'_, T, Align>
+where
+    M: Monoid,
+    T: Summarize<M>,
+{
+    fn summarize(&self) -> M::Value {
+        self.data.summarize()
+    }
+}
+
+impl<T: fmt::Debug, Align> fmt::Debug for RefLeaf<'_, T, Align> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("RefLeaf")
             .field("addr", &(self as *const _))
@@ -89,6 +159,16 @@ impl<T: fmt::Debug> fmt::Debug for RefLeaf<'_, T> {
     }
 }
 
+impl<T, Align: FlagAlign> LeafExt for RefLeaf<'_, T, Align> {
+    fn user_flag(&self) -> bool {
+        self.next.flag()
+    }
+
+    fn set_user_flag(&self, flag: bool) {
+        self.next.set_flag(flag);
+    }
+}
+
 // SAFETY:
 // * `Self` is not `Send` or `Sync` because `RefLeaf` is not `Sync` (due to the
 //   `Cell` member).
@@ -98,16 +178,21 @@ impl<T: fmt::Debug> fmt::Debug for RefLeaf<'_, T> {
 //   function that modifies that field. `Self::next` retrieves the value
 //   appropriately.
 // * Clones of references behave like the original reference.
-unsafe impl<'a, T: BasicLeaf> LeafRef for &RefLeaf<'a, T> {
+unsafe impl<'a, T: BasicLeaf, Align> LeafRef for &RefLeaf<'a, T, Align> {
     type Options = TypedOptions<
         <T::Options as BasicOptions>::SizeType,
         <T::Options as BasicOptions>::StoreKeys,
         <T::Options as BasicOptions>::Fanout,
-        RefLeaf<'a, T>, /* Align */
+        RefLeaf<'a, T, Align>, /* Align */
+        <T::Options as BasicOptions>::SlabSize,
+        <T::Options as BasicOptions>::StoreHashes,
+        <T::Options as BasicOptions>::EmptyPolicy,
+        <T::Options as BasicOptions>::StoreMaxEnd,
+        <T::Options as BasicOptions>::Aggregate,
     >;
 
     fn next(&self) -> Option<LeafNext<Self>> {
-        self.next.get().map(|p| match p.get() {
+        self.next.get().map(|p| match p {
             // SAFETY: A tag of 0 corresponds to a leaf pointer.
             (ptr, 0) => LeafNext::Leaf(unsafe { ptr.as_ref() }),
             (ptr, _) => LeafNext::Data(ptr.cast()),
@@ -116,22 +201,49 @@ unsafe impl<'a, T: BasicLeaf> LeafRef for &RefLeaf<'a, T> {
 
     fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
         this.next.set(next.map(|n| match n {
-            LeafNext::Leaf(leaf) => TaggedPtr::new(NonNull::from(leaf), 0),
-            LeafNext::Data(data) => TaggedPtr::new(data.cast(), 1),
+            LeafNext::Leaf(leaf) => (NonNull::from(leaf), 0),
+            LeafNext::Data(data) => (data.cast(), 1),
         }))
     }
 
     fn size(&self) -> LeafSize<Self> {
         self.data.size()
     }
+
+    fn chunk_level(&self) -> usize {
+        self.data.chunk_level()
+    }
+}
+
+// SAFETY: `RefLeaf` doesn't use thread-local state, and `T` is `Send`, so
+// the data can be accessed from another thread as long as accesses from
+// different threads don't overlap.
+unsafe impl<T: BasicLeaf + Send, Align> SyncLeafRef
+    for &RefLeaf<'_, T, Align>
+{
+}
+
+// SAFETY: Copying and dropping references has no side effects, `next` is
+// only read (not written) when `set_next` isn't called, and `T` is `Sync`.
+unsafe impl<T: BasicLeaf + Sync, Align> SharedLeafRef
+    for &RefLeaf<'_, T, Align>
+{
+}
+
+// SAFETY: Copies of a reference have the same address, and leaves that exist
+// at the same time have different addresses.
+unsafe impl<T: BasicLeaf, Align> crate::LeafId for &RefLeaf<'_, T, Align> {
+    fn id(&self) -> usize {
+        *self as *const RefLeaf<'_, T, Align> as usize
+    }
 }
 
-#[cfg(skippy_debug)]
-impl<'a, T> crate::list::debug::LeafDebug for &RefLeaf<'a, T>
+#[cfg(feature = "debug-graphviz")]
+impl<'a, T, Align> crate::list::debug::LeafDebug for &RefLeaf<'a, T, Align>
 where
     T: BasicLeaf + fmt::Debug,
 {
-    type Id = *const RefLeaf<'a, T>;
+    type Id = *const RefLeaf<'a, T, Align>;
 
     fn id(&self) -> Self::Id {
         *self as _
@@ -0,0 +1,220 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Serde support for lists of basic leaves.
+//!
+//! A [`SkipList`] whose leaves implement [`Serialize`] is serialized as a
+//! sequence of its items. Lists of <code>[Rc]\<[RcLeaf]\></code> and
+//! <code>[Arc]\<[ArcLeaf]\></code> implement [`Deserialize`] directly, and
+//! lists of <code>&[RefLeaf]</code> can be deserialized with a
+//! [`LeafArena`] used as a [`DeserializeSeed`]. In each case the items are
+//! appended to the new list in a single bulk load, so deserializing *n* items
+//! takes Θ(*n*) time rather than Θ(*n* log *n*).
+
+#[cfg(target_has_atomic = "ptr")]
+use super::ArcLeaf;
+use super::{BasicLeaf, BoxLeaf, LeafArena, RcLeaf, RefLeaf};
+use crate::allocator::Allocator;
+use crate::{LeafRef, SkipList};
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use core::fmt;
+use core::iter;
+use core::marker::PhantomData;
+use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+impl<L, A> Serialize for SkipList<L, A>
+where
+    L: LeafRef + Serialize,
+    A: Allocator,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for leaf in self.iter() {
+            seq.serialize_element(&leaf)?;
+        }
+        seq.end()
+    }
+}
+
+impl<T: Serialize, Align> Serialize for RefLeaf<'_, T, Align> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<T: Serialize, Align> Serialize for RcLeaf<T, Align> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Serialize, Align> Serialize for ArcLeaf<T, Align> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<T: Serialize, Align> Serialize for BoxLeaf<T, Align> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Builds a list from a serialized sequence, turning each item into a leaf
+/// with `F`.
+struct ListVisitor<T, F> {
+    make_leaf: F,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, F> ListVisitor<T, F> {
+    fn new(make_leaf: F) -> Self {
+        Self {
+            make_leaf,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, L, F> Visitor<'de> for ListVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    L: LeafRef,
+    F: FnMut(T) -> L,
+{
+    type Value = SkipList<L>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<S>(mut self, mut seq: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let mut error = None;
+        let items = iter::from_fn(|| match seq.next_element() {
+            Ok(item) => item.map(&mut self.make_leaf),
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        });
+        let mut list = SkipList::new();
+        list.push_back_from(items);
+        match error {
+            Some(e) => Err(e),
+            None => Ok(list),
+        }
+    }
+}
+
+impl<'de, T, Align> Deserialize<'de> for SkipList<Rc<RcLeaf<T, Align>>>
+where
+    T: BasicLeaf + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ListVisitor::new(|data| {
+            Rc::new(RcLeaf::with_align(data))
+        }))
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<'de, T, Align> Deserialize<'de> for SkipList<Arc<ArcLeaf<T, Align>>>
+where
+    T: BasicLeaf + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ListVisitor::new(|data| {
+            Arc::new(ArcLeaf::with_align(data))
+        }))
+    }
+}
+
+/// Deserializes a list whose leaves are allocated in the arena.
+///
+/// This is the [`Deserialize`] counterpart for lists of
+/// <code>&[RefLeaf]</code>, which can't create their own leaves:
+///
+/// ```
+/// use serde::de::value::{Error, SeqDeserializer};
+/// use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+/// use skippy::basic::{self, BasicLeaf, LeafArena};
+///
+/// struct Data(u32);
+///
+/// impl BasicLeaf for Data {
+///     type Options = basic::options::Options;
+/// }
+///
+/// impl<'de> Deserialize<'de> for Data {
+///     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+///         u32::deserialize(d).map(Data)
+///     }
+/// }
+///
+/// let arena = LeafArena::<Data>::new();
+/// let seq = SeqDeserializer::<_, Error>::new([1, 2, 3].into_iter());
+/// let list = (&arena).deserialize(seq).unwrap();
+/// assert_eq!(list.iter().map(|leaf| leaf.0).sum::<u32>(), 6);
+/// assert_eq!(arena.len(), 3);
+/// ```
+///
+/// If deserialization fails partway through, leaves that were already
+/// allocated stay in the arena until it is dropped.
+impl<'de, 'a, T, Align> DeserializeSeed<'de> for &'a LeafArena<'a, T, Align>
+where
+    T: BasicLeaf + Deserialize<'de>,
+{
+    type Value = SkipList<&'a RefLeaf<'a, T, Align>>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ListVisitor::new(|data| self.alloc(data)))
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Error types.
+
+use core::fmt;
+
+/// An error returned by fallible [`SkipList`](crate::SkipList) operations.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// An item was expected to be in the list, but it isn't in any list.
+    NotInList,
+    /// An item to be inserted is already in a list.
+    AlreadyInList,
+    /// An item is in a different list than the one the operation was
+    /// performed on.
+    WrongList,
+    /// Memory for the list's internal nodes couldn't be allocated.
+    Alloc,
+    /// A handle no longer refers to the item it was created for.
+    StaleHandle,
+    /// An operation that requires items to be in sorted order was given
+    /// items that aren't.
+    NotSorted,
+    /// Data that was expected to be in a particular format isn't.
+    InvalidFormat,
+    /// A shared list is already borrowed in a way that conflicts with the
+    /// operation.
+    Borrowed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotInList => "item is not in a list",
+            Self::AlreadyInList => "item is already in a list",
+            Self::WrongList => "item is in a different list",
+            Self::Alloc => "memory allocation failed",
+            Self::StaleHandle => "handle is stale",
+            Self::NotSorted => "items are not sorted",
+            Self::InvalidFormat => "data is not in a valid format",
+            Self::Borrowed => "list is already borrowed",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// An inconsistency in the structure of a [`SkipList`], found by
+/// [`SkipList::validate`].
+///
+/// These errors indicate either a bug in this crate or, more likely, an
+/// incorrect implementation of [`LeafRef`], such as one whose
+/// [`set_next`] doesn't store its argument faithfully or whose [`size`]
+/// changes without the list being notified.
+///
+/// [`SkipList`]: crate::SkipList
+/// [`SkipList::validate`]: crate::SkipList::validate
+/// [`LeafRef`]: crate::LeafRef
+/// [`set_next`]: crate::LeafRef::set_next
+/// [`size`]: crate::LeafRef::size
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Following a node's `next` link doesn't lead to its next sibling or its
+    /// parent.
+    Link,
+    /// A node's stored size isn't the sum of its children's sizes.
+    Size,
+    /// A node's stored number of children or items doesn't match its actual
+    /// children.
+    Length,
+    /// A node's stored key isn't the key of its first child.
+    Key,
+    /// A node has fewer or more children than the list's fanout allows.
+    Fanout,
+    /// Not all items are at the same depth.
+    Depth,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Link => "node has an invalid link",
+            Self::Size => "node size doesn't match its children",
+            Self::Length => "node length doesn't match its children",
+            Self::Key => "node key doesn't match its first child",
+            Self::Fanout => "node has an invalid number of children",
+            Self::Depth => "items are at different depths",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
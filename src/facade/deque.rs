@@ -0,0 +1,279 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A double-ended queue with indexed insertion and removal.
+
+use crate::SkipList;
+use crate::basic::{self, BasicLeaf, BoxLeaf};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+/// The data stored in each leaf of a [`Deque`].
+///
+/// The element is only accessed through the [`Deque`] that owns the leaf, so
+/// references to it follow the usual borrowing rules for that [`Deque`]. The
+/// list itself never accesses the element, as every item has a size of 1.
+struct Slot<T>(UnsafeCell<T>);
+
+impl<T> BasicLeaf for Slot<T> {
+    type Options = basic::Options<usize>;
+
+    fn size(&self) -> usize {
+        1
+    }
+}
+
+type Leaf<T> = BoxLeaf<Slot<T>>;
+
+/// Allocates a leaf containing `value`.
+fn new_leaf<T>(value: T) -> Leaf<T> {
+    // SAFETY: Leaves are never accessible outside of `Deque`, which doesn't
+    // use them after they are freed.
+    unsafe { Leaf::new(Slot(UnsafeCell::new(value))) }
+}
+
+/// A double-ended queue that also supports indexing and insertion and
+/// removal at any index.
+///
+/// Unlike [`SkipList`], this type owns its elements: each element is stored
+/// in a [`BoxLeaf`] that is allocated and freed internally. Every operation
+/// except iteration takes worst-case Θ(log *n*) time.
+pub struct Deque<T> {
+    list: SkipList<Leaf<T>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Deque<T> {
+    /// Creates a new, empty [`Deque`].
+    pub fn new() -> Self {
+        Self {
+            list: SkipList::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gets the number of elements.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Checks whether the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets a reference to the element at `index`, or returns [`None`] if
+    /// `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // SAFETY: The leaf is owned by `self`, which is borrowed immutably for
+        // the lifetime of the returned reference.
+        self.list.get(&index).map(|leaf| unsafe { &*leaf.0.get() })
+    }
+
+    /// Gets a mutable reference to the element at `index`, or returns
+    /// [`None`] if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        // SAFETY: The leaf is owned by `self`, which is borrowed mutably for
+        // the lifetime of the returned reference.
+        self.list.get(&index).map(|leaf| unsafe { &mut *leaf.0.get() })
+    }
+
+    /// Gets a reference to the first element, or returns [`None`] if the
+    /// deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: See `Self::get`.
+        self.list.first().map(|leaf| unsafe { &*leaf.0.get() })
+    }
+
+    /// Gets a reference to the last element, or returns [`None`] if the
+    /// deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: See `Self::get`.
+        self.list.last().map(|leaf| unsafe { &*leaf.0.get() })
+    }
+
+    /// Adds an element to the front of the deque.
+    pub fn push_front(&mut self, value: T) {
+        self.list.push_front(new_leaf(value));
+    }
+
+    /// Adds an element to the back of the deque.
+    pub fn push_back(&mut self, value: T) {
+        self.list.push_back(new_leaf(value));
+    }
+
+    /// Removes and returns the first element, or returns [`None`] if the
+    /// deque is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.list.pop_front().map(free)
+    }
+
+    /// Removes and returns the last element, or returns [`None`] if the deque
+    /// is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.list.pop_back().map(free)
+    }
+
+    /// Inserts an element at `index`, shifting every element at or after
+    /// `index` back by one.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is greater than [`self.len()`].
+    ///
+    /// [`self.len()`]: Self::len
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "index out of bounds");
+        self.list.insert_at(&index, new_leaf(value));
+    }
+
+    /// Removes and returns the element at `index`, or returns [`None`] if
+    /// `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.list.remove_at(&index).map(free)
+    }
+
+    /// Removes every element.
+    pub fn clear(&mut self) {
+        // The old list frees its leaves when dropped.
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the elements.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.list.iter_exact(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Frees a leaf that has been removed from its list and returns its element.
+fn free<T>(leaf: Leaf<T>) -> T {
+    // SAFETY: The leaf was just removed from the list, and no copies of it
+    // exist outside of the list.
+    unsafe { BoxLeaf::into_inner(leaf) }.0.into_inner()
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for Deque<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.list.push_back_from(iter.into_iter().map(new_leaf));
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+/// Builds the deque in parallel; see
+/// <code>SkipList::<[BoxLeaf]>::[from_par_iter]</code>.
+///
+/// This implementation is available only when the crate feature `rayon` is
+/// enabled.
+///
+/// [from_par_iter]: SkipList::from_par_iter
+#[cfg(feature = "rayon")]
+impl<T: Send> rayon::iter::FromParallelIterator<T> for Deque<T> {
+    fn from_par_iter<I>(iter: I) -> Self
+    where
+        I: rayon::iter::IntoParallelIterator<Item = T>,
+    {
+        use rayon::iter::ParallelIterator;
+        let items =
+            iter.into_par_iter().map(|elem| Slot(UnsafeCell::new(elem)));
+        Self {
+            // SAFETY: See `new_leaf`.
+            list: unsafe { SkipList::<Leaf<T>>::from_par_iter(items) },
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`Deque`].
+///
+/// This type is returned by [`Deque::iter`].
+pub struct Iter<'a, T> {
+    iter: crate::iter::ListIter<'a, Leaf<T>>,
+    phantom: PhantomData<&'a Deque<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // SAFETY: The deque is borrowed immutably for `'a`.
+        self.iter.next().map(|leaf| unsafe { &*leaf.0.get() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        // SAFETY: See `Self::next`.
+        self.iter.next_back().map(|leaf| unsafe { &*leaf.0.get() })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
@@ -0,0 +1,39 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Owning collections built on [`SkipList`].
+//!
+//! [`SkipList`] is intrusive: it stores items of a user-provided [`LeafRef`]
+//! type and leaves allocating and freeing them to the user. The types in this
+//! module hide that entirely. They own their elements like the collections in
+//! [`alloc::collections`], allocating a leaf for each element internally.
+//!
+//! * [`Deque`], a sequence that supports Θ(log *n*) insertion and removal at
+//!   any index.
+//! * [`PriorityQueue`], a priority queue whose elements can be removed or
+//!   changed through handles.
+
+#[cfg(doc)]
+use crate::{LeafRef, SkipList};
+
+pub mod deque;
+pub mod priority_queue;
+
+pub use deque::Deque;
+pub use priority_queue::PriorityQueue;
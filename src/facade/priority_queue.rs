@@ -0,0 +1,242 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A priority queue with stable handles to its elements.
+
+use crate::SkipList;
+use crate::basic::{self, BasicLeaf, RcLeaf};
+use crate::options::NoSize;
+use alloc::rc::{Rc, Weak};
+use core::cell::UnsafeCell;
+use core::fmt;
+
+/// The data stored in each leaf of a [`PriorityQueue`].
+///
+/// The element is mutated only by [`PriorityQueue::replace`], which requires
+/// mutable access to the queue and removes the leaf from the list first, so
+/// no references to the element can exist at that time.
+struct Item<T>(UnsafeCell<T>);
+
+impl<T> Item<T> {
+    fn get(&self) -> &T {
+        // SAFETY: See the documentation for `Item`.
+        unsafe { &*self.0.get() }
+    }
+}
+
+impl<T> BasicLeaf for Item<T> {
+    type Options = basic::Options<NoSize, true>;
+}
+
+type Leaf<T> = Rc<RcLeaf<Item<T>>>;
+
+/// A min-priority queue that supports removing and changing the priority of
+/// any element.
+///
+/// [`Self::push`] returns a [`Handle`] that refers to the new element for as
+/// long as it remains in the queue. Unlike with
+/// [`BinaryHeap`](alloc::collections::BinaryHeap), a handle can be used to
+/// remove the element or change its value (e.g., to decrease its key) in
+/// Θ(log *n*) time.
+///
+/// Elements that compare equal are popped in the order they were pushed.
+pub struct PriorityQueue<T> {
+    list: SkipList<Leaf<T>>,
+    /// Identifies this queue in its [`Handle`]s.
+    id: Rc<()>,
+}
+
+/// A handle to an element in a [`PriorityQueue`].
+///
+/// The handle refers to the element until it is removed from the queue. After
+/// that, methods that take the handle treat it as referring to nothing.
+pub struct Handle<T> {
+    leaf: Weak<RcLeaf<Item<T>>>,
+    queue: Weak<()>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            leaf: self.leaf.clone(),
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.leaf.as_ptr()).finish()
+    }
+}
+
+/// Takes the element out of a leaf that has been removed from its list.
+fn into_value<T>(leaf: Leaf<T>) -> T {
+    match Rc::try_unwrap(leaf) {
+        Ok(leaf) => RcLeaf::into_inner(leaf).0.into_inner(),
+        Err(_) => unreachable!("removed leaf is still referenced"),
+    }
+}
+
+impl<T: Ord> PriorityQueue<T> {
+    /// Creates a new, empty priority queue.
+    pub fn new() -> Self {
+        Self {
+            list: SkipList::new(),
+            id: Rc::new(()),
+        }
+    }
+
+    /// Gets the number of elements.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Checks whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets the leaf that `handle` refers to, if it's in this queue.
+    fn leaf(&self, handle: &Handle<T>) -> Option<Leaf<T>> {
+        // Leaves are freed as soon as they leave the list they were pushed
+        // to, so a leaf that is still alive is in that list.
+        if Weak::as_ptr(&handle.queue) != Rc::as_ptr(&self.id) {
+            return None;
+        }
+        handle.leaf.upgrade()
+    }
+
+    fn insert(&mut self, leaf: Leaf<T>) {
+        let pos =
+            match self.list.find_last_with_cmp(|n| n.get().cmp(leaf.get())) {
+                Ok(pos) => Some(pos),
+                Err(pos) => pos,
+            };
+        self.list.insert_after_opt(pos, leaf);
+    }
+
+    /// Adds an element to the queue and returns a handle to it.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn push(&mut self, value: T) -> Handle<T> {
+        let leaf = Rc::new(RcLeaf::new(Item(UnsafeCell::new(value))));
+        let handle = Handle {
+            leaf: Rc::downgrade(&leaf),
+            queue: Rc::downgrade(&self.id),
+        };
+        self.insert(leaf);
+        handle
+    }
+
+    /// Gets the smallest element, or returns [`None`] if the queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn peek_min(&self) -> Option<&T> {
+        let leaf = self.list.first()?;
+        let value: *const T = leaf.get();
+        // SAFETY: The list keeps the leaf alive until it is removed, which
+        // requires mutable access to `self`.
+        Some(unsafe { &*value })
+    }
+
+    /// Removes and returns the smallest element, or returns [`None`] if the
+    /// queue is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.list.pop_front().map(into_value)
+    }
+
+    /// Checks whether `handle` refers to an element in this queue.
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.leaf(handle).is_some()
+    }
+
+    /// Gets the element that `handle` refers to, or returns [`None`] if it
+    /// isn't in this queue.
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        let leaf = self.leaf(handle)?;
+        let value: *const T = leaf.get();
+        // SAFETY: See `Self::peek_min`.
+        Some(unsafe { &*value })
+    }
+
+    /// Removes and returns the element that `handle` refers to, or returns
+    /// [`None`] if it isn't in this queue.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let leaf = self.leaf(handle)?;
+        self.list.remove(leaf.clone());
+        Some(into_value(leaf))
+    }
+
+    /// Replaces the element that `handle` refers to with `value`, moving it
+    /// to its new position, and returns the old element. `handle` continues
+    /// to refer to the element.
+    ///
+    /// If `handle` doesn't refer to an element in this queue, `value` is
+    /// returned in an [`Err`] value.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn replace(&mut self, handle: &Handle<T>, value: T) -> Result<T, T> {
+        let leaf = match self.leaf(handle) {
+            Some(leaf) => leaf,
+            None => return Err(value),
+        };
+        self.list.remove(leaf.clone());
+        // SAFETY: The leaf is no longer in the list, and any references to
+        // its element borrowed `self`, which is now borrowed mutably.
+        let old = core::mem::replace(unsafe { &mut *leaf.0.get() }, value);
+        self.insert(leaf);
+        Ok(old)
+    }
+
+    /// Returns an iterator over the elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.list.iter().map(|leaf| {
+            let value: *const T = leaf.get();
+            // SAFETY: See `Self::peek_min`.
+            unsafe { &*value }
+        })
+    }
+}
+
+impl<T: Ord> Default for PriorityQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for PriorityQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -41,12 +41,29 @@ use alloc::alloc as allocator;
 use allocator_fallback as allocator;
 
 pub mod basic;
+mod error;
+pub mod facade;
 mod list;
+pub mod mapped;
 pub mod options;
 mod persistent_alloc;
+pub mod rope;
 
-#[cfg(skippy_debug)]
+pub use error::{Error, ValidationError};
+pub use list::SubtreeBuilder;
+pub use list::UpdateGuard;
+#[cfg(feature = "chaos")]
+pub use list::chaos;
+#[cfg(feature = "debug-graphviz")]
 pub use list::debug;
-pub use list::{AllocItem, LeafNext, LeafRef, SkipList, This, iter};
-pub use options::{LeafSize, ListOptions, NoSize, Options};
+pub use list::{AllocItem, Cursor, LeafNext, LeafRef, ListSlice, NextHint};
+pub use list::{BatchOp, SpillCheckpoint, This, iter, persist, testing};
+pub use list::{DenseSkipList, Interval, Summarize};
+pub use list::{Disposer, DropPolicy, DuplicatePolicy, ListDump, ListStats};
+pub use list::{Entry, Finger, ListObserver, OccupiedEntry, VacantEntry};
+pub use list::{FrozenSkipList, SharedLeafRef, SyncLeafRef, SyncSkipList};
+pub use list::{Handle, LeafId};
+pub use list::{PartialFind, PartialGet, SharedSkipList, SkipList};
+pub use options::{CheckedSize, LeafSize, ListOptions, NoSize, Options};
+pub use persistent_alloc::AllocFallback;
 use persistent_alloc::PersistentAlloc;
@@ -0,0 +1,113 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! User-defined aggregates, used when
+//! [`ListOptions::Aggregate`](crate::ListOptions::Aggregate) is enabled.
+//!
+//! Each internal node caches the combined value of the items in its subtree.
+//! The value of a range of items is combined from the cached values of the
+//! subtrees that lie entirely within the range and the items along its
+//! boundaries.
+
+use super::node::{Down, LeafRef, NodeRef};
+use crate::options::{Aggregate, ListOptions, Monoid};
+
+/// Gets the value that an item contributes to an aggregate of type `M`; see
+/// [`ListOptions::Aggregate`].
+///
+/// The basic leaf types in [`crate::basic`] implement this trait when their
+/// data does.
+pub trait Summarize<M: Monoid> {
+    /// Gets the value of this item.
+    fn summarize(&self) -> M::Value;
+}
+
+/// Gets the aggregate value of the subtree rooted at `node`.
+///
+/// The results for internal nodes are cached. Like hashes, they're
+/// invalidated when the subtree changes and recomputed from the node's
+/// children when next needed.
+pub fn subtree_aggregate<L, M>(node: Down<L>) -> M::Value
+where
+    L: LeafRef + Summarize<M>,
+    M: Monoid,
+    L::Options: ListOptions<Aggregate = Aggregate<M>>,
+{
+    let node = match node {
+        Down::Leaf(leaf) => return leaf.summarize(),
+        Down::Internal(node) => node,
+    };
+    if let Some(value) = node.aggregate() {
+        return value;
+    }
+    let mut child = node.down();
+    let mut value = M::identity();
+    while let Some(down) = child {
+        value = M::combine(&value, &subtree_aggregate(down.clone()));
+        child = match down {
+            Down::Leaf(leaf) => leaf.next_sibling().map(Down::Leaf),
+            Down::Internal(node) => node.next_sibling().map(Down::Internal),
+        };
+    }
+    node.set_aggregate(value.clone());
+    value
+}
+
+/// Gets the aggregate value of the items at positions `start` to `end`
+/// (exclusive) within the subtree rooted at `node`.
+pub fn range_aggregate<L, M>(
+    node: Down<L>,
+    start: usize,
+    end: usize,
+) -> M::Value
+where
+    L: LeafRef + Summarize<M>,
+    M: Monoid,
+    L::Options: ListOptions<Aggregate = Aggregate<M>>,
+{
+    if start == 0 && end == node.leaf_count() {
+        return subtree_aggregate(node);
+    }
+    let mut child = match node {
+        // A leaf's range is either empty or the whole leaf.
+        Down::Leaf(_) => return M::identity(),
+        Down::Internal(node) => node.down(),
+    };
+    let mut value = M::identity();
+    let mut offset = 0;
+    while let Some(down) = child {
+        if offset >= end {
+            break;
+        }
+        let count = down.leaf_count();
+        if offset + count > start {
+            let child_start = start.saturating_sub(offset);
+            let child_end = (end - offset).min(count);
+            let child_value =
+                range_aggregate(down.clone(), child_start, child_end);
+            value = M::combine(&value, &child_value);
+        }
+        offset += count;
+        child = match down {
+            Down::Leaf(leaf) => leaf.next_sibling().map(Down::Leaf),
+            Down::Internal(node) => node.next_sibling().map(Down::Internal),
+        };
+    }
+    value
+}
@@ -0,0 +1,384 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::build::build;
+use super::destroy::{deconstruct_into_chain, destroy_node_list};
+use super::node::{Down, InternalNodeRef, LeafExt, LeafNext, LeafRef};
+use super::node::{Next, NodeRef};
+use super::retain::Chain;
+use super::{SkipList, roots_match};
+use crate::Error;
+use crate::allocator::Allocator;
+use alloc::vec::Vec;
+use core::ptr::NonNull;
+
+/// An operation in a batch passed to [`SkipList::apply_sorted_batch`].
+///
+/// `F` is the type of the functions used by [`BatchOp::Update`]. If a batch
+/// needs updates with different functions, a type like
+/// <code>[Box]\<dyn [FnOnce]\()></code> can be used.
+///
+/// [Box]: alloc::boxed::Box
+#[derive(Debug)]
+pub enum BatchOp<L, F = fn()> {
+    /// Inserts `item` directly after `after`, or at the start of the list if
+    /// `after` is [`None`].
+    Insert {
+        /// The item after which to insert `item`.
+        after: Option<L>,
+        /// The item to insert.
+        item: L,
+    },
+    /// Removes an item.
+    Remove(L),
+    /// Calls the function, which may change the size of the item, as with
+    /// [`SkipList::update`].
+    Update(L, F),
+}
+
+impl<L, F> BatchOp<L, F> {
+    /// Operations at the same position are applied in this order.
+    fn order(&self) -> u8 {
+        match self {
+            Self::Update(..) => 0,
+            Self::Remove(_) => 1,
+            Self::Insert {
+                ..
+            } => 2,
+        }
+    }
+}
+
+/// A validated operation and the position (number of preceding items) of the
+/// item it refers to, or [`None`] for insertions at the start of the list.
+type Positioned<L, F> = (Option<usize>, BatchOp<L, F>);
+
+/// Finds the positions of items in a list, starting from the ancestors of the
+/// previous item rather than from the root.
+///
+/// Finding the position of an item takes time proportional to the height of
+/// its lowest common ancestor with the previous item, so finding the
+/// positions of nearby items in order is fast.
+struct Positions<L: LeafRef> {
+    /// Each ancestor of the previous item, starting with its parent, and the
+    /// position just past the end of that ancestor's subtree.
+    path: Vec<(InternalNodeRef<L>, usize)>,
+}
+
+impl<L: LeafRef> Positions<L> {
+    fn new() -> Self {
+        Self {
+            path: Vec::new(),
+        }
+    }
+
+    /// Adds to `count` the number of leaves in the siblings after `node`, and
+    /// returns the parent of `node`.
+    fn count_siblings<N: NodeRef<Leaf = L>>(
+        mut node: N,
+        count: &mut usize,
+    ) -> Option<InternalNodeRef<L>> {
+        loop {
+            node = match node.next()? {
+                Next::Parent(parent) => return Some(parent),
+                Next::Sibling(node) => {
+                    *count += node.leaf_count();
+                    node
+                }
+            }
+        }
+    }
+
+    /// Gets the position of `item`, or returns an error if it isn't in
+    /// `list`.
+    fn get<A: Allocator>(
+        &mut self,
+        list: &SkipList<L, A>,
+        item: &L,
+    ) -> Result<usize, Error> {
+        if item.next().is_none() {
+            // `item` can only be in `list` as its only item.
+            return list.check_member(item).map(|_| 0);
+        }
+
+        // The number of leaves from `item` to the end of the subtree of the
+        // current ancestor.
+        let mut after = 1;
+        let mut new_path: Vec<(InternalNodeRef<L>, usize)> = Vec::new();
+        let mut node = Self::count_siblings(item.clone(), &mut after);
+        let (pos, level) = loop {
+            let level = new_path.len();
+            let parent = match node {
+                Some(parent) => parent,
+                None => {
+                    let root =
+                        new_path.last().map(|(n, _)| Down::Internal(*n));
+                    match (&list.root, root) {
+                        (Some(a), Some(b)) if roots_match(a, &b) => {}
+                        _ => return Err(Error::WrongList),
+                    }
+                    break (list.len() - after, self.path.len());
+                }
+            };
+            match self.path.get(level) {
+                Some((ancestor, end)) if *ancestor == parent => {
+                    break (end - after, level);
+                }
+                _ => {}
+            }
+            new_path.push((parent, after));
+            node = Self::count_siblings(parent, &mut after);
+        };
+        let new_path = new_path.into_iter().map(|(n, after)| (n, pos + after));
+        self.path.splice(..level, new_path);
+        Ok(pos)
+    }
+}
+
+/// Temporarily marks the items that will be inserted, so that later
+/// insertions of the same item fail. The marks are removed when this value is
+/// dropped, even if validation panics.
+struct Marks<L: LeafRef>(Vec<L>);
+
+impl<L: LeafRef> Marks<L> {
+    /// Marks `item`, or returns false if it was already marked.
+    fn mark(&mut self, item: &L) -> bool {
+        if item.next().is_some() {
+            return false;
+        }
+        // The pointer is never dereferenced.
+        item.set_next_leaf(Some(LeafNext::Data(NonNull::dangling())));
+        self.0.push(item.clone());
+        true
+    }
+}
+
+impl<L: LeafRef> Drop for Marks<L> {
+    fn drop(&mut self) {
+        for item in self.0.drain(..) {
+            item.set_next_leaf(None);
+        }
+    }
+}
+
+fn validate<L, A, F>(
+    list: &SkipList<L, A>,
+    ops: Vec<BatchOp<L, F>>,
+    results: &mut Vec<Result<(), Error>>,
+) -> Vec<Positioned<L, F>>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    // Find the position of each operation in a single ordered walk, without
+    // changing any items.
+    let mut positions = Positions::new();
+    let mut valid: Vec<Positioned<L, F>> = Vec::with_capacity(ops.len());
+    let mut removed = None;
+    for op in ops {
+        let pos = match &op {
+            BatchOp::Insert {
+                after: None,
+                ..
+            } => Ok(None),
+            BatchOp::Insert {
+                after: Some(item),
+                ..
+            }
+            | BatchOp::Remove(item)
+            | BatchOp::Update(item, _) => positions.get(list, item).map(Some),
+        };
+        let result = pos.and_then(|pos| match &op {
+            _ if valid.last().map_or(false, |(last, _)| pos < *last) => {
+                Err(Error::NotSorted)
+            }
+            BatchOp::Insert {
+                item,
+                ..
+            } => list.check_unlinked(item).map(|_| pos),
+            _ if removed == Some(pos) => Err(Error::NotInList),
+            BatchOp::Remove(_) => {
+                removed = Some(pos);
+                Ok(pos)
+            }
+            BatchOp::Update(..) => Ok(pos),
+        });
+        results.push(result.map(|pos| valid.push((pos, op))));
+    }
+
+    // Items can't be inserted more than once. This is checked only after the
+    // other checks pass, so that items are never left marked if they fail.
+    let mut marks = Marks(Vec::new());
+    let mut result = results.iter_mut().filter(|r| r.is_ok());
+    valid.retain(|(_, op)| {
+        let result = result.next().unwrap();
+        match op {
+            BatchOp::Insert {
+                item,
+                ..
+            } if !marks.mark(item) => {
+                *result = Err(Error::AlreadyInList);
+                false
+            }
+            _ => true,
+        }
+    });
+    drop(marks);
+
+    // Sorting is stable, and operations are already sorted by position.
+    valid.sort_by_key(|(pos, op)| (*pos, op.order()));
+    valid
+}
+
+/// Applies each operation with a separate traversal of the list.
+fn apply_each<L, A, F>(list: &mut SkipList<L, A>, ops: Vec<Positioned<L, F>>)
+where
+    L: LeafRef,
+    A: Allocator,
+    F: FnOnce(),
+{
+    let mut group = None;
+    let mut last_inserted: Option<L> = None;
+    let mut pending_remove = None;
+    for (pos, op) in ops {
+        if group != Some(pos) {
+            group = Some(pos);
+            last_inserted = None;
+            if let Some(item) = pending_remove.take() {
+                list.remove(item);
+            }
+        }
+        match op {
+            BatchOp::Update(item, update) => list.update(item, update),
+            BatchOp::Remove(item) => pending_remove = Some(item),
+            BatchOp::Insert {
+                after,
+                item,
+            } => {
+                list.insert_after_opt(last_inserted.or(after), item.clone());
+                last_inserted = Some(item);
+            }
+        }
+    }
+    if let Some(item) = pending_remove {
+        list.remove(item);
+    }
+}
+
+/// Applies all of the operations in a single pass over the list, and then
+/// rebuilds the list.
+fn apply_rebuild<L, A, F>(
+    list: &mut SkipList<L, A>,
+    ops: Vec<Positioned<L, F>>,
+) where
+    L: LeafRef,
+    A: Allocator,
+    F: FnOnce(),
+{
+    let mut ops = ops.into_iter().peekable();
+    let mut out = Chain::new();
+    let (mut rest, mut rest_len, mut spare) = match list.root.take() {
+        Some(root) => {
+            let (first, len, spare) = deconstruct_into_chain(root);
+            (Some(first), len, spare)
+        }
+        None => (None, 0, None),
+    };
+    let mut pos = None;
+    loop {
+        let mut leaf = None;
+        if pos.is_some() {
+            let next = rest.take().unwrap();
+            rest = next.next_sibling();
+            rest_len -= 1;
+            next.set_next_leaf(None);
+            leaf = Some(next);
+        }
+        while let Some((_, op)) = ops.next_if(|(p, _)| *p == pos) {
+            match op {
+                BatchOp::Update(_, update) => update(),
+                BatchOp::Remove(_) => leaf = None,
+                BatchOp::Insert {
+                    item,
+                    ..
+                } => {
+                    if let Some(leaf) = leaf.take() {
+                        out.push(leaf);
+                    }
+                    out.push(item);
+                }
+            }
+        }
+        if let Some(leaf) = leaf {
+            out.push(leaf);
+        }
+        if ops.peek().is_none() {
+            break;
+        }
+        pos = Some(pos.map_or(0, |p| p + 1));
+    }
+
+    // Append the items after the last operation.
+    if let Some(rest) = rest {
+        match &out.last {
+            Some(last) => {
+                last.set_next_leaf(Some(LeafNext::Leaf(rest.clone())))
+            }
+            None => out.first = Some(rest),
+        }
+        out.len += rest_len;
+    }
+    if let Some(first) = out.first {
+        list.root = Some(build(first, out.len, &mut spare, &list.alloc));
+    }
+
+    // SAFETY:
+    //
+    // * Every `InternalNode` in `spare` was allocated by `list.alloc`.
+    // * There are no other `InternalNodeRef`s that refer to these nodes,
+    //   since they are no longer used by the list.
+    unsafe {
+        destroy_node_list(&mut spare, &list.alloc);
+    }
+}
+
+pub fn apply_sorted_batch<L, A, I, F>(
+    list: &mut SkipList<L, A>,
+    ops: I,
+) -> Vec<Result<(), Error>>
+where
+    L: LeafRef,
+    A: Allocator,
+    I: IntoIterator<Item = BatchOp<L, F>>,
+    F: FnOnce(),
+{
+    let ops: Vec<_> = ops.into_iter().collect();
+    let mut results = Vec::with_capacity(ops.len());
+    let valid = validate(list, ops, &mut results);
+    let len = list.len();
+    let height = (usize::BITS - len.leading_zeros()) as usize;
+    // Applying the operations individually lets an observer be notified of
+    // each one.
+    if valid.len().saturating_mul(height) >= len && !list.is_observed() {
+        apply_rebuild(list, valid);
+    } else {
+        apply_each(list, valid);
+    }
+    results
+}
@@ -0,0 +1,282 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::node::{Down, NodeRef};
+use super::{LeafRef, SkipList, can_get_zero_sized_last};
+use crate::allocator::{Allocator, Global};
+use crate::options::{LeafSize, ListOptions};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::ControlFlow;
+use integral_constant::Bool;
+
+pub(super) fn next_sibling<L: LeafRef>(node: &Down<L>) -> Option<Down<L>> {
+    match node {
+        Down::Leaf(node) => node.next_sibling().map(Down::Leaf),
+        Down::Internal(node) => node.next_sibling().map(Down::Internal),
+    }
+}
+
+/// A search by index that was aborted before it finished.
+///
+/// This type is returned by [`SkipList::get_with_cmp_budgeted`] when the
+/// comparison function returns [`ControlFlow::Break`]. The search can be
+/// continued from where it stopped with [`Self::resume`]. While this value
+/// exists, the list is borrowed, so it can't be modified.
+pub struct PartialGet<'a, L, A = Global, B = ()>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a SkipList<L, A>,
+    node: Down<L>,
+    /// The combined size of the items before `node`.
+    size: LeafSize<L>,
+    reason: B,
+}
+
+impl<'a, L, A, B> PartialGet<'a, L, A, B>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Gets the list being searched.
+    pub fn list(&self) -> &'a SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets the value that the comparison function returned in
+    /// [`ControlFlow::Break`].
+    pub fn reason(&self) -> &B {
+        &self.reason
+    }
+
+    /// Discards the search and returns [`Self::reason`].
+    pub fn into_reason(self) -> B {
+        self.reason
+    }
+
+    /// Continues the search with the given comparison function.
+    ///
+    /// `cmp` should compare sizes in the same way as the function originally
+    /// passed to [`SkipList::get_with_cmp_budgeted`]. The first size it is
+    /// given is the one that it was last given before the search was
+    /// aborted.
+    pub fn resume<F>(self, cmp: F) -> ControlFlow<Self, Option<L>>
+    where
+        F: Fn(&LeafSize<L>) -> ControlFlow<B, Ordering>,
+    {
+        get(self.list, self.node, self.size, cmp)
+    }
+}
+
+/// Searches by index starting at `node`, where `size` is the combined size
+/// of the items before `node`.
+pub(super) fn get<'a, L, A, B, F>(
+    list: &'a SkipList<L, A>,
+    mut node: Down<L>,
+    mut size: LeafSize<L>,
+    cmp: F,
+) -> ControlFlow<PartialGet<'a, L, A, B>, Option<L>>
+where
+    L: LeafRef,
+    A: Allocator,
+    F: Fn(&LeafSize<L>) -> ControlFlow<B, Ordering>,
+{
+    loop {
+        // As in `SkipList::subtree_get`, `size` is updated in place, and
+        // the size of the node that is descended into (or returned) is
+        // subtracted afterward.
+        size += node.size();
+        let ord = match cmp(&size) {
+            ControlFlow::Continue(ord) => ord,
+            ControlFlow::Break(reason) => {
+                size -= node.size();
+                return ControlFlow::Break(PartialGet {
+                    list,
+                    node,
+                    size,
+                    reason,
+                });
+            }
+        };
+        if ord.is_le() {
+            if let Some(next) = next_sibling(&node) {
+                node = next;
+                continue;
+            }
+            let found = ord.is_eq()
+                && match &node {
+                    // Item is the last element of the list, has a size
+                    // of zero, and is at the right index.
+                    Down::Leaf(node) => {
+                        node.size() == Default::default()
+                            && can_get_zero_sized_last::<L>()
+                    }
+                    Down::Internal(_) => true,
+                };
+            if !found {
+                return ControlFlow::Continue(None);
+            }
+        }
+        size -= node.size();
+        node = match node {
+            Down::Leaf(node) => return ControlFlow::Continue(Some(node)),
+            Down::Internal(node) => node.down().unwrap(),
+        };
+    }
+}
+
+impl<L, A, B> fmt::Debug for PartialGet<'_, L, A, B>
+where
+    L: LeafRef,
+    A: Allocator,
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartialGet")
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A search in a sorted list that was aborted before it finished.
+///
+/// This type is returned by [`SkipList::find_with_cmp_budgeted`] when the
+/// comparison function returns [`ControlFlow::Break`]. The search can be
+/// continued from where it stopped with [`Self::resume`]. While this value
+/// exists, the list is borrowed, so it can't be modified.
+pub struct PartialFind<'a, L, A = Global, B = ()>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a SkipList<L, A>,
+    node: Down<L>,
+    /// The sibling before `node`, if it has been compared.
+    prev: Option<Down<L>>,
+    reason: B,
+}
+
+impl<'a, L, A, B> PartialFind<'a, L, A, B>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// Gets the list being searched.
+    pub fn list(&self) -> &'a SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets the value that the comparison function returned in
+    /// [`ControlFlow::Break`].
+    pub fn reason(&self) -> &B {
+        &self.reason
+    }
+
+    /// Discards the search and returns [`Self::reason`].
+    pub fn into_reason(self) -> B {
+        self.reason
+    }
+
+    /// Continues the search with the given comparison function.
+    ///
+    /// `cmp` should compare items in the same way as the function originally
+    /// passed to [`SkipList::find_with_cmp_budgeted`]. The first item it is
+    /// given is the one that it was last given before the search was
+    /// aborted.
+    pub fn resume<F>(self, cmp: F) -> ControlFlow<Self, Result<L, Option<L>>>
+    where
+        F: Fn(&L) -> ControlFlow<B, Ordering>,
+    {
+        find(self.list, self.node, self.prev, cmp)
+    }
+}
+
+/// Searches a sorted list starting at `node`, where `prev` is the sibling
+/// before `node` if it has been compared.
+pub(super) fn find<'a, L, A, B, F>(
+    list: &'a SkipList<L, A>,
+    mut node: Down<L>,
+    mut prev: Option<Down<L>>,
+    cmp: F,
+) -> ControlFlow<PartialFind<'a, L, A, B>, Result<L, Option<L>>>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+    F: Fn(&L) -> ControlFlow<B, Ordering>,
+{
+    loop {
+        let key = match &node {
+            Down::Leaf(node) => node.clone(),
+            Down::Internal(node) => node.key().unwrap(),
+        };
+        let ord = match cmp(&key) {
+            ControlFlow::Continue(ord) => ord,
+            ControlFlow::Break(reason) => {
+                return ControlFlow::Break(PartialFind {
+                    list,
+                    node,
+                    prev,
+                    reason,
+                });
+            }
+        };
+        let down = match ord {
+            Ordering::Equal => return ControlFlow::Continue(Ok(key)),
+            Ordering::Less => match next_sibling(&node) {
+                Some(next) => {
+                    prev = Some(node);
+                    node = next;
+                    continue;
+                }
+                None => node,
+            },
+            // Only the first node in the list has no previous sibling
+            // whose key compared less, as every other first child has
+            // the same key as its parent.
+            Ordering::Greater => match prev {
+                Some(prev) => prev,
+                None => return ControlFlow::Continue(Err(None)),
+            },
+        };
+        node = match down {
+            Down::Leaf(node) => {
+                return ControlFlow::Continue(Err(Some(node)));
+            }
+            Down::Internal(node) => node.down().unwrap(),
+        };
+        prev = None;
+    }
+}
+
+impl<L, A, B> fmt::Debug for PartialFind<'_, L, A, B>
+where
+    L: LeafRef,
+    A: Allocator,
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartialFind")
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
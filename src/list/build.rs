@@ -0,0 +1,132 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::node::{Down, InternalNodeRef, Next, NodeRef};
+use super::split::{InternalNodeSetup, split_content};
+use crate::PersistentAlloc;
+use crate::allocator::Allocator;
+
+/// Builds the internal levels of a list, bottom-up, above the sequence of
+/// `len` nodes starting at `first`, which must be linked only to each other.
+/// Nodes are taken from `spare` (a list of unused internal nodes, linked as
+/// siblings) before new ones are allocated.
+///
+/// Returns the new root.
+///
+/// # Time complexity
+///
+/// Θ(*n*).
+pub fn build<N, A>(
+    first: N,
+    len: usize,
+    spare: &mut Option<InternalNodeRef<N::Leaf>>,
+    alloc: &PersistentAlloc<A>,
+) -> Down<N::Leaf>
+where
+    N: NodeRef,
+    A: Allocator,
+{
+    build_level(first, len, 0, true, spare, alloc)
+}
+
+/// Builds the levels above the nodes of height `height`. Boundaries from
+/// [`LeafRef::chunk_level`] are considered only if `content` is true.
+///
+/// [`LeafRef::chunk_level`]: super::LeafRef::chunk_level
+fn build_level<N, A>(
+    first: N,
+    len: usize,
+    height: usize,
+    content: bool,
+    spare: &mut Option<InternalNodeRef<N::Leaf>>,
+    alloc: &PersistentAlloc<A>,
+) -> Down<N::Leaf>
+where
+    N: NodeRef,
+    A: Allocator,
+{
+    if len == 1 {
+        return first.as_down();
+    }
+    let mut iter = split_content(first, len, height, content);
+    let first = iter.next().unwrap().into_reused(spare, alloc);
+    let mut last = first;
+    let count = iter
+        .by_ref()
+        .map(|setup| {
+            let node = setup.into_reused(spare, alloc);
+            last.set_next(Some(Next::Sibling(node)));
+            last = node;
+        })
+        .count();
+    last.set_next(None);
+    // Only boundaries with a level greater than the new nodes' height matter
+    // above this level.
+    let content = content && iter.max_level > height + 1;
+    build_level(first, count + 1, height + 1, content, spare, alloc)
+}
+
+/// Builds the internal levels of a list above the sequence of `len` nodes
+/// starting at `first`, like [`build`], but gives the new nodes the lengths
+/// produced by `shape`: the lengths of the nodes at height 1, from left to
+/// right, followed by those at height 2, and so on up to the root.
+///
+/// The lengths at each height must add up to the number of nodes below them,
+/// and must otherwise describe a valid list.
+///
+/// Returns the new root.
+///
+/// # Time complexity
+///
+/// Θ(*n*).
+pub fn build_shape<N, A, I>(
+    first: N,
+    len: usize,
+    shape: &mut I,
+    alloc: &PersistentAlloc<A>,
+) -> Down<N::Leaf>
+where
+    N: NodeRef,
+    A: Allocator,
+    I: Iterator<Item = usize>,
+{
+    if len == 1 {
+        return first.as_down();
+    }
+    let mut remaining = len;
+    let mut node = Some(first);
+    let mut new_first = None;
+    let mut last: Option<InternalNodeRef<N::Leaf>> = None;
+    let mut count = 0;
+    while remaining > 0 {
+        let node_len = shape.next().expect("shape ended early");
+        remaining -= node_len;
+        let setup = InternalNodeSetup::new(node.take().unwrap(), node_len);
+        node = setup.next_sibling();
+        let new = setup.into_new(alloc);
+        if let Some(last) = last {
+            last.set_next(Some(Next::Sibling(new)));
+        }
+        new_first.get_or_insert(new);
+        last = Some(new);
+        count += 1;
+    }
+    last.unwrap().set_next(None);
+    build_shape(new_first.unwrap(), count, shape, alloc)
+}
@@ -0,0 +1,62 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! Random structural changes for testing.
+//!
+//! When the crate feature `chaos` is enabled, the list occasionally splits
+//! nodes that aren't full and merges nodes that aren't underfull, as long as
+//! the resulting nodes still have a valid number of children. This exercises
+//! code paths (in both this crate and implementations of [`LeafRef`]) that
+//! would otherwise run only for particular sizes of lists.
+//!
+//! The changes are pseudorandom and deterministic for a given seed, which
+//! can be set with [`set_seed`].
+//!
+//! [`LeafRef`]: crate::LeafRef
+
+#[cfg(feature = "chaos")]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+#[cfg(feature = "chaos")]
+static STATE: AtomicU32 = AtomicU32::new(0x9e3779b9);
+
+/// Sets the seed used to decide when to force structural changes.
+///
+/// A seed of 0 is treated as 1.
+#[cfg(feature = "chaos")]
+pub fn set_seed(seed: u32) {
+    STATE.store(seed.max(1), Ordering::Relaxed);
+}
+
+/// Returns whether a split or merge that isn't required should be performed
+/// anyway. This always returns false when the feature `chaos` is disabled.
+pub(crate) fn force() -> bool {
+    #[cfg(feature = "chaos")]
+    {
+        // xorshift32. Races between threads may repeat values, which is
+        // harmless.
+        let mut x = STATE.load(Ordering::Relaxed);
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        STATE.store(x, Ordering::Relaxed);
+        x % 4 == 0
+    }
+    #[cfg(not(feature = "chaos"))]
+    false
+}
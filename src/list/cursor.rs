@@ -0,0 +1,178 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::node::SizeExt;
+use super::{LeafRef, SkipList};
+use crate::allocator::Allocator;
+use crate::options::LeafSize;
+
+/// A position in a [`SkipList`] that keeps track of its own index.
+///
+/// A cursor refers to an item in a list and caches that item's index, so
+/// moving to nearby items doesn't require recomputing the index from scratch
+/// with [`SkipList::index`].
+///
+/// The cached index is not updated when the list is modified. After modifying
+/// the list in a way that could change the cursor's index (e.g., inserting
+/// or removing items before it, or changing the size of those items), call
+/// [`Self::refresh`]. If the cursor's item is removed from the list, the
+/// cursor must not be used until it is moved to an item in a list with
+/// [`Self::set`].
+pub struct Cursor<L: LeafRef> {
+    item: L,
+    position: LeafSize<L>,
+}
+
+impl<L: LeafRef> Cursor<L> {
+    /// Creates a new cursor at `item`.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn new(item: L) -> Self {
+        Self {
+            position: SkipList::index(item.clone()),
+            item,
+        }
+    }
+
+    /// Gets the item the cursor is at.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn item(&self) -> L {
+        self.item.clone()
+    }
+
+    /// Gets the index of the item the cursor is at.
+    ///
+    /// This is equivalent to <code>[SkipList::index]\([self.item()])</code>,
+    /// as of the last time the cursor was moved or refreshed.
+    ///
+    /// [self.item()]: Self::item
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn position(&self) -> LeafSize<L> {
+        self.position.clone()
+    }
+
+    /// Moves the cursor to `item`.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn set(&mut self, item: L) {
+        *self = Self::new(item);
+    }
+
+    /// Recomputes the cursor's index after the list has been modified.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn refresh(&mut self) {
+        self.position = SkipList::index(self.item.clone());
+    }
+
+    /// Moves the cursor to the next item.
+    ///
+    /// If the cursor is at the last item in the list, it is not moved, and
+    /// this method returns false.
+    ///
+    /// # Time complexity
+    ///
+    /// Same as [`SkipList::next`].
+    pub fn move_next(&mut self) -> bool {
+        let next = match SkipList::next(self.item.clone()) {
+            Some(next) => next,
+            None => return false,
+        };
+        self.position += self.item.size();
+        self.item = next;
+        true
+    }
+
+    /// Moves the cursor to the previous item.
+    ///
+    /// If the cursor is at the first item in the list, it is not moved, and
+    /// this method returns false.
+    ///
+    /// # Time complexity
+    ///
+    /// Same as [`SkipList::previous`].
+    pub fn move_prev(&mut self) -> bool {
+        let prev = match SkipList::previous(self.item.clone()) {
+            Some(prev) => prev,
+            None => return false,
+        };
+        self.position -= prev.size();
+        self.item = prev;
+        true
+    }
+
+    /// Moves the cursor to the item at `index`, which is determined in the
+    /// same way as [`SkipList::get`].
+    ///
+    /// `list` must be the list that contains the cursor's item. If there is no
+    /// item at `index`, the cursor is not moved, and this method returns
+    /// false.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*). Seeking forward is usually faster when `index`
+    /// is close to the cursor's current position.
+    pub fn seek<A>(
+        &mut self,
+        list: &SkipList<L, A>,
+        index: &LeafSize<L>,
+    ) -> bool
+    where
+        A: Allocator,
+        LeafSize<L>: Ord,
+    {
+        let result = if *index >= self.position {
+            let offset = index.clone().sub(self.position.clone());
+            SkipList::get_after_with_cmp_offset(self.item.clone(), |size| {
+                size.cmp(&offset)
+            })
+            .map(|(item, offset)| (item, offset.add(self.position.clone())))
+        } else {
+            list.get_with_cmp_offset(|size| size.cmp(index))
+        };
+        if let Some((item, position)) = result {
+            self.item = item;
+            self.position = position;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<L: LeafRef> Clone for Cursor<L> {
+    fn clone(&self) -> Self {
+        Self {
+            item: self.item.clone(),
+            position: self.position.clone(),
+        }
+    }
+}
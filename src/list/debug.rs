@@ -17,6 +17,14 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
+//! Graphviz rendering of a list's internal structure.
+//!
+//! This module is available only when the crate feature `debug-graphviz` is
+//! enabled. [`SkipList::debug`] returns a value whose [`Display`] output is a
+//! graph in the DOT format, which can be rendered with a command like
+//! `dot -Tpng -ograph.png graph.dot`. For a plain-text summary that doesn't
+//! require [`LeafDebug`], see [`SkipList::dump`].
+
 use super::SkipList;
 use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
 use crate::allocator::Allocator;
@@ -44,12 +52,26 @@ impl<T: Ord> IdMap<T> {
     }
 }
 
+/// A [`LeafRef`] that can be shown in a graph.
+///
+/// This trait is implemented by the leaves in [`basic`](crate::basic) when
+/// their data implements [`Debug`].
 pub trait LeafDebug: LeafRef {
+    /// A value that uniquely identifies a leaf, like its address.
     type Id: Ord;
+
+    /// Gets the identifier of this leaf.
     fn id(&self) -> Self::Id;
+
+    /// Writes the label shown for this leaf's data.
     fn fmt_data(&self, f: &mut Formatter<'_>) -> fmt::Result;
 }
 
+/// Identifiers assigned to the nodes in a graph.
+///
+/// Reusing the same state across multiple calls to [`SkipList::debug`] keeps
+/// the names of nodes consistent, which makes it easier to compare graphs of
+/// a list before and after a change.
 pub struct State<L: LeafDebug> {
     internal_map: IdMap<usize>,
     leaf_map: IdMap<L::Id>,
@@ -57,6 +79,7 @@ pub struct State<L: LeafDebug> {
 }
 
 impl<L: LeafDebug> State<L> {
+    /// Creates a new state with no assigned identifiers.
     pub fn new() -> Self {
         Self {
             internal_map: IdMap::new(),
@@ -86,6 +109,8 @@ where
     A: Allocator,
     LeafSize<L>: Debug,
 {
+    /// Returns a value that displays the list's internal structure as a
+    /// graph in the DOT format.
     pub fn debug<'a>(
         &'a self,
         state: &'a mut State<L>,
@@ -105,6 +130,9 @@ where
     }
 }
 
+/// A graph of a list's internal structure, returned by [`SkipList::debug`].
+///
+/// The graph is produced by this type's [`Display`] implementation.
 #[must_use]
 pub struct ListDebug<'a, L, A>
 where
@@ -0,0 +1,312 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, SharedLeafRef, SkipList, can_get_zero_sized_last};
+use crate::allocator::{Allocator, Global};
+use crate::options::{Dimension, LeafSize, ListOptions};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::iter::Cloned;
+use core::slice;
+use integral_constant::Bool;
+
+/// A [`SkipList`] that can't be modified, with a flat index for faster
+/// queries.
+///
+/// This type is returned by [`SkipList::freeze_dense`]. Like
+/// [`FrozenSkipList`](super::FrozenSkipList), it provides the read-only
+/// operations of [`SkipList`], but in addition to the list itself, it stores
+/// every item in a contiguous array along with the index at which each item
+/// ends. Queries are then binary searches over these arrays rather than walks
+/// through the list's nodes, which is considerably more cache-friendly.
+///
+/// The arrays take Θ(*n*) extra memory and are built in Θ(*n*) time. Use
+/// [`Self::thaw`] to discard them and get a list that can be modified again.
+///
+/// If `L` implements [`SharedLeafRef`] and `A` is [`Sync`], this type is
+/// [`Sync`].
+pub struct DenseSkipList<L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: SkipList<L, A>,
+    items: Vec<L>,
+    /// `ends[i]` is the combined size of `items[..=i]`.
+    ends: Vec<LeafSize<L>>,
+}
+
+impl<L, A> DenseSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(list: SkipList<L, A>) -> Self {
+        let mut items = Vec::with_capacity(list.len());
+        let mut ends = Vec::with_capacity(list.len());
+        let mut end = LeafSize::<L>::default();
+        for item in list.iter() {
+            end += item.size();
+            ends.push(end.clone());
+            items.push(item);
+        }
+        Self {
+            list,
+            items,
+            ends,
+        }
+    }
+
+    /// Turns this list back into a [`SkipList`] that can be modified.
+    pub fn thaw(self) -> SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets the items in the list as a slice.
+    pub fn as_slice(&self) -> &[L] {
+        &self.items
+    }
+
+    /// See [`SkipList::len`].
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// See [`SkipList::is_empty`].
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// See [`SkipList::size`].
+    pub fn size(&self) -> LeafSize<L> {
+        self.ends.last().cloned().unwrap_or_default()
+    }
+
+    /// See [`SkipList::get`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get<S>(&self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.get_with_cmp(|size| size.borrow().cmp(index))
+    }
+
+    /// See [`SkipList::get_with`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_with<S>(&self, index: &S) -> Option<L>
+    where
+        S: ?Sized,
+        LeafSize<L>: PartialOrd<S>,
+    {
+        self.get_with_cmp(|size| {
+            size.partial_cmp(index).unwrap_or_else(
+                #[cold]
+                || panic!("`partial_cmp` returned `None`"),
+            )
+        })
+    }
+
+    /// See [`SkipList::get_by`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_by<D>(&self, index: &D::Value) -> Option<L>
+    where
+        D: Dimension<LeafSize<L>>,
+    {
+        self.get_with_cmp(|size| D::get(size).cmp(index))
+    }
+
+    /// See [`SkipList::get_with_cmp`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        // The desired item is the first one whose end is past the index.
+        let i = self.ends.partition_point(|end| cmp(end).is_le());
+        if let Some(item) = self.items.get(i) {
+            return Some(item.clone());
+        }
+        let last = self.items.last()?;
+        (cmp(self.ends.last()?).is_eq()
+            && last.size() == Default::default()
+            && can_get_zero_sized_last::<L>())
+        .then(|| last.clone())
+    }
+
+    /// See [`SkipList::first`].
+    pub fn first(&self) -> Option<L> {
+        self.items.first().cloned()
+    }
+
+    /// See [`SkipList::last`].
+    pub fn last(&self) -> Option<L> {
+        self.items.last().cloned()
+    }
+
+    /// Gets an iterator over the items in the list.
+    pub fn iter(&self) -> Cloned<slice::Iter<'_, L>> {
+        self.items.iter().cloned()
+    }
+}
+
+impl<L, A> DenseSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// See [`SkipList::find`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn find<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.find_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// See [`SkipList::find_with`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn find_with<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: ?Sized,
+        L: PartialOrd<K>,
+    {
+        self.find_with_cmp(|item| {
+            item.partial_cmp(key).unwrap_or_else(
+                #[cold]
+                || panic!("`partial_cmp` returned `None`"),
+            )
+        })
+    }
+
+    /// See [`SkipList::find_with_cmp`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn find_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        let i = self.lower_bound(&cmp);
+        match self.items.get(i) {
+            Some(item) if cmp(item).is_eq() => Ok(item.clone()),
+            _ => Err(i.checked_sub(1).map(|i| self.items[i].clone())),
+        }
+    }
+
+    /// See [`SkipList::rank`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn rank<K>(&self, key: &K) -> LeafSize<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.rank_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// See [`SkipList::rank_with_cmp`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn rank_with_cmp<F>(&self, cmp: F) -> LeafSize<L>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        match self.lower_bound(&cmp).checked_sub(1) {
+            Some(i) => self.ends[i].clone(),
+            None => Default::default(),
+        }
+    }
+
+    /// See [`SkipList::nearest_by_key`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn nearest_by_key<K>(&self, key: &K) -> (Option<L>, Option<L>)
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        let cmp = |item: &L| item.borrow().cmp(key);
+        let start = self.lower_bound(&cmp);
+        let end = self.items.partition_point(|item| cmp(item).is_le());
+        let prev = start.checked_sub(1).map(|i| self.items[i].clone());
+        (prev, self.items.get(end).cloned())
+    }
+
+    /// Gets the index of the first item for which `cmp` doesn't return
+    /// [`Ordering::Less`].
+    fn lower_bound<F>(&self, cmp: &F) -> usize
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        self.items.partition_point(|item| cmp(item).is_lt())
+    }
+}
+
+impl<L, A> From<DenseSkipList<L, A>> for SkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn from(list: DenseSkipList<L, A>) -> Self {
+        list.thaw()
+    }
+}
+
+// SAFETY: Like `FrozenSkipList`, `DenseSkipList` provides no way to modify
+// the list, and its methods only read the list's nodes and the arrays built
+// when it was created (never the list's observer). The only operations
+// performed on the stored leaves are those `SharedLeafRef` permits to be
+// performed concurrently (plus the comparisons the user provides, as with
+// `FrozenSkipList`), and `A` is `Sync`. Leaves obtained from the list can't
+// be sent to other threads (as `L` is not `Send`).
+unsafe impl<L, A> Sync for DenseSkipList<L, A>
+where
+    L: SharedLeafRef,
+    A: Allocator + Sync,
+{
+}
@@ -17,20 +17,53 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::SkipList;
 use super::destroy_safety::can_safely_destroy;
-use super::node::{Down, InternalNodeRef, LeafExt, LeafRef, Next, NodeRef};
+use super::node::{Down, InternalNodeRef, LeafExt, LeafNext, LeafRef};
+use super::node::{Next, NodeRef};
+use crate::PersistentAlloc;
 use crate::allocator::Allocator;
 
 /// Returns a node list that can be passed to [`destroy_node_list`].
 pub fn deconstruct<L: LeafRef>(root: Down<L>) -> Option<InternalNodeRef<L>> {
-    deconstruct_impl(root, None)
+    deconstruct_impl(root, None, true)
+}
+
+/// Like [`deconstruct`], but doesn't access the leaves, which may have been
+/// relinked outside of the list.
+pub fn deconstruct_internal<L: LeafRef>(
+    root: Down<L>,
+) -> Option<InternalNodeRef<L>> {
+    deconstruct_impl(root, None, false)
+}
+
+/// Links every leaf in the list directly to the next leaf, so that the leaves
+/// form a single chain that doesn't depend on any internal nodes.
+///
+/// Returns the first leaf, the number of leaves, and a node list that can be
+/// passed to [`destroy_node_list`].
+pub fn deconstruct_into_chain<L: LeafRef>(
+    root: Down<L>,
+) -> (L, usize, Option<InternalNodeRef<L>>) {
+    let first = SkipList::subtree_first(root.clone());
+    let mut leaf = first.clone();
+    let mut len = 1;
+    while let Some(next) = SkipList::next(leaf.clone()) {
+        leaf.set_next_leaf(Some(LeafNext::Leaf(next.clone())));
+        leaf = next;
+        len += 1;
+    }
+    leaf.set_next_leaf(None);
+    (first, len, deconstruct_impl(root, None, false))
 }
 
 fn deconstruct_impl<L: LeafRef>(
     root: Down<L>,
     mut head: Option<InternalNodeRef<L>>,
+    unlink_leaves: bool,
 ) -> Option<InternalNodeRef<L>> {
     match root {
+        Down::Leaf(_) if !unlink_leaves => {}
         Down::Leaf(mut node) => loop {
             let next = node.next_sibling();
             node.set_next_leaf(None);
@@ -42,7 +75,7 @@ fn deconstruct_impl<L: LeafRef>(
         },
         Down::Internal(mut node) => loop {
             if let Some(down) = node.down() {
-                head = deconstruct_impl(down, head);
+                head = deconstruct_impl(down, head, unlink_leaves);
             }
             let next = node.next_sibling();
             node.set_next(head.map(Next::Sibling));
@@ -64,7 +97,7 @@ fn deconstruct_impl<L: LeafRef>(
 ///   the list.
 pub unsafe fn destroy_node_list<L: LeafRef, A: Allocator>(
     head: &mut Option<InternalNodeRef<L>>,
-    alloc: &A,
+    alloc: &PersistentAlloc<A>,
 ) {
     if !can_safely_destroy() {
         return;
@@ -0,0 +1,140 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::destroy_safety::can_safely_destroy;
+use super::node::{Down, InternalNodeRef, LeafExt, Next, NodeRef};
+use super::traverse::get_last_sibling;
+use super::{DropPolicy, LeafRef, SkipList};
+use crate::allocator::{Allocator, Global};
+
+/// Destroys a [`SkipList`] a few nodes at a time.
+///
+/// This type is returned by [`SkipList::dispose_incremental`]. Dropping a
+/// large list frees every internal node at once, which can take a long time;
+/// [`Self::step`] instead frees a bounded number of nodes per call, so the
+/// work can be spread out (e.g., across the frames of an interactive
+/// application). Items are unlinked from the list, and passed to
+/// [`LeafRef::on_list_drop`] if the list's [`DropPolicy`] is
+/// [`DropPolicy::Release`], as the nodes that contain them are freed.
+///
+/// Any remaining nodes are freed when this value is dropped.
+pub struct Disposer<L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// The list being destroyed, whose root has been removed. This owns the
+    /// allocator.
+    list: SkipList<L, A>,
+    /// Internal nodes that haven't been freed yet, linked as siblings.
+    pending: Option<InternalNodeRef<L>>,
+}
+
+impl<L, A> Disposer<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(mut list: SkipList<L, A>) -> Self {
+        let pending = match list.root.take() {
+            Some(Down::Internal(node)) => Some(node),
+            Some(Down::Leaf(leaf)) => {
+                leaf.set_next_leaf(None);
+                if list.drop_policy == DropPolicy::Release {
+                    leaf.list_dropped();
+                }
+                None
+            }
+            None => None,
+        };
+        Self {
+            list,
+            pending,
+        }
+    }
+
+    /// Frees up to `n` internal nodes, and unlinks the items they contain.
+    ///
+    /// Each node contains at most <code>[Fanout] + 1</code> children, so the
+    /// time taken by each call is bounded. Returns whether every node has
+    /// been freed.
+    ///
+    /// [Fanout]: crate::ListOptions::Fanout
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn step(&mut self, n: usize) -> bool {
+        for _ in 0..n {
+            let node = match self.pending {
+                Some(node) => node,
+                None => break,
+            };
+            if !can_safely_destroy() {
+                // As in `SkipList::drop`, leak the remaining nodes.
+                self.pending = None;
+                break;
+            }
+            self.pending = node.next_sibling();
+            match node.down().unwrap() {
+                Down::Leaf(mut leaf) => loop {
+                    let next = leaf.next_sibling();
+                    leaf.set_next_leaf(None);
+                    if self.list.drop_policy == DropPolicy::Release {
+                        leaf.list_dropped();
+                    }
+                    leaf = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+                },
+                Down::Internal(first) => {
+                    let last = get_last_sibling(first);
+                    last.set_next(self.pending.map(Next::Sibling));
+                    self.pending = Some(first);
+                }
+            }
+            // SAFETY:
+            //
+            // * Every `InternalNode` in the list was allocated by
+            //   `self.list.alloc`.
+            // * There are no other `InternalNodeRef`s that refer to this
+            //   node, since its children are no longer linked to it.
+            unsafe {
+                node.dealloc(&self.list.alloc);
+            }
+        }
+        self.is_done()
+    }
+
+    /// Checks whether every node has been freed.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_none()
+    }
+}
+
+impl<L, A> Drop for Disposer<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        self.step(usize::MAX);
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! A plain-text dump of a list's structure.
+
+use super::SkipList;
+use super::node::{Down, LeafRef, NodeRef};
+use crate::allocator::Allocator;
+use crate::options::LeafSize;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// Displays the internal structure of a [`SkipList`] as plain text.
+///
+/// This type is returned by [`SkipList::dump`]. Each line of output shows one
+/// level of the list, starting with the root. Internal nodes are shown with
+/// their length (number of children) and size; items are shown with their
+/// size. Nodes with the same parent are grouped together, and groups are
+/// separated by `|`. For example:
+///
+/// ```text
+/// 0: [len 2, size 5]
+/// 1: [len 3, size 3] [len 2, size 2]
+/// 2: (1) (1) (1) | (1) (1)
+/// ```
+///
+/// [`SkipList`]: super::SkipList
+/// [`SkipList::dump`]: super::SkipList::dump
+#[must_use]
+pub struct ListDump<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) list: &'a SkipList<L, A>,
+}
+
+impl<L, A> Display for ListDump<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    LeafSize<L>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The first node of each group of siblings in the current level.
+        let mut level: Vec<_> = self.list.root.clone().into_iter().collect();
+        let mut depth = 0;
+        while !level.is_empty() {
+            write!(f, "{depth}:")?;
+            let mut next_level = Vec::new();
+            for (i, first) in level.drain(..).enumerate() {
+                if i > 0 {
+                    write!(f, " |")?;
+                }
+                match first {
+                    Down::Leaf(mut node) => loop {
+                        write!(f, " ({:?})", node.size())?;
+                        node = match node.next_sibling() {
+                            Some(next) => next,
+                            None => break,
+                        };
+                    },
+                    Down::Internal(mut node) => loop {
+                        write!(
+                            f,
+                            " [len {}, size {:?}]",
+                            node.len.get(),
+                            node.size(),
+                        )?;
+                        next_level.extend(node.down());
+                        node = match node.next_sibling() {
+                            Some(next) => next,
+                            None => break,
+                        };
+                    },
+                }
+            }
+            writeln!(f)?;
+            level = next_level;
+            depth += 1;
+        }
+        Ok(())
+    }
+}
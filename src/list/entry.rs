@@ -0,0 +1,176 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, SkipList};
+use crate::allocator::{Allocator, Global};
+use crate::options::ListOptions;
+use integral_constant::Bool;
+
+/// A position in a sorted [`SkipList`] that may or may not contain an item.
+///
+/// This type is returned by [`SkipList::entry`]. The list is searched only
+/// once, so inserting an item through a [`VacantEntry`] doesn't need to find
+/// its position again.
+pub enum Entry<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// The list contains an item with the given key.
+    Occupied(OccupiedEntry<'a, L, A>),
+    /// The list doesn't contain an item with the given key.
+    Vacant(VacantEntry<'a, L, A>),
+}
+
+/// An [`Entry`] for an item in the list.
+pub struct OccupiedEntry<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a mut SkipList<L, A>,
+    item: L,
+}
+
+/// An [`Entry`] for a key that isn't in the list.
+pub struct VacantEntry<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a mut SkipList<L, A>,
+    /// The item after which a new item should be inserted.
+    prev: Option<L>,
+}
+
+impl<'a, L, A> Entry<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    pub(super) fn new(
+        list: &'a mut SkipList<L, A>,
+        found: Result<L, Option<L>>,
+    ) -> Self {
+        match found {
+            Ok(item) => Self::Occupied(OccupiedEntry {
+                list,
+                item,
+            }),
+            Err(prev) => Self::Vacant(VacantEntry {
+                list,
+                prev,
+            }),
+        }
+    }
+
+    /// Returns the item in the list, or inserts the item returned by `make`
+    /// and returns it if there is no such item.
+    ///
+    /// The inserted item must have the key that was passed to
+    /// [`SkipList::entry`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the item returned by `make` is already in a
+    /// list. Memory may be leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn or_insert_with<F>(self, make: F) -> L
+    where
+        F: FnOnce() -> L,
+    {
+        match self {
+            Self::Occupied(entry) => entry.get(),
+            Self::Vacant(entry) => {
+                let item = make();
+                entry.insert(item.clone());
+                item
+            }
+        }
+    }
+}
+
+impl<L, A> OccupiedEntry<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// Gets the item.
+    pub fn get(&self) -> L {
+        self.item.clone()
+    }
+
+    /// Removes the item from the list and returns it.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn remove(self) -> L {
+        self.list.remove(self.item.clone());
+        self.item
+    }
+
+    /// Replaces the item with `new`, which must have the same key, and
+    /// returns the old item.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `new` is already in a list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn replace(self, new: L) -> L {
+        self.list.replace(self.item.clone(), new);
+        self.item
+    }
+}
+
+impl<L, A> VacantEntry<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// Gets the item that would precede an item inserted at this entry, or
+    /// [`None`] if it would be the first item.
+    pub fn prev(&self) -> Option<L> {
+        self.prev.clone()
+    }
+
+    /// Inserts `item`, which must have the key that was passed to
+    /// [`SkipList::entry`], at this entry's position.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` is already in a list. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn insert(self, item: L) {
+        self.list.insert_after_opt(self.prev, item);
+    }
+}
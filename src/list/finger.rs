@@ -0,0 +1,192 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::budget::next_sibling;
+use super::node::Down;
+use super::{LeafRef, SkipList, can_get_zero_sized_last};
+use crate::allocator::{Allocator, Global};
+use crate::options::LeafSize;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// Speeds up searches by index that are close to each other.
+///
+/// This type is returned by [`SkipList::finger`]. It remembers the nodes
+/// visited by the last search, so a search for an index at a distance of
+/// *d* from the previous one takes Θ(log *d*) time on average, rather than
+/// Θ(log *n*). While this value exists, the list is borrowed, so it can't be
+/// modified.
+pub struct Finger<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a SkipList<L, A>,
+    /// The nodes descended through by the last search, starting at the
+    /// root, along with the combined size of the items before each node.
+    path: Vec<(Down<L>, LeafSize<L>)>,
+}
+
+impl<'a, L, A> Finger<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(list: &'a SkipList<L, A>) -> Self {
+        Self {
+            list,
+            path: Vec::new(),
+        }
+    }
+
+    /// Gets the list being searched.
+    pub fn list(&self) -> &'a SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets an item by index.
+    ///
+    /// This method returns the same item as [`SkipList::get`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *d*) on average, where *d* is the distance from the index of
+    /// the previous search; Θ(log *n*) for the first search.
+    pub fn get<S>(&mut self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.get_with_cmp(|size| size.borrow().cmp(index))
+    }
+
+    /// Gets an item by index using the given comparison function.
+    ///
+    /// This method returns the same item as [`SkipList::get_with_cmp`], and
+    /// `cmp` has the same meaning.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `cmp` returns results inconsistent with the
+    /// total order on [`LeafSize<L>`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *d*) on average, where *d* is the distance from the index of
+    /// the previous search; Θ(log *n*) for the first search.
+    pub fn get_with_cmp<F>(&mut self, cmp: F) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        // Climb to the lowest node that contains the desired index. If the
+        // index isn't before the previous one, it isn't before the start of
+        // any node on the path, and the search can continue from the
+        // sibling after the node climbed out of, rather than from the first
+        // child of the node that contains the index.
+        let mut resume = None;
+        let forward = match self.path.last() {
+            Some((_, offset)) => cmp(offset).is_le(),
+            None => false,
+        };
+        if forward {
+            while let Some((node, offset)) = self.path.last() {
+                let mut end = offset.clone();
+                end += node.size();
+                if cmp(&end).is_gt() {
+                    break;
+                }
+                resume = next_sibling(node).map(|next| (next, end));
+                self.path.pop();
+            }
+        } else {
+            // The index is before the end of every node on the path.
+            self.path.pop();
+            while let Some((_, offset)) = self.path.last() {
+                if cmp(offset).is_le() {
+                    break;
+                }
+                self.path.pop();
+            }
+        }
+        let (node, offset) = match (self.path.last(), resume) {
+            (Some((Down::Leaf(node), _)), _) => return Some(node.clone()),
+            (Some(_), Some(resume)) => resume,
+            (Some((Down::Internal(node), offset)), None) => {
+                (node.down().unwrap(), offset.clone())
+            }
+            (None, _) => (self.list.root.clone()?, Default::default()),
+        };
+        self.descend(node, offset, cmp)
+    }
+
+    /// Searches by index starting at `node`, where `size` is the combined
+    /// size of the items before `node`, and records the nodes descended
+    /// through. This works like [`SkipList::subtree_get`].
+    fn descend<F>(
+        &mut self,
+        mut node: Down<L>,
+        mut size: LeafSize<L>,
+        cmp: F,
+    ) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        loop {
+            size += node.size();
+            let ord = cmp(&size);
+            if ord.is_le() {
+                if let Some(next) = next_sibling(&node) {
+                    node = next;
+                    continue;
+                }
+                let found = ord.is_eq()
+                    && match &node {
+                        // Item is the last element of the list, has a size
+                        // of zero, and is at the right index.
+                        Down::Leaf(node) => {
+                            node.size() == Default::default()
+                                && can_get_zero_sized_last::<L>()
+                        }
+                        Down::Internal(_) => true,
+                    };
+                if !found {
+                    return None;
+                }
+            }
+            size -= node.size();
+            self.path.push((node.clone(), size.clone()));
+            node = match node {
+                Down::Leaf(node) => return Some(node),
+                Down::Internal(node) => node.down().unwrap(),
+            };
+        }
+    }
+}
+
+impl<L, A> fmt::Debug for Finger<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Finger").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,232 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::iter::{Iter, ListIter};
+use super::{LeafRef, SkipList};
+use crate::allocator::{Allocator, Global};
+use crate::options::{Dimension, LeafSize, ListOptions};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use integral_constant::Bool;
+
+/// A [`LeafRef`] whose clones can be used from multiple threads at the same
+/// time, provided that the list they belong to is never modified.
+///
+/// This trait allows a [`FrozenSkipList`] to be [`Sync`].
+///
+/// # Safety
+///
+/// As long as [`LeafRef::set_next`] is not called, it must be safe to call
+/// [`Clone::clone`], [`Drop::drop`], [`LeafRef::next`], and
+/// [`LeafRef::size`] on clones of `Self` from multiple threads concurrently.
+/// For example, reference counts must be updated atomically, and the data a
+/// leaf refers to must be [`Sync`].
+///
+/// The requirement in [`LeafRef`] that `Self` must not be [`Send`] or
+/// [`Sync`] still applies.
+pub unsafe trait SharedLeafRef: LeafRef {}
+
+/// A [`SkipList`] that can't be modified.
+///
+/// This type is returned by [`SkipList::freeze`]. It provides the read-only
+/// operations of [`SkipList`], and [`Self::thaw`] turns it back into a
+/// [`SkipList`].
+///
+/// If `L` implements [`SharedLeafRef`] and `A` is [`Sync`], this type is
+/// [`Sync`], so the list can be read from multiple threads at once (e.g.,
+/// with [`std::thread::scope`]).
+///
+/// [`std::thread::scope`]: https://doc.rust-lang.org/std/thread/fn.scope.html
+pub struct FrozenSkipList<L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: SkipList<L, A>,
+}
+
+impl<L, A> FrozenSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(list: SkipList<L, A>) -> Self {
+        Self {
+            list,
+        }
+    }
+
+    /// Turns this list back into a [`SkipList`] that can be modified.
+    pub fn thaw(self) -> SkipList<L, A> {
+        self.list
+    }
+
+    /// See [`SkipList::len`].
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// See [`SkipList::is_empty`].
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// See [`SkipList::size`].
+    pub fn size(&self) -> LeafSize<L> {
+        self.list.size()
+    }
+
+    /// See [`SkipList::get`].
+    pub fn get<S>(&self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.list.get(index)
+    }
+
+    /// See [`SkipList::get_with`].
+    pub fn get_with<S>(&self, index: &S) -> Option<L>
+    where
+        S: ?Sized,
+        LeafSize<L>: PartialOrd<S>,
+    {
+        self.list.get_with(index)
+    }
+
+    /// See [`SkipList::get_by`].
+    pub fn get_by<D>(&self, index: &D::Value) -> Option<L>
+    where
+        D: Dimension<LeafSize<L>>,
+    {
+        self.list.get_by::<D>(index)
+    }
+
+    /// See [`SkipList::get_with_cmp`].
+    pub fn get_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        self.list.get_with_cmp(cmp)
+    }
+
+    /// See [`SkipList::first`].
+    pub fn first(&self) -> Option<L> {
+        self.list.first()
+    }
+
+    /// See [`SkipList::last`].
+    pub fn last(&self) -> Option<L> {
+        self.list.last()
+    }
+
+    /// See [`SkipList::iter`].
+    pub fn iter(&self) -> Iter<L> {
+        self.list.iter()
+    }
+
+    /// See [`SkipList::iter_exact`].
+    pub fn iter_exact(&self) -> ListIter<'_, L> {
+        self.list.iter_exact()
+    }
+}
+
+impl<L, A> FrozenSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// See [`SkipList::find`].
+    pub fn find<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.list.find(key)
+    }
+
+    /// See [`SkipList::find_with`].
+    pub fn find_with<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: ?Sized,
+        L: PartialOrd<K>,
+    {
+        self.list.find_with(key)
+    }
+
+    /// See [`SkipList::find_with_cmp`].
+    pub fn find_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        self.list.find_with_cmp(cmp)
+    }
+
+    /// See [`SkipList::rank`].
+    pub fn rank<K>(&self, key: &K) -> LeafSize<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.list.rank(key)
+    }
+
+    /// See [`SkipList::rank_with_cmp`].
+    pub fn rank_with_cmp<F>(&self, cmp: F) -> LeafSize<L>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        self.list.rank_with_cmp(cmp)
+    }
+
+    /// See [`SkipList::nearest_by_key`].
+    pub fn nearest_by_key<K>(&self, key: &K) -> (Option<L>, Option<L>)
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.list.nearest_by_key(key)
+    }
+}
+
+impl<L, A> From<FrozenSkipList<L, A>> for SkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn from(list: FrozenSkipList<L, A>) -> Self {
+        list.thaw()
+    }
+}
+
+// SAFETY: `FrozenSkipList` provides no way to modify the list, and none of
+// its methods modify the list's internal nodes or use its observer, so they
+// only read memory shared with other threads. `L` implements
+// `SharedLeafRef`, so leaves can be used concurrently as long as the list
+// isn't modified, and `A` is `Sync`.
+// The list can be modified again only after it is thawed, which requires
+// ownership of the `FrozenSkipList`, and leaves obtained from it can't be
+// sent to other threads (as `L` is not `Send`).
+unsafe impl<L, A> Sync for FrozenSkipList<L, A>
+where
+    L: SharedLeafRef,
+    A: Allocator + Sync,
+{
+}
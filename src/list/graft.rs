@@ -0,0 +1,125 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, SkipList};
+use crate::allocator::{Allocator, Global};
+use crate::options::LeafSize;
+
+/// Builds a group of items that can later be inserted into a [`SkipList`]
+/// all at once.
+///
+/// Items are added to the builder with [`Self::push`] and
+/// [`Self::push_from`], which arrange them into internal nodes without
+/// affecting any list. [`SkipList::graft_after`] then links the whole group
+/// into a list in worst-case Θ(log *n*) time, regardless of the number of
+/// items, so large insertions can be prepared ahead of time (e.g., spread
+/// across multiple frames) before being spliced into a list that is in use.
+///
+/// A builder for a list with a custom allocator can be obtained with
+/// [`SkipList::subtree_builder`], which ensures that the nodes it allocates
+/// can be freed by that list.
+pub struct SubtreeBuilder<L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) list: SkipList<L, A>,
+}
+
+impl<L: LeafRef> SubtreeBuilder<L> {
+    /// Creates a new, empty builder.
+    pub fn new() -> Self {
+        Self {
+            list: SkipList::new(),
+        }
+    }
+}
+
+impl<L, A> SubtreeBuilder<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Adds `item` after the items already in the builder.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` is already in a list. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *k*), where *k* is the number of items in the builder.
+    pub fn push(&mut self, item: L) {
+        self.list.push_back(item);
+    }
+
+    /// Adds the items in `items` after the items already in the builder.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if any items in `items` are already in a list.
+    /// Memory may be leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*m* + log *k*), where *m* is the number of items in `items` and
+    /// *k* is the number of items in the builder.
+    pub fn push_from<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = L>,
+    {
+        self.list.push_back_from(items);
+    }
+
+    /// Gets the number of items in the builder.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Checks whether the builder is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets the total size of the items in the builder.
+    pub fn size(&self) -> LeafSize<L> {
+        self.list.size()
+    }
+}
+
+impl<L: LeafRef> Default for SubtreeBuilder<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L, A> Extend<L> for SubtreeBuilder<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Equivalent to [`Self::push_from`].
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = L>,
+    {
+        self.push_from(iter);
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, ListObserver, SkipList};
+use crate::allocator::Allocator;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A [`LeafRef`] with an identity that can be used to track it.
+///
+/// This trait allows [`SkipList::handle`] to be used. It is implemented by
+/// the leaves in [`basic`](crate::basic).
+///
+/// # Safety
+///
+/// [`Self::id`] must return the same value for all clones of a leaf, and
+/// different values for different leaves that exist at the same time.
+pub unsafe trait LeafId: LeafRef {
+    /// Gets the identifier of this leaf, like its address.
+    fn id(&self) -> usize;
+}
+
+/// A stable reference to an item in a [`SkipList`].
+///
+/// Handles are returned by [`SkipList::handle`] and turned back into items
+/// with [`SkipList::resolve`]. Unlike the items themselves, handles can't
+/// be used after their item is removed from the list: once that happens,
+/// [`SkipList::resolve`] returns [`None`], even if the item is inserted
+/// again. Each handle consists of an index and a generation, so handles
+/// are cheap to copy, compare, and store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: u64,
+}
+
+struct Slot<L> {
+    generation: u64,
+    item: Option<L>,
+}
+
+/// The handles issued for the items in a list.
+pub struct HandleMap<L> {
+    slots: Vec<Slot<L>>,
+    /// The indices of the slots that don't contain an item.
+    free: Vec<usize>,
+    /// Maps the ID of each item that has a handle to its slot.
+    indices: BTreeMap<usize, usize>,
+    id: fn(&L) -> usize,
+}
+
+impl<L: LeafRef> HandleMap<L> {
+    fn new(id: fn(&L) -> usize) -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            indices: BTreeMap::new(),
+            id,
+        }
+    }
+
+    fn insert(&mut self, item: L) -> Handle {
+        let id = (self.id)(&item);
+        if let Some(&index) = self.indices.get(&id) {
+            return Handle {
+                index,
+                generation: self.slots[index].generation,
+            };
+        }
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    item: None,
+                });
+                self.slots.len() - 1
+            }
+        };
+        let slot = &mut self.slots[index];
+        slot.item = Some(item);
+        self.indices.insert(id, index);
+        Handle {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    fn get(&self, handle: Handle) -> Option<L> {
+        let slot = self.slots.get(handle.index)?;
+        slot.item.clone().filter(|_| slot.generation == handle.generation)
+    }
+
+    fn free(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        slot.item = None;
+        slot.generation += 1;
+        self.free.push(index);
+    }
+
+    /// Checks whether `item` has a handle.
+    pub fn contains(&self, item: &L) -> bool {
+        self.indices.contains_key(&(self.id)(item))
+    }
+
+    /// Invalidates every handle.
+    pub fn clear(&mut self) {
+        let indices = core::mem::take(&mut self.indices);
+        indices.into_values().for_each(|index| self.free(index));
+    }
+}
+
+impl<L: LeafRef> ListObserver<L> for HandleMap<L> {
+    fn on_remove(&mut self, item: &L) {
+        if let Some(index) = self.indices.remove(&(self.id)(item)) {
+            self.free(index);
+        }
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Gets a [`Handle`] that refers to `item`.
+    ///
+    /// The handle can be turned back into `item` with [`Self::resolve`] until
+    /// `item` is removed from the list, after which [`Self::resolve`] returns
+    /// [`None`]. If `item` already has a handle, the same handle is returned.
+    ///
+    /// The list stores the handles it issues, starting with the first call
+    /// to this method, and frees them as items are removed. This makes
+    /// removing items slower (see [`ListObserver`]), so lists that never
+    /// issue handles don't pay this cost.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` is not from this list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn handle(&mut self, item: L) -> Handle
+    where
+        L: LeafId,
+    {
+        assert!(
+            self.check_member(&item).is_ok(),
+            "`item` is not from this list",
+        );
+        self.handles
+            .get_or_insert_with(|| Box::new(HandleMap::new(L::id)))
+            .insert(item)
+    }
+
+    /// Gets the item that `handle` refers to, or [`None`] if that item has
+    /// been removed from the list.
+    ///
+    /// `handle` should have been returned by [`Self::handle`] on this list.
+    /// If it came from a different list, this method may return [`None`] or
+    /// an item from this list, but it is never unsafe.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn resolve(&self, handle: Handle) -> Option<L> {
+        self.handles.as_ref()?.get(handle)
+    }
+}
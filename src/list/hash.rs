@@ -0,0 +1,121 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Rolling hashes of subtrees, used when
+//! [`ListOptions::StoreHashes`](crate::ListOptions::StoreHashes) is true.
+//!
+//! The hash of a sequence of leaves *h*₁, …, *h*ₙ is the polynomial
+//! *h*₁·*B*ⁿ⁻¹ + … + *h*ₙ (using wrapping arithmetic), where *hᵢ* is the hash
+//! of the *i*th leaf. This depends only on the leaves and not on the shape of
+//! the tree, so the hash of a node can be combined from the hashes of its
+//! children, and two lists with the same items have the same root hash.
+
+use super::node::{Down, LeafRef, NodeRef};
+use core::hash::{Hash, Hasher};
+
+/// The base of the polynomial hash.
+const BASE: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The 64-bit FNV-1a hash function. Unlike `std`'s `DefaultHasher`, this is
+/// available in `no_std` environments and is the same across platforms and
+/// Rust versions.
+struct FnvHasher(u64);
+
+impl FnvHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+/// Computes `BASE` raised to the power of `exp`, with wrapping arithmetic.
+fn base_pow(mut exp: usize) -> u64 {
+    let mut base = BASE;
+    let mut result = 1_u64;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.wrapping_mul(base);
+        }
+        base = base.wrapping_mul(base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn leaf_hash<L: Hash>(leaf: &L) -> u64 {
+    let mut hasher = FnvHasher::new();
+    leaf.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Combines the hashes of `first` and all of its siblings.
+fn combine_siblings<N>(first: N) -> u64
+where
+    N: NodeRef,
+    N::Leaf: Hash,
+{
+    let mut hash = 0_u64;
+    let mut node = Some(first);
+    while let Some(n) = node {
+        hash = hash
+            .wrapping_mul(base_pow(n.leaf_count()))
+            .wrapping_add(subtree_hash(n.as_down()));
+        node = n.next_sibling();
+    }
+    hash
+}
+
+/// Gets the hash of the leaves in the subtree rooted at `node`.
+///
+/// The hashes of internal nodes are cached. Only nodes whose cached hash has
+/// been invalidated are recomputed from their children, so after a single
+/// change to the list, only the ancestors of the changed item are visited.
+pub fn subtree_hash<L>(node: Down<L>) -> u64
+where
+    L: LeafRef + Hash,
+{
+    let node = match node {
+        Down::Leaf(leaf) => return leaf_hash(&leaf),
+        Down::Internal(node) => node,
+    };
+    if let Some(hash) = node.hash() {
+        return hash;
+    }
+    let hash = match node.down().unwrap() {
+        Down::Leaf(first) => combine_siblings(first),
+        Down::Internal(first) => combine_siblings(first),
+    };
+    node.set_hash(hash);
+    hash
+}
@@ -17,11 +17,11 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::max_node_length;
 use super::node::{Down, InternalNodeRef, Next, NodeRef};
 use super::node::{LeafExt, LeafNext, LeafRef};
 use super::split::split;
 use super::traverse::get_parent;
+use super::{chaos, max_node_length};
 use crate::PersistentAlloc;
 use crate::allocator::Allocator;
 use crate::options::LeafSize;
@@ -36,6 +36,8 @@ struct Insertion<N: NodeRef> {
     pub last: N,
     /// Change in total list size due to the initial insertion of leaves.
     pub diff: LeafSize<N::Leaf>,
+    /// Number of leaves initially inserted.
+    pub leaves: usize,
     /// New root.
     pub root: Option<Down<N::Leaf>>,
 }
@@ -50,8 +52,28 @@ pub struct FinishedInsertion<L: LeafRef> {
     pub new_root: Down<L>,
 }
 
+/// Takes the first node from `spare` (a list of unused internal nodes, linked
+/// as siblings), or allocates a new node if `spare` is empty.
+fn take_node<L, A>(
+    spare: &mut Option<InternalNodeRef<L>>,
+    alloc: &PersistentAlloc<A>,
+) -> InternalNodeRef<L>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    if let Some(node) = *spare {
+        *spare = node.next_sibling();
+        node.set_next(None);
+        node
+    } else {
+        InternalNodeRef::alloc(alloc)
+    }
+}
+
 fn handle_insertion<N, A>(
     mut insertion: Insertion<N>,
+    spare: &mut Option<InternalNodeRef<N::Leaf>>,
     alloc: &PersistentAlloc<A>,
 ) -> InsertionResult<N::Leaf>
 where
@@ -71,7 +93,7 @@ where
             });
         }
         // Create new root.
-        let root = InternalNodeRef::alloc(alloc);
+        let root = take_node(spare, alloc);
         root.set_down(Some(first.as_down()));
         root.len.set(1);
         root
@@ -79,13 +101,17 @@ where
 
     let first_parent = parent;
     let new_len = parent.len.get() + insertion.count;
-    let use_fast_insertion =
-        new_len <= max_node_length::<N::Leaf>() && insertion.root.is_none();
+    // With the `chaos` feature, sometimes take the slow path even when not
+    // required, which splits the node if it can be split legally.
+    let use_fast_insertion = new_len <= max_node_length::<N::Leaf>()
+        && insertion.root.is_none()
+        && !chaos::force();
 
     let count = if use_fast_insertion {
         let diff = insertion.diff.clone();
         parent.len.set(new_len);
         parent.size.with_mut(|s| *s += diff);
+        parent.with_leaf_count(|n| *n += insertion.leaves);
         0
     } else {
         let first: N = parent.down_as().unwrap();
@@ -94,7 +120,7 @@ where
         iter.next().unwrap().apply_to(parent);
         let count = iter
             .map(|setup| {
-                let node = setup.into_new(alloc);
+                let node = setup.into_reused(spare, alloc);
                 parent.set_next(Some(Next::Sibling(node)));
                 parent = node;
             })
@@ -108,13 +134,21 @@ where
         first: first_parent,
         last: parent,
         diff: insertion.diff,
+        leaves: insertion.leaves,
         root: insertion.root,
     })
 }
 
+/// Inserts `items` after `pos`.
+///
+/// New internal nodes are taken from `spare` (a list of unused internal
+/// nodes, linked as siblings) before any are allocated. Inserting a single
+/// item needs at most one more node than the number of levels of internal
+/// nodes in the list.
 pub fn insert_after<L, I, A>(
     mut pos: L,
     items: I,
+    spare: &mut Option<InternalNodeRef<L>>,
     alloc: &PersistentAlloc<A>,
 ) -> FinishedInsertion<L>
 where
@@ -139,14 +173,15 @@ where
         first,
         last: pos,
         diff: size,
+        leaves: count,
         root: None,
     };
-    let mut result = handle_insertion(insertion, alloc);
+    let mut result = handle_insertion(insertion, spare, alloc);
     loop {
         match result {
             InsertionResult::Done(done) => return done,
             InsertionResult::Insertion(insertion) => {
-                result = handle_insertion(insertion, alloc);
+                result = handle_insertion(insertion, spare, alloc);
             }
         }
     }
@@ -0,0 +1,108 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Interval queries, used when
+//! [`ListOptions::StoreMaxEnd`](crate::ListOptions::StoreMaxEnd) is true.
+//!
+//! Each internal node caches the leaf in its subtree whose interval ends
+//! last. In a list sorted by the start of each interval, a subtree whose
+//! cached leaf ends at or before a given point contains no intervals that
+//! overlap anything after that point, so it can be skipped entirely.
+
+use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
+
+/// A half-open interval [`start`](Self::start)..[`end`](Self::end).
+///
+/// Implementing this trait for a [`LeafRef`] type (with
+/// [`ListOptions::StoreMaxEnd`] enabled) allows a list of intervals sorted by
+/// their start to be queried for overlapping intervals with
+/// [`SkipList::find_overlapping`] and [`SkipList::find_all_overlapping`].
+/// The basic leaf types in [`crate::basic`] implement this trait when their
+/// data does.
+///
+/// An interval whose end is not greater than its start is empty and never
+/// overlaps anything.
+///
+/// [`ListOptions::StoreMaxEnd`]: crate::ListOptions::StoreMaxEnd
+/// [`SkipList::find_overlapping`]: crate::SkipList::find_overlapping
+/// [`SkipList::find_all_overlapping`]: crate::SkipList::find_all_overlapping
+pub trait Interval {
+    /// The type of the interval's endpoints.
+    type Point: Ord + Clone;
+
+    /// Gets the (inclusive) start of the interval.
+    fn start(&self) -> Self::Point;
+
+    /// Gets the (exclusive) end of the interval.
+    fn end(&self) -> Self::Point;
+}
+
+/// Gets the leaf in the subtree rooted at `node` whose interval ends last.
+///
+/// The results for internal nodes are cached. Like hashes, they're
+/// invalidated when the subtree changes and recomputed from the node's
+/// children when next needed.
+pub fn subtree_max_end<L>(node: Down<L>) -> L
+where
+    L: LeafRef + Interval,
+{
+    let node = match node {
+        Down::Leaf(leaf) => return leaf,
+        Down::Internal(node) => node,
+    };
+    if let Some(leaf) = node.max_end() {
+        return leaf;
+    }
+    let mut child = node.down();
+    let mut max: Option<L> = None;
+    while let Some(down) = child {
+        let leaf = subtree_max_end(down.clone());
+        if max.as_ref().map_or(true, |m| leaf.end() > m.end()) {
+            max = Some(leaf);
+        }
+        child = match down {
+            Down::Leaf(leaf) => leaf.next_sibling().map(Down::Leaf),
+            Down::Internal(node) => node.next_sibling().map(Down::Internal),
+        };
+    }
+    let max = max.unwrap();
+    node.set_max_end(max.clone());
+    max
+}
+
+/// Gets the node that follows the subtree rooted at `node` in a pre-order
+/// traversal of the list.
+pub fn skip_subtree<L: LeafRef>(node: Down<L>) -> Option<Down<L>> {
+    let mut parent: InternalNodeRef<L> = match node {
+        Down::Leaf(leaf) => match NodeRef::next(&leaf)? {
+            Next::Sibling(next) => return Some(Down::Leaf(next)),
+            Next::Parent(parent) => parent,
+        },
+        Down::Internal(node) => match node.next()? {
+            Next::Sibling(next) => return Some(Down::Internal(next)),
+            Next::Parent(parent) => parent,
+        },
+    };
+    loop {
+        parent = match parent.next()? {
+            Next::Sibling(next) => return Some(Down::Internal(next)),
+            Next::Parent(parent) => parent,
+        };
+    }
+}
@@ -19,25 +19,475 @@
 
 //! Skip list iterators.
 
-use super::{LeafRef, SkipList};
+use super::interval::{Interval, skip_subtree, subtree_max_end};
+use super::node::{Down, LeafExt, LeafNext};
+use super::traverse::get_root;
+use super::{DropPolicy, LeafRef, SkipList};
 use crate::allocator::Allocator;
+use crate::options::LeafSize;
+use core::borrow::Borrow;
 use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::Bound;
 
 /// An iterator over the items in a [`SkipList`].
-pub struct Iter<L>(pub(super) Option<L>);
+///
+/// This iterator is double-ended. The last item is found only when
+/// [`next_back`](DoubleEndedIterator::next_back) is first called, so forward
+/// iteration never has to find it. At that point, the iterator also counts
+/// the items it has yet to yield, which tells it when the ends have met.
+/// This iterator doesn't borrow the list, so if the list is modified after
+/// iteration from the back has started, it may stop early or yield items
+/// past where the ends would have met.
+pub struct Iter<L> {
+    front: Option<L>,
+    /// The next item to yield from the back, or [`None`] if iteration from
+    /// the back hasn't started (or the iterator is exhausted).
+    back: Option<L>,
+    /// The number of items not yet yielded, if known. This is never
+    /// `Some(0)` while `front` is [`Some`].
+    remaining: Option<usize>,
+}
+
+impl<L: LeafRef> Iter<L> {
+    pub(super) fn new(first: Option<L>) -> Self {
+        Self {
+            front: first,
+            back: None,
+            remaining: None,
+        }
+    }
+
+    /// `len` must be the number of items from `first` to the end of the
+    /// iteration.
+    pub(super) fn with_len(first: Option<L>, len: usize) -> Self {
+        Self {
+            front: first.filter(|_| len > 0),
+            back: None,
+            remaining: Some(len),
+        }
+    }
+
+    /// Creates an iterator over the items from `first` to `last`
+    /// (inclusive).
+    pub(super) fn between(first: L, last: L) -> Self {
+        let len = SkipList::position(last.clone()) + 1;
+        let len = len - SkipList::position(first.clone());
+        Self {
+            front: Some(first),
+            back: Some(last),
+            remaining: Some(len),
+        }
+    }
+}
+
+impl<L: LeafRef> Iter<L> {
+    /// Gets a token that can be used to resume iteration with
+    /// [`SkipList::resume`], even after the list has been modified.
+    ///
+    /// The token refers to the next item this iterator would yield from the
+    /// front. Items that have been yielded from the back are not taken into
+    /// account; a resumed iterator continues to the end of the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn resume_token(&self) -> ResumeToken<L> {
+        let item = self.front.clone();
+        ResumeToken {
+            index: item.clone().map_or(0, SkipList::position),
+            item,
+        }
+    }
+}
+
+/// A token that records the position of an [`Iter`], so that iteration can
+/// be resumed after the list is modified.
+///
+/// This type is returned by [`Iter::resume_token`] and used by
+/// [`SkipList::resume`].
+#[derive(Clone, Debug)]
+pub struct ResumeToken<L> {
+    /// The next item to yield, or [`None`] if iteration was finished.
+    pub(super) item: Option<L>,
+    /// The number of items that were before `item` when the token was
+    /// created.
+    pub(super) index: usize,
+}
+
+impl<L> ResumeToken<L> {
+    /// Gets the next item that was to be yielded when the token was created,
+    /// or [`None`] if iteration was finished.
+    pub fn item(&self) -> Option<&L> {
+        self.item.as_ref()
+    }
+
+    /// Gets the number of items that were before [`Self::item`] when the
+    /// token was created.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
 
 impl<L: LeafRef> Iterator for Iter<L> {
     type Item = L;
 
     fn next(&mut self) -> Option<L> {
-        let leaf = self.0.take();
-        self.0 = leaf.clone().and_then(SkipList::next);
-        leaf
+        let leaf = self.front.take()?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.back = None;
+                return Some(leaf);
+            }
+        }
+        self.front = SkipList::next(leaf.clone());
+        Some(leaf)
+    }
+}
+
+impl<L: LeafRef> DoubleEndedIterator for Iter<L> {
+    fn next_back(&mut self) -> Option<L> {
+        let front = self.front.clone()?;
+        let remaining = self
+            .remaining
+            .get_or_insert_with(|| SkipList::count_from(front.clone()));
+        let leaf = self
+            .back
+            .take()
+            .unwrap_or_else(|| SkipList::subtree_last(get_root(front)));
+        *remaining -= 1;
+        if *remaining == 0 {
+            self.front = None;
+        } else {
+            self.back = SkipList::previous(leaf.clone());
+        }
+        Some(leaf)
     }
 }
 
 impl<L: LeafRef> FusedIterator for Iter<L> {}
 
+/// An iterator over the items in a borrowed [`SkipList`] or [`ListSlice`].
+///
+/// [`ListSlice`]: crate::ListSlice
+///
+/// This type is returned by [`SkipList::iter_exact`] and
+/// [`ListSlice::iter`](crate::ListSlice::iter). It works like [`Iter`], but
+/// because the list can't be modified while it is borrowed, the number of
+/// remaining items is always known, so this type implements
+/// [`ExactSizeIterator`].
+pub struct ListIter<'a, L> {
+    /// Always knows its remaining length.
+    iter: Iter<L>,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl<L: LeafRef> ListIter<'_, L> {
+    /// `iter` must have been created with [`Iter::with_len`] or
+    /// [`Iter::between`].
+    pub(super) fn new(iter: Iter<L>) -> Self {
+        debug_assert!(iter.remaining.is_some());
+        Self {
+            iter,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gets a token that can be used to resume iteration with
+    /// [`SkipList::resume`]; see [`Iter::resume_token`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn resume_token(&self) -> ResumeToken<L> {
+        self.iter.resume_token()
+    }
+}
+
+impl<L: LeafRef> Iterator for ListIter<'_, L> {
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.remaining.unwrap_or(0);
+        (len, Some(len))
+    }
+}
+
+impl<L: LeafRef> DoubleEndedIterator for ListIter<'_, L> {
+    fn next_back(&mut self) -> Option<L> {
+        self.iter.next_back()
+    }
+}
+
+impl<L: LeafRef> ExactSizeIterator for ListIter<'_, L> {}
+
+impl<L: LeafRef> FusedIterator for ListIter<'_, L> {}
+
+/// An iterator over the items in a sorted [`SkipList`] whose keys start with
+/// a given prefix.
+///
+/// This type is returned by [`SkipList::find_prefix`].
+pub struct Prefix<'a, L, T, F> {
+    pub(super) iter: Iter<L>,
+    pub(super) prefix: &'a [T],
+    pub(super) key: F,
+}
+
+impl<L, T, F> Iterator for Prefix<'_, L, T, F>
+where
+    L: LeafRef,
+    T: PartialEq,
+    F: Fn(&L) -> &[T],
+{
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        let item = self.iter.next()?;
+        if (self.key)(&item).starts_with(self.prefix) {
+            return Some(item);
+        }
+        self.iter = Iter::new(None);
+        None
+    }
+}
+
+impl<L, T, F> FusedIterator for Prefix<'_, L, T, F>
+where
+    L: LeafRef,
+    T: PartialEq,
+    F: Fn(&L) -> &[T],
+{
+}
+
+/// An iterator over the items in a [`SkipList`] whose indices are in a given
+/// range.
+///
+/// This type is returned by [`SkipList::iter_range`].
+pub struct Range<'a, L: LeafRef, S: ?Sized> {
+    pub(super) iter: Iter<L>,
+    /// The index of the next item.
+    pub(super) index: LeafSize<L>,
+    pub(super) end: &'a S,
+}
+
+impl<L, S> Iterator for Range<'_, L, S>
+where
+    L: LeafRef,
+    S: Ord + ?Sized,
+    LeafSize<L>: Borrow<S>,
+{
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        if self.index.borrow() >= self.end {
+            self.iter = Iter::new(None);
+            return None;
+        }
+        let item = self.iter.next()?;
+        self.index += item.size();
+        Some(item)
+    }
+}
+
+impl<L, S> FusedIterator for Range<'_, L, S>
+where
+    L: LeafRef,
+    S: Ord + ?Sized,
+    LeafSize<L>: Borrow<S>,
+{
+}
+
+/// An iterator over the items at a sequence of indices in a [`SkipList`].
+///
+/// This type is returned by [`SkipList::get_many`].
+pub struct GetMany<'a, L, A, I>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) list: &'a SkipList<L, A>,
+    pub(super) indices: I,
+    /// The most recently returned item and its index.
+    pub(super) current: Option<(L, LeafSize<L>)>,
+}
+
+impl<L, A, I> GetMany<'_, L, A, I>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn get<S>(&mut self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        if self.list.size().borrow() < index {
+            return None;
+        }
+        let (mut item, mut start) = match self.current.take() {
+            Some((item, start)) if start.borrow() <= index => (item, start),
+            _ => return self.search(index),
+        };
+        loop {
+            let mut end = start.clone();
+            end += item.size();
+            if end.borrow() > index {
+                self.current = Some((item.clone(), start));
+                return Some(item);
+            }
+            // Only the end of the list is searched for from the root, since
+            // it may be subject to `ListOptions::EmptyPolicy`.
+            item = match SkipList::next(item) {
+                Some(next) => next,
+                None => return self.search(index),
+            };
+            start = end;
+        }
+    }
+
+    fn search<S>(&mut self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.current =
+            self.list.get_with_cmp_offset(|size| size.borrow().cmp(index));
+        self.current.as_ref().map(|(item, _)| item.clone())
+    }
+}
+
+impl<'b, L, A, I, S> Iterator for GetMany<'_, L, A, I>
+where
+    L: LeafRef,
+    A: Allocator,
+    I: Iterator<Item = &'b S>,
+    S: Ord + ?Sized + 'b,
+    LeafSize<L>: Borrow<S>,
+{
+    type Item = Option<L>;
+
+    fn next(&mut self) -> Option<Option<L>> {
+        let index = self.indices.next()?;
+        Some(self.get(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<'b, L, A, I, S> FusedIterator for GetMany<'_, L, A, I>
+where
+    L: LeafRef,
+    A: Allocator,
+    I: FusedIterator<Item = &'b S>,
+    S: Ord + ?Sized + 'b,
+    LeafSize<L>: Borrow<S>,
+{
+}
+
+/// An iterator over the intervals in a [`SkipList`] that overlap a point or
+/// range.
+///
+/// This type is returned by [`SkipList::find_overlapping`] and
+/// [`SkipList::find_all_overlapping`].
+pub struct Overlapping<'a, L: LeafRef + Interval> {
+    /// The next node to visit.
+    pub(super) node: Option<Down<L>>,
+    /// Intervals must end after this point.
+    pub(super) low: L::Point,
+    /// Intervals must start before this bound.
+    pub(super) high: Bound<L::Point>,
+    pub(super) phantom: PhantomData<&'a ()>,
+}
+
+impl<L: LeafRef + Interval> Overlapping<'_, L> {
+    fn starts_in_range(&self, leaf: &L) -> bool {
+        match &self.high {
+            Bound::Included(high) => leaf.start() <= *high,
+            Bound::Excluded(high) => leaf.start() < *high,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<L: LeafRef + Interval> Iterator for Overlapping<'_, L> {
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        loop {
+            let node = self.node.take()?;
+            let leaf = match node {
+                Down::Leaf(leaf) => leaf,
+                Down::Internal(node) => {
+                    let max = subtree_max_end(Down::Internal(node));
+                    self.node = if max.end() > self.low {
+                        node.down()
+                    } else {
+                        skip_subtree(Down::Internal(node))
+                    };
+                    continue;
+                }
+            };
+            // The list is sorted by start, so no later intervals can start
+            // in range either.
+            if !self.starts_in_range(&leaf) {
+                return None;
+            }
+            self.node = skip_subtree(Down::Leaf(leaf.clone()));
+            if leaf.end() > self.low {
+                return Some(leaf);
+            }
+        }
+    }
+}
+
+impl<L: LeafRef + Interval> FusedIterator for Overlapping<'_, L> {}
+
+/// An iterator over the items in a sorted [`SkipList`] whose keys are in a
+/// given range.
+///
+/// This type is returned by [`SkipList::range_by_key`].
+pub struct KeyRange<'a, L, K: ?Sized> {
+    pub(super) iter: Iter<L>,
+    pub(super) end: Bound<&'a K>,
+}
+
+impl<L, K> Iterator for KeyRange<'_, L, K>
+where
+    L: LeafRef + Borrow<K>,
+    K: Ord + ?Sized,
+{
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        let item = self.iter.next()?;
+        let key = item.borrow();
+        let in_range = match self.end {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        };
+        if in_range {
+            return Some(item);
+        }
+        self.iter = Iter::new(None);
+        None
+    }
+}
+
+impl<L, K> FusedIterator for KeyRange<'_, L, K>
+where
+    L: LeafRef + Borrow<K>,
+    K: Ord + ?Sized,
+{
+}
+
 impl<L, A> IntoIterator for &SkipList<L, A>
 where
     L: LeafRef,
@@ -51,14 +501,174 @@ where
     }
 }
 
+/// An iterator over items that have been removed from a [`SkipList`].
+///
+/// Each item's link to the next item is cleared as the item is yielded, so
+/// yielded items can immediately be inserted into another list. Any items
+/// not yielded are unlinked when the iterator is dropped, so this iterator
+/// never needs to allocate, regardless of how many items were removed.
+///
+/// This type is returned by [`SkipList::remove_range`] and
+/// [`SkipList::drain`].
+pub struct Removed<L: LeafRef> {
+    /// A chain of the items to yield from the front, in order.
+    front: Option<L>,
+    front_len: usize,
+    /// A chain of the items to yield from the back, in reverse order.
+    back: Option<L>,
+    back_len: usize,
+}
+
+impl<L: LeafRef> Removed<L> {
+    /// `first` must be the start of a chain of `len` leaves that are linked
+    /// directly to each other (and not to any internal nodes).
+    pub(super) fn new(first: Option<L>, len: usize) -> Self {
+        Self {
+            front: first,
+            front_len: len,
+            back: None,
+            back_len: 0,
+        }
+    }
+
+    /// Calls `f` with each item that hasn't been yielded yet, in order. This
+    /// must be called before the iterator is used.
+    pub(super) fn for_each_pending<F: FnMut(&L)>(&self, mut f: F) {
+        debug_assert!(self.back.is_none());
+        let mut leaf = self.front.clone();
+        for i in 1..=self.front_len {
+            let item = leaf.take().unwrap();
+            f(&item);
+            if i < self.front_len {
+                leaf = chain_next(&item);
+            }
+        }
+    }
+}
+
+fn chain_next<L: LeafRef>(leaf: &L) -> Option<L> {
+    leaf.next().map(|next| match next {
+        LeafNext::Leaf(next) => next,
+        LeafNext::Data(_) => panic!("unexpected `LeafNext::Data`"),
+    })
+}
+
+/// Removes the first item from a chain and clears its link.
+fn pop_chain<L: LeafRef>(head: &mut Option<L>, len: &mut usize) -> Option<L> {
+    let leaf = head.take()?;
+    *head = chain_next(&leaf);
+    leaf.set_next_leaf(None);
+    debug_assert!(leaf.next().is_none(), "`set_next(None)` was ignored");
+    *len -= 1;
+    Some(leaf)
+}
+
+/// Moves the second half of the chain `src` (rounded up) to the empty chain
+/// `dest`, reversing it.
+fn move_half<L: LeafRef>(
+    src: &mut Option<L>,
+    src_len: &mut usize,
+    dest: &mut Option<L>,
+    dest_len: &mut usize,
+) {
+    debug_assert!(dest.is_none());
+    let keep = *src_len / 2;
+    let mut node = if keep == 0 {
+        src.take()
+    } else {
+        let mut last = src.clone().unwrap();
+        for _ in 1..keep {
+            last = chain_next(&last).unwrap();
+        }
+        let rest = chain_next(&last);
+        last.set_next_leaf(None);
+        rest
+    };
+    *dest_len = *src_len - keep;
+    *src_len = keep;
+    while let Some(leaf) = node {
+        node = chain_next(&leaf);
+        leaf.set_next_leaf(dest.take().map(LeafNext::Leaf));
+        *dest = Some(leaf);
+    }
+}
+
+impl<L: LeafRef> Iterator for Removed<L> {
+    type Item = L;
+
+    fn next(&mut self) -> Option<L> {
+        if self.front.is_none() {
+            move_half(
+                &mut self.back,
+                &mut self.back_len,
+                &mut self.front,
+                &mut self.front_len,
+            );
+        }
+        pop_chain(&mut self.front, &mut self.front_len)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front_len + self.back_len;
+        (len, Some(len))
+    }
+}
+
+impl<L: LeafRef> DoubleEndedIterator for Removed<L> {
+    fn next_back(&mut self) -> Option<L> {
+        if self.back.is_none() {
+            move_half(
+                &mut self.front,
+                &mut self.front_len,
+                &mut self.back,
+                &mut self.back_len,
+            );
+        }
+        pop_chain(&mut self.back, &mut self.back_len)
+    }
+}
+
+impl<L: LeafRef> ExactSizeIterator for Removed<L> {}
+
+impl<L: LeafRef> FusedIterator for Removed<L> {}
+
+impl<L: LeafRef> Drop for Removed<L> {
+    fn drop(&mut self) {
+        while pop_chain(&mut self.front, &mut self.front_len).is_some() {}
+        while pop_chain(&mut self.back, &mut self.back_len).is_some() {}
+        debug_assert!(
+            self.front_len == 0 && self.back_len == 0,
+            "not every item was reachable",
+        );
+    }
+}
+
 /// An owning iterator over the items in a [`SkipList`].
+///
+/// As with [`Removed`], items are unlinked as they are yielded, and any items
+/// not yielded are unlinked when the iterator is dropped. If the list's
+/// [`DropPolicy`] is [`DropPolicy::Release`], those items are then passed to
+/// [`LeafRef::on_list_drop`].
 pub struct IntoIter<L, A>
 where
     L: LeafRef,
     A: Allocator,
 {
-    iter: Iter<L>,
-    _list: SkipList<L, A>,
+    removed: Removed<L>,
+    drop_policy: DropPolicy,
+    phantom: PhantomData<SkipList<L, A>>,
+}
+
+impl<L, A> Drop for IntoIter<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        if self.drop_policy == DropPolicy::Release {
+            self.removed.by_ref().for_each(LeafExt::list_dropped);
+        }
+    }
 }
 
 impl<L, A> Iterator for IntoIter<L, A>
@@ -69,10 +679,31 @@ where
     type Item = L;
 
     fn next(&mut self) -> Option<L> {
-        self.iter.next()
+        self.removed.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.removed.size_hint()
     }
 }
 
+impl<L, A> DoubleEndedIterator for IntoIter<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn next_back(&mut self) -> Option<L> {
+        self.removed.next_back()
+    }
+}
+
+impl<L, A> ExactSizeIterator for IntoIter<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+}
+
 impl<L, A> FusedIterator for IntoIter<L, A>
 where
     L: LeafRef,
@@ -88,10 +719,11 @@ where
     type Item = L;
     type IntoIter = IntoIter<L, A>;
 
-    fn into_iter(self) -> Self::IntoIter {
+    fn into_iter(mut self) -> Self::IntoIter {
         IntoIter {
-            iter: Iter(self.first()),
-            _list: self,
+            removed: self.drain(),
+            drop_policy: self.drop_policy(),
+            phantom: PhantomData,
         }
     }
 }
@@ -0,0 +1,479 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Concatenation and splitting of detached trees.
+
+use super::max_node_length;
+use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef, SizeExt};
+use super::split::split;
+use super::traverse::{get_last_sibling, get_parent, get_parent_info};
+use crate::PersistentAlloc;
+use crate::allocator::Allocator;
+use crate::options::LeafSize;
+use cell_ref::CellExt;
+
+/// A tree whose root has no next node.
+pub struct Tree<L: LeafRef> {
+    pub root: Down<L>,
+    /// Number of internal levels (0 if the root is a leaf).
+    pub height: usize,
+}
+
+impl<L: LeafRef> Tree<L> {
+    /// Creates a tree from the root of a list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn new(root: Down<L>) -> Self {
+        let mut height = 0;
+        let mut node = root.clone();
+        while let Down::Internal(internal) = node {
+            node = internal.down().unwrap();
+            height += 1;
+        }
+        Self {
+            root,
+            height,
+        }
+    }
+}
+
+/// Trees containing the items before and after a split point.
+type Parts<L> = (Option<Tree<L>>, Option<Tree<L>>);
+
+/// A list of removed internal nodes that can be passed to
+/// [`destroy_node_list`](super::destroy::destroy_node_list).
+pub type Freed<L> = Option<InternalNodeRef<L>>;
+
+fn free<L: LeafRef>(node: InternalNodeRef<L>, freed: &mut Freed<L>) {
+    node.set_down(None);
+    node.set_next(freed.map(Next::Sibling));
+    *freed = Some(node);
+}
+
+/// Links `node` to `next`, which must be the same kind of node.
+fn link<L: LeafRef>(node: &Down<L>, next: &Down<L>) {
+    match (node, next) {
+        (Down::Leaf(node), Down::Leaf(next)) => {
+            node.set_next(Some(Next::Sibling(next.clone())));
+        }
+        (Down::Internal(node), Down::Internal(next)) => {
+            node.set_next(Some(Next::Sibling(*next)));
+        }
+        _ => unreachable!("nodes are at different levels"),
+    }
+}
+
+fn set_parent<L: LeafRef>(node: &Down<L>, parent: InternalNodeRef<L>) {
+    match node {
+        Down::Leaf(node) => node.set_next(Some(Next::Parent(parent))),
+        Down::Internal(node) => node.set_next(Some(Next::Parent(parent))),
+    }
+}
+
+fn last_child<L: LeafRef>(node: InternalNodeRef<L>) -> Down<L> {
+    match node.down().unwrap() {
+        Down::Leaf(child) => Down::Leaf(get_last_sibling(child)),
+        Down::Internal(child) => Down::Internal(get_last_sibling(child)),
+    }
+}
+
+fn into_internal<L: LeafRef>(node: Down<L>) -> InternalNodeRef<L> {
+    match node {
+        Down::Internal(node) => node,
+        Down::Leaf(_) => unreachable!("expected internal node"),
+    }
+}
+
+/// Splits the `len` children of `node`, starting with `first`, among `node`
+/// and newly allocated siblings. Returns the number of new siblings and the
+/// last sibling, which is not linked to anything.
+fn split_children<N, A>(
+    node: InternalNodeRef<N::Leaf>,
+    first: N,
+    len: usize,
+    alloc: &PersistentAlloc<A>,
+) -> (usize, InternalNodeRef<N::Leaf>)
+where
+    N: NodeRef,
+    A: Allocator,
+{
+    let mut iter = split(first, len);
+    iter.next().unwrap().apply_to(node);
+    let mut last = node;
+    let count = iter
+        .map(|setup| {
+            let new = setup.into_new(alloc);
+            last.set_next(Some(Next::Sibling(new)));
+            last = new;
+        })
+        .count();
+    (count, last)
+}
+
+/// Splits `node`, and then its ancestors as necessary, until no node has more
+/// than the maximum number of children.
+fn fix_overflow<L, A>(
+    mut node: InternalNodeRef<L>,
+    tree: &mut Tree<L>,
+    alloc: &PersistentAlloc<A>,
+) where
+    L: LeafRef,
+    A: Allocator,
+{
+    while node.len.get() > max_node_length::<L>() {
+        let len = node.len.get();
+        let size = node.size();
+        let leaf_count = node.leaf_count();
+        let end = node.next();
+        let (count, last) = match node.down().unwrap() {
+            Down::Leaf(first) => split_children(node, first, len, alloc),
+            Down::Internal(first) => split_children(node, first, len, alloc),
+        };
+        let parent = match end {
+            _ if count == 0 => {
+                last.set_next(end);
+                return;
+            }
+            Some(Next::Parent(parent)) => {
+                last.set_next(Some(Next::Parent(parent)));
+                parent
+            }
+            Some(Next::Sibling(next)) => {
+                last.set_next(Some(Next::Sibling(next)));
+                get_parent(last).unwrap()
+            }
+            None => {
+                // `node` was the root, so create a new root.
+                let root = InternalNodeRef::alloc(alloc);
+                root.set_down(Some(Down::Internal(node)));
+                root.len.set(count + 1);
+                root.size.set(size);
+                root.set_leaf_count(leaf_count);
+                root.key.set(node.key());
+                last.set_next(Some(Next::Parent(root)));
+                tree.root = Down::Internal(root);
+                tree.height += 1;
+                return;
+            }
+        };
+        parent.len.with_mut(|n| *n += count);
+        node = parent;
+    }
+}
+
+/// Concatenates two trees. Every item in `a` will precede every item in `b`.
+///
+/// # Time complexity
+///
+/// Θ(|*h<sub>a</sub>* − *h<sub>b</sub>*| + 1) amortized over a sequence of
+/// joins, where *h<sub>a</sub>* and *h<sub>b</sub>* are the heights of the
+/// trees.
+pub fn join<L, A>(
+    a: Option<Tree<L>>,
+    b: Option<Tree<L>>,
+    alloc: &PersistentAlloc<A>,
+    freed: &mut Freed<L>,
+) -> Option<Tree<L>>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    match (a, b) {
+        (Some(a), Some(b)) if a.height >= b.height => {
+            Some(append(a, b, alloc, freed))
+        }
+        (Some(a), Some(b)) => Some(prepend(a, b, alloc, freed)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Joins `b` to the end of `a`, which must be at least as tall as `b`.
+fn append<L, A>(
+    mut a: Tree<L>,
+    b: Tree<L>,
+    alloc: &PersistentAlloc<A>,
+    freed: &mut Freed<L>,
+) -> Tree<L>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    let size = b.root.size();
+    let leaf_count = b.root.leaf_count();
+    let mut parent = None;
+    let mut node = a.root.clone();
+    for _ in b.height..a.height {
+        let internal = into_internal(node);
+        internal.size.with_mut(|s| *s += size.clone());
+        internal.with_leaf_count(|n| *n += leaf_count);
+        node = last_child(internal);
+        parent = Some(internal);
+    }
+
+    let overflowing = match (node, b.root) {
+        (Down::Internal(node), Down::Internal(other)) => {
+            // Move all of `other`'s children to the end of `node`.
+            let last = last_child(node);
+            let other_last = last_child(other);
+            link(&last, &other.down().unwrap());
+            set_parent(&other_last, node);
+            node.len.with_mut(|n| *n += other.len.get());
+            node.size.with_mut(|s| *s += other.size());
+            node.with_leaf_count(|n| *n += other.leaf_count());
+            free(other, freed);
+            node
+        }
+        (node @ Down::Leaf(_), other @ Down::Leaf(_)) => {
+            link(&node, &other);
+            if let Some(parent) = parent {
+                set_parent(&other, parent);
+                parent.len.with_mut(|n| *n += 1);
+                parent
+            } else {
+                // Both trees consist of a single leaf.
+                let root = InternalNodeRef::alloc(alloc);
+                root.set_down(Some(node.clone()));
+                root.len.set(2);
+                root.size.set(node.size().add(other.size()));
+                root.set_leaf_count(2);
+                root.key.set(node.key());
+                set_parent(&other, root);
+                return Tree {
+                    root: Down::Internal(root),
+                    height: 1,
+                };
+            }
+        }
+        _ => unreachable!("nodes are at different levels"),
+    };
+    fix_overflow(overflowing, &mut a, alloc);
+    a
+}
+
+/// Joins `a` to the start of `b`, which must be taller than `a`.
+fn prepend<L, A>(
+    a: Tree<L>,
+    mut b: Tree<L>,
+    alloc: &PersistentAlloc<A>,
+    freed: &mut Freed<L>,
+) -> Tree<L>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    let size = a.root.size();
+    let leaf_count = a.root.leaf_count();
+    let key = a.root.key();
+    let mut parent = None;
+    let mut node = b.root.clone();
+    for _ in a.height..b.height {
+        let internal = into_internal(node);
+        internal.size.with_mut(|s| *s += size.clone());
+        internal.with_leaf_count(|n| *n += leaf_count);
+        internal.key.set(key.clone());
+        node = internal.down().unwrap();
+        parent = Some(internal);
+    }
+
+    let overflowing = match (a.root, node) {
+        (Down::Internal(other), Down::Internal(node)) => {
+            // Move all of `other`'s children to the start of `node`.
+            let other_last = last_child(other);
+            link(&other_last, &node.down().unwrap());
+            node.set_down(other.down());
+            node.len.with_mut(|n| *n += other.len.get());
+            node.size.with_mut(|s| *s += other.size());
+            node.with_leaf_count(|n| *n += other.leaf_count());
+            node.key.set(other.key());
+            free(other, freed);
+            node
+        }
+        (other @ Down::Leaf(_), node @ Down::Leaf(_)) => {
+            link(&other, &node);
+            let parent = parent.unwrap();
+            parent.set_down(Some(other));
+            parent.len.with_mut(|n| *n += 1);
+            parent
+        }
+        _ => unreachable!("nodes are at different levels"),
+    };
+    fix_overflow(overflowing, &mut b, alloc);
+    b
+}
+
+/// A sequence of siblings.
+struct Chain<N: NodeRef> {
+    first: N,
+    last: N,
+    len: usize,
+    size: LeafSize<N::Leaf>,
+    leaf_count: usize,
+}
+
+impl<N: NodeRef> Chain<N> {
+    /// Creates a chain of `len` nodes starting at `first`.
+    fn new(first: N, len: usize) -> Self {
+        let mut last = first.clone();
+        let mut size = first.size();
+        let mut leaf_count = first.leaf_count();
+        for _ in 1..len {
+            last = last.next_sibling().unwrap();
+            size += last.size();
+            leaf_count += last.leaf_count();
+        }
+        Self {
+            first,
+            last,
+            len,
+            size,
+            leaf_count,
+        }
+    }
+
+    /// Creates a chain from `first` to the last sibling.
+    fn to_end(first: N) -> Self {
+        let mut last = first.clone();
+        let mut size = first.size();
+        let mut leaf_count = first.leaf_count();
+        let mut len = 1;
+        while let Some(next) = last.next_sibling() {
+            last = next;
+            size += last.size();
+            leaf_count += last.leaf_count();
+            len += 1;
+        }
+        Self {
+            first,
+            last,
+            len,
+            size,
+            leaf_count,
+        }
+    }
+
+    /// Turns the chain into a tree, using `spare` as the new root if
+    /// possible. `height` is the height of each node in the chain.
+    fn into_tree<A: Allocator>(
+        self,
+        height: usize,
+        spare: &mut Option<InternalNodeRef<N::Leaf>>,
+        alloc: &PersistentAlloc<A>,
+    ) -> Tree<N::Leaf> {
+        if self.len == 1 {
+            self.first.set_next(None);
+            return Tree {
+                root: self.first.as_down(),
+                height,
+            };
+        }
+        let root =
+            spare.take().unwrap_or_else(|| InternalNodeRef::alloc(alloc));
+        root.set_down(Some(self.first.as_down()));
+        root.set_next(None);
+        root.len.set(self.len);
+        root.size.set(self.size);
+        root.set_leaf_count(self.leaf_count);
+        root.key.set(self.first.key());
+        self.last.set_next(Some(Next::Parent(root)));
+        Tree {
+            root: Down::Internal(root),
+            height: height + 1,
+        }
+    }
+}
+
+/// Splits the children of `parent` into those before the child at `index`
+/// and those after it (starting at `next`). `parent` is reused or freed.
+fn split_level<N, A>(
+    parent: InternalNodeRef<N::Leaf>,
+    index: usize,
+    next: Option<N>,
+    height: usize,
+    alloc: &PersistentAlloc<A>,
+    freed: &mut Freed<N::Leaf>,
+) -> Parts<N::Leaf>
+where
+    N: NodeRef,
+    A: Allocator,
+{
+    let left =
+        (index > 0).then(|| Chain::<N>::new(parent.down_as().unwrap(), index));
+    let right = next.map(Chain::to_end);
+    let mut spare = Some(parent);
+    let left = left.map(|c| c.into_tree(height, &mut spare, alloc));
+    let right = right.map(|c| c.into_tree(height, &mut spare, alloc));
+    if let Some(parent) = spare {
+        free(parent, freed);
+    }
+    (left, right)
+}
+
+/// Splits the tree containing `at` into two trees: one with the items before
+/// `at`, and one with `at` and the items after it.
+///
+/// # Time complexity
+///
+/// Θ(log *n*).
+pub fn split_at<L, A>(
+    at: L,
+    alloc: &PersistentAlloc<A>,
+    freed: &mut Freed<L>,
+) -> (Option<Tree<L>>, Tree<L>)
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    let info = get_parent_info(at.clone());
+    let next = at.next_sibling();
+    at.set_next(None);
+    let mut right = Tree {
+        root: Down::Leaf(at),
+        height: 0,
+    };
+    let parent = match info.parent {
+        Some(parent) => parent,
+        None => return (None, right),
+    };
+
+    // Information about `parent` must be obtained before `split_level` is
+    // called, as it reuses `parent`.
+    let mut parent_info = get_parent_info(parent);
+    let mut parent_next = parent.next_sibling();
+    let (left_part, right_part) =
+        split_level(parent, info.index, next, 0, alloc, freed);
+    let mut left = left_part;
+    right = join(Some(right), right_part, alloc, freed).unwrap();
+
+    let mut height = 1;
+    while let Some(grandparent) = parent_info.parent {
+        let index = parent_info.index;
+        let next = parent_next;
+        parent_info = get_parent_info(grandparent);
+        parent_next = grandparent.next_sibling();
+        let (left_part, right_part) =
+            split_level(grandparent, index, next, height, alloc, freed);
+        left = join(left_part, left, alloc, freed);
+        right = join(Some(right), right_part, alloc, freed).unwrap();
+        height += 1;
+    }
+    (left, right)
+}
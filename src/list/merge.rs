@@ -0,0 +1,147 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::SkipList;
+use super::build::build;
+use super::destroy::{deconstruct_into_chain, destroy_node_list};
+use super::node::{InternalNodeRef, LeafExt, LeafRef, NodeRef};
+use super::retain::Chain;
+use crate::allocator::Allocator;
+
+/// The state of an in-progress call to [`SkipList::merge_sorted`].
+///
+/// If a comparison panics, dropping this value rebuilds the list from the
+/// items that have been merged, followed by the remaining items from each
+/// list, so the list remains valid (though possibly unsorted).
+struct Merge<'a, L: LeafRef, A: Allocator> {
+    list: &'a mut SkipList<L, A>,
+    spare: Option<InternalNodeRef<L>>,
+    merged: Chain<L>,
+    /// The remaining items from the original list.
+    left: Option<L>,
+    left_len: usize,
+    /// The remaining items from the list being merged.
+    right: Option<L>,
+    right_len: usize,
+}
+
+impl<L: LeafRef, A: Allocator> Merge<'_, L, A> {
+    /// Rebuilds the list. After this method is called, the state is empty,
+    /// so calling it again has no effect.
+    fn finish(&mut self) {
+        let mut chain = core::mem::replace(&mut self.merged, Chain::new());
+        if self.right.is_some() {
+            // Both lists still have items only if a comparison panicked.
+            // The last of the remaining items in `left` isn't known, so
+            // they're moved to the chain individually.
+            while let Some(leaf) = self.left.take() {
+                self.left = leaf.next_sibling();
+                leaf.set_next_leaf(None);
+                chain.push(leaf);
+            }
+            self.left_len = 0;
+        }
+        chain.append(Chain {
+            first: self.left.take(),
+            last: None,
+            len: core::mem::take(&mut self.left_len),
+        });
+        chain.append(Chain {
+            first: self.right.take(),
+            last: None,
+            len: core::mem::take(&mut self.right_len),
+        });
+        if let Some(first) = chain.first {
+            self.list.root = Some(build(
+                first,
+                chain.len,
+                &mut self.spare,
+                &self.list.alloc,
+            ));
+        }
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in `self.spare` was allocated by
+        //   `self.list.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut self.spare, &self.list.alloc);
+        }
+    }
+}
+
+impl<L: LeafRef, A: Allocator> Drop for Merge<'_, L, A> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Merges the items in `other` into `list`, both of which must be sorted.
+/// See [`SkipList::merge_sorted`].
+pub fn merge_sorted<L, A>(list: &mut SkipList<L, A>, mut other: SkipList<L, A>)
+where
+    L: LeafRef + Ord,
+    A: Allocator,
+{
+    let (right, right_len, mut other_spare) = match other.root.take() {
+        Some(root) => deconstruct_into_chain(root),
+        None => return,
+    };
+
+    // SAFETY:
+    //
+    // * Every `InternalNode` in `other_spare` was allocated by `other.alloc`.
+    // * There are no other `InternalNodeRef`s that refer to these nodes,
+    //   since they are no longer used by `other`.
+    unsafe {
+        destroy_node_list(&mut other_spare, &other.alloc);
+    }
+    let (left, left_len, spare) = match list.root.take() {
+        Some(root) => {
+            let (first, len, spare) = deconstruct_into_chain(root);
+            (Some(first), len, spare)
+        }
+        None => (None, 0, None),
+    };
+    let mut state = Merge {
+        list,
+        spare,
+        merged: Chain::new(),
+        left,
+        left_len,
+        right: Some(right),
+        right_len,
+    };
+    while let (Some(left), Some(right)) = (&state.left, &state.right) {
+        // Items from `list` go first when items are equal.
+        let (rest, rest_len) = if right < left {
+            (&mut state.right, &mut state.right_len)
+        } else {
+            (&mut state.left, &mut state.left_len)
+        };
+        let leaf = rest.take().unwrap();
+        *rest = leaf.next_sibling();
+        *rest_len -= 1;
+        leaf.set_next_leaf(None);
+        state.merged.push(leaf);
+    }
+    state.finish();
+}
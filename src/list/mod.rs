@@ -18,37 +18,107 @@
  */
 
 use crate::allocator::{Allocator, Global};
-use crate::options::{LeafSize, ListOptions};
+use crate::options::{self, ListOptions, Monoid};
+use crate::options::{Dimension, EmptyPolicy, EmptyPolicyType, LeafSize};
 use cell_ref::CellExt;
 use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::convert::TryFrom;
+use core::hash::Hash;
 use core::iter::once;
 use core::marker::PhantomData;
 use core::mem;
+use core::ops;
+use core::ops::Bound;
+use core::ops::ControlFlow;
 use integral_constant::{Bool, Constant};
 
-#[cfg(skippy_debug)]
+mod aggregate;
+mod batch;
+mod budget;
+mod build;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(not(feature = "chaos"))]
+mod chaos;
+mod cursor;
+#[cfg(feature = "debug-graphviz")]
 pub mod debug;
+mod dense;
 mod destroy;
 mod destroy_safety;
+mod dispose;
+mod dump;
+mod entry;
+mod finger;
+mod frozen;
+mod graft;
+mod handle;
+mod hash;
 mod insert;
+mod interval;
 pub mod iter;
+mod join;
+mod merge;
 mod node;
+mod observer;
+#[cfg(feature = "rayon")]
+mod par;
+pub mod persist;
 mod remove;
+mod retain;
+mod shared;
+mod slice;
+mod spill;
 mod split;
+mod stats;
+mod sync;
+pub mod testing;
 mod traverse;
+mod update;
+mod validate;
 
-use crate::PersistentAlloc;
-use destroy::{deconstruct, destroy_node_list};
-use destroy_safety::SetUnsafeOnDrop;
+use crate::persistent_alloc::AllocFallback;
+use crate::{Error, PersistentAlloc, ValidationError};
+pub use aggregate::Summarize;
+use alloc::alloc::Layout;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+pub use batch::BatchOp;
+pub use budget::{PartialFind, PartialGet};
+use build::build;
+pub use cursor::Cursor;
+pub use dense::DenseSkipList;
+use destroy::deconstruct_into_chain;
+use destroy::{deconstruct, deconstruct_internal, destroy_node_list};
+use destroy_safety::{SetUnsafeOnDrop, can_safely_destroy};
+pub use dispose::Disposer;
+pub use dump::ListDump;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use finger::Finger;
+pub use frozen::{FrozenSkipList, SharedLeafRef};
+pub use graft::SubtreeBuilder;
+use handle::HandleMap;
+pub use handle::{Handle, LeafId};
 use insert::insert_after;
-use iter::Iter;
+pub use interval::Interval;
+use iter::ResumeToken;
+use iter::{GetMany, Iter, KeyRange, ListIter, Overlapping, Prefix};
+use iter::{Range, Removed};
+use join::{Tree, join, split_at};
+use node::has_caches;
 pub use node::{AllocItem, LeafNext, LeafRef, This};
-use node::{Down, InternalNodeRef, Key, Next, NodeRef, SizeExt};
-use remove::remove;
-use traverse::{get_last_sibling, get_parent_info};
-use traverse::{get_previous, get_previous_info};
+use node::{Down, InternalNodeRef, Key, LeafExt, Next, NodeRef, SizeExt};
+pub use observer::ListObserver;
+use remove::{remove, remove_with_path};
+pub use shared::SharedSkipList;
+pub use slice::ListSlice;
+pub use spill::SpillCheckpoint;
+pub use stats::ListStats;
+pub use sync::{SyncLeafRef, SyncSkipList};
+use traverse::{Path, get_previous, get_previous_info};
+use traverse::{get_last_sibling, get_parent, get_parent_info, get_root};
+pub use update::UpdateGuard;
 
 fn min_node_length<L: LeafRef>() -> usize {
     (max_node_length::<L>() + 1) / 2
@@ -63,27 +133,70 @@ fn roots_match<L: LeafRef>(a: &Down<L>, b: &Down<L>) -> bool {
     Internal::try_from(a) == Internal::try_from(b)
 }
 
+/// Checks whether `a` and `b` refer to the same leaf. Neither leaf may be in a
+/// list, except as the only item in a list.
+fn same_unlinked_leaf<L: LeafRef>(a: &L, b: &L) -> bool {
+    debug_assert!(a.next().is_none() && b.next().is_none());
+    // By the safety requirements of `LeafRef`, `b` observes changes made
+    // through `a` if and only if they refer to the same leaf. The pointer is
+    // never dereferenced.
+    a.set_next_leaf(Some(LeafNext::Data(core::ptr::NonNull::dangling())));
+    let same = b.next().is_some();
+    a.set_next_leaf(None);
+    same
+}
+
+/// Checks whether `a` and `b`, which must be in the same list, refer to the
+/// same leaf.
+fn same_leaf<L: LeafRef>(a: &L, b: &L) -> bool {
+    let a = get_parent_info(a.clone());
+    let b = get_parent_info(b.clone());
+    a.parent == b.parent && a.index == b.index
+}
+
 /// Propagate a change in the size of an item (or the item itself, which could
-/// change [`Key`]s) throughout the list.
+/// change [`Key`]s or cached values) throughout the list.
 fn propagate_update_diff<N: NodeRef>(
+    node: N,
+    key: Option<Key<N::Leaf>>,
+    old_size: LeafSize<N::Leaf>,
+    new_size: LeafSize<N::Leaf>,
+) {
+    propagate_update_diff_in(node, key, old_size, new_size, None);
+}
+
+/// Like [`propagate_update_diff`], but uses `path`, if provided, to find the
+/// ancestors of `node`, which must be a leaf.
+fn propagate_update_diff_in<N: NodeRef>(
     node: N,
     mut key: Option<Key<N::Leaf>>,
     old_size: LeafSize<N::Leaf>,
     new_size: LeafSize<N::Leaf>,
+    path: Option<&Path<N::Leaf>>,
 ) {
     let has_size_diff = old_size != new_size;
-    let info = get_parent_info(node);
-    let mut parent = info.parent;
-    let mut index = info.index;
+    let (mut parent, mut index) = match path {
+        Some(path) => {
+            path.first().map_or((None, 0), |l| (Some(l.parent), l.index))
+        }
+        None => {
+            let info = get_parent_info(node);
+            (info.parent, info.index)
+        }
+    };
+    let mut level = 0;
 
     while let Some(node) = parent {
         key = key.filter(|_| index == 0);
-        let mut updated = false;
+        let mut updated = has_caches::<N::Leaf>();
+        node.invalidate_caches();
         if has_size_diff {
             updated = true;
+            // Subtracting first ensures the size never exceeds the new
+            // total size of the list, which matters for narrow size types.
             node.size.with_mut(|s| {
-                *s += new_size.clone();
                 *s -= old_size.clone();
+                *s += new_size.clone();
             });
         }
         if let Some(key) = &key {
@@ -93,12 +206,92 @@ fn propagate_update_diff<N: NodeRef>(
         if !updated {
             break;
         }
-        let info = get_parent_info(node);
-        parent = info.parent;
-        index = info.index;
+        level += 1;
+        (parent, index) = match path {
+            Some(path) => path
+                .level(level)
+                .map_or((None, 0), |l| (Some(l.parent), l.index)),
+            None => {
+                let info = get_parent_info(node);
+                (info.parent, info.index)
+            }
+        };
     }
 }
 
+/// Counts the clones of `leaf` held by the list's internal structure.
+///
+/// This also clears the cached [`MaxEnd`](node::MaxEnd) of each internal node
+/// whose first item is `leaf`, as those can refer to any item in the node's
+/// subtree. Calling this on each item in order leaves only the clones
+/// counted here.
+fn structure_refs<L: LeafRef>(leaf: L) -> usize {
+    // Either the previous leaf or the parent refers to `leaf`.
+    let mut count = 1;
+    let info = get_parent_info(leaf);
+    let mut node = info.parent.filter(|_| info.index == 0);
+    while let Some(parent) = node {
+        parent.clear_max_end();
+        count += usize::from(parent.key().is_some());
+        let info = get_parent_info(parent);
+        node = info.parent.filter(|_| info.index == 0);
+    }
+    count
+}
+
+/// Whether index queries can return the last item in the list when it has a
+/// size of 0 and the desired index is the size of the list.
+fn can_get_zero_sized_last<L: LeafRef>() -> bool {
+    let policy = <L::Options as ListOptions>::EmptyPolicy::POLICY;
+    policy == EmptyPolicy::ReturnLast
+}
+
+/// The result of [`SkipList::next_hint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NextHint<L> {
+    /// The item directly after the given item.
+    Sibling(L),
+    /// The next item (if any) can't be obtained without climbing the list;
+    /// [`SkipList::next`] should be called instead.
+    Climb,
+}
+
+/// Where [`SkipList::insert_with_policy`] inserts an item that is equal to
+/// items already in the list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// The item is inserted before all equal items.
+    Before,
+    /// The item is inserted after all equal items.
+    After,
+    /// The item is not inserted, as with [`SkipList::insert`].
+    Reject,
+}
+
+/// What a [`SkipList`] does with its items when it is dropped; see
+/// [`SkipList::set_drop_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DropPolicy {
+    /// Items are unlinked from the list, after which they can be inserted
+    /// into another list. This is the default, unless `L` specifies
+    /// otherwise with [`LeafRef::DROP_POLICY`].
+    ///
+    /// Unlinking an item clears its link to the next item, so any
+    /// references held through those links (e.g., the strong references in
+    /// [`RcLeaf`]) are released, and items referenced only by the list are
+    /// dropped along with it.
+    ///
+    /// [`RcLeaf`]: crate::basic::RcLeaf
+    #[default]
+    Unlink,
+    /// Items are unlinked from the list and then passed to
+    /// [`LeafRef::on_list_drop`], which can release any resources the list
+    /// owns through them (e.g., [`BoxLeaf`] frees its data).
+    ///
+    /// [`BoxLeaf`]: crate::basic::BoxLeaf
+    Release,
+}
+
 /// A flexible intrusive skip list with worst-case non-amortized O(log *n*)
 /// operations.
 ///
@@ -110,7 +303,9 @@ fn propagate_update_diff<N: NodeRef>(
 /// that, under certain conditions, no other thread could possibly use that
 /// particular skip list or its items, it may be safe to send that skip list
 /// and all of its items to another thread (but this must be internal---users
-/// cannot have direct access to the skip list or items).
+/// cannot have direct access to the skip list or items). [`SyncSkipList`]
+/// can be used to do this without writing `unsafe impl Send` for the type
+/// that contains the skip list.
 ///
 /// Similarly, if you can guarantee that, under certain conditions, no thread
 /// could possibly call any methods of [`SkipList`] (with that particular skip
@@ -119,7 +314,8 @@ fn propagate_update_diff<N: NodeRef>(
 /// to use that skip list and those items immutably from multiple threads
 /// concurrently (which could involve sending <code>[&][r][SkipList]</code> and
 /// `L` across threads). Again, this must be internal---users cannot have
-/// direct access to the skip list or items.
+/// direct access to the skip list or items. [`SkipList::freeze`] provides a
+/// safe way to do this for leaf types that implement [`SharedLeafRef`].
 ///
 /// Additionally, no methods of the skip list to be used concurrently should
 /// ever have been called with leaf items (of type `L`) that already belonged
@@ -140,6 +336,13 @@ where
 {
     alloc: PersistentAlloc<A>,
     root: Option<Down<L>>,
+    drop_policy: DropPolicy,
+    /// Notified of changes to the list's items; see [`Self::set_observer`].
+    observer: Option<Box<dyn ListObserver<L>>>,
+    /// The handles issued by [`Self::handle`], if any.
+    handles: Option<Box<HandleMap<L>>>,
+    /// Scratch space for compound operations. This is empty when not in use.
+    path: Path<L>,
     /// Ensures that [`Self`] isn't [`Send`] or [`Sync`].
     phantom: PhantomData<*mut ()>,
 }
@@ -182,6 +385,23 @@ impl<L: LeafRef> SkipList<L> {
         }
     }
 
+    /// Gets the item directly after `item` if it can be obtained cheaply.
+    ///
+    /// If `item` and the item after it share the same parent node, the next
+    /// item is returned as [`NextHint::Sibling`]. Otherwise,
+    /// [`NextHint::Climb`] is returned, and [`Self::next`] must be called to
+    /// obtain the next item (or determine that there isn't one).
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn next_hint(item: L) -> NextHint<L> {
+        match NodeRef::next(&item) {
+            Some(Next::Sibling(node)) => NextHint::Sibling(node),
+            _ => NextHint::Climb,
+        }
+    }
+
     /// Gets the item directly before `item`.
     ///
     /// # Time complexity
@@ -216,7 +436,7 @@ impl<L: LeafRef> SkipList<L> {
     ///
     /// Iteration over the entire list is Θ(*n*).
     pub fn iter_at(item: L) -> Iter<L> {
-        Iter(Some(item))
+        Iter::new(Some(item))
     }
 
     fn subtree_first(first_child: Down<L>) -> L {
@@ -246,17 +466,155 @@ where
     A: Allocator,
 {
     /// Creates a new skip list with the given allocator.
+    ///
+    /// See [`Self::new_in_unchecked`] for allocators that aren't `'static`,
+    /// such as references to a stack-allocated arena.
     pub fn new_in(alloc: A) -> Self
     where
         A: 'static,
     {
+        // SAFETY: `A` is `'static`.
+        unsafe { Self::new_in_unchecked(alloc) }
+    }
+
+    /// Creates a new skip list with the given allocator, which doesn't have
+    /// to be `'static`.
+    ///
+    /// Normally, every internal node is deallocated when the list is dropped.
+    /// However, if the list is leaked (e.g., with [`mem::forget`]), or if a
+    /// panic occurs while the list is being modified, some nodes are never
+    /// deallocated, and items that were in the list may still refer to them.
+    /// [`Self::new_in`] requires a `'static` allocator so that this memory
+    /// can't be invalidated when a borrowed allocator goes out of scope.
+    ///
+    /// # Safety
+    ///
+    /// Memory allocated by `alloc` must remain valid for as long as any item
+    /// that was ever in this list (or in any list created from it, such as
+    /// with [`Self::split_off`]) may be used, unless the list is dropped
+    /// normally (without a panic having occurred during an operation on the
+    /// list). For example, it is sufficient to ensure that the items are
+    /// never used after the allocator goes out of scope.
+    pub unsafe fn new_in_unchecked(alloc: A) -> Self {
         Self {
-            alloc: PersistentAlloc::new(alloc),
+            // SAFETY: Checked by caller.
+            alloc: unsafe { PersistentAlloc::new_unchecked(alloc) },
             root: None,
+            drop_policy: L::DROP_POLICY,
+            observer: None,
+            handles: None,
+            path: Path::new(),
             phantom: PhantomData,
         }
     }
 
+    /// Creates a new skip list with the given allocator, containing the items
+    /// in `items`.
+    ///
+    /// The list is built bottom-up, which is faster than inserting the items
+    /// one at a time. The same happens when items are inserted into an empty
+    /// list with methods like [`Self::push_back_from`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if any items in `items` are already in a list.
+    /// Memory may be leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn from_iter_in<I>(items: I, alloc: A) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        A: 'static,
+    {
+        let mut list = Self::new_in(alloc);
+        list.push_back_from(items);
+        list
+    }
+
+    /// Sets a function to be called when the list fails to allocate memory.
+    ///
+    /// By default, allocation failure results in a call to
+    /// [`handle_alloc_error`], which typically aborts the process. If a hook
+    /// is set, it is called first with the layout of the failed allocation,
+    /// and if it returns [`AllocFallback::Retry`], the allocation is
+    /// attempted once more. If that attempt also fails, the hook is not
+    /// called again. This can be used to, e.g., free cached memory before
+    /// retrying.
+    ///
+    /// Because the hook is called while the list is being modified, it must
+    /// not access this list or any of its items.
+    ///
+    /// [`handle_alloc_error`]: alloc::alloc::handle_alloc_error
+    pub fn set_alloc_error_hook(&mut self, hook: fn(Layout) -> AllocFallback) {
+        self.alloc.set_hook(Some(hook));
+    }
+
+    /// Removes the hook set by [`Self::set_alloc_error_hook`], if any.
+    pub fn clear_alloc_error_hook(&mut self) {
+        self.alloc.set_hook(None);
+    }
+
+    /// Attaches an observer that is notified of changes to the list's items,
+    /// or detaches the current observer if `observer` is [`None`].
+    ///
+    /// The previous observer, if any, is returned. See [`ListObserver`] for
+    /// which changes are reported. Because the observer is called while the
+    /// list is mutably borrowed, it can't access the list itself, but it may
+    /// use the items it is given (e.g., with [`Self::index`]).
+    pub fn set_observer(
+        &mut self,
+        observer: Option<Box<dyn ListObserver<L>>>,
+    ) -> Option<Box<dyn ListObserver<L>>> {
+        mem::replace(&mut self.observer, observer)
+    }
+
+    /// Gets the list's [`DropPolicy`].
+    pub fn drop_policy(&self) -> DropPolicy {
+        self.drop_policy
+    }
+
+    /// Sets what the list does with its items when it is dropped.
+    ///
+    /// New lists use [`LeafRef::DROP_POLICY`], which is usually
+    /// [`DropPolicy::Unlink`]: items are only unlinked. With
+    /// [`DropPolicy::Release`], [`LeafRef::on_list_drop`] is called with each
+    /// item after it is unlinked, including items not yet yielded by an
+    /// [`IntoIter`] when it is dropped; lists of leaves that own their data
+    /// (like [`BoxLeaf`]) use this policy by default to free that data.
+    /// Lists returned by [`Self::split_off`] inherit this policy.
+    ///
+    /// [`BoxLeaf`]: crate::basic::BoxLeaf
+    /// [`IntoIter`]: crate::iter::IntoIter
+    ///
+    /// # Safety
+    ///
+    /// If `policy` is [`DropPolicy::Release`], the items must uphold any
+    /// requirements documented by `L`'s implementation of
+    /// [`LeafRef::on_list_drop`].
+    pub unsafe fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+
+    /// Gets the number of items in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |r| r.leaf_count())
+    }
+
+    /// Checks whether the list is empty.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
     /// Gets the total size of the list.
     ///
     /// This is the sum of [`L::size`](LeafRef::size) for every item in the
@@ -269,12 +627,63 @@ where
         self.root.as_ref().map_or_else(Default::default, |r| r.size())
     }
 
+    /// Gets statistics about the structure of the list, such as its height
+    /// and how full its internal nodes are.
+    ///
+    /// This can be useful when choosing a [fanout](ListOptions::Fanout).
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn stats(&self) -> ListStats {
+        stats::stats(self.root.clone())
+    }
+
+    /// Checks the internal structure of the list for consistency.
+    ///
+    /// This verifies that every internal node's stored size, length, and key
+    /// match its children, that every node has a number of children allowed
+    /// by the list's fanout, that all items are at the same depth, and that
+    /// the links between nodes (including those stored by each item through
+    /// [`LeafRef::set_next`]) form a proper tree.
+    ///
+    /// A list can become inconsistent only through an incorrect [`LeafRef`]
+    /// implementation or a change to an item's size or key without a
+    /// corresponding call to [`Self::update`]. Calling this method after each
+    /// operation in tests can help find such bugs where they occur, rather
+    /// than when the corruption is eventually noticed.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate::validate(self.root.clone())
+    }
+
+    /// Returns a value that displays the internal structure of the list as
+    /// plain text, showing the length and size of each node at each level.
+    ///
+    /// See [`ListDump`] for the format. This is intended for debugging; the
+    /// format may change.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*) to display.
+    pub fn dump(&self) -> ListDump<'_, L, A> {
+        ListDump {
+            list: self,
+        }
+    }
+
     /// Gets an item by index.
     ///
     /// Note that if there are items with a size of 0, this method will return
     /// the first non–zero-sized item at `index`, or the last item in the list
     /// if `index` is [`self.size()`](Self::size) and the list ends with a
-    /// zero-sized item.
+    /// zero-sized item. The latter case can be disabled with
+    /// [`ListOptions::EmptyPolicy`], in which case this method returns
+    /// [`None`] instead. On an empty list, this method always returns
+    /// [`None`], even for an `index` of 0.
     ///
     /// # Time complexity
     ///
@@ -320,6 +729,26 @@ where
         })
     }
 
+    /// Gets an item by one component of its index.
+    ///
+    /// This is equivalent to [`Self::get`], except that only the component
+    /// of each size given by `D` is compared with `index`. See [`Dimension`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `D` does not meet the requirements described
+    /// in [`Dimension`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_by<D>(&self, index: &D::Value) -> Option<L>
+    where
+        D: Dimension<LeafSize<L>>,
+    {
+        self.get_with_cmp(|size| D::get(size).cmp(index))
+    }
+
     /// Gets an item by index using the given comparison function.
     ///
     /// `cmp` checks whether its argument is less than, equal to, or greater
@@ -340,11 +769,266 @@ where
     ///
     /// Θ(log *n*).
     pub fn get_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        self.get_with_cmp_offset(cmp).map(|(item, _)| item)
+    }
+
+    /// Gets an item by index using a comparison function that can abort the
+    /// search.
+    ///
+    /// This method is like [`Self::get_with_cmp`], except that `cmp` returns
+    /// a [`ControlFlow`]. If it returns [`ControlFlow::Break`] (e.g., because
+    /// a deadline has passed), the search stops and a [`PartialGet`] is
+    /// returned, which can be used to resume the search later without
+    /// repeating the work already done.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `cmp` returns results inconsistent with the
+    /// total order on [`LeafSize<L>`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*) calls to `cmp`, in total across all resumptions.
+    pub fn get_with_cmp_budgeted<F, B>(
+        &self,
+        cmp: F,
+    ) -> ControlFlow<PartialGet<'_, L, A, B>, Option<L>>
+    where
+        F: Fn(&LeafSize<L>) -> ControlFlow<B, Ordering>,
+    {
+        match self.root.clone() {
+            Some(root) => budget::get(self, root, Default::default(), cmp),
+            None => ControlFlow::Continue(None),
+        }
+    }
+
+    /// Gets a [`Finger`], which speeds up searches by index that are close
+    /// to each other.
+    ///
+    /// Each search through the finger starts from the nodes visited by the
+    /// previous one, rather than from the root, so workloads that query
+    /// positions near each other (e.g., a moving cursor) take Θ(log *d*)
+    /// time per search on average, where *d* is the distance from the
+    /// previous index.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn finger(&self) -> Finger<'_, L, A> {
+        Finger::new(self)
+    }
+
+    /// Gets the item at position `index`, where positions count items rather
+    /// than sizes.
+    fn nth(&self, mut index: usize) -> Option<L> {
+        let mut node = self.root.clone()?;
+        if index >= node.leaf_count() {
+            return None;
+        }
+        loop {
+            let mut child = match node {
+                Down::Leaf(leaf) => return Some(leaf),
+                Down::Internal(node) => node.down().unwrap(),
+            };
+            while child.leaf_count() <= index {
+                index -= child.leaf_count();
+                child = match &child {
+                    Down::Leaf(node) => node.next_sibling().map(Down::Leaf),
+                    Down::Internal(node) => {
+                        node.next_sibling().map(Down::Internal)
+                    }
+                }
+                .unwrap();
+            }
+            node = child;
+        }
+    }
+
+    /// Writes the next buffer of items, starting at `checkpoint`, to
+    /// `write`.
+    ///
+    /// At most `buffer_len` items are passed to `write` (but always at least
+    /// one). Buffers end at the boundaries of the list's internal nodes where
+    /// possible. If `write` succeeds, `checkpoint` is advanced past the
+    /// written items; otherwise, it is left unchanged so the buffer can be
+    /// retried. Returns `Ok(false)` without calling `write` if there are no
+    /// more items.
+    ///
+    /// Because `checkpoint` doesn't borrow the list, this method can be used
+    /// to write very large lists incrementally, e.g., to disk, without
+    /// holding an iterator across `.await` points.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*k* + log *n*), where *k* is `buffer_len`.
+    pub fn spill<W, E>(
+        &self,
+        checkpoint: &mut SpillCheckpoint,
+        buffer_len: usize,
+        write: W,
+    ) -> Result<bool, E>
+    where
+        W: FnOnce(&[L]) -> Result<(), E>,
+    {
+        let first = match self.nth(checkpoint.index()) {
+            Some(first) => first,
+            None => return Ok(false),
+        };
+        let mut buffer = Vec::with_capacity(buffer_len);
+        spill::fill(first, buffer_len.max(1), &mut buffer);
+        write(&buffer)?;
+        *checkpoint =
+            SpillCheckpoint::at_index(checkpoint.index() + buffer.len());
+        Ok(true)
+    }
+
+    /// Gets an iterator over the items whose indices are in the range
+    /// [`start`, `end`).
+    ///
+    /// The first item is the one returned by [`self.get(start)`](Self::get),
+    /// which may begin before `start` if it is larger than 1. Iteration
+    /// stops at the first item whose index is at least `end`.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*k* + log *n*), where *k* is the number of items yielded.
+    pub fn iter_range<'a, S>(&self, start: &S, end: &'a S) -> Range<'a, L, S>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        let first = if start < end {
+            self.get_with_cmp_offset(|size| size.borrow().cmp(start))
+        } else {
+            None
+        };
+        let (first, index) = match first {
+            Some((item, index)) => (Some(item), index),
+            None => (None, Default::default()),
+        };
+        Range {
+            iter: Iter::new(first),
+            index,
+            end,
+        }
+    }
+
+    /// Gets the item at the given fraction of the list's total size.
+    ///
+    /// This method returns the same item as [`Self::get`] with an index of
+    /// ⌊*num* / *denom* × [`self.size()`](Self::size)⌋. The index is computed
+    /// exactly with 128-bit arithmetic, so this method works even when the
+    /// product of `num` and the list's size doesn't fit in the size type.
+    /// [`None`] is returned if the index doesn't fit in the size type.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `denom` is 0.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get_fraction(&self, num: u64, denom: u64) -> Option<L>
+    where
+        LeafSize<L>: Ord + TryInto<u128> + TryFrom<u128>,
+    {
+        assert!(denom != 0, "denominator is zero");
+        let total: u128 = self.size().try_into().ok()?;
+        let (num, denom) = (u128::from(num), u128::from(denom));
+        // ⌊num × total / denom⌋, computed without overflowing when the
+        // result fits.
+        let index = (total / denom)
+            .checked_mul(num)?
+            .checked_add(total % denom * num / denom)?;
+        self.get(&LeafSize::<L>::try_from(index).ok()?)
+    }
+
+    /// Gets the items at each index in `indices`.
+    ///
+    /// The returned iterator yields, for each index, the same value as
+    /// [`Self::get`]. If `indices` is in ascending order, the list is
+    /// searched only for the first index; each subsequent item is found by
+    /// moving forward from the previous one. This is faster than calling
+    /// [`Self::get`] repeatedly when the indices are close together, such as
+    /// when retrieving the visible portion of a list. Indices that are
+    /// smaller than the previous index are searched for from the root.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*k* + log *n* + *m*) for *k* ascending indices, where *m* is the
+    /// number of items between the first and last index.
+    pub fn get_many<'b, S, I>(
+        &self,
+        indices: I,
+    ) -> GetMany<'_, L, A, I::IntoIter>
+    where
+        S: Ord + ?Sized + 'b,
+        LeafSize<L>: Borrow<S>,
+        I: IntoIterator<Item = &'b S>,
+    {
+        GetMany {
+            list: self,
+            indices: indices.into_iter(),
+            current: None,
+        }
+    }
+
+    /// Like [`Self::get_with_cmp`], but also returns the index of the item.
+    fn get_with_cmp_offset<F>(&self, cmp: F) -> Option<(L, LeafSize<L>)>
     where
         F: Fn(&LeafSize<L>) -> Ordering,
     {
         SkipList::subtree_get(cmp, self.root.clone()?, Default::default())
     }
+
+    /// Gets the first item whose index satisfies `pred`, along with that
+    /// index. `pred` must be false for all indices below some threshold and
+    /// true for all others.
+    fn first_index_where<F>(&self, pred: F) -> Option<(L, LeafSize<L>)>
+    where
+        F: Fn(&LeafSize<L>) -> bool,
+    {
+        let mut node = self.root.clone()?;
+        if !pred(&self.size()) {
+            return None;
+        }
+        let mut index = LeafSize::<L>::default();
+        loop {
+            node = match node {
+                Down::Leaf(mut node) => loop {
+                    if pred(&index) {
+                        return Some((node, index));
+                    }
+                    index += node.size();
+                    node = match node.next_sibling() {
+                        Some(next) => next,
+                        // The index after the last item in this node
+                        // satisfies `pred` (see below), so the next item
+                        // (if any) is the first to do so.
+                        None => {
+                            return SkipList::next(node).map(|n| (n, index));
+                        }
+                    };
+                },
+                Down::Internal(mut node) => {
+                    // Descend into the first node whose end satisfies `pred`.
+                    while let Some(next) = node.next_sibling() {
+                        let mut end = index.clone();
+                        end += node.size();
+                        if pred(&end) {
+                            break;
+                        }
+                        index = end;
+                        node = next;
+                    }
+                    node.down().unwrap()
+                }
+            };
+        }
+    }
 }
 
 impl<L: LeafRef> SkipList<L> {
@@ -386,48 +1070,122 @@ impl<L: LeafRef> SkipList<L> {
         }
     }
 
+    /// Gets the number of items before `item`.
+    fn position(item: L) -> usize {
+        get_root(item.clone()).leaf_count() - Self::count_from(item)
+    }
+
+    /// Gets the number of items from `item` to the end of the list
+    /// (inclusive).
+    fn count_from(item: L) -> usize {
+        fn count_siblings<N: NodeRef>(
+            mut node: N,
+            count: &mut usize,
+        ) -> Option<InternalNodeRef<N::Leaf>> {
+            loop {
+                node = match node.next()? {
+                    Next::Parent(parent) => return Some(parent),
+                    Next::Sibling(node) => {
+                        *count += node.leaf_count();
+                        node
+                    }
+                }
+            }
+        }
+
+        let mut count = 1;
+        let mut node = count_siblings(item, &mut count);
+        while let Some(parent) = node {
+            node = count_siblings(parent, &mut count);
+        }
+        count
+    }
+
+    /// Returns the item and its index relative to the start of the subtree
+    /// (plus `offset`).
     fn subtree_get<F>(
         cmp: F,
         first_child: Down<L>,
         offset: LeafSize<L>,
-    ) -> Option<L>
+    ) -> Option<(L, LeafSize<L>)>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        Self::subtree_get_in(cmp, first_child, offset, None)
+    }
+
+    /// Like [`Self::subtree_get`], but if `path` is provided, records in it
+    /// the path from `first_child`, which must be the root, to the item.
+    fn subtree_get_in<F>(
+        cmp: F,
+        first_child: Down<L>,
+        offset: LeafSize<L>,
+        mut path: Option<&mut Path<L>>,
+    ) -> Option<(L, LeafSize<L>)>
     where
         F: Fn(&LeafSize<L>) -> Ordering,
     {
+        if let Some(path) = &mut path {
+            path.start_descent(first_child.clone());
+        }
         let mut node = first_child;
         let mut size = offset;
+        // The parent of `node`, if it is being recorded in `path`.
+        let mut parent = None;
         loop {
+            let mut index = 0;
             node = match node {
+                // `size` is updated in place, rather than cloned for each
+                // node, as sizes may be expensive to clone (e.g., big
+                // integers). The size of the node that is descended into (or
+                // returned) is subtracted afterward.
                 Down::Leaf(mut node) => loop {
-                    let new_size = size.clone().add(node.size());
-                    let ord = cmp(&new_size);
+                    size += node.size();
+                    let ord = cmp(&size);
                     if ord.is_le() {
                         if let Some(next) = node.next_sibling() {
                             node = next;
-                            size = new_size;
+                            index += 1;
                             continue;
                         }
-                        if !(ord.is_eq() && size == new_size) {
+                        if !(ord.is_eq()
+                            && node.size() == Default::default()
+                            && can_get_zero_sized_last::<L>())
+                        {
                             return None;
                         }
                         // Item is the last element of the list, has a size of
                         // zero, and is at the right index.
                     }
-                    return Some(node);
+                    size -= node.size();
+                    if let Some(path) = path {
+                        if let Some(parent) = parent {
+                            path.push_descent(parent, index);
+                        }
+                        path.finish_descent();
+                    }
+                    return Some((node, size));
                 },
                 Down::Internal(mut node) => loop {
-                    let new_size = size.clone().add(node.size());
-                    let ord = cmp(&new_size);
+                    size += node.size();
+                    let ord = cmp(&size);
                     if ord.is_le() {
                         if let Some(next) = node.next_sibling() {
                             node = next;
-                            size = new_size;
+                            index += 1;
                             continue;
                         }
                         if !ord.is_eq() {
                             return None;
                         }
                     }
+                    size -= node.size();
+                    if let Some(path) = &mut path {
+                        if let Some(parent) = parent {
+                            path.push_descent(parent, index);
+                        }
+                        parent = Some(node);
+                    }
                     break node.down().unwrap();
                 },
             }
@@ -457,6 +1215,27 @@ impl<L: LeafRef> SkipList<L> {
         Self::get_after_with_cmp(start, |size| size.borrow().cmp(offset))
     }
 
+    /// Gets an item by one component of its index, relative to the index of
+    /// another item.
+    ///
+    /// This method is to [`Self::get_after`] what [`Self::get_by`] is to
+    /// [`Self::get`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `D` does not meet the requirements described
+    /// in [`Dimension`].
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn get_after_by<D>(start: L, offset: &D::Value) -> Option<L>
+    where
+        D: Dimension<LeafSize<L>>,
+    {
+        Self::get_after_with_cmp(start, |size| D::get(size).cmp(offset))
+    }
+
     /// Gets an item by index, relative to the index of another item, using
     /// a size type that [`LeafSize<L>`] can't be borrowed as.
     ///
@@ -531,11 +1310,23 @@ impl<L: LeafRef> SkipList<L> {
     where
         F: Fn(&LeafSize<L>) -> Ordering,
     {
-        let mut leaf = start;
+        Self::get_after_with_cmp_offset(start, cmp).map(|(item, _)| item)
+    }
+
+    /// Like [`Self::get_after_with_cmp`], but also returns the index of the
+    /// item relative to the index of `start`.
+    fn get_after_with_cmp_offset<F>(
+        start: L,
+        cmp: F,
+    ) -> Option<(L, LeafSize<L>)>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        let mut leaf = start;
         let mut size = LeafSize::<L>::default();
         let mut ord;
+        // As in `subtree_get`, `size` is updated in place rather than cloned.
         let mut internal = loop {
-            let old_size = size.clone();
             size += leaf.size();
             ord = cmp(&size);
             if ord.is_le() {
@@ -548,11 +1339,14 @@ impl<L: LeafRef> SkipList<L> {
                     // If this match arm is taken: the item is the last element
                     // of the list, has a size of zero, and is at the right
                     // index.
-                    None if ord.is_eq() && old_size == size => {}
+                    None if ord.is_eq()
+                        && leaf.size() == Default::default()
+                        && can_get_zero_sized_last::<L>() => {}
                     None => return None,
                 }
             }
-            return Some(leaf);
+            size -= leaf.size();
+            return Some((leaf, size));
         };
 
         let mut leaf_is_last = true;
@@ -566,26 +1360,26 @@ impl<L: LeafRef> SkipList<L> {
                     internal = node;
                     continue;
                 }
-                None if ord.is_eq() => {
+                None if ord.is_eq() && can_get_zero_sized_last::<L>() => {
                     let last = if leaf_is_last {
                         leaf
                     } else {
                         Self::subtree_last(internal.as_down())
                     };
                     return if last.size() == Default::default() {
-                        Some(last)
+                        Some((last, size))
                     } else {
                         None
                     };
                 }
                 None => return None,
             }
-            let new_size = size.clone().add(internal.size());
-            ord = cmp(&new_size);
+            size += internal.size();
+            ord = cmp(&size);
             if ord.is_gt() {
+                size -= internal.size();
                 return Self::subtree_get(cmp, internal.down().unwrap(), size);
             }
-            size = new_size;
         }
     }
 }
@@ -609,6 +1403,83 @@ where
         self.insert_after_from(pos, once(item));
     }
 
+    /// Inserts `item` directly after `pos`, or returns an error if `pos` is
+    /// not from this list, `item` is already in a list, or memory couldn't be
+    /// allocated.
+    ///
+    /// Unlike [`Self::insert_after`], this method checks its arguments before
+    /// modifying the list. Note that an item that is the only item in a
+    /// different list cannot be detected as being in a list.
+    ///
+    /// This method also allocates any memory the insertion needs before
+    /// modifying the list, so if allocation fails, [`Error::Alloc`] is
+    /// returned and the list is left unchanged, rather than calling
+    /// [`handle_alloc_error`] (after consulting the hook set by
+    /// [`Self::set_alloc_error_hook`], if any).
+    ///
+    /// [`handle_alloc_error`]: alloc::alloc::handle_alloc_error
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn try_insert_after(&mut self, pos: L, item: L) -> Result<(), Error> {
+        self.check_unlinked(&item)?;
+        self.check_member(&pos)?;
+        let mut spare = self.reserve_for_insertion()?;
+        self.observe_insert(once(item), |list, items| {
+            list.insert_after_in(pos, items, &mut spare);
+        });
+        // SAFETY:
+        //
+        // * Every `InternalNode` in `spare` was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since the insertion didn't use them.
+        unsafe {
+            destroy_node_list(&mut spare, &self.alloc);
+        }
+        Ok(())
+    }
+
+    /// Allocates enough unused internal nodes, linked as siblings, for a
+    /// single item to be inserted into the list without further allocation.
+    fn reserve_for_insertion(
+        &self,
+    ) -> Result<Option<InternalNodeRef<L>>, Error> {
+        // Each level of internal nodes gains at most one node, and a new
+        // root may be needed.
+        let mut count = 0;
+        let mut down = self.root.clone();
+        while let Some(node) = down {
+            count += 1;
+            down = match node {
+                Down::Internal(node) => node.down(),
+                Down::Leaf(_) => None,
+            };
+        }
+        let mut spare = None;
+        for _ in 0..count {
+            match InternalNodeRef::try_alloc(&self.alloc) {
+                Ok(node) => {
+                    node.set_next(spare.map(Next::Sibling));
+                    spare = Some(node);
+                }
+                Err(_) => {
+                    // SAFETY:
+                    //
+                    // * Every `InternalNode` in `spare` was allocated by
+                    //   `self.alloc`.
+                    // * There are no other `InternalNodeRef`s that refer to
+                    //   these nodes, since they were just allocated.
+                    unsafe {
+                        destroy_node_list(&mut spare, &self.alloc);
+                    }
+                    return Err(Error::Alloc);
+                }
+            }
+        }
+        Ok(spare)
+    }
+
     /// Inserts the items in `items` directly after `pos`.
     ///
     /// # Panics
@@ -623,10 +1494,25 @@ where
     pub fn insert_after_from<I>(&mut self, pos: L, items: I)
     where
         I: IntoIterator<Item = L>,
+    {
+        self.observe_insert(items.into_iter(), |list, items| {
+            list.insert_after_in(pos, items, &mut None);
+        });
+    }
+
+    /// Like [`Self::insert_after_from`], but takes new internal nodes from
+    /// `spare` (a list of unused nodes, linked as siblings) first.
+    fn insert_after_in<I>(
+        &mut self,
+        pos: L,
+        items: I,
+        spare: &mut Option<InternalNodeRef<L>>,
+    ) where
+        I: Iterator<Item = L>,
     {
         let root = self.root.as_ref().expect("`pos` is not from this list");
         let set_unsafe_on_drop = SetUnsafeOnDrop;
-        let result = insert_after(pos, items.into_iter(), &self.alloc);
+        let result = insert_after(pos, items, spare, &self.alloc);
         assert!(
             roots_match(root, &result.old_root),
             "`pos` is not from this list",
@@ -635,6 +1521,62 @@ where
         self.root = Some(result.new_root);
     }
 
+    /// Creates an item for each element of `items` with `make_leaf` and
+    /// inserts the new items directly after `pos`.
+    ///
+    /// The new items are linked together and arranged into internal nodes
+    /// as they are created, and the resulting group is then spliced into the
+    /// list in one step, as with [`Self::graft_after`]. `make_leaf` is called
+    /// once for each element, in order.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `pos` is not from this list, or if any items
+    /// returned by `make_leaf` are already in a list. If `make_leaf` panics,
+    /// none of the items are inserted, but the items it already returned may
+    /// remain linked to each other. Memory may be leaked in these cases.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(*m* + log *n*), where *m* is the length of `items`.
+    pub fn insert_slice_after<T, F>(
+        &mut self,
+        pos: L,
+        items: &[T],
+        mut make_leaf: F,
+    ) where
+        F: FnMut(&T) -> L,
+    {
+        let root = self.root.as_ref().expect("`pos` is not from this list");
+        assert!(
+            roots_match(root, &get_root(pos.clone())),
+            "`pos` is not from this list",
+        );
+        let (first, rest) = match items.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        let first = make_leaf(first);
+        assert!(first.next().is_none(), "item is already in a list");
+        let mut last = first.clone();
+        for item in rest {
+            let item = make_leaf(item);
+            assert!(item.next().is_none(), "item is already in a list");
+            last.set_next_leaf(Some(LeafNext::Leaf(item.clone())));
+            last = item;
+        }
+
+        let mut inserted = Vec::new();
+        if self.is_observed() {
+            inserted.extend(Iter::with_len(Some(first.clone()), items.len()));
+        }
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let middle = build(first, items.len(), &mut None, &self.alloc);
+        mem::forget(set_unsafe_on_drop);
+        self.splice_after(Some(pos), Tree::new(middle));
+        self.notify_each(&inserted, |observer, item| observer.on_insert(item));
+    }
+
     /// Inserts `item` directly after `pos`, or at the start of the list if
     /// `pos` is [`None`].
     ///
@@ -743,6 +1685,70 @@ where
         }
     }
 
+    /// Inserts `item` at the given index.
+    ///
+    /// `item` is inserted directly before the first item whose index is
+    /// greater than or equal to `index`, so it is inserted before any
+    /// zero-sized items at `index`, and if `index` is
+    /// [`self.size()`](Self::size), it is inserted at the end of the list. If
+    /// `index` falls in the middle of an item, `item` is inserted directly
+    /// after that item. In every case, the new index of `item` is the
+    /// smallest index at or after `index` at which an item starts or the list
+    /// ends.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is greater than
+    /// [`self.size()`](Self::size). It may also panic if `item` is already in
+    /// a list. Memory may be leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn insert_at<S>(&mut self, index: &S, item: L)
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        let cmp = |size: &LeafSize<L>| size.borrow().cmp(index);
+        assert!(cmp(&self.size()).is_ge(), "index out of bounds");
+        let mut size = LeafSize::<L>::default();
+        let pos = if cmp(&size).is_ge() {
+            None
+        } else {
+            // Find the last item that starts before `index`. The list isn't
+            // empty, as `index` is greater than 0 but not greater than the
+            // size of the list.
+            let mut node = self.root.clone().unwrap();
+            Some(loop {
+                node = match node {
+                    Down::Leaf(mut node) => {
+                        while let Some(next) = node.next_sibling() {
+                            size += node.size();
+                            if cmp(&size).is_ge() {
+                                break;
+                            }
+                            node = next;
+                        }
+                        break node;
+                    }
+                    Down::Internal(mut node) => {
+                        while let Some(next) = node.next_sibling() {
+                            size += node.size();
+                            if cmp(&size).is_ge() {
+                                size -= node.size();
+                                break;
+                            }
+                            node = next;
+                        }
+                        node.down().unwrap()
+                    }
+                }
+            })
+        };
+        self.insert_after_opt(pos, item);
+    }
+
     /// Inserts `item` at the start of the list.
     ///
     /// # Panics
@@ -771,7 +1777,16 @@ where
     where
         I: IntoIterator<Item = L>,
     {
-        let mut iter = items.into_iter();
+        self.observe_insert(items.into_iter(), |list, items| {
+            list.push_front_in(items);
+        });
+    }
+
+    /// Implements [`Self::push_front_from`].
+    fn push_front_in<I>(&mut self, mut iter: I)
+    where
+        I: Iterator<Item = L>,
+    {
         let first = match iter.next() {
             Some(item) => item,
             None => return,
@@ -786,6 +1801,7 @@ where
                     Down::Leaf(node) => return node,
                     Down::Internal(node) => {
                         node.size.with_mut(|s| *s += size.clone());
+                        node.with_leaf_count(|n| *n += 1);
                         node.key.set(first.key());
                         down = node.down().unwrap();
                         parent = Some(node);
@@ -805,9 +1821,26 @@ where
             self.insert_after_from(first, iter.chain(once(next)));
         } else {
             debug_assert!(self.root.is_none());
-            self.root = Some(Down::Leaf(first.clone()));
-            self.insert_after_from(first, iter);
+            self.build_from(first, iter);
+        }
+    }
+
+    /// Builds the list from `first` and `rest`. The list must be empty.
+    fn build_from<I>(&mut self, first: L, rest: I)
+    where
+        I: Iterator<Item = L>,
+    {
+        let mut last = first.clone();
+        let mut len = 1;
+        for item in rest {
+            assert!(item.next().is_none(), "item is already in a list");
+            last.set_next_leaf(Some(LeafNext::Leaf(item.clone())));
+            last = item;
+            len += 1;
         }
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        self.root = Some(build(first, len, &mut None, &self.alloc));
+        mem::forget(set_unsafe_on_drop);
     }
 
     /// Inserts `item` at the end of the list.
@@ -841,6 +1874,27 @@ where
         self.insert_after_opt_from(self.last(), items);
     }
 
+    /// Adds `item` to the back of the list, or returns an error if `item` is
+    /// already in a list or memory couldn't be allocated.
+    ///
+    /// Like [`Self::try_insert_after`], this method checks its argument and
+    /// allocates any memory it needs before modifying the list, so the list
+    /// is left unchanged if an error is returned.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn try_push_back(&mut self, item: L) -> Result<(), Error> {
+        match self.last() {
+            Some(last) => self.try_insert_after(last, item),
+            None => {
+                self.check_unlinked(&item)?;
+                self.push_back(item);
+                Ok(())
+            }
+        }
+    }
+
     /// Removes `item` from the list.
     ///
     /// # Panics
@@ -853,7 +1907,7 @@ where
     /// Worst-case Θ(log *n*).
     pub fn remove(&mut self, item: L) {
         let root = self.root.as_ref().expect("`item` is not from this list");
-        let mut result = remove(item);
+        let mut result = remove(item.clone());
         assert!(
             roots_match(root, &result.old_root),
             "`item` is not from this list"
@@ -867,197 +1921,1911 @@ where
             destroy_node_list(&mut result.removed, &self.alloc);
         }
         self.root = result.new_root;
+        self.notify(|observer| observer.on_remove(&item));
     }
 
-    /// Updates the [`size`] of an item.
-    ///
-    /// This method should be used whenever `item` needs to be modified in a
-    /// way that could change the value returned by [`L::size`][`size`].
-    /// `update` should be a function that performs the modifications.
+    /// Removes and returns the item at `index`.
     ///
-    /// [`size`]: LeafRef::size
-    ///
-    /// # Panics
-    ///
-    /// This function may panic if `item` is not from this list.
+    /// The item is determined in the same way as [`Self::get`]. If there is
+    /// no item at `index`, the list is not modified and [`None`] is returned.
+    /// The ancestors of the item are recorded as it is found, so unlike
+    /// calling [`Self::get`] and then [`Self::remove`], the list doesn't have
+    /// to be climbed again to remove the item.
     ///
     /// # Time complexity
     ///
     /// Worst-case Θ(log *n*).
-    pub fn update<F>(&mut self, item: L, update: F)
+    pub fn remove_at<S>(&mut self, index: &S) -> Option<L>
     where
-        F: FnOnce(),
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
     {
-        let old_size = item.size();
-        update();
-        let new_size = item.size();
-        propagate_update_diff(item, None, old_size, new_size);
+        let root = self.root.clone()?;
+        let mut path = mem::take(&mut self.path);
+        let item = SkipList::subtree_get_in(
+            |size| size.borrow().cmp(index),
+            root,
+            Default::default(),
+            Some(&mut path),
+        );
+        if let Some((item, _)) = &item {
+            let mut result = remove_with_path(item.clone(), &path);
+            // SAFETY:
+            //
+            // * Every `InternalNode` in the list was allocated by
+            //   `self.alloc`.
+            // * There are no other `InternalNodeRef`s that refer to these
+            //   nodes, since `remove_with_path` removed them from the skip
+            //   list.
+            unsafe {
+                destroy_node_list(&mut result.removed, &self.alloc);
+            }
+            self.root = result.new_root;
+        }
+        path.clear();
+        self.path = path;
+        let (item, _) = item?;
+        self.notify(|observer| observer.on_remove(&item));
+        Some(item)
     }
 
-    /// Replaces an item with another item.
+    /// Removes and returns the first item in the list, or returns [`None`]
+    /// if the list is empty.
     ///
-    /// `old` should be an item in this list, while `new` should not be in any
-    /// list.
-    ///
-    /// # Panics
-    ///
-    /// This method may panic if `old` is not from this list, or if `new` is
-    /// already in a list.
+    /// The item is found and removed in one operation, without the check
+    /// that [`Self::remove`] performs to ensure the item is from this list.
     ///
     /// # Time complexity
     ///
     /// Worst-case Θ(log *n*).
-    pub fn replace(&mut self, old: L, new: L) {
-        assert!(new.next().is_none(), "new item is already in a list");
-        let old_size = old.size();
-        new.set_next(NodeRef::next(&old));
-        old.set_next(None);
-
-        let info = get_previous_info(new.clone());
-        let (parent, previous) = if let Some(prev) = info.previous {
-            (prev.parent, prev.node)
-        } else {
-            self.root = Some(new.as_down());
-            return;
-        };
-
-        match previous {
-            Next::Parent(parent) => parent.set_down(Some(new.as_down())),
-            Next::Sibling(prev) => {
-                prev.set_next(Some(Next::Sibling(new.clone())))
-            }
-        };
-
-        propagate_update_diff(
-            parent,
-            if info.index == 0 {
-                let key = new.key();
-                parent.key.set(key.clone());
-                key
-            } else {
-                None
-            },
-            old_size,
-            new.size(),
-        );
+    pub fn pop_front(&mut self) -> Option<L> {
+        let item = self.root.clone().map(SkipList::subtree_first)?;
+        self.remove_found(item.clone());
+        Some(item)
     }
 
-    /// Gets the first item in the list.
+    /// Removes and returns the last item in the list, or returns [`None`] if
+    /// the list is empty.
+    ///
+    /// As with [`Self::pop_front`], the item is found and removed in one
+    /// operation.
     ///
     /// # Time complexity
     ///
-    /// Θ(log *n*).
-    pub fn first(&self) -> Option<L> {
-        self.root.clone().map(SkipList::subtree_first)
+    /// Worst-case Θ(log *n*).
+    pub fn pop_back(&mut self) -> Option<L> {
+        let item = self.root.clone().map(SkipList::subtree_last)?;
+        self.remove_found(item.clone());
+        Some(item)
     }
 
-    /// Gets the last item in the list.
-    ///
-    /// # Time complexity
-    ///
-    /// Θ(log *n*).
-    pub fn last(&self) -> Option<L> {
-        self.root.clone().map(SkipList::subtree_last)
+    /// Removes `item`, which must have been obtained from this list.
+    fn remove_found(&mut self, item: L) {
+        let mut result = remove(item.clone());
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since `remove` removed them from the skip list.
+        unsafe {
+            destroy_node_list(&mut result.removed, &self.alloc);
+        }
+        self.root = result.new_root;
+        self.notify(|observer| observer.on_remove(&item));
     }
 
-    /// Gets an iterator over the items in the list.
+    /// Removes `item` from the list, or returns an error if `item` is not
+    /// from this list.
+    ///
+    /// Unlike [`Self::remove`], this method checks its argument before
+    /// modifying the list.
     ///
     /// # Time complexity
     ///
-    /// Iteration over the entire list is Θ(*n*).
-    pub fn iter(&self) -> Iter<L> {
-        Iter(self.first())
+    /// Worst-case Θ(log *n*).
+    pub fn try_remove(&mut self, item: L) -> Result<(), Error> {
+        self.check_member(&item)?;
+        self.remove(item);
+        Ok(())
     }
-}
 
-impl<L, A> SkipList<L, A>
-where
-    L: LeafRef,
-    A: Allocator,
-    L::Options: ListOptions<StoreKeys = Bool<true>>,
-{
-    /// Inserts an item in a sorted list.
+    /// Removes the items from `first` to `last` (inclusive).
+    ///
+    /// The removed items are returned as a lazy iterator; see [`Removed`].
     ///
     /// # Panics
     ///
-    /// This method may panic if the list is not sorted.
+    /// This method may panic if `first` or `last` is not from this list, or
+    /// if `last` comes before `first`. Memory may be leaked in this case.
     ///
     /// # Time complexity
     ///
-    /// Worst-case Θ(log *n*).
-    pub fn insert(&mut self, item: L) -> Result<(), L>
-    where
-        L: Ord,
-    {
-        self.insert_after_opt(
-            match self.find(&item) {
-                Ok(n) => Err(n), // Node already in list
-                Err(n) => Ok(n), // Node not in list
-            }?,
-            item,
+    /// Worst-case Θ(*k* + log *n*), where *k* is the number of removed items.
+    pub fn remove_range(&mut self, first: L, last: L) -> Removed<L> {
+        let root = self.root.as_ref().expect("`first` is not from this list");
+        assert!(
+            roots_match(root, &get_root(first.clone())),
+            "`first` is not from this list",
         );
-        Ok(())
+        let end = SkipList::next(last.clone());
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let mut freed = None;
+        let (left, mid) = split_at(first, &self.alloc, &mut freed);
+        assert!(
+            roots_match(&mid.root, &get_root(last)),
+            "`last` is not from this list or comes before `first`",
+        );
+        let (mid, right) = match end {
+            Some(end) => {
+                let (mid, right) = split_at(end, &self.alloc, &mut freed);
+                (mid.unwrap(), Some(right))
+            }
+            None => (mid, None),
+        };
+        mem::forget(set_unsafe_on_drop);
+        self.root = join(left, right, &self.alloc, &mut freed).map(|t| t.root);
+
+        let (first, len, mut nodes) = deconstruct_into_chain(mid.root);
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they have been removed from the list.
+        unsafe {
+            destroy_node_list(&mut nodes, &self.alloc);
+            destroy_node_list(&mut freed, &self.alloc);
+        }
+        let removed = Removed::new(Some(first), len);
+        self.notify_removed(&removed);
+        removed
     }
 
-    /// Finds an item in a sorted list.
-    ///
-    /// If the item is not in the list, this method returns an [`Err`] value
-    /// containing the existing list item that would immediately precede the
-    /// desired item if it were to be inserted. This can be used with
-    /// [`Self::insert_after_opt`].
+    /// Removes the first `n` items from the list, or every item if the list
+    /// has fewer than `n` items.
     ///
-    /// # Panics
-    ///
-    /// This method may panic if the list is not sorted.
+    /// The removed items are returned as a lazy iterator; see [`Removed`].
+    /// The items are detached with a single split of the list, which is
+    /// faster than calling [`Self::pop_front`] `n` times.
     ///
     /// # Time complexity
     ///
-    /// Worst-case Θ(log *n*).
-    pub fn find<K>(&self, key: &K) -> Result<L, Option<L>>
-    where
-        K: Ord + ?Sized,
-        L: Borrow<K>,
-    {
-        self.find_with_cmp(|item| item.borrow().cmp(key))
+    /// Worst-case Θ(*k* + log *n*), where *k* is the number of removed items.
+    pub fn take_front(&mut self, n: usize) -> Removed<L> {
+        let len = self.len();
+        if n >= len {
+            return self.drain();
+        }
+        if n == 0 {
+            return Removed::new(None, 0);
+        }
+        let first = self.first().unwrap();
+        let last = self.nth(n - 1).unwrap();
+        self.remove_range(first, last)
     }
 
-    /// Finds an item in a sorted list with a key type that `L` can't be
-    /// borrowed as.
+    /// Removes the last `n` items from the list, or every item if the list
+    /// has fewer than `n` items.
     ///
-    /// For this method to yield correct results, `K` and `L` must form a
-    /// total order ([`PartialOrd::partial_cmp`] should always return
-    /// [`Some`]).
+    /// The removed items are returned as a lazy iterator; see [`Removed`].
+    /// As with [`Self::take_front`], the items are detached with a single
+    /// split of the list.
     ///
-    /// The return value is the same as for [`Self::find`].
+    /// # Time complexity
     ///
-    /// # Panics
+    /// Worst-case Θ(*k* + log *n*), where *k* is the number of removed items.
+    pub fn take_back(&mut self, n: usize) -> Removed<L> {
+        let len = self.len();
+        if n >= len {
+            return self.drain();
+        }
+        if n == 0 {
+            return Removed::new(None, 0);
+        }
+        let first = self.nth(len - n).unwrap();
+        let last = self.last().unwrap();
+        self.remove_range(first, last)
+    }
+
+    /// Destroys the list incrementally.
     ///
-    /// This method may panic if the list is not sorted, or if `K` and `L` do
-    /// not form a total order.
+    /// Instead of freeing every internal node at once, as dropping the list
+    /// does, this method returns a [`Disposer`] that frees a bounded number
+    /// of nodes each time [`Disposer::step`] is called.
     ///
     /// # Time complexity
     ///
-    /// Worst-case Θ(log *n*).
-    pub fn find_with<K>(&self, key: &K) -> Result<L, Option<L>>
+    /// Constant.
+    pub fn dispose_incremental(self) -> Disposer<L, A> {
+        Disposer::new(self)
+    }
+
+    /// Removes all items from the list.
+    ///
+    /// The removed items are returned as a lazy iterator; see [`Removed`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn drain(&mut self) -> Removed<L> {
+        let (first, len, mut nodes) = match self.root.take() {
+            Some(root) => deconstruct_into_chain(root),
+            None => return Removed::new(None, 0),
+        };
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since we replaced `self.root` with `None`.
+        unsafe {
+            destroy_node_list(&mut nodes, &self.alloc);
+        }
+        let removed = Removed::new(Some(first), len);
+        self.notify_removed(&removed);
+        removed
+    }
+
+    /// Resumes iteration from a token obtained from [`Iter::resume_token`].
+    ///
+    /// If the token's item is still in this list, iteration resumes at that
+    /// item. Otherwise, iteration resumes at the item at the token's
+    /// [index](ResumeToken::index) (counting items rather than sizes), if
+    /// any. For sorted lists, [`Self::resume_sorted`] can resume at the
+    /// item's sorted position instead.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*) to create the iterator.
+    pub fn resume(&self, token: ResumeToken<L>) -> Iter<L> {
+        let item = match token.item {
+            Some(item) => item,
+            None => return Iter::new(None),
+        };
+        if self.check_member(&item).is_ok() {
+            return Iter::new(Some(item));
+        }
+        Iter::new(self.nth(token.index))
+    }
+
+    /// Applies a batch of operations, sorted by the position in the list to
+    /// which they refer.
+    ///
+    /// Each operation refers to an item that must already be in the list
+    /// before the batch is applied (except for the items being inserted).
+    /// Operations must be sorted by the position of that item; operations on
+    /// the same item may be in any order, except that an item cannot be
+    /// updated or removed after it is removed. Multiple insertions after the
+    /// same item are inserted in the order they appear in the batch.
+    ///
+    /// Returns the result of each operation. Operations that fail (e.g.,
+    /// because they refer to an item that isn't in this list, or because
+    /// they are out of order) are skipped and don't affect the list.
+    ///
+    /// If the batch is large relative to the list, the operations are
+    /// applied in a single pass and the list is rebuilt once, instead of
+    /// rebalancing the list after each operation.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if an update function panics. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(min(*k* log *n*, *n* + *k*)) to apply the batch, where *k* is the
+    /// number of operations. Validating it takes Θ(*k* + log *n*) when the
+    /// operations refer to items close to each other, and at most Θ(*k* log
+    /// *n*), as the position of each item is found starting from the previous
+    /// operation's item rather than from the root.
+    pub fn apply_sorted_batch<I, F>(
+        &mut self,
+        ops: I,
+    ) -> Vec<Result<(), Error>>
+    where
+        I: IntoIterator<Item = BatchOp<L, F>>,
+        F: FnOnce(),
+    {
+        batch::apply_sorted_batch(self, ops)
+    }
+
+    /// Removes every item for which `pred` returns true.
+    ///
+    /// Items are tested in order, and the list is rebuilt once at the end,
+    /// which is more efficient than calling [`Self::remove`] for each item.
+    /// The removed items are returned as a lazy iterator; see [`Removed`].
+    ///
+    /// If `pred` panics, the items that have already been tested and kept,
+    /// along with the items that haven't been tested yet, remain in the
+    /// list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), plus the time taken by `pred`.
+    pub fn extract_if<F>(&mut self, pred: F) -> Removed<L>
+    where
+        F: FnMut(&L) -> bool,
+    {
+        let removed = retain::extract_if(self, pred);
+        self.notify_removed(&removed);
+        removed
+    }
+
+    /// Keeps only the items for which `f` returns true.
+    ///
+    /// This is equivalent to calling [`Self::extract_if`] with the negation
+    /// of `f` and dropping the returned iterator.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), plus the time taken by `f`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&L) -> bool,
+    {
+        self.extract_if(|item| !f(item));
+    }
+
+    /// Removes the items for which `dead` returns true, and returns how many
+    /// items were removed.
+    ///
+    /// Along with each item, `dead` is given the number of clones of the
+    /// item that the list holds at that point, including the one passed to
+    /// `dead` and any held by the list's internal structure, so
+    /// reference-counted leaves can use this to tell whether an item is
+    /// referenced outside of the list.
+    ///
+    /// Dead items are unlinked in place as they're found, so the rest of the
+    /// list keeps its internal nodes.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), plus Θ(log *n*) for each item removed, plus the time taken by
+    /// `dead`.
+    pub(crate) fn purge<F>(&mut self, mut dead: F) -> usize
+    where
+        F: FnMut(&L, usize) -> bool,
+    {
+        let mut count = 0;
+        let mut node = self.first();
+        while let Some(item) = node {
+            node = SkipList::next(item.clone());
+            let held =
+                self.handles.as_ref().map_or(false, |h| h.contains(&item));
+            // The clone in `item` is also counted.
+            let refs = structure_refs(item.clone()) + 1 + usize::from(held);
+            if dead(&item, refs) {
+                self.remove(item);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Reverses the order of the items in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn reverse(&mut self) {
+        let (first, len, mut nodes) = match self.root.take() {
+            Some(root) => deconstruct_into_chain(root),
+            None => return,
+        };
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let mut prev = None;
+        let mut node = Some(first);
+        while let Some(leaf) = node {
+            node = leaf.next_sibling();
+            leaf.set_next_leaf(prev.map(LeafNext::Leaf));
+            prev = Some(leaf);
+        }
+        self.root = Some(build(prev.unwrap(), len, &mut nodes, &self.alloc));
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut nodes, &self.alloc);
+        }
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Reorders the list so that every item for which `pred` returns true
+    /// comes before every item for which it returns false, preserving the
+    /// relative order of the items within each group.
+    ///
+    /// Items are tested in order, and the list is rebuilt once at the end.
+    /// Returns the number of items for which `pred` returned true.
+    ///
+    /// If `pred` panics, the items that have already been tested are
+    /// partitioned, and the items that haven't been tested yet follow them
+    /// in their original order.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), plus the time taken by `pred`.
+    pub fn stable_partition_in_place<F>(&mut self, pred: F) -> usize
+    where
+        F: FnMut(&L) -> bool,
+    {
+        let count = retain::stable_partition(self, pred);
+        self.notify(|observer| observer.on_reorder());
+        count
+    }
+
+    /// Rebuilds the list from a chain of leaves that were linked outside of
+    /// the list.
+    ///
+    /// This is intended for implementations of [`LeafRef`] that construct
+    /// the chain themselves (e.g., when loading items from a custom format),
+    /// by storing <code>[LeafNext::Leaf]\(next)</code> in each leaf and
+    /// [`None`] in the last leaf. The list's internal levels are constructed
+    /// from scratch over the chain starting at `first`, reusing the list's
+    /// existing internal nodes where possible. The items previously in the
+    /// list are discarded without being accessed, so they may be part of the
+    /// new chain.
+    ///
+    /// If `count_hint` is [`Some`], it is used as the number of leaves in the
+    /// chain, which avoids an extra pass over the leaves.
+    ///
+    /// # Safety
+    ///
+    /// * Starting at `first` and repeatedly calling [`LeafRef::next`] must
+    ///   yield only [`LeafNext::Leaf`] values until [`None`] is returned.
+    /// * No leaf in the chain may be in any list other than this one.
+    /// * Items previously in this list that aren't part of the chain must not
+    ///   be inserted into any list again.
+    /// * If `count_hint` is [`Some`], it must be equal to the number of
+    ///   leaves in the chain.
+    ///
+    /// Every [handle](Self::handle) issued by this list is invalidated.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*), where *n* is the number of leaves in the chain, plus the
+    /// number of items previously in the list.
+    pub unsafe fn rebuild(&mut self, first: L, count_hint: Option<usize>) {
+        if let Some(handles) = &mut self.handles {
+            handles.clear();
+        }
+        let mut nodes = self.root.take().and_then(deconstruct_internal);
+        let len = count_hint.unwrap_or_else(|| {
+            let mut len = 1;
+            let mut leaf = first.clone();
+            while let Some(next) = leaf.next() {
+                leaf = match next {
+                    LeafNext::Leaf(next) => next,
+                    LeafNext::Data(_) => panic!("chain contains `Data`"),
+                };
+                len += 1;
+            }
+            len
+        });
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        self.root = Some(build(first, len, &mut nodes, &self.alloc));
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut nodes, &self.alloc);
+        }
+    }
+
+    /// Rebuilds the list's internal levels so that every node is as full as
+    /// possible.
+    ///
+    /// After many insertions and removals, nodes may be close to the minimum
+    /// length, which wastes memory and makes the list deeper than necessary.
+    /// This method restores the layout produced by [`Self::from_iter_in`],
+    /// reusing the list's existing internal nodes and freeing any that are
+    /// left over. The order of the items is unchanged.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn compact(&mut self) {
+        let (first, len, mut nodes) = match self.root.take() {
+            Some(root) => deconstruct_into_chain(root),
+            None => return,
+        };
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        self.root = Some(build(first, len, &mut nodes, &self.alloc));
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut nodes, &self.alloc);
+        }
+    }
+
+    /// Rotates the list so that the item at index `k` becomes the first item.
+    ///
+    /// The item at index `k` is determined in the same way as [`Self::get`].
+    /// That item and every item after it are moved to the start of the list,
+    /// and the items before it are moved to the end. If there is no item at
+    /// index `k`, the list is not modified.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn rotate_left_by_size<S>(&mut self, k: &S)
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        let item = match self.get(k) {
+            Some(item) => item,
+            None => return,
+        };
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let mut freed = None;
+        let (left, right) = split_at(item, &self.alloc, &mut freed);
+        self.root =
+            join(Some(right), left, &self.alloc, &mut freed).map(|t| t.root);
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut freed, &self.alloc);
+        }
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Splits the list into two at `at`.
+    ///
+    /// `at` and every item after it are moved to a new list, which is
+    /// returned, and the items before `at` remain in this list. Internal
+    /// nodes are reused where possible, so the new list uses a clone of this
+    /// list's allocator (and the same allocation error hook, if any).
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `at` is not from this list. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn split_off(&mut self, at: L) -> Self
+    where
+        A: Clone,
+    {
+        let root = self.root.as_ref().expect("`at` is not from this list");
+        assert!(
+            roots_match(root, &get_root(at.clone())),
+            "`at` is not from this list",
+        );
+        // SAFETY: Either `A` is `'static`, or this list was created with
+        // `Self::new_in_unchecked`, whose caller guaranteed that the memory
+        // remains valid for the items in lists created from this one.
+        let mut other =
+            unsafe { Self::new_in_unchecked(A::clone(&self.alloc)) };
+        other.alloc.set_hook(self.alloc.hook());
+        other.drop_policy = self.drop_policy;
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let mut freed = None;
+        let (left, right) = split_at(at, &self.alloc, &mut freed);
+        self.root = left.map(|t| t.root);
+        other.root = Some(right.root);
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by either list.
+        unsafe {
+            destroy_node_list(&mut freed, &self.alloc);
+        }
+        if self.is_observed() {
+            let moved: Vec<_> = other.iter().collect();
+            self.notify_each(&moved, |observer, item| {
+                observer.on_remove(item)
+            });
+        }
+        other
+    }
+
+    /// Creates a [`SubtreeBuilder`] whose items can be grafted into this
+    /// list.
+    ///
+    /// The builder uses a clone of this list's allocator, so the nodes it
+    /// allocates can be freed by this list.
+    pub fn subtree_builder(&self) -> SubtreeBuilder<L, A>
+    where
+        A: Clone,
+    {
+        // SAFETY: Either `A` is `'static`, or this list was created with
+        // `Self::new_in_unchecked`, whose caller guaranteed that the memory
+        // remains valid for the items in lists created from this one.
+        let mut list =
+            unsafe { Self::new_in_unchecked(A::clone(&self.alloc)) };
+        list.alloc.set_hook(self.alloc.hook());
+        SubtreeBuilder {
+            list,
+        }
+    }
+
+    /// Inserts the items in `subtree` after `pos`.
+    ///
+    /// Unlike [`Self::insert_after_from`], this method doesn't depend on the
+    /// number of items inserted, since they have already been arranged into
+    /// internal nodes by the builder.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `pos` is not from this list. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n* + log *k*), where *k* is the number of items in
+    /// `subtree`.
+    pub fn graft_after(&mut self, pos: L, subtree: SubtreeBuilder<L, A>) {
+        let root = self.root.as_ref().expect("`pos` is not from this list");
+        assert!(
+            roots_match(root, &get_root(pos.clone())),
+            "`pos` is not from this list",
+        );
+        self.graft_after_opt(Some(pos), subtree);
+    }
+
+    /// Inserts the items in `subtree` after `pos`, or at the start of the
+    /// list if `pos` is [`None`].
+    ///
+    /// See [`Self::graft_after`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `pos` is not from this list. Memory may be
+    /// leaked in this case.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n* + log *k*), where *k* is the number of items in
+    /// `subtree`.
+    pub fn graft_after_opt(
+        &mut self,
+        pos: Option<L>,
+        mut subtree: SubtreeBuilder<L, A>,
+    ) {
+        let mut grafted = Vec::new();
+        if self.is_observed() {
+            grafted.extend(subtree.list.iter());
+        }
+        let middle = match subtree.list.root.take() {
+            Some(root) => Tree::new(root),
+            None => return,
+        };
+        self.splice_after(pos, middle);
+        self.notify_each(&grafted, |observer, item| observer.on_insert(item));
+    }
+
+    /// Inserts the items in `middle`, which must have been allocated by
+    /// `self.alloc` or a clone of it, after `pos`, or at the start of the list
+    /// if `pos` is [`None`].
+    fn splice_after(&mut self, pos: Option<L>, middle: Tree<L>) {
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        let mut freed = None;
+        let (left, right) =
+            match (pos.clone().and_then(SkipList::next), pos.is_some()) {
+                (Some(next), _) => {
+                    let (left, right) =
+                        split_at(next, &self.alloc, &mut freed);
+                    (left, Some(right))
+                }
+                (None, true) => (self.root.take().map(Tree::new), None),
+                (None, false) => (None, self.root.take().map(Tree::new)),
+            };
+        let left = join(left, Some(middle), &self.alloc, &mut freed);
+        self.root = join(left, right, &self.alloc, &mut freed).map(|t| t.root);
+        mem::forget(set_unsafe_on_drop);
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in the list was allocated by `self.alloc`,
+        //   or by a clone of it for `middle`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut freed, &self.alloc);
+        }
+    }
+
+    /// Updates the [`size`] of an item.
+    ///
+    /// This method should be used whenever `item` needs to be modified in a
+    /// way that could change the value returned by [`L::size`][`size`].
+    /// `update` should be a function that performs the modifications.
+    ///
+    /// [`size`]: LeafRef::size
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if `item` is not from this list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn update<F>(&mut self, item: L, update: F)
+    where
+        F: FnOnce(),
+    {
+        let old_size = item.size();
+        update();
+        let new_size = item.size();
+        propagate_update_diff(item.clone(), None, old_size, new_size);
+        self.notify(|observer| observer.on_update(&item));
+    }
+
+    /// Returns a guard that updates the [`size`] of `item` when dropped.
+    ///
+    /// This is an alternative to [`Self::update`] for modifications that
+    /// don't fit in a closure, such as those that span an `await` or need to
+    /// return a value. `item` may be modified in any way while the guard
+    /// exists; when the guard is dropped, the list is updated with the
+    /// difference between the item's old and new sizes.
+    ///
+    /// [`size`]: LeafRef::size
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if `item` is not from this list. The panic
+    /// may occur when the guard is dropped.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*), when the guard is dropped.
+    pub fn update_guard(&mut self, item: L) -> UpdateGuard<'_, L, A> {
+        UpdateGuard::new(self, item)
+    }
+
+    /// Updates the [`size`] of many items.
+    ///
+    /// This method is equivalent to calling [`Self::update`] with each item
+    /// in `items`, where `update` is called with the item to perform the
+    /// modifications. However, changes are propagated through the list one
+    /// level at a time, so internal nodes shared by multiple items are
+    /// updated only once.
+    ///
+    /// [`size`]: LeafRef::size
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if any item is not from this list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(*m* log *m* + *m* log *n*), where *m* is the number of
+    /// items in `items`. If the items are contiguous, this is only
+    /// Θ(*m* log *m* + log *n*).
+    pub fn update_many<I, F>(&mut self, items: I, mut update: F)
+    where
+        I: IntoIterator<Item = L>,
+        F: FnMut(&L),
+    {
+        let mut updated = Vec::new();
+        let observed = self.is_observed();
+        let mut level: Vec<_> = items
+            .into_iter()
+            .filter_map(|item| {
+                let old_size = item.size();
+                update(&item);
+                if observed {
+                    updated.push(item.clone());
+                }
+                let new_size = item.size();
+                let changed = has_caches::<L>() || old_size != new_size;
+                get_parent(item)
+                    .filter(|_| changed)
+                    .map(|parent| (parent, old_size, new_size))
+            })
+            .collect();
+
+        while !level.is_empty() {
+            level.sort_unstable_by_key(|(node, _, _)| *node);
+            let mut next_level = Vec::new();
+            let mut entries = level.drain(..).peekable();
+            while let Some((node, old, new)) = entries.next() {
+                let old_size = node.size();
+                // Each entry is applied separately, subtracting first, so
+                // that the same item appearing more than once can't make
+                // the size overflow, as in `propagate_update_diff`.
+                node.size.with_mut(|s| {
+                    *s -= old;
+                    *s += new;
+                    while let Some((_, old, new)) =
+                        entries.next_if(|(other, _, _)| *other == node)
+                    {
+                        *s -= old;
+                        *s += new;
+                    }
+                });
+                node.invalidate_caches();
+                let new_size = node.size();
+                let changed = old_size != new_size;
+                if !(changed || has_caches::<L>()) {
+                    continue;
+                }
+                if let Some(parent) = get_parent(node) {
+                    next_level.push((parent, old_size, new_size));
+                }
+            }
+            drop(entries);
+            level = next_level;
+        }
+        self.notify_each(&updated, |observer, item| observer.on_update(item));
+    }
+
+    /// Replaces an item with another item.
+    ///
+    /// `old` should be an item in this list, while `new` should not be in any
+    /// list.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `old` is not from this list, or if `new` is
+    /// already in a list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn replace(&mut self, old: L, new: L) {
+        self.replace_in(old.clone(), new.clone(), None);
+        self.notify(|observer| {
+            observer.on_remove(&old);
+            observer.on_insert(&new);
+        });
+    }
+
+    /// Implements [`Self::replace`]. If provided, `path` must have been
+    /// recorded for `old`.
+    fn replace_in(&mut self, old: L, new: L, path: Option<&Path<L>>) {
+        assert!(new.next().is_none(), "new item is already in a list");
+        let old_size = old.size();
+        new.set_next(NodeRef::next(&old));
+        old.set_next(None);
+
+        let previous = match path {
+            Some(path) => path.previous(),
+            None => get_previous(new.clone()),
+        };
+        let previous = if let Some(previous) = previous {
+            previous
+        } else {
+            self.root = Some(new.as_down());
+            return;
+        };
+
+        match previous {
+            Next::Parent(parent) => parent.set_down(Some(new.as_down())),
+            Next::Sibling(prev) => {
+                prev.set_next(Some(Next::Sibling(new.clone())))
+            }
+        };
+        let new_size = new.size();
+        let key = new.key();
+        propagate_update_diff_in(new, key, old_size, new_size, path);
+    }
+
+    /// Replaces an item with another item, or returns an error if `old` is
+    /// not from this list or `new` is already in a list.
+    ///
+    /// Unlike [`Self::replace`], this method checks its arguments before
+    /// modifying the list. Note that an item that is the only item in a
+    /// different list cannot be detected as being in a list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn try_replace(&mut self, old: L, new: L) -> Result<(), Error> {
+        self.check_unlinked(&new)?;
+        self.check_member(&old)?;
+        self.replace(old, new);
+        Ok(())
+    }
+
+    /// Replaces the item at `index` with `new`, returning the old item.
+    ///
+    /// The item is determined in the same way as [`Self::get`]. If there is
+    /// no item at `index`, the list is not modified and [`None`] is returned.
+    /// This is useful when only the index of the item to replace is known.
+    /// The ancestors of the old item are recorded as it is found, so the list
+    /// doesn't have to be climbed again to replace it.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `new` is already in a list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn replace_at<S>(&mut self, index: &S, new: L) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        let root = self.root.clone()?;
+        let mut path = mem::take(&mut self.path);
+        let old = SkipList::subtree_get_in(
+            |size| size.borrow().cmp(index),
+            root,
+            Default::default(),
+            Some(&mut path),
+        );
+        if let Some((old, _)) = &old {
+            self.replace_in(old.clone(), new.clone(), Some(&path));
+        }
+        path.clear();
+        self.path = path;
+        let (old, _) = old?;
+        self.notify(|observer| {
+            observer.on_remove(&old);
+            observer.on_insert(&new);
+        });
+        Some(old)
+    }
+
+    /// Exchanges the positions of two items in the list.
+    ///
+    /// Both `a` and `b` should be items in this list. Unlike removing and
+    /// reinserting the items, this method updates the list in place, so the
+    /// list is never in a state where either item is missing.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `a` or `b` is not from this list.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn swap(&mut self, a: L, b: L) {
+        self.swap_in(a, b);
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Implements [`Self::swap`].
+    fn swap_in(&mut self, a: L, b: L) {
+        let info_a = get_previous_info(a.clone());
+        let info_b = get_previous_info(b.clone());
+        let (prev_a, prev_b) = match (info_a.previous, info_b.previous) {
+            (Some(prev_a), Some(prev_b)) => (prev_a, prev_b),
+            // The list contains only one item.
+            _ => return,
+        };
+
+        fn link<L: LeafRef>(previous: Next<L>, item: &L) {
+            match previous {
+                Next::Parent(parent) => parent.set_down(Some(item.as_down())),
+                Next::Sibling(prev) => {
+                    prev.set_next(Some(Next::Sibling(item.clone())))
+                }
+            }
+        }
+
+        if prev_a.parent == prev_b.parent {
+            let ((a, prev_a, index), (b, prev_b, b_index)) =
+                match info_a.index.cmp(&info_b.index) {
+                    Ordering::Less => (
+                        (a, prev_a.node, info_a.index),
+                        (b, prev_b.node, info_b.index),
+                    ),
+                    Ordering::Greater => (
+                        (b, prev_b.node, info_b.index),
+                        (a, prev_a.node, info_a.index),
+                    ),
+                    Ordering::Equal => return,
+                };
+
+            // `a` now comes before `b`.
+            let b_next = NodeRef::next(&b);
+            if b_index == index + 1 {
+                b.set_next(Some(Next::Sibling(a.clone())));
+            } else {
+                b.set_next(NodeRef::next(&a));
+                link(prev_b, &a);
+            }
+            a.set_next(b_next);
+            link(prev_a, &b);
+            let size = a.size();
+            propagate_update_diff(b.clone(), b.key(), size.clone(), size);
+            return;
+        }
+
+        let a_next = NodeRef::next(&a);
+        a.set_next(NodeRef::next(&b));
+        b.set_next(a_next);
+        link(prev_a.node, &b);
+        link(prev_b.node, &a);
+        let (a_size, b_size) = (a.size(), b.size());
+        propagate_update_diff(
+            b.clone(),
+            b.key(),
+            a_size.clone(),
+            b_size.clone(),
+        );
+        propagate_update_diff(a.clone(), a.key(), b_size, a_size);
+    }
+
+    /// Moves `item` so that it is directly after `pos`.
+    ///
+    /// Both `item` and `pos` should be items in this list. If `item` and
+    /// `pos` are the same item, or `item` is already directly after `pos`,
+    /// this method does nothing. If both items have the same parent node,
+    /// `item` is moved by relinking it within that node, without removing
+    /// and reinserting it.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` or `pos` is not from this list. Both
+    /// items are checked before the list is modified.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn move_after(&mut self, item: L, pos: L) {
+        let mut path = mem::take(&mut self.path);
+        self.detached(|list| list.move_after_in(item, pos, &mut path));
+        path.clear();
+        self.path = path;
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Implements [`Self::move_after`], using `path` as scratch space.
+    fn move_after_in(&mut self, item: L, pos: L, path: &mut Path<L>) {
+        assert!(
+            self.check_member_in(&item, path).is_ok(),
+            "`item` is not from this list",
+        );
+        let (parent, index) = match path.first() {
+            Some(level) => (level.parent, level.index),
+            // The list contains only one item.
+            None => return,
+        };
+        let prev: Next<L> = path.previous().unwrap();
+        assert!(
+            self.check_member_in(&pos, path).is_ok(),
+            "`pos` is not from this list"
+        );
+        let pos_index = match path.first() {
+            Some(level) if level.parent == parent => level.index,
+            _ => {
+                self.remove(item.clone());
+                self.insert_after(pos, item);
+                return;
+            }
+        };
+        if pos_index == index || pos_index + 1 == index {
+            return;
+        }
+
+        match prev {
+            Next::Parent(parent) => {
+                let next = item.next_sibling().unwrap();
+                parent.set_down(Some(next.as_down()));
+            }
+            Next::Sibling(prev) => prev.set_next(NodeRef::next(&item)),
+        }
+        item.set_next(NodeRef::next(&pos));
+        pos.set_next(Some(Next::Sibling(item)));
+
+        let first: L = parent.down_as().unwrap();
+        let key = if index == 0 {
+            first.key()
+        } else {
+            None
+        };
+        let size = first.size();
+        propagate_update_diff(first, key, size.clone(), size);
+    }
+
+    /// Moves `item` so that it is directly before `pos`.
+    ///
+    /// This method is like [`Self::move_after`], except that `item` is
+    /// placed before `pos`.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` or `pos` is not from this list. Both
+    /// items are checked before the list is modified.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn move_before(&mut self, item: L, pos: L) {
+        assert!(
+            self.check_member(&item).is_ok(),
+            "`item` is not from this list",
+        );
+        assert!(
+            self.check_member(&pos).is_ok(),
+            "`pos` is not from this list"
+        );
+        if same_leaf(&item, &pos) {
+            return;
+        }
+        self.detached(|list| {
+            if let Some(prev) = SkipList::previous(pos) {
+                list.move_after(item, prev);
+            } else {
+                list.remove(item.clone());
+                list.push_front(item);
+            }
+        });
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Checks that `item` is in this list.
+    fn check_member(&self, item: &L) -> Result<(), Error> {
+        match (&self.root, item.next().is_some()) {
+            (Some(Down::Leaf(root)), false)
+                if same_unlinked_leaf(root, item) =>
+            {
+                Ok(())
+            }
+            (_, false) => Err(Error::NotInList),
+            // If the root is a leaf, `item` can't be linked to it.
+            (Some(root @ Down::Internal(_)), true)
+                if roots_match(root, &get_root(item.clone())) =>
+            {
+                Ok(())
+            }
+            (_, true) => Err(Error::WrongList),
+        }
+    }
+
+    /// Like [`Self::check_member`], but also fills `path` with the ancestors
+    /// of `item` if it is in this list.
+    fn check_member_in(
+        &self,
+        item: &L,
+        path: &mut Path<L>,
+    ) -> Result<(), Error> {
+        path.clear();
+        match (&self.root, item.next().is_some()) {
+            (Some(Down::Leaf(root)), false)
+                if same_unlinked_leaf(root, item) =>
+            {
+                path.fill(item.clone());
+                Ok(())
+            }
+            (_, false) => Err(Error::NotInList),
+            (Some(root @ Down::Internal(_)), true) => {
+                path.fill(item.clone());
+                match path.root() {
+                    Some(item_root) if roots_match(root, item_root) => Ok(()),
+                    _ => Err(Error::WrongList),
+                }
+            }
+            (_, true) => Err(Error::WrongList),
+        }
+    }
+
+    /// Checks that `item` is not in a list, as far as can be determined.
+    fn check_unlinked(&self, item: &L) -> Result<(), Error> {
+        match &self.root {
+            _ if item.next().is_some() => Err(Error::AlreadyInList),
+            Some(Down::Leaf(root)) if same_unlinked_leaf(root, item) => {
+                Err(Error::AlreadyInList)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the first item in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn first(&self) -> Option<L> {
+        self.root.clone().map(SkipList::subtree_first)
+    }
+
+    /// Gets the last item in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn last(&self) -> Option<L> {
+        self.root.clone().map(SkipList::subtree_last)
+    }
+
+    /// Gets an iterator over the items in the list.
+    ///
+    /// # Time complexity
+    ///
+    /// Iteration over the entire list is Θ(*n*).
+    pub fn iter(&self) -> Iter<L> {
+        Iter::new(self.first())
+    }
+
+    /// Gets an iterator over the items in the list that implements
+    /// [`ExactSizeIterator`].
+    ///
+    /// Unlike [`Self::iter`], the returned iterator borrows the list, so the
+    /// number of remaining items is always known.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*), plus Θ(*n*) for iteration over the entire list.
+    pub fn iter_exact(&self) -> ListIter<'_, L> {
+        ListIter::new(Iter::with_len(self.first(), self.len()))
+    }
+
+    /// Gets a view of the items from `first` to `last` (inclusive), with
+    /// indices relative to the index of `first`.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `first` or `last` is not from this list, or
+    /// if `last` comes before `first`.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn slice(&self, first: L, last: L) -> ListSlice<'_, L, A> {
+        assert!(
+            self.check_member(&first).is_ok(),
+            "`first` is not from this list",
+        );
+        assert!(
+            self.check_member(&last).is_ok(),
+            "`last` is not from this list",
+        );
+        ListSlice::new(self, first, last)
+    }
+
+    /// Turns this list into a [`FrozenSkipList`], which can't be modified.
+    ///
+    /// If `L` implements [`SharedLeafRef`], the returned list can be read
+    /// from multiple threads at once. Use [`FrozenSkipList::thaw`] to get a
+    /// list that can be modified again.
+    pub fn freeze(self) -> FrozenSkipList<L, A> {
+        FrozenSkipList::new(self)
+    }
+
+    /// Turns this list into a [`DenseSkipList`], which can't be modified but
+    /// answers queries from a flat, cache-friendly index.
+    ///
+    /// This is useful for lists that are built once and then queried many
+    /// times. Use [`DenseSkipList::thaw`] to get a list that can be modified
+    /// again.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*).
+    pub fn freeze_dense(self) -> DenseSkipList<L, A> {
+        DenseSkipList::new(self)
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+    L::Options: ListOptions<StoreKeys = Bool<true>>,
+{
+    /// Inserts an item in a sorted list.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn insert(&mut self, item: L) -> Result<(), L>
+    where
+        L: Ord,
+    {
+        self.insert_after_opt(
+            match self.find(&item) {
+                Ok(n) => Err(n), // Node already in list
+                Err(n) => Ok(n), // Node not in list
+            }?,
+            item,
+        );
+        Ok(())
+    }
+
+    /// Inserts an item in a sorted list that may contain equal items.
+    ///
+    /// `policy` determines where the item is inserted relative to any items
+    /// that are equal to it. With [`DuplicatePolicy::Reject`], this method
+    /// behaves like [`Self::insert`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn insert_with_policy(
+        &mut self,
+        item: L,
+        policy: DuplicatePolicy,
+    ) -> Result<(), L>
+    where
+        L: Ord,
+    {
+        let prev = match policy {
+            DuplicatePolicy::Before => self.last_where(|n| *n < item),
+            DuplicatePolicy::After => self.last_where(|n| *n <= item),
+            DuplicatePolicy::Reject => return self.insert(item),
+        };
+        self.insert_after_opt(prev, item);
+        Ok(())
+    }
+
+    /// Updates an item in a sorted list in a way that could change its
+    /// position.
+    ///
+    /// `update` should be a function that performs the modifications, which
+    /// may change both the item's sort order and its [`size`]. Internal
+    /// nodes store their keys as clones of the items themselves, so there
+    /// are no stored copies of the old key to invalidate; instead, if the
+    /// item is no longer in order relative to its neighbors, it is moved to
+    /// its new position, after any items equal to it.
+    ///
+    /// [`size`]: LeafRef::size
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if `item` is not from this list or if the list
+    /// is not sorted (apart from `item`, after it is modified).
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn update_key<F>(&mut self, item: L, update: F)
+    where
+        L: Ord,
+        F: FnOnce(),
+    {
+        self.update(item.clone(), update);
+        let in_order = SkipList::previous(item.clone())
+            .map_or(true, |prev| prev <= item)
+            && SkipList::next(item.clone()).map_or(true, |next| item <= next);
+        if in_order {
+            return;
+        }
+        self.detached(|list| {
+            list.remove(item.clone());
+            let prev = list.last_where(|n| *n <= item);
+            list.insert_after_opt(prev, item);
+        });
+        self.notify(|observer| observer.on_reorder());
+    }
+
+    /// Gets the [`Entry`] for `key` in a sorted list.
+    ///
+    /// This searches the list once; the returned entry can then be used to
+    /// access, remove, or insert the item without searching again.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn entry<K>(&mut self, key: &K) -> Entry<'_, L, A>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        let found = self.find(key);
+        Entry::new(self, found)
+    }
+
+    /// Finds an item in a sorted list.
+    ///
+    /// If the item is not in the list, this method returns an [`Err`] value
+    /// containing the existing list item that would immediately precede the
+    /// desired item if it were to be inserted. This can be used with
+    /// [`Self::insert_after_opt`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.find_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Finds an item in a sorted list with a key type that `L` can't be
+    /// borrowed as.
+    ///
+    /// For this method to yield correct results, `K` and `L` must form a
+    /// total order ([`PartialOrd::partial_cmp`] should always return
+    /// [`Some`]).
+    ///
+    /// The return value is the same as for [`Self::find`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `K` and `L` do
+    /// not form a total order.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_with<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: ?Sized,
+        L: PartialOrd<K>,
+    {
+        self.find_with_cmp(|item| {
+            item.partial_cmp(key).unwrap_or_else(
+                #[cold]
+                || panic!("`partial_cmp` returned `None`"),
+            )
+        })
+    }
+
+    /// Finds an item in a sorted list using the given comparison function.
+    ///
+    /// `cmp` checks whether its argument is less than, equal to, or greater
+    /// than the desired item. Thus, the argument provided to `cmp` is
+    /// logically the *left-hand* side of the comparison.
+    ///
+    /// The return value is the same as for [`Self::find`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        SkipList::subtree_find(cmp, self.root.clone().ok_or(None)?)
+    }
+
+    /// Finds the first of a run of equal items in a sorted list.
+    ///
+    /// This method is like [`Self::find`], except that if the list contains
+    /// multiple items equal to `key`, the first one is returned. The [`Err`]
+    /// value is the same as for [`Self::find`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_first<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.find_first_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Finds the first of a run of equal items in a sorted list using the
+    /// given comparison function.
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`]. The
+    /// return value is the same as for [`Self::find_first`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_first_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        let prev = self.last_where(|item| cmp(item).is_lt());
+        match prev.clone().map_or_else(|| self.first(), SkipList::next) {
+            Some(item) if cmp(&item).is_eq() => Ok(item),
+            _ => Err(prev),
+        }
+    }
+
+    /// Finds the last of a run of equal items in a sorted list.
+    ///
+    /// This method is like [`Self::find`], except that if the list contains
+    /// multiple items equal to `key`, the last one is returned. The [`Err`]
+    /// value is the same as for [`Self::find`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_last<K>(&self, key: &K) -> Result<L, Option<L>>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.find_last_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Finds the last of a run of equal items in a sorted list using the
+    /// given comparison function.
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`]. The
+    /// return value is the same as for [`Self::find_last`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_last_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        match self.last_where(|item| cmp(item).is_le()) {
+            Some(item) if cmp(&item).is_eq() => Ok(item),
+            prev => Err(prev),
+        }
+    }
+
+    /// Gets the first item in a sorted list that is not less than `key`.
+    ///
+    /// Unlike [`Self::find`], this method doesn't distinguish between an item
+    /// that is equal to `key` and one that is greater, which makes it
+    /// convenient for starting a scan over a range of items. If the list
+    /// contains multiple items equal to `key`, the first one is returned.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn lower_bound<K>(&self, key: &K) -> Option<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.lower_bound_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Gets the first item in a sorted list for which `cmp` doesn't return
+    /// [`Ordering::Less`].
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn lower_bound_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        self.after_last_where(|item| cmp(item).is_lt())
+    }
+
+    /// Gets the first item in a sorted list that is greater than `key`.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn upper_bound<K>(&self, key: &K) -> Option<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.upper_bound_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Gets the first item in a sorted list for which `cmp` returns
+    /// [`Ordering::Greater`].
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn upper_bound_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        self.after_last_where(|item| cmp(item).is_le())
+    }
+
+    /// Gets the item after the one returned by [`Self::last_where`], or the
+    /// first item if `pred` returns false for every item.
+    fn after_last_where<F>(&self, pred: F) -> Option<L>
+    where
+        F: Fn(&L) -> bool,
+    {
+        match self.last_where(pred) {
+            Some(item) => SkipList::next(item),
+            None => self.first(),
+        }
+    }
+
+    /// Finds the first item in a sorted list that has the given key and whose
+    /// index is in the range [`start`, `end`).
+    ///
+    /// This is useful for searching within part of a list, like a selection.
+    /// Unlike filtering the items returned by [`Self::iter_range`], this
+    /// doesn't examine every item in the range.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn find_key_in_range<K, S>(
+        &self,
+        key: &K,
+        start: &S,
+        end: &S,
+    ) -> Option<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        let cmp = |item: &L| item.borrow().cmp(key);
+        let first = self.lower_bound_with_cmp(cmp)?;
+        let index = self.rank_with_cmp(cmp);
+        // Items are ordered by both key and index, so the desired item is the
+        // first that is past both `start` and the items less than `key`.
+        let (item, index) = if index.borrow() >= start {
+            (first, index)
+        } else {
+            self.first_index_where(|i| i.borrow() >= start)?
+        };
+        (cmp(&item).is_eq() && index.borrow() < end).then_some(item)
+    }
+
+    /// Merges the items in `other`, which must also be sorted, into this
+    /// sorted list.
+    ///
+    /// Both lists are walked in tandem and their items are relinked in
+    /// sorted order, after which the list is rebuilt in a single pass. This
+    /// is faster than calling [`Self::insert`] for each item in `other`.
+    /// Items in `other` are placed after any equal items in this list, and
+    /// the relative order of equal items from each list is preserved.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if either list is not sorted. If a comparison
+    /// panics, every item from both lists remains in this list, but the list
+    /// may no longer be sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n* + *m*), where *m* is the length of `other`.
+    pub fn merge_sorted(&mut self, other: SkipList<L, A>)
+    where
+        L: Ord,
+    {
+        let mut merged = Vec::new();
+        if self.is_observed() {
+            merged.extend(other.iter());
+        }
+        merge::merge_sorted(self, other);
+        self.notify_each(&merged, |observer, item| observer.on_insert(item));
+    }
+
+    /// Gets an iterator over the items in a sorted list whose keys are
+    /// between `start` and `end`.
+    ///
+    /// The first item is found with [`Self::lower_bound`] or
+    /// [`Self::upper_bound`], depending on `start`, and iteration stops at
+    /// the first item that is past `end`.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*k* + log *n*), where *k* is the number of items yielded.
+    pub fn range_by_key<'a, K>(
+        &self,
+        start: Bound<&K>,
+        end: Bound<&'a K>,
+    ) -> KeyRange<'a, L, K>
     where
-        K: ?Sized,
-        L: PartialOrd<K>,
+        K: Ord + ?Sized,
+        L: Borrow<K>,
     {
-        self.find_with_cmp(|item| {
-            item.partial_cmp(key).unwrap_or_else(
-                #[cold]
-                || panic!("`partial_cmp` returned `None`"),
-            )
+        let first = match start {
+            Bound::Included(start) => self.lower_bound(start),
+            Bound::Excluded(start) => self.upper_bound(start),
+            Bound::Unbounded => self.first(),
+        };
+        KeyRange {
+            iter: Iter::new(first),
+            end,
+        }
+    }
+
+    /// Gets the last item in a sorted list for which `pred` returns true.
+    ///
+    /// `pred` must return true for every item before the returned item and
+    /// false for every item after it.
+    fn last_where<F>(&self, pred: F) -> Option<L>
+    where
+        F: Fn(&L) -> bool,
+    {
+        let mut node = self.root.clone()?;
+        let mut last = None;
+        loop {
+            node = match node {
+                Down::Leaf(mut node) => loop {
+                    if !pred(&node) {
+                        return last;
+                    }
+                    match node.next_sibling() {
+                        Some(next) => {
+                            last = Some(node);
+                            node = next;
+                        }
+                        None => return Some(node),
+                    }
+                },
+                Down::Internal(mut node) => {
+                    // Keys are lower bounds of the items in each node, so
+                    // the desired item is in the last node whose key matches.
+                    if !pred(&node.key().unwrap()) {
+                        return last;
+                    }
+                    while let Some(next) = node.next_sibling() {
+                        if !pred(&next.key().unwrap()) {
+                            break;
+                        }
+                        node = next;
+                    }
+                    node.down().unwrap()
+                }
+            };
+        }
+    }
+
+    /// Finds an item in a sorted list using a comparison function that can
+    /// abort the search.
+    ///
+    /// This method is like [`Self::find_with_cmp`], except that `cmp` returns
+    /// a [`ControlFlow`]. If it returns [`ControlFlow::Break`] (e.g., because
+    /// a deadline has passed), the search stops and a [`PartialFind`] is
+    /// returned, which can be used to resume the search later without
+    /// repeating the work already done.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*) calls to `cmp`, in total across all
+    /// resumptions.
+    pub fn find_with_cmp_budgeted<F, B>(
+        &self,
+        cmp: F,
+    ) -> ControlFlow<PartialFind<'_, L, A, B>, Result<L, Option<L>>>
+    where
+        F: Fn(&L) -> ControlFlow<B, Ordering>,
+    {
+        match self.root.clone() {
+            Some(root) => budget::find(self, root, None, cmp),
+            None => ControlFlow::Continue(Err(None)),
+        }
+    }
+
+    /// Resumes iteration in a sorted list from a token obtained from
+    /// [`Iter::resume_token`].
+    ///
+    /// This method is like [`Self::resume`], except that if the token's item
+    /// is no longer in this list, iteration resumes at the first item that
+    /// is not less than it.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*) to create the iterator.
+    pub fn resume_sorted(&self, token: ResumeToken<L>) -> Iter<L>
+    where
+        L: Ord,
+    {
+        let item = match token.item {
+            Some(item) => item,
+            None => return Iter::new(None),
+        };
+        if self.check_member(&item).is_ok() {
+            return Iter::new(Some(item));
+        }
+        Iter::new(match self.find(&item) {
+            Ok(item) => Some(item),
+            Err(None) => self.first(),
+            Err(Some(prev)) => SkipList::next(prev),
         })
     }
 
-    /// Finds an item in a sorted list using the given comparison function.
+    /// Gets the combined size of all the items in a sorted list that are less
+    /// than `key`.
     ///
-    /// `cmp` checks whether its argument is less than, equal to, or greater
-    /// than the desired item. Thus, the argument provided to `cmp` is
-    /// logically the *left-hand* side of the comparison.
+    /// This is the index that an item equal to `key` has, or would have if
+    /// it were inserted. Unlike calling [`Self::find`] followed by
+    /// [`Self::index`], this method walks the list only once. The inverse
+    /// operation (getting an item by its index) is [`Self::get`].
     ///
-    /// The return value is the same as for [`Self::find`].
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn rank<K>(&self, key: &K) -> LeafSize<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.rank_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Gets the combined size of all the items in a sorted list for which
+    /// `cmp` returns [`Ordering::Less`].
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`].
     ///
     /// # Panics
     ///
@@ -1067,11 +3835,348 @@ where
     /// # Time complexity
     ///
     /// Worst-case Θ(log *n*).
-    pub fn find_with_cmp<F>(&self, cmp: F) -> Result<L, Option<L>>
+    pub fn rank_with_cmp<F>(&self, cmp: F) -> LeafSize<L>
     where
         F: Fn(&L) -> Ordering,
     {
-        SkipList::subtree_find(cmp, self.root.clone().ok_or(None)?)
+        let mut size = LeafSize::<L>::default();
+        let mut node = match self.root.clone() {
+            Some(root) => root,
+            None => return size,
+        };
+        loop {
+            node = match node {
+                Down::Leaf(mut node) => loop {
+                    if !cmp(&node).is_lt() {
+                        return size;
+                    }
+                    size += node.size();
+                    node = match node.next_sibling() {
+                        Some(next) => next,
+                        None => return size,
+                    };
+                },
+                Down::Internal(mut node) => {
+                    // Keys are lower bounds of the items in each node, so
+                    // every item in the siblings before the last node whose
+                    // key is less than `key` is also less than `key`.
+                    if !cmp(&node.key().unwrap()).is_lt() {
+                        return size;
+                    }
+                    while let Some(next) = node.next_sibling() {
+                        if !cmp(&next.key().unwrap()).is_lt() {
+                            break;
+                        }
+                        size += node.size();
+                        node = next;
+                    }
+                    node.down().unwrap()
+                }
+            };
+        }
+    }
+
+    /// Gets the combined size of the items in a sorted list that are at
+    /// least `lo` and less than `hi`.
+    ///
+    /// This is the difference between [`Self::rank`] of `hi` and of `lo`, so
+    /// the items in the range aren't visited. If `hi` is not greater than
+    /// `lo`, the range is empty.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn count_between<K>(&self, lo: &K, hi: &K) -> LeafSize<L>
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        if hi <= lo {
+            return Default::default();
+        }
+        let mut size = self.rank(hi);
+        size -= self.rank(lo);
+        size
+    }
+
+    /// Gets the items in a sorted list that immediately precede and follow
+    /// `key`.
+    ///
+    /// This method returns `(predecessor, successor)`, where `predecessor`
+    /// is the last item less than `key` and `successor` is the first item
+    /// greater than `key`. Items equal to `key` are not returned; use
+    /// [`Self::find`] to get them.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn nearest_by_key<K>(&self, key: &K) -> (Option<L>, Option<L>)
+    where
+        K: Ord + ?Sized,
+        L: Borrow<K>,
+    {
+        self.nearest_with_cmp(|item| item.borrow().cmp(key))
+    }
+
+    /// Gets the items in a sorted list that immediately precede and follow
+    /// the items for which `cmp` returns [`Ordering::Equal`].
+    ///
+    /// `cmp` is used in the same way as in [`Self::find_with_cmp`]. The
+    /// return value is the same as for [`Self::nearest_by_key`].
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted, or if `cmp` returns
+    /// results inconsistent with the total order on `L`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn nearest_with_cmp<F>(&self, cmp: F) -> (Option<L>, Option<L>)
+    where
+        F: Fn(&L) -> Ordering,
+    {
+        let mut node = match self.root.clone() {
+            Some(root) => root,
+            None => return (None, None),
+        };
+        let mut prev = None;
+        // The first item after the subtree currently being searched.
+        let mut after = None;
+        let mut next = 'descend: loop {
+            node = match node {
+                Down::Leaf(mut node) => loop {
+                    if !cmp(&node).is_lt() {
+                        break 'descend Some(node);
+                    }
+                    prev = Some(node.clone());
+                    node = match node.next_sibling() {
+                        Some(next) => next,
+                        None => break 'descend after,
+                    };
+                },
+                Down::Internal(mut node) => {
+                    let key = node.key().unwrap();
+                    // This can only happen at the root level, as any other
+                    // node is descended into only if its key is less than
+                    // `key`.
+                    if !cmp(&key).is_lt() {
+                        break Some(key);
+                    }
+                    while let Some(next) = node.next_sibling() {
+                        let key = next.key().unwrap();
+                        if !cmp(&key).is_lt() {
+                            after = Some(key);
+                            break;
+                        }
+                        node = next;
+                    }
+                    node.down().unwrap()
+                }
+            };
+        };
+        while let Some(item) = next.clone().filter(|n| cmp(n).is_eq()) {
+            next = SkipList::next(item);
+        }
+        (prev, next)
+    }
+
+    /// Gets an iterator over the items in a sorted list whose keys start with
+    /// `prefix`.
+    ///
+    /// `key` returns the key of an item as a slice (e.g., the bytes of a
+    /// string). The list must be sorted such that these keys are in
+    /// lexicographic order.
+    ///
+    /// # Panics
+    ///
+    /// This method may panic if the list is not sorted by `key`.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*) to find the first matching item. Iteration over
+    /// all matching items is Θ(*m* + log *n*), where *m* is the number of
+    /// matching items.
+    pub fn find_prefix<'a, T, F>(
+        &self,
+        prefix: &'a [T],
+        key: F,
+    ) -> Prefix<'a, L, T, F>
+    where
+        T: Ord,
+        F: Fn(&L) -> &[T],
+    {
+        let first = match self.find_with_cmp(|item| {
+            if key(item) < prefix {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }) {
+            Ok(item) => Some(item),
+            Err(Some(prev)) => SkipList::next(prev),
+            Err(None) => self.first(),
+        };
+        Prefix {
+            iter: Iter::new(first),
+            prefix,
+            key,
+        }
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef + Hash,
+    A: Allocator,
+    L::Options: ListOptions<StoreHashes = Bool<true>>,
+{
+    /// Gets a hash of all the items in the list.
+    ///
+    /// The hash depends only on the items in the list (hashed with [`Hash`])
+    /// and their order, not on the shape of the list's internal tree, so
+    /// replicas of a list with equal items have equal hashes. The hash is not
+    /// cryptographically secure. An empty list has a hash of 0.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(1) if the hash is cached. Each change to the list invalidates the
+    /// cached hashes of Θ(log *n*) internal nodes, which are recomputed the
+    /// next time they're needed.
+    pub fn content_hash(&self) -> u64 {
+        self.root.clone().map_or(0, hash::subtree_hash)
+    }
+
+    /// Gets a hash of the items in a subtree of the list.
+    ///
+    /// `path` identifies the subtree: starting at the root, each element is
+    /// the index of the child to descend into. An empty path refers to the
+    /// whole list, in which case this method is equivalent to
+    /// [`Self::content_hash`]. Returns [`None`] if `path` does not refer to
+    /// a node in the list.
+    ///
+    /// Subtree hashes are computed in the same way as [`Self::content_hash`].
+    /// Note that paths are only meaningful between lists whose trees have the
+    /// same shape, such as replicas that were built by the same sequence of
+    /// operations. Two replicas can locate the items in which they differ by
+    /// comparing the hashes of the children of nodes whose hashes differ.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*d*), where *d* is the length of `path`, if the hash is cached.
+    pub fn subtree_hash(&self, path: &[usize]) -> Option<u64> {
+        let mut node = self.root.clone()?;
+        for &index in path {
+            let mut child = match node {
+                Down::Leaf(_) => return None,
+                Down::Internal(node) => node.down().unwrap(),
+            };
+            for _ in 0..index {
+                child = match child {
+                    Down::Leaf(leaf) => leaf.next_sibling()?.as_down(),
+                    Down::Internal(node) => node.next_sibling()?.as_down(),
+                };
+            }
+            node = child;
+        }
+        Some(hash::subtree_hash(node))
+    }
+}
+
+impl<L, A, M> SkipList<L, A>
+where
+    L: LeafRef + Summarize<M>,
+    A: Allocator,
+    M: Monoid,
+    L::Options: ListOptions<Aggregate = options::Aggregate<M>>,
+{
+    /// Combines the aggregate values (see [`ListOptions::Aggregate`]) of the
+    /// items at positions `range.start` to `range.end` (exclusive), in list
+    /// order.
+    ///
+    /// Positions count items (starting at 0 for the first item) rather than
+    /// sizes. The result is combined from the values cached for each subtree
+    /// that lies entirely within the range, so only the nodes along the
+    /// boundaries of the range are visited.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `range.start` is greater than `range.end` or if
+    /// `range.end` is greater than [`self.len()`](Self::len).
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*), once the cached values have been computed. Each change to
+    /// the list invalidates the cached values of Θ(log *n*) internal nodes,
+    /// which are recomputed the next time they're needed.
+    pub fn query_aggregate(&self, range: ops::Range<usize>) -> M::Value {
+        assert!(range.start <= range.end, "range start is after range end");
+        assert!(range.end <= self.len(), "range end is out of bounds");
+        match self.root.clone() {
+            Some(root) if range.start < range.end => {
+                aggregate::range_aggregate(root, range.start, range.end)
+            }
+            _ => M::identity(),
+        }
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef + Interval,
+    A: Allocator,
+    L::Options: ListOptions<StoreMaxEnd = Bool<true>>,
+{
+    /// Returns an iterator over the intervals that contain `point` (i.e.,
+    /// whose start is less than or equal to `point` and whose end is greater
+    /// than `point`), in list order.
+    ///
+    /// The list must be sorted by the start of each interval; see
+    /// [`Interval`]. Otherwise, the results are unspecified.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ((*k* + 1) log *n*), where *k* is the number of intervals yielded,
+    /// once the cached values described in [`ListOptions::StoreMaxEnd`] have
+    /// been computed.
+    pub fn find_overlapping(&self, point: &L::Point) -> Overlapping<'_, L> {
+        Overlapping {
+            node: self.root.clone(),
+            low: point.clone(),
+            high: Bound::Included(point.clone()),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the intervals that overlap `range` (i.e.,
+    /// whose start is less than `range.end` and whose end is greater than
+    /// `range.start`), in list order. If `range` is empty, the iterator
+    /// yields nothing.
+    ///
+    /// The list must be sorted by the start of each interval; see
+    /// [`Interval`]. Otherwise, the results are unspecified.
+    ///
+    /// # Time complexity
+    ///
+    /// Same as [`Self::find_overlapping`].
+    pub fn find_all_overlapping(
+        &self,
+        range: ops::Range<L::Point>,
+    ) -> Overlapping<'_, L> {
+        Overlapping {
+            node: self.root.clone().filter(|_| range.start < range.end),
+            low: range.start,
+            high: Bound::Excluded(range.end),
+            phantom: PhantomData,
+        }
     }
 }
 
@@ -1273,10 +4378,13 @@ where
     A: Allocator,
 {
     fn drop(&mut self) {
-        let mut nodes = deconstruct(match self.root.take() {
-            Some(root) => root,
-            None => return,
-        });
+        let (mut nodes, released) = match self.root.take() {
+            Some(root) if self.drop_policy == DropPolicy::Release => {
+                let (first, len, nodes) = deconstruct_into_chain(root);
+                (nodes, Some(Removed::new(Some(first), len)))
+            }
+            root => (root.and_then(deconstruct), None),
+        };
 
         // SAFETY:
         //
@@ -1286,6 +4394,9 @@ where
         unsafe {
             destroy_node_list(&mut nodes, &self.alloc);
         }
+        if can_safely_destroy() {
+            released.into_iter().flatten().for_each(LeafExt::list_dropped);
+        }
 
         // SAFETY:
         //
@@ -1298,6 +4409,16 @@ where
     }
 }
 
+impl<L: LeafRef> FromIterator<L> for SkipList<L> {
+    /// Equivalent to [`Self::from_iter_in`] with [`Global`].
+    fn from_iter<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+    {
+        Self::from_iter_in(items, Global)
+    }
+}
+
 impl<L, A> Extend<L> for SkipList<L, A>
 where
     L: LeafRef,
@@ -17,9 +17,10 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::{Down, Key, LeafRef, Next, NextKind, NodeKind, NodeRef};
+use super::{AggregateValue, Down, Hash, Key, LeafRef, MaxEnd, Next};
+use super::{NextKind, NodeKind, NodeRef};
 use crate::PersistentAlloc;
-use crate::allocator::Allocator;
+use crate::allocator::{AllocError, Allocator};
 use crate::options::{LeafSize, ListOptions};
 use alloc::alloc::{Layout, handle_alloc_error};
 use cell_ref::{Cell, CellExt};
@@ -29,6 +30,7 @@ use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::Deref;
 use core::panic::{RefUnwindSafe, UnwindSafe};
 use core::ptr::NonNull;
+use integral_constant::Constant;
 use tagged_pointer::TaggedPtr;
 
 union DownUnion<L: LeafRef> {
@@ -50,6 +52,12 @@ impl<L: LeafRef> Default for DownUnion<L> {
 /// This may be useful when using custom allocators. The alignment of this
 /// type is guaranteed to be at least as large as the alignment of
 /// <code>L::[Options](LeafRef::Options)::[Align](ListOptions::Align)</code>.
+///
+/// If <code>L::[Options](LeafRef::Options)::[SlabSize]</code> is greater
+/// than 1, the list instead allocates larger blocks, each of which holds
+/// multiple items of this type.
+///
+/// [SlabSize]: ListOptions::SlabSize
 #[repr(transparent)]
 pub struct AllocItem<L: LeafRef>(MaybeUninit<InternalNode<L>>);
 
@@ -70,7 +78,16 @@ pub struct InternalNode<L: LeafRef> {
     down: Cell<DownUnion<L>>,
     pub size: Cell<LeafSize<L>>,
     pub len: Cell<usize>,
+    /// The number of leaves in this node's subtree.
+    leaf_count: Cell<usize>,
     pub key: Cell<Option<Key<L>>>,
+    /// The cached hash of the leaves in this node's subtree, if computed.
+    hash: Cell<Option<Hash<L>>>,
+    /// The cached leaf in this node's subtree whose interval ends last, if
+    /// computed.
+    max_end: Cell<Option<MaxEnd<L>>>,
+    /// The cached aggregate value of this node's subtree, if computed.
+    aggregate: Cell<Option<AggregateValue<L>>>,
 }
 
 impl<L: LeafRef> Default for InternalNode<L> {
@@ -81,11 +98,38 @@ impl<L: LeafRef> Default for InternalNode<L> {
             down: Cell::default(),
             size: Cell::default(),
             len: Cell::default(),
+            leaf_count: Cell::default(),
             key: Cell::default(),
+            hash: Cell::default(),
+            max_end: Cell::default(),
+            aggregate: Cell::default(),
         }
     }
 }
 
+/// Clones the value in `cell` without moving it out of the cell.
+///
+/// Unlike [`CellExt::get`], which temporarily replaces the value, this only
+/// reads the cell, so nodes can be read from multiple threads at once (see
+/// [`FrozenSkipList`](crate::FrozenSkipList)).
+///
+/// This must be used only for cells that are modified exclusively by methods
+/// of [`SkipList`](crate::SkipList) that take `&mut self` (or by code that
+/// owns the nodes outright, such as a builder). This holds for `size`, `key`,
+/// and `down`; the cached values, which are written on read paths, must not
+/// be read with this function.
+fn clone_cell<T: Clone>(cell: &Cell<T>) -> T {
+    // SAFETY: `T::clone` is user code and may re-enter the list, but only
+    // through shared access: the list is either borrowed for the duration of
+    // this call or, if a `&mut self` method is running, can be reached only
+    // through leaves, with associated functions like `SkipList::index` that
+    // only read the list's structure. Neither kind of access modifies `cell`
+    // (see above), and the crate never modifies a node while one of its
+    // cells is being cloned, so the cell isn't modified while this reference
+    // exists.
+    unsafe { &*cell.as_ptr() }.clone()
+}
+
 impl<L: LeafRef> InternalNode<L> {
     fn sentinel() -> NonNull<Self> {
         #[repr(align(4))]
@@ -127,9 +171,11 @@ impl<L: LeafRef> InternalNode<L> {
     }
 
     pub fn down(&self) -> Option<Down<L>> {
-        let next = self.next.take();
-        let down = self.down.take();
-        let result = if next.down_kind() == NodeKind::Leaf {
+        // SAFETY: `self.down` is modified only by `&mut self` methods of
+        // `SkipList`, so it isn't modified while this reference exists, even
+        // if `L::clone` re-enters the list. See `clone_cell`.
+        let down = unsafe { &*self.down.as_ptr() };
+        if self.next.get().down_kind() == NodeKind::Leaf {
             // SAFETY: Safe due to this type's invariants (`down` and
             // `down_kind` are always in sync).
             Some(Down::Leaf(L::clone(unsafe { &down.leaf })))
@@ -137,10 +183,7 @@ impl<L: LeafRef> InternalNode<L> {
             // SAFETY: Safe due to this type's invariants (`down` and
             // `down_kind` are always in sync).
             unsafe { down.internal }.map(Down::Internal)
-        };
-        self.down.set(down);
-        self.next.set(next);
-        result
+        }
     }
 
     pub fn down_as<N: NodeRef<Leaf = L>>(&self) -> Option<N> {
@@ -169,7 +212,67 @@ impl<L: LeafRef> InternalNode<L> {
     }
 
     pub fn size(&self) -> LeafSize<L> {
-        self.size.get()
+        clone_cell(&self.size)
+    }
+
+    /// Sets the number of leaves in this node's subtree. This also
+    /// invalidates the node's cached values.
+    pub fn set_leaf_count(&self, count: usize) {
+        self.leaf_count.set(count);
+        self.invalidate_caches();
+    }
+
+    /// Modifies the number of leaves in this node's subtree. This also
+    /// invalidates the node's cached values.
+    pub fn with_leaf_count<F: FnOnce(&mut usize)>(&self, f: F) {
+        self.leaf_count.with_mut(f);
+        self.invalidate_caches();
+    }
+
+    /// Takes the number of leaves in this node's subtree, leaving zero. This
+    /// also invalidates the node's cached values.
+    pub fn take_leaf_count(&self) -> usize {
+        self.invalidate_caches();
+        self.leaf_count.take()
+    }
+
+    pub fn hash(&self) -> Option<u64> {
+        use crate::options::StoreHashesPriv;
+        super::StoreHashes::<L>::to_u64(self.hash.get())
+    }
+
+    pub fn set_hash(&self, hash: u64) {
+        use crate::options::StoreHashesPriv;
+        self.hash.set(super::StoreHashes::<L>::as_hash(hash));
+    }
+
+    pub fn max_end(&self) -> Option<L> {
+        use crate::options::StoreMaxEndPriv;
+        super::StoreMaxEnd::<L>::to_leaf(self.max_end.get())
+    }
+
+    pub fn set_max_end(&self, leaf: L) {
+        use crate::options::StoreMaxEndPriv;
+        self.max_end.set(super::StoreMaxEnd::<L>::as_max_end(leaf));
+    }
+
+    pub fn clear_max_end(&self) {
+        self.max_end.set(None);
+    }
+
+    pub fn aggregate(&self) -> Option<AggregateValue<L>> {
+        self.aggregate.get()
+    }
+
+    pub fn set_aggregate(&self, value: AggregateValue<L>) {
+        self.aggregate.set(Some(value));
+    }
+
+    /// Invalidates the node's cached hash, [`MaxEnd`], and aggregate value.
+    pub fn invalidate_caches(&self) {
+        self.hash.set(None);
+        self.max_end.set(None);
+        self.aggregate.set(None);
     }
 }
 
@@ -220,33 +323,44 @@ impl<L: LeafRef> InternalNext<L> {
     }
 }
 
+fn slab_size<L: LeafRef>() -> usize {
+    <L::Options as ListOptions>::SlabSize::VALUE
+}
+
 pub struct InternalNodeRef<L: LeafRef>(NonNull<InternalNode<L>>);
 
 impl<L: LeafRef> InternalNodeRef<L> {
     pub fn alloc<A: Allocator>(alloc: &PersistentAlloc<A>) -> Self {
+        Self::try_alloc(alloc).unwrap_or_else(|_| {
+            handle_alloc_error(Layout::new::<InternalNode<L>>())
+        })
+    }
+
+    pub fn try_alloc<A: Allocator>(
+        alloc: &PersistentAlloc<A>,
+    ) -> Result<Self, AllocError> {
         let layout = Layout::new::<InternalNode<L>>();
         let ptr = alloc
-            .allocate(layout)
-            .unwrap_or_else(|_| handle_alloc_error(layout))
+            .allocate_in_slab(layout, slab_size::<L>())?
             .cast::<InternalNode<L>>();
         // SAFETY: `Allocator::allocate` returns valid memory matching the
         // provied layout.
         unsafe {
             ptr.as_ptr().write(InternalNode::default());
         }
-        Self(ptr)
+        Ok(Self(ptr))
     }
 
     /// # Safety
     ///
     /// * This node must have been allocated by `alloc`.
     /// * There must be no other [`InternalNodeRef`]s that refer to this node.
-    pub unsafe fn dealloc<A: Allocator>(self, alloc: &A) {
+    pub unsafe fn dealloc<A: Allocator>(self, alloc: &PersistentAlloc<A>) {
         // SAFETY: `self.0` is always an initialized, properly aligned pointer.
         let layout = Layout::for_value(&unsafe { self.0.as_ptr().read() });
         // SAFETY: Checked by caller.
         unsafe {
-            alloc.deallocate(self.0.cast(), layout);
+            alloc.deallocate_in_slab(self.0.cast(), layout, slab_size::<L>());
         }
     }
 
@@ -282,6 +396,10 @@ impl<L: LeafRef> NodeRef for InternalNodeRef<L> {
         (**self).size()
     }
 
+    fn leaf_count(&self) -> usize {
+        self.leaf_count.get()
+    }
+
     fn as_down(&self) -> Down<L> {
         Down::Internal(*self)
     }
@@ -294,7 +412,7 @@ impl<L: LeafRef> NodeRef for InternalNodeRef<L> {
     }
 
     fn key(&self) -> Option<Key<L>> {
-        self.key.get()
+        clone_cell(&self.key)
     }
 }
 
@@ -18,6 +18,7 @@
  */
 
 use super::{AllocItem, Down, InternalNodeRef, Key, Next, NodeRef};
+use crate::DropPolicy;
 use crate::options::{LeafSize, ListOptions};
 use core::ops::{AddAssign, Deref, SubAssign};
 use core::ptr::NonNull;
@@ -52,6 +53,17 @@ pub unsafe trait LeafRef: Clone {
     /// [`Options`]: crate::Options
     type Options: ListOptions;
 
+    /// The [`DropPolicy`] that new lists of this type use; see
+    /// [`SkipList::set_drop_policy`].
+    ///
+    /// This is [`DropPolicy::Unlink`] by default. If it is
+    /// [`DropPolicy::Release`], it must be safe for a list to call
+    /// [`Self::on_list_drop`] with every item it contains when it is
+    /// dropped.
+    ///
+    /// [`SkipList::set_drop_policy`]: crate::SkipList::set_drop_policy
+    const DROP_POLICY: DropPolicy = DropPolicy::Unlink;
+
     /// Gets the item/data that follows this leaf.
     ///
     /// Leaf items should be able to store the item provided to
@@ -82,6 +94,47 @@ pub unsafe trait LeafRef: Clone {
     fn size(&self) -> LeafSize<Self> {
         Default::default()
     }
+
+    /// Gets the level of the node boundary that follows this item, which can
+    /// be used to make the list's structure depend on its contents.
+    ///
+    /// When the list builds its internal nodes from many items at once (in
+    /// [`SkipList::compact`], [`SkipList::rebuild`], and when items are
+    /// inserted into an empty list), a node at height *h* (where leaves have
+    /// a height of 0) ends after this item, if the minimum and maximum node
+    /// lengths allow it, when this method returns a value greater than *h*.
+    /// If the level is derived from the item's contents (e.g., from the
+    /// number of trailing zero bits in a hash of the item, divided by a
+    /// constant), sequences of equal items are grouped into the same nodes
+    /// in every list, except near the ends of the sequence. This can be used
+    /// to share or compare nodes across lists.
+    ///
+    /// Items inserted individually are not affected by this method;
+    /// [`SkipList::compact`] can be used to restore content-defined nodes.
+    /// By default, this method returns 0, so nodes are split based only on
+    /// their lengths.
+    ///
+    /// [`SkipList::compact`]: crate::SkipList::compact
+    /// [`SkipList::rebuild`]: crate::SkipList::rebuild
+    fn chunk_level(&self) -> usize {
+        0
+    }
+
+    /// Called with each item in a list when the list is dropped, if its
+    /// [`DropPolicy`] is [`DropPolicy::Release`]. The item has already been
+    /// unlinked from the list.
+    ///
+    /// This can be used to release resources that the list owns through its
+    /// items. By default, this method does nothing (other than dropping
+    /// `this`).
+    ///
+    /// This function takes a value of type <code>[This]\<Self></code> so
+    /// that it can be called only by the list; use [`This::into_inner`] to
+    /// obtain the item.
+    ///
+    fn on_list_drop(this: This<Self>) {
+        let _ = this;
+    }
 }
 
 /// The item/data that can be stored and retrieved with [`LeafRef::set_next`]
@@ -115,6 +168,13 @@ pub enum LeafNext<L: LeafRef> {
 /// [r]: reference
 pub struct This<T>(T);
 
+impl<T> This<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(this: Self) -> T {
+        this.0
+    }
+}
+
 impl<T> Deref for This<&'_ T> {
     type Target = T;
 
@@ -150,6 +210,10 @@ impl<L: LeafRef> NodeRef for L {
         LeafRef::size(self)
     }
 
+    fn leaf_count(&self) -> usize {
+        1
+    }
+
     fn as_down(&self) -> Down<Self> {
         Down::Leaf(self.clone())
     }
@@ -171,6 +235,10 @@ pub trait LeafExt: LeafRef {
     fn set_next_leaf(&self, next: Option<LeafNext<Self>>) {
         Self::set_next(This(self), next);
     }
+
+    fn list_dropped(self) {
+        Self::on_list_drop(This(self));
+    }
 }
 
 impl<L: LeafRef> LeafExt for L {}
@@ -17,7 +17,8 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::options::{LeafSize, ListOptions, StoreKeysPriv};
+use crate::options::{AggregatePriv, StoreMaxEndPriv};
+use crate::options::{LeafSize, ListOptions, StoreHashesPriv, StoreKeysPriv};
 
 pub mod internal;
 pub mod leaf;
@@ -28,11 +29,31 @@ pub use leaf::{LeafExt, LeafNext, LeafRef, SizeExt, This};
 type StoreKeys<L> = <<L as LeafRef>::Options as ListOptions>::StoreKeys;
 pub type Key<L> = <StoreKeys<L> as StoreKeysPriv>::Key<L>;
 
+type StoreHashes<L> = <<L as LeafRef>::Options as ListOptions>::StoreHashes;
+pub type Hash<L> = <StoreHashes<L> as StoreHashesPriv>::Hash;
+
+type StoreMaxEnd<L> = <<L as LeafRef>::Options as ListOptions>::StoreMaxEnd;
+pub type MaxEnd<L> = <StoreMaxEnd<L> as StoreMaxEndPriv>::MaxEnd<L>;
+
+type AggregateOption<L> = <<L as LeafRef>::Options as ListOptions>::Aggregate;
+pub type AggregateValue<L> = <AggregateOption<L> as AggregatePriv>::Value;
+
+/// Whether internal nodes cache values (hashes, [`MaxEnd`]s, or
+/// [`AggregateValue`]s) that must be invalidated when any item in their
+/// subtree changes.
+pub fn has_caches<L: LeafRef>() -> bool {
+    StoreHashes::<L>::ENABLED
+        || StoreMaxEnd::<L>::ENABLED
+        || AggregateOption::<L>::ENABLED
+}
+
 pub trait NodeRef: Clone {
     type Leaf: LeafRef;
     fn next(&self) -> Option<Next<Self>>;
     fn set_next(&self, next: Option<Next<Self>>);
     fn size(&self) -> LeafSize<Self::Leaf>;
+    /// The number of leaves in the subtree rooted at this node.
+    fn leaf_count(&self) -> usize;
     fn as_down(&self) -> Down<Self::Leaf>;
     fn from_down(down: Down<Self::Leaf>) -> Option<Self>;
     fn key(&self) -> Option<Key<Self::Leaf>>;
@@ -77,6 +98,13 @@ impl<L: LeafRef> Down<L> {
         }
     }
 
+    pub fn leaf_count(&self) -> usize {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Internal(node) => node.leaf_count(),
+        }
+    }
+
     pub fn key(&self) -> Option<Key<L>> {
         match self {
             Self::Leaf(node) => node.key(),
@@ -0,0 +1,170 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::iter::Removed;
+use super::{LeafRef, SkipList};
+use crate::allocator::Allocator;
+use alloc::vec::Vec;
+
+/// Receives notifications about changes to the items in a [`SkipList`].
+///
+/// An observer is attached to a list with [`SkipList::set_observer`]. This
+/// is useful for keeping another data structure (like a buffer on a GPU) in
+/// sync with the list without having to wrap every call that modifies it.
+///
+/// Each method is called once the operation that caused it has finished, so
+/// the list is in a consistent state. Operations that are implemented in
+/// terms of others (like [`SkipList::push_back`] or
+/// [`SkipList::move_before`]) report their changes once, as described by
+/// the methods below, rather than once for each step. Every method does
+/// nothing by default.
+///
+/// Reporting changes takes constant time per reported item, which can make
+/// some operations slower than their documented complexity. For example,
+/// [`SkipList::split_off`] reports every item moved to the new list.
+///
+/// Changes made with [`SkipList::rebuild`] aren't reported, and neither are
+/// the items unlinked when the list is dropped. The list's internal nodes
+/// aren't exposed, so changes to them alone (as with [`SkipList::compact`])
+/// aren't reported either.
+pub trait ListObserver<L> {
+    /// Called after `item` is inserted into the list.
+    ///
+    /// Items inserted by [`SkipList::merge_sorted`],
+    /// [`SkipList::graft_after`], and [`SkipList::replace`] are reported with
+    /// this method too.
+    fn on_insert(&mut self, item: &L) {
+        let _ = item;
+    }
+
+    /// Called after `item` is removed from the list.
+    ///
+    /// This includes items removed by [`SkipList::replace`] and items moved
+    /// to another list by [`SkipList::split_off`]. Items removed with a lazy
+    /// iterator (like the one returned by [`SkipList::drain`]) are reported
+    /// before the iterator is returned.
+    fn on_remove(&mut self, item: &L) {
+        let _ = item;
+    }
+
+    /// Called after `item` is updated with [`SkipList::update`] or a similar
+    /// method.
+    fn on_update(&mut self, item: &L) {
+        let _ = item;
+    }
+
+    /// Called after an operation that may have changed the order of the
+    /// items in the list without inserting or removing any (like
+    /// [`SkipList::reverse`] or [`SkipList::swap`]).
+    fn on_reorder(&mut self) {}
+}
+
+/// An iterator that records the items it yields if `items` is [`Some`].
+pub struct Record<'a, I: Iterator> {
+    iter: I,
+    items: Option<&'a mut Vec<I::Item>>,
+}
+
+impl<I> Iterator for Record<'_, I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next()?;
+        if let Some(items) = &mut self.items {
+            items.push(item.clone());
+        }
+        Some(item)
+    }
+}
+
+impl<L, A> SkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Returns whether changes to the list need to be reported, either to
+    /// an observer or to the list's [handles](SkipList::handle).
+    pub(super) fn is_observed(&self) -> bool {
+        self.observer.is_some() || self.handles.is_some()
+    }
+
+    /// Calls `f` with the list's handle map and observer, if any.
+    pub(super) fn notify<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut dyn ListObserver<L>),
+    {
+        if let Some(handles) = &mut self.handles {
+            f(&mut **handles);
+        }
+        if let Some(observer) = &mut self.observer {
+            f(&mut **observer);
+        }
+    }
+
+    /// Calls `op` with the list's handle map and observer detached, so that
+    /// the operations `op` performs aren't reported individually.
+    pub(super) fn detached<R, F>(&mut self, op: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let handles = self.handles.take();
+        let observer = self.observer.take();
+        let result = op(self);
+        self.handles = handles;
+        self.observer = observer;
+        result
+    }
+
+    /// Calls `insert` with the list's observer detached, and then reports
+    /// every item that `insert` took from `items` as inserted.
+    pub(super) fn observe_insert<I, F>(&mut self, items: I, insert: F)
+    where
+        I: Iterator<Item = L>,
+        F: FnOnce(&mut Self, Record<'_, I>),
+    {
+        let mut inserted = Vec::new();
+        let record = Record {
+            iter: items,
+            items: self.is_observed().then_some(&mut inserted),
+        };
+        self.detached(|list| insert(list, record));
+        self.notify_each(&inserted, |observer, item| observer.on_insert(item));
+    }
+
+    /// Reports each item in `items` with `f`.
+    pub(super) fn notify_each<F>(&mut self, items: &[L], mut f: F)
+    where
+        F: FnMut(&mut dyn ListObserver<L>, &L),
+    {
+        self.notify(|observer| {
+            items.iter().for_each(|item| f(observer, item));
+        });
+    }
+
+    /// Reports the items that haven't been yielded by `removed` as removed.
+    pub(super) fn notify_removed(&mut self, removed: &Removed<L>) {
+        self.notify(|observer| {
+            removed.for_each_pending(|item| observer.on_remove(item));
+        });
+    }
+}
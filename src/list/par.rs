@@ -0,0 +1,80 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{SkipList, SubtreeBuilder, SyncLeafRef, SyncSkipList};
+use alloc::vec::Vec;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+impl<L: SyncLeafRef> SkipList<L> {
+    /// Creates a new skip list containing the items produced by `items` in
+    /// parallel, in order, using `make_leaf` to turn each one into a leaf.
+    ///
+    /// The input is partitioned across [rayon]'s thread pool; the leaves for
+    /// each part are created and arranged into internal nodes on the thread
+    /// that handles that part, and the parts are then joined together like
+    /// [`Self::graft_after`].
+    ///
+    /// # Safety
+    ///
+    /// `make_leaf` must return leaves that aren't in any list, and no clones
+    /// of those leaves may exist outside the returned list.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(*n*) total work, but with *p* threads, roughly Θ(*n* / *p* + *p* log
+    /// *n*) time.
+    pub(crate) unsafe fn from_par_iter_unchecked<I, F>(
+        items: I,
+        make_leaf: F,
+    ) -> Self
+    where
+        I: IntoParallelIterator,
+        F: Fn(I::Item) -> L + Sync + Send,
+    {
+        items
+            .into_par_iter()
+            .fold(Vec::new, |mut part, item| {
+                part.push(item);
+                part
+            })
+            .map(|part| {
+                let mut list = SkipList::new();
+                list.push_back_from(part.into_iter().map(&make_leaf));
+                // SAFETY: The caller guaranteed that no clones of the items
+                // in `list` exist elsewhere, `list` shares no memory with
+                // any other list, and it has no observer.
+                unsafe { SyncSkipList::new(list) }
+            })
+            .reduce(
+                // SAFETY: The list is empty.
+                || unsafe { SyncSkipList::new(SkipList::new()) },
+                |mut left, right| {
+                    let last = left.last();
+                    // SAFETY: `right` was also created by this function, so
+                    // the caller's guarantees apply to its items as well.
+                    let list = unsafe { left.get_mut() };
+                    list.graft_after_opt(last, SubtreeBuilder {
+                        list: right.into_inner(),
+                    });
+                    left
+                },
+            )
+            .into_inner()
+    }
+}
@@ -0,0 +1,320 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A binary snapshot format for saving lists and loading them quickly.
+//!
+//! [`save`] writes a [`SkipList`] to a byte buffer, including the shape of
+//! its internal nodes, and [`load`] rebuilds a list with exactly that shape in
+//! Θ(*n*) time. Loading doesn't insert items one at a time or rebalance
+//! anything, so even very large lists can be restored quickly. Unlike a
+//! [`MappedList`], the loaded list is an ordinary [`SkipList`] that can be
+//! modified.
+//!
+//! Items are converted to and from bytes by a [`LeafCodec`]. The sizes and
+//! keys of internal nodes aren't stored; they're computed from the decoded
+//! items while the nodes are created.
+//!
+//! # Format
+//!
+//! All integers are stored as little-endian [`u64`]s, and no alignment is
+//! required. For a list of *n* items, the format is:
+//!
+//! 1. `magic`: the bytes `SKIPPYS1` (8 bytes).
+//! 2. `fanout`: the maximum number of children of an internal node, as
+//!    configured by [`ListOptions::Fanout`] (8 bytes).
+//! 3. `len`: *n*, the number of items (8 bytes).
+//! 4. `shape`: the number of children of each internal node, level by level
+//!    from the bottom up, and from left to right within each level. The
+//!    lengths in each level add up to the number of nodes in the level below
+//!    it (or *n*, for the bottom level), and the last level contains only
+//!    the root.
+//! 5. `items`: for each item, the number of bytes written by
+//!    [`LeafCodec::encode`], followed by those bytes.
+//!
+//! A snapshot can be loaded only by a list with the same fanout.
+//! [`load`] checks the entire format and returns
+//! [`Error::InvalidFormat`] if anything is wrong, so malformed data can't
+//! produce an invalid list.
+//!
+//! # Example
+//!
+//! ```
+//! use skippy::basic::{self, BasicLeaf, RcLeaf};
+//! use skippy::{Error, SkipList};
+//! use skippy::persist::{self, LeafCodec};
+//! use std::rc::Rc;
+//!
+//! struct Word(String);
+//!
+//! impl BasicLeaf for Word {
+//!     type Options = basic::options::Options<usize>;
+//!
+//!     fn size(&self) -> usize {
+//!         self.0.len()
+//!     }
+//! }
+//!
+//! fn new_leaf(word: String) -> Rc<RcLeaf<Word>> {
+//!     Rc::new(RcLeaf::new(Word(word)))
+//! }
+//!
+//! struct Codec;
+//!
+//! impl LeafCodec<Rc<RcLeaf<Word>>> for Codec {
+//!     fn encode(&self, item: &Rc<RcLeaf<Word>>, out: &mut Vec<u8>) {
+//!         out.extend_from_slice(item.0.as_bytes());
+//!     }
+//!
+//!     fn decode(&self, bytes: &[u8]) -> Result<Rc<RcLeaf<Word>>, Error> {
+//!         let word = String::from_utf8(bytes.to_vec());
+//!         word.map(new_leaf).map_err(|_| Error::InvalidFormat)
+//!     }
+//! }
+//!
+//! let words = ["skip", "lists", "are", "neat"];
+//! let leaves = words.map(|w| new_leaf(w.to_string()));
+//! let list: SkipList<_> = leaves.into_iter().collect();
+//! let mut bytes = Vec::new();
+//! persist::save(&list, &Codec, &mut bytes);
+//!
+//! let loaded = persist::load(&bytes, &Codec).unwrap();
+//! assert_eq!(loaded.size(), 16);
+//! assert!(loaded.iter().map(|w| w.0.clone()).eq(words));
+//! ```
+//!
+//! [`MappedList`]: crate::mapped::MappedList
+//! [`ListOptions::Fanout`]: crate::ListOptions::Fanout
+
+use super::build::build_shape;
+use super::destroy_safety::SetUnsafeOnDrop;
+use super::node::{Down, LeafExt, LeafNext, NodeRef};
+use super::{LeafRef, SkipList, max_node_length, min_node_length};
+use crate::Error;
+use crate::allocator::{Allocator, Global};
+use alloc::vec::Vec;
+use core::mem;
+
+const MAGIC: &[u8; 8] = b"SKIPPYS1";
+
+/// Converts items to and from bytes for [`save`] and [`load`].
+pub trait LeafCodec<L> {
+    /// Appends the bytes that represent `item` to `out`.
+    fn encode(&self, item: &L, out: &mut Vec<u8>);
+
+    /// Creates an item from the bytes that were written by [`Self::encode`].
+    ///
+    /// The returned item must not be in any list. [`load`] returns any error
+    /// from this method.
+    fn decode(&self, bytes: &[u8]) -> Result<L, Error>;
+}
+
+fn push_u64(out: &mut Vec<u8>, n: u64) {
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+/// Reads the parts of a snapshot in order.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::InvalidFormat);
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn read_usize(&mut self) -> Result<usize, Error> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.take(8)?);
+        usize::try_from(u64::from_le_bytes(buf))
+            .map_err(|_| Error::InvalidFormat)
+    }
+}
+
+/// Writes `list` to `out` in the format read by [`load`].
+///
+/// # Time complexity
+///
+/// Θ(*n*), plus the time taken by [`LeafCodec::encode`].
+pub fn save<L, A, C>(list: &SkipList<L, A>, codec: &C, out: &mut Vec<u8>)
+where
+    L: LeafRef,
+    A: Allocator,
+    C: LeafCodec<L> + ?Sized,
+{
+    out.extend_from_slice(MAGIC);
+    push_u64(out, max_node_length::<L>() as u64);
+    push_u64(out, list.len() as u64);
+
+    // Collect the lengths of the internal nodes from the top down, so they
+    // can be written from the bottom up.
+    let mut levels: Vec<Vec<usize>> = Vec::new();
+    let mut level = match list.root.clone() {
+        Some(Down::Internal(root)) => Vec::from([root]),
+        _ => Vec::new(),
+    };
+    while !level.is_empty() {
+        levels.push(level.iter().map(|node| node.len.get()).collect());
+        let mut below = Vec::new();
+        for node in &level {
+            let mut child = match node.down() {
+                Some(Down::Internal(child)) => Some(child),
+                _ => None,
+            };
+            while let Some(node) = child {
+                below.push(node);
+                child = node.next_sibling();
+            }
+        }
+        level = below;
+    }
+    for level in levels.iter().rev() {
+        level.iter().for_each(|&len| push_u64(out, len as u64));
+    }
+
+    for item in list.iter() {
+        let start = out.len();
+        push_u64(out, 0);
+        codec.encode(&item, out);
+        let len = (out.len() - start - 8) as u64;
+        out[start..start + 8].copy_from_slice(&len.to_le_bytes());
+    }
+}
+
+/// Loads a list that was written by [`save`], using `codec` to decode its
+/// items.
+///
+/// Returns [`Error::InvalidFormat`] if `bytes` isn't a valid snapshot or was
+/// saved by a list with a different fanout, or any error returned by
+/// [`LeafCodec::decode`]. Returns [`Error::AlreadyInList`] if a decoded item
+/// is already in a list.
+///
+/// # Time complexity
+///
+/// Θ(*n*), plus the time taken by [`LeafCodec::decode`].
+pub fn load<L, C>(bytes: &[u8], codec: &C) -> Result<SkipList<L>, Error>
+where
+    L: LeafRef,
+    C: LeafCodec<L> + ?Sized,
+{
+    load_in(bytes, codec, Global)
+}
+
+/// Like [`load`], but creates the list with the given allocator, as with
+/// [`SkipList::new_in`].
+pub fn load_in<L, A, C>(
+    bytes: &[u8],
+    codec: &C,
+    alloc: A,
+) -> Result<SkipList<L, A>, Error>
+where
+    L: LeafRef,
+    A: Allocator + 'static,
+    C: LeafCodec<L> + ?Sized,
+{
+    let mut reader = Reader {
+        bytes,
+    };
+    if reader.take(8)? != MAGIC {
+        return Err(Error::InvalidFormat);
+    }
+    if reader.read_usize()? != max_node_length::<L>() {
+        return Err(Error::InvalidFormat);
+    }
+    let len = reader.read_usize()?;
+    let shape = read_shape::<L>(&mut reader, len)?;
+
+    let mut items = Vec::new();
+    if let Err(e) = read_items(&mut reader, len, codec, &mut items) {
+        items.iter().for_each(|item| item.set_next_leaf(None));
+        return Err(e);
+    }
+
+    let mut list = SkipList::new_in(alloc);
+    if let Some(first) = items.into_iter().next() {
+        let set_unsafe_on_drop = SetUnsafeOnDrop;
+        list.root =
+            Some(build_shape(first, len, &mut shape.into_iter(), &list.alloc));
+        mem::forget(set_unsafe_on_drop);
+    }
+    Ok(list)
+}
+
+/// Reads and checks the lengths of the internal nodes of a list with `len`
+/// items.
+fn read_shape<L: LeafRef>(
+    reader: &mut Reader<'_>,
+    len: usize,
+) -> Result<Vec<usize>, Error> {
+    let min = min_node_length::<L>();
+    // See `validate::check_children`.
+    let max = max_node_length::<L>() + 1;
+    let mut shape = Vec::new();
+    let mut count = len;
+    while count > 1 {
+        let mut remaining = count;
+        count = 0;
+        while remaining > 0 {
+            let node_len = reader.read_usize()?;
+            // The root may have fewer than `min` children.
+            let is_root = count == 0 && node_len == remaining;
+            if node_len > remaining.min(max) || (node_len < min && !is_root) {
+                return Err(Error::InvalidFormat);
+            }
+            remaining -= node_len;
+            count += 1;
+            shape.push(node_len);
+        }
+    }
+    Ok(shape)
+}
+
+/// Decodes `len` items and links them together, pushing them to `items`.
+/// If an error is returned, the items that were already decoded are left in
+/// `items` so they can be unlinked.
+fn read_items<L, C>(
+    reader: &mut Reader<'_>,
+    len: usize,
+    codec: &C,
+    items: &mut Vec<L>,
+) -> Result<(), Error>
+where
+    L: LeafRef,
+    C: LeafCodec<L> + ?Sized,
+{
+    for _ in 0..len {
+        let item_len = reader.read_usize()?;
+        let item = codec.decode(reader.take(item_len)?)?;
+        if item.next().is_some() {
+            return Err(Error::AlreadyInList);
+        }
+        if let Some(last) = items.last() {
+            last.set_next_leaf(Some(LeafNext::Leaf(item.clone())));
+        }
+        items.push(item);
+    }
+    if !reader.bytes.is_empty() {
+        return Err(Error::InvalidFormat);
+    }
+    Ok(())
+}
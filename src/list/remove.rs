@@ -17,9 +17,10 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::min_node_length;
-use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
+use super::node::{Down, InternalNodeRef, Key, LeafRef, Next, NodeRef};
+use super::traverse::{ParentInfo, Path, get_parent_info};
 use super::traverse::{get_nth_sibling, get_previous, get_previous_info};
+use super::{chaos, max_node_length, min_node_length};
 use crate::options::LeafSize;
 use cell_ref::CellExt;
 
@@ -70,17 +71,60 @@ pub struct FinishedRemoval<L: LeafRef> {
     pub removed: Option<InternalNodeRef<L>>,
 }
 
-fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
+/// Sets the key of `node`, which has a new first child, and of each ancestor
+/// of which it is the first descendant.
+fn update_first_key<L: LeafRef>(
+    mut node: InternalNodeRef<L>,
+    key: Option<Key<L>>,
+) {
+    if key.is_none() {
+        // Keys aren't stored.
+        return;
+    }
+    loop {
+        node.key.set(key.clone());
+        match get_parent_info(node) {
+            ParentInfo {
+                parent: Some(parent),
+                index: 0,
+                ..
+            } => node = parent,
+            _ => return,
+        }
+    }
+}
+
+/// Finds the parent of `node` and the node before it, using `path` if
+/// provided. `level` is the level of `path` that corresponds to `node`.
+fn find_previous<N: NodeRef>(
+    node: N,
+    path: Option<&Path<N::Leaf>>,
+    level: usize,
+) -> Option<(InternalNodeRef<N::Leaf>, Next<N>)> {
+    if let Some(path) = path {
+        let parent = path.level(level)?.parent;
+        return Some((parent, path.previous_at(level).unwrap()));
+    }
+    let previous = get_previous_info(node).previous?;
+    Some((previous.parent, previous.node))
+}
+
+fn handle_removal<N: NodeRef>(
+    removal: Removal<N>,
+    path: Option<&Path<N::Leaf>>,
+    level: usize,
+) -> RemovalResult<N> {
     let child = removal.child;
     let diff = removal.diff;
-    let info = get_previous_info(child.clone());
-    let (parent, previous) = if let Some(prev) = info.previous {
-        (prev.parent, prev.node)
-    } else {
-        return RemovalResult::Done(child);
-    };
+    let (parent, previous) =
+        if let Some(found) = find_previous(child.clone(), path, level) {
+            found
+        } else {
+            return RemovalResult::Done(child);
+        };
 
     parent.size.with_mut(|s| *s -= diff.clone());
+    parent.with_leaf_count(|n| *n -= 1);
     if removal.kind == RemovalKind::Update {
         return RemovalResult::Removal(Removal::update(parent, diff));
     }
@@ -88,20 +132,19 @@ fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
     match &previous {
         Next::Sibling(node) => node.set_next(child.next()),
         Next::Parent(node) => {
-            node.set_down(Some(child.next_sibling().unwrap().as_down()))
+            let next = child.next_sibling().unwrap();
+            node.set_down(Some(next.as_down()));
+            update_first_key(*node, next.key());
         }
     };
 
     let first: N = parent.down_as().unwrap();
-    let last = if info.index + 1 == parent.len.get() {
-        previous.into_sibling().unwrap()
-    } else {
-        info.last
-    };
-
     child.set_next(None);
     parent.len.with_mut(|n| *n -= 1);
-    if parent.len.get() >= min_node_length::<N::Leaf>() {
+    // With the `chaos` feature, sometimes merge with a neighbor even when not
+    // required, if the merged node wouldn't be too long.
+    let optional = parent.len.get() >= min_node_length::<N::Leaf>();
+    if optional && !chaos::force() {
         return RemovalResult::Removal(Removal::update(parent, diff));
     }
 
@@ -113,17 +156,27 @@ fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
         }
     };
 
+    let merged_len = parent.len.get() + neighbor.len.get();
+    if optional && merged_len > max_node_length::<N::Leaf>() {
+        return RemovalResult::Removal(Removal::update(parent, diff));
+    }
+
+    let last = get_nth_sibling(first.clone(), parent.len.get() - 1).unwrap();
+
     if is_right {
         let right = neighbor;
         let right_first: N = right.down_as().unwrap();
 
-        if right.len.get() > min_node_length::<N::Leaf>() {
+        if !optional && right.len.get() > min_node_length::<N::Leaf>() {
             // Transfer child from right sibling.
             let right_second = right_first.next_sibling().unwrap();
             right.len.with_mut(|n| *n -= 1);
             parent.len.with_mut(|n| *n += 1);
             right.size.with_mut(|s| *s -= right_first.size());
             parent.size.with_mut(|s| *s += right_first.size());
+            let count = right_first.leaf_count();
+            right.with_leaf_count(|n| *n -= count);
+            parent.with_leaf_count(|n| *n += count);
 
             right.set_down(Some(right_second.as_down()));
             right_first.set_next(last.next());
@@ -135,8 +188,10 @@ fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
         // Merge with right sibling.
         last.set_next(Some(Next::Sibling(right_first)));
         right.set_down(Some(first.as_down()));
+        right.key.set(first.key());
         parent.set_down(None);
         right.size.with_mut(|s| *s += parent.size.take());
+        right.with_leaf_count(|n| *n += parent.take_leaf_count());
         right.len.with_mut(|n| *n += parent.len.take());
         return RemovalResult::Removal(Removal::remove(parent, diff));
     }
@@ -147,12 +202,15 @@ fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
     let left_penultimate = get_nth_sibling(left_first, left_len - 2).unwrap();
     let left_last = left_penultimate.next_sibling().unwrap();
 
-    if left_len > min_node_length::<N::Leaf>() {
+    if !optional && left_len > min_node_length::<N::Leaf>() {
         // Transfer child from left sibling.
         left.len.with_mut(|n| *n -= 1);
         parent.len.with_mut(|n| *n += 1);
         left.size.with_mut(|s| *s -= left_last.size());
         parent.size.with_mut(|s| *s += left_last.size());
+        let count = left_last.leaf_count();
+        left.with_leaf_count(|n| *n -= count);
+        parent.with_leaf_count(|n| *n += count);
 
         left_penultimate.set_next(left_last.next());
         left_last.set_next(Some(Next::Sibling(first)));
@@ -166,13 +224,30 @@ fn handle_removal<N: NodeRef>(removal: Removal<N>) -> RemovalResult<N> {
     last.set_next(Some(Next::Parent(left)));
     parent.set_down(None);
     left.size.with_mut(|s| *s += parent.size.take());
+    left.with_leaf_count(|n| *n += parent.take_leaf_count());
     left.len.with_mut(|n| *n += parent.len.take());
     RemovalResult::Removal(Removal::remove(parent, diff))
 }
 
 pub fn remove<L: LeafRef>(item: L) -> FinishedRemoval<L> {
+    remove_in(item, None)
+}
+
+/// Like [`remove`], but uses `path`, which must have been recorded for
+/// `item`, instead of climbing the list to find each ancestor.
+pub fn remove_with_path<L: LeafRef>(
+    item: L,
+    path: &Path<L>,
+) -> FinishedRemoval<L> {
+    remove_in(item, Some(path))
+}
+
+fn remove_in<L: LeafRef>(
+    item: L,
+    path: Option<&Path<L>>,
+) -> FinishedRemoval<L> {
     let size = item.size();
-    let result = handle_removal(Removal::remove(item, size));
+    let result = handle_removal(Removal::remove(item, size), path, 0);
     let mut head = None;
     let mut removal = match result {
         RemovalResult::Removal(removal) => removal,
@@ -185,12 +260,14 @@ pub fn remove<L: LeafRef>(item: L) -> FinishedRemoval<L> {
         }
     };
 
+    let mut level = 1;
     let root = loop {
         let child = match removal.kind {
             RemovalKind::Remove => Some(removal.child),
             RemovalKind::Update => None,
         };
-        let result = handle_removal(removal);
+        let result = handle_removal(removal, path, level);
+        level += 1;
         if let Some(child) = child {
             child.set_next(head.map(Next::Sibling));
             head = Some(child);
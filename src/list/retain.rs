@@ -0,0 +1,264 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::SkipList;
+use super::build::build;
+use super::destroy::{deconstruct_into_chain, destroy_node_list};
+use super::iter::Removed;
+use super::node::{InternalNodeRef, LeafExt, LeafNext, LeafRef, NodeRef};
+use crate::allocator::Allocator;
+
+/// A chain of leaves, linked directly to each other.
+pub struct Chain<L> {
+    pub first: Option<L>,
+    pub last: Option<L>,
+    pub len: usize,
+}
+
+impl<L: LeafRef> Chain<L> {
+    pub fn new() -> Self {
+        Self {
+            first: None,
+            last: None,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, leaf: L) {
+        match self.last.replace(leaf.clone()) {
+            Some(last) => last.set_next_leaf(Some(LeafNext::Leaf(leaf))),
+            None => self.first = Some(leaf),
+        }
+        self.len += 1;
+    }
+
+    /// Appends `other` to this chain.
+    ///
+    /// `other.last` may be [`None`] even if `other` isn't empty, but nothing
+    /// can be appended to this chain afterward in that case.
+    pub fn append(&mut self, other: Self) {
+        let first = match other.first {
+            Some(first) => first,
+            None => return,
+        };
+        match &self.last {
+            Some(last) => last.set_next_leaf(Some(LeafNext::Leaf(first))),
+            None => {
+                debug_assert!(self.first.is_none());
+                self.first = Some(first);
+            }
+        }
+        self.last = other.last;
+        self.len += other.len;
+    }
+}
+
+/// The state of an in-progress call to [`SkipList::extract_if`].
+///
+/// If the predicate panics, dropping this value rebuilds the list from the
+/// items that have been kept and the items that haven't been tested yet, so
+/// the list remains valid.
+struct ExtractIf<'a, L: LeafRef, A: Allocator> {
+    list: &'a mut SkipList<L, A>,
+    spare: Option<InternalNodeRef<L>>,
+    kept: Chain<L>,
+    removed: Chain<L>,
+    /// The items that haven't been tested yet.
+    rest: Option<L>,
+    rest_len: usize,
+}
+
+impl<L: LeafRef, A: Allocator> ExtractIf<'_, L, A> {
+    /// Rebuilds the list and returns the removed items. After this method is
+    /// called, the state is empty, so calling it again has no effect.
+    fn finish(&mut self) -> Removed<L> {
+        let rest = self.rest.take();
+        let rest_len = core::mem::take(&mut self.rest_len);
+        if let Some(rest) = rest {
+            if let Some(last) = &self.kept.last {
+                last.set_next_leaf(Some(LeafNext::Leaf(rest.clone())));
+            }
+            self.kept.first.get_or_insert(rest);
+            self.kept.len += rest_len;
+        }
+        let kept = core::mem::replace(&mut self.kept, Chain::new());
+        if let Some(first) = kept.first {
+            self.list.root = Some(build(
+                first,
+                kept.len,
+                &mut self.spare,
+                &self.list.alloc,
+            ));
+        }
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in `self.spare` was allocated by
+        //   `self.list.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut self.spare, &self.list.alloc);
+        }
+        let removed = core::mem::replace(&mut self.removed, Chain::new());
+        Removed::new(removed.first, removed.len)
+    }
+}
+
+impl<L: LeafRef, A: Allocator> Drop for ExtractIf<'_, L, A> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Removes every item in `list` for which `pred` returns true, in a single
+/// pass. See [`SkipList::extract_if`].
+pub fn extract_if<L, A, F>(
+    list: &mut SkipList<L, A>,
+    mut pred: F,
+) -> Removed<L>
+where
+    L: LeafRef,
+    A: Allocator,
+    F: FnMut(&L) -> bool,
+{
+    let (first, len, spare) = match list.root.take() {
+        Some(root) => deconstruct_into_chain(root),
+        None => return Removed::new(None, 0),
+    };
+    let mut state = ExtractIf {
+        list,
+        spare,
+        kept: Chain::new(),
+        removed: Chain::new(),
+        rest: Some(first),
+        rest_len: len,
+    };
+    while let Some(leaf) = state.rest.clone() {
+        // `leaf` stays at the front of `state.rest` while `pred` is called,
+        // in case `pred` panics.
+        let remove = pred(&leaf);
+        state.rest = leaf.next_sibling();
+        state.rest_len -= 1;
+        leaf.set_next_leaf(None);
+        if remove {
+            state.removed.push(leaf);
+        } else {
+            state.kept.push(leaf);
+        }
+    }
+    state.finish()
+}
+
+/// The state of an in-progress call to
+/// [`SkipList::stable_partition_in_place`].
+///
+/// If the predicate panics, dropping this value rebuilds the list from the
+/// items that passed, the items that failed, and the items that haven't been
+/// tested yet, in that order.
+struct Partition<'a, L: LeafRef, A: Allocator> {
+    list: &'a mut SkipList<L, A>,
+    spare: Option<InternalNodeRef<L>>,
+    passed: Chain<L>,
+    failed: Chain<L>,
+    /// The items that haven't been tested yet.
+    rest: Option<L>,
+    rest_len: usize,
+}
+
+impl<L: LeafRef, A: Allocator> Partition<'_, L, A> {
+    /// Rebuilds the list and returns the number of items that passed. After
+    /// this method is called, the state is empty, so calling it again has no
+    /// effect.
+    fn finish(&mut self) -> usize {
+        let mut chain = core::mem::replace(&mut self.passed, Chain::new());
+        let passed = chain.len;
+        chain.append(core::mem::replace(&mut self.failed, Chain::new()));
+        chain.append(Chain {
+            first: self.rest.take(),
+            last: None,
+            len: core::mem::take(&mut self.rest_len),
+        });
+        if let Some(first) = chain.first {
+            self.list.root = Some(build(
+                first,
+                chain.len,
+                &mut self.spare,
+                &self.list.alloc,
+            ));
+        }
+
+        // SAFETY:
+        //
+        // * Every `InternalNode` in `self.spare` was allocated by
+        //   `self.list.alloc`.
+        // * There are no other `InternalNodeRef`s that refer to these nodes,
+        //   since they are no longer used by the list.
+        unsafe {
+            destroy_node_list(&mut self.spare, &self.list.alloc);
+        }
+        passed
+    }
+}
+
+impl<L: LeafRef, A: Allocator> Drop for Partition<'_, L, A> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Moves every item in `list` for which `pred` returns true before every item
+/// for which it returns false, in a single pass. See
+/// [`SkipList::stable_partition_in_place`].
+pub fn stable_partition<L, A, F>(
+    list: &mut SkipList<L, A>,
+    mut pred: F,
+) -> usize
+where
+    L: LeafRef,
+    A: Allocator,
+    F: FnMut(&L) -> bool,
+{
+    let (first, len, spare) = match list.root.take() {
+        Some(root) => deconstruct_into_chain(root),
+        None => return 0,
+    };
+    let mut state = Partition {
+        list,
+        spare,
+        passed: Chain::new(),
+        failed: Chain::new(),
+        rest: Some(first),
+        rest_len: len,
+    };
+    while let Some(leaf) = state.rest.clone() {
+        // `leaf` stays at the front of `state.rest` while `pred` is called,
+        // in case `pred` panics.
+        let passed = pred(&leaf);
+        state.rest = leaf.next_sibling();
+        state.rest_len -= 1;
+        leaf.set_next_leaf(None);
+        if passed {
+            state.passed.push(leaf);
+        } else {
+            state.failed.push(leaf);
+        }
+    }
+    state.finish()
+}
@@ -0,0 +1,226 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::{LeafRef, SkipList};
+use crate::Error;
+use crate::allocator::{Allocator, Global};
+use crate::options::LeafSize;
+use alloc::rc::Rc;
+use core::cell::{Ref, RefCell, RefMut};
+
+/// A [`SkipList`] shared between multiple owners on a single thread.
+///
+/// This type wraps <code>[Rc]\<[RefCell]\<[SkipList]\<L, A>>></code>.
+/// Cloning it creates another reference to the same list. Every method
+/// returns [`Error::Borrowed`] instead of panicking if the list is already
+/// borrowed in a conflicting way, which commonly happens when a callback
+/// (like the one passed to [`Self::update`]) tries to use the list that
+/// invoked it.
+///
+/// Methods that take items check that they are valid before modifying the
+/// list, returning errors like [`Error::NotInList`] rather than panicking.
+///
+/// [Rc]: alloc::rc::Rc
+pub struct SharedSkipList<L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: Rc<RefCell<SkipList<L, A>>>,
+}
+
+impl<L: LeafRef> SharedSkipList<L> {
+    /// Creates a new, empty shared list.
+    pub fn new() -> Self {
+        Self::from(SkipList::new())
+    }
+}
+
+impl<L, A> SharedSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Creates a new, empty shared list that uses the given allocator.
+    pub fn new_in(alloc: A) -> Self
+    where
+        A: 'static,
+    {
+        Self::from(SkipList::new_in(alloc))
+    }
+
+    /// Returns whether `self` and `other` refer to the same list.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.list, &other.list)
+    }
+
+    /// Returns the inner [`SkipList`] if this is the only reference to it.
+    /// Otherwise, `self` is returned.
+    pub fn try_into_inner(self) -> Result<SkipList<L, A>, Self> {
+        Rc::try_unwrap(self.list).map(RefCell::into_inner).map_err(|list| {
+            Self {
+                list,
+            }
+        })
+    }
+
+    /// Immutably borrows the list.
+    pub fn try_borrow(&self) -> Result<Ref<'_, SkipList<L, A>>, Error> {
+        self.list.try_borrow().map_err(|_| Error::Borrowed)
+    }
+
+    /// Mutably borrows the list.
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, SkipList<L, A>>, Error> {
+        self.list.try_borrow_mut().map_err(|_| Error::Borrowed)
+    }
+
+    /// Calls `f` with an immutable reference to the list.
+    pub fn with<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&SkipList<L, A>) -> R,
+    {
+        Ok(f(&*self.try_borrow()?))
+    }
+
+    /// Calls `f` with a mutable reference to the list.
+    pub fn with_mut<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut SkipList<L, A>) -> R,
+    {
+        Ok(f(&mut *self.try_borrow_mut()?))
+    }
+
+    /// Returns the number of items in the list. See [`SkipList::len`].
+    pub fn len(&self) -> Result<usize, Error> {
+        self.with(|list| list.len())
+    }
+
+    /// Returns whether the list is empty. See [`SkipList::is_empty`].
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        self.with(|list| list.is_empty())
+    }
+
+    /// Returns the total size of the list. See [`SkipList::size`].
+    pub fn size(&self) -> Result<LeafSize<L>, Error> {
+        self.with(|list| list.size())
+    }
+
+    /// Gets the first item in the list. See [`SkipList::first`].
+    pub fn first(&self) -> Result<Option<L>, Error> {
+        self.with(|list| list.first())
+    }
+
+    /// Gets the last item in the list. See [`SkipList::last`].
+    pub fn last(&self) -> Result<Option<L>, Error> {
+        self.with(|list| list.last())
+    }
+
+    /// Gets an item by index. See [`SkipList::get`].
+    pub fn get<S>(&self, index: &S) -> Result<Option<L>, Error>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: core::borrow::Borrow<S>,
+    {
+        self.with(|list| list.get(index))
+    }
+
+    /// Inserts `item` at the front of the list. See [`SkipList::push_front`].
+    pub fn push_front(&self, item: L) -> Result<(), Error> {
+        let mut list = self.try_borrow_mut()?;
+        list.check_unlinked(&item)?;
+        list.push_front(item);
+        Ok(())
+    }
+
+    /// Inserts `item` at the back of the list. See [`SkipList::push_back`].
+    pub fn push_back(&self, item: L) -> Result<(), Error> {
+        let mut list = self.try_borrow_mut()?;
+        list.check_unlinked(&item)?;
+        list.push_back(item);
+        Ok(())
+    }
+
+    /// Inserts `item` after `pos`. See [`SkipList::try_insert_after`].
+    pub fn insert_after(&self, pos: L, item: L) -> Result<(), Error> {
+        self.try_borrow_mut()?.try_insert_after(pos, item)
+    }
+
+    /// Inserts `item` before `pos`. See [`SkipList::insert_before`].
+    pub fn insert_before(&self, pos: L, item: L) -> Result<(), Error> {
+        let mut list = self.try_borrow_mut()?;
+        list.check_unlinked(&item)?;
+        list.check_member(&pos)?;
+        list.insert_before(pos, item);
+        Ok(())
+    }
+
+    /// Removes `item` from the list. See [`SkipList::try_remove`].
+    pub fn remove(&self, item: L) -> Result<(), Error> {
+        self.try_borrow_mut()?.try_remove(item)
+    }
+
+    /// Calls `update`, which may change the size of `item`, and updates the
+    /// list accordingly. See [`SkipList::update`].
+    ///
+    /// The list is mutably borrowed while `update` runs, so any attempt to
+    /// use the list from within `update` returns [`Error::Borrowed`].
+    pub fn update<F>(&self, item: L, update: F) -> Result<(), Error>
+    where
+        F: FnOnce(),
+    {
+        let mut list = self.try_borrow_mut()?;
+        list.check_member(&item)?;
+        list.update(item, update);
+        Ok(())
+    }
+}
+
+impl<L, A> Clone for SharedSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    /// Creates another reference to the same list.
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<L, A> Default for SharedSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator + Default + 'static,
+{
+    fn default() -> Self {
+        Self::from(SkipList::default())
+    }
+}
+
+impl<L, A> From<SkipList<L, A>> for SharedSkipList<L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn from(list: SkipList<L, A>) -> Self {
+        Self {
+            list: Rc::new(RefCell::new(list)),
+        }
+    }
+}
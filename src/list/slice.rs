@@ -0,0 +1,207 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::iter::ListIter;
+use super::{Iter, LeafRef, SkipList};
+use crate::allocator::{Allocator, Global};
+use crate::options::LeafSize;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// A view of a contiguous range of items in a [`SkipList`], with indices
+/// relative to the start of the range.
+///
+/// This type is returned by [`SkipList::slice`]. The index of the first item
+/// and the size of the range are computed when the slice is created, so
+/// queries don't need to add or subtract offsets.
+pub struct ListSlice<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a SkipList<L, A>,
+    first: L,
+    last: L,
+    start: LeafSize<L>,
+    size: LeafSize<L>,
+    len: usize,
+}
+
+impl<'a, L, A> ListSlice<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(list: &'a SkipList<L, A>, first: L, last: L) -> Self {
+        let first_pos = SkipList::position(first.clone());
+        let last_pos = SkipList::position(last.clone());
+        assert!(first_pos <= last_pos, "`last` comes before `first`");
+        let start = SkipList::index(first.clone());
+        let mut size = SkipList::index(last.clone());
+        size += last.size();
+        size -= start.clone();
+        Self {
+            list,
+            first,
+            last,
+            start,
+            size,
+            len: last_pos - first_pos + 1,
+        }
+    }
+
+    /// Gets the list this slice refers to.
+    pub fn list(&self) -> &'a SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets the number of items in the slice.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the slice is empty. This is always false, as a slice
+    /// contains at least one item.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Gets the total size of the items in the slice.
+    pub fn size(&self) -> LeafSize<L> {
+        self.size.clone()
+    }
+
+    /// Gets the index of the first item in the slice within the whole list.
+    pub fn start(&self) -> LeafSize<L> {
+        self.start.clone()
+    }
+
+    /// Gets the first item in the slice.
+    pub fn first(&self) -> L {
+        self.first.clone()
+    }
+
+    /// Gets the last item in the slice.
+    pub fn last(&self) -> L {
+        self.last.clone()
+    }
+
+    /// Gets the item at `index`, relative to the start of the slice.
+    ///
+    /// This method is like [`SkipList::get`], but returns [`None`] if `index`
+    /// is not less than the [size](Self::size) of the slice.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn get<S>(&self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.get_with_cmp(|size| size.borrow().cmp(index))
+    }
+
+    /// Gets the item at a relative index using the given comparison
+    /// function.
+    ///
+    /// This method is to [`Self::get`] what [`SkipList::get_with_cmp`] is to
+    /// [`SkipList::get`].
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn get_with_cmp<F>(&self, cmp: F) -> Option<L>
+    where
+        F: Fn(&LeafSize<L>) -> Ordering,
+    {
+        if cmp(&self.size).is_le() {
+            return None;
+        }
+        SkipList::get_after_with_cmp(self.first.clone(), cmp)
+    }
+
+    /// Gets the index of `item` relative to the start of the slice.
+    ///
+    /// `item` should be an item in this slice; otherwise, the result is
+    /// unspecified (and may panic if sizes can't be negative).
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn index(&self, item: L) -> LeafSize<L> {
+        let mut index = SkipList::index(item);
+        index -= self.start.clone();
+        index
+    }
+
+    /// Gets an iterator over the items in the slice.
+    pub fn iter(&self) -> ListIter<'a, L> {
+        let iter = Iter::between(self.first.clone(), self.last.clone());
+        ListIter::new(iter)
+    }
+}
+
+impl<L, A> Clone for ListSlice<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list,
+            first: self.first.clone(),
+            last: self.last.clone(),
+            start: self.start.clone(),
+            size: self.size.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<L, A> fmt::Debug for ListSlice<'_, L, A>
+where
+    L: LeafRef + fmt::Debug,
+    A: Allocator,
+    LeafSize<L>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListSlice")
+            .field("first", &self.first)
+            .field("last", &self.last)
+            .field("start", &self.start)
+            .field("size", &self.size)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<'a, L, A> IntoIterator for &ListSlice<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    type Item = L;
+    type IntoIter = ListIter<'a, L>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,80 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, NextHint, SkipList};
+use alloc::vec::Vec;
+
+/// A position in a [`SkipList`] from which [`SkipList::spill`] can resume.
+///
+/// A checkpoint stores only the index of the next item to write, so it does
+/// not borrow the list. If the list is modified between calls to
+/// [`SkipList::spill`], spilling resumes at the same index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SpillCheckpoint {
+    index: usize,
+}
+
+impl SpillCheckpoint {
+    /// Creates a checkpoint at the start of a list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a checkpoint at the item with the given index.
+    ///
+    /// The index is the number of items before the item, not the sum of
+    /// their sizes.
+    pub fn at_index(index: usize) -> Self {
+        Self {
+            index,
+        }
+    }
+
+    /// Gets the index of the next item to be written.
+    pub fn index(self) -> usize {
+        self.index
+    }
+}
+
+/// Fills `buffer` with at most `max_len` items, starting at `first`.
+///
+/// If `buffer` becomes full, it is truncated to the end of the last complete
+/// group of sibling leaves in it, if any.
+pub fn fill<L: LeafRef>(first: L, max_len: usize, buffer: &mut Vec<L>) {
+    let mut item = first;
+    let mut boundary = 0;
+    loop {
+        buffer.push(item.clone());
+        let next = match SkipList::next_hint(item) {
+            NextHint::Sibling(next) => Some(next),
+            NextHint::Climb => {
+                boundary = buffer.len();
+                SkipList::next(buffer.last().unwrap().clone())
+            }
+        };
+        match next {
+            Some(next) if buffer.len() < max_len => item = next,
+            Some(_) if boundary > 0 => {
+                buffer.truncate(boundary);
+                return;
+            }
+            _ => return,
+        }
+    }
+}
@@ -17,8 +17,8 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::min_node_length;
-use super::node::{InternalNodeRef, Next, NodeRef};
+use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
+use super::{SkipList, max_node_length, min_node_length};
 use crate::PersistentAlloc;
 use crate::allocator::Allocator;
 use crate::options::LeafSize;
@@ -42,17 +42,67 @@ pub struct InternalNodeSetup<N: NodeRef> {
     len: usize,
     /// Sum of child sizes.
     size: LeafSize<N::Leaf>,
+    /// Total number of leaves in the children's subtrees.
+    leaf_count: usize,
 }
 
 impl<N: NodeRef> InternalNodeSetup<N> {
+    /// Gets the data for a node whose children are the `len` nodes starting
+    /// at `start`.
+    pub fn new(start: N, len: usize) -> Self {
+        let mut node = start.clone();
+        let mut size = node.size();
+        let mut leaf_count = node.leaf_count();
+
+        for _ in 1..len {
+            node = node.next_sibling().unwrap();
+            size += node.size();
+            leaf_count += node.leaf_count();
+        }
+
+        Self {
+            start,
+            end: node,
+            len,
+            size,
+            leaf_count,
+        }
+    }
+
+    /// Gets the node after the last child, before the setup is applied.
+    pub fn next_sibling(&self) -> Option<N> {
+        self.end.next_sibling()
+    }
+
     pub fn apply_to(self, node: InternalNodeRef<N::Leaf>) {
         node.len.set(self.len);
         node.size.set(self.size);
+        node.set_leaf_count(self.leaf_count);
         node.set_down(Some(self.start.as_down()));
         node.key.set(self.start.key());
         self.end.set_next(Some(Next::Parent(node)));
     }
 
+    /// Like [`Self::into_new`], but uses the first node in `spare` (a list of
+    /// unused internal nodes, linked as siblings) if there is one.
+    pub fn into_reused<A>(
+        self,
+        spare: &mut Option<InternalNodeRef<N::Leaf>>,
+        alloc: &PersistentAlloc<A>,
+    ) -> InternalNodeRef<N::Leaf>
+    where
+        A: Allocator,
+    {
+        let node = if let Some(node) = *spare {
+            *spare = node.next_sibling();
+            node
+        } else {
+            InternalNodeRef::alloc(alloc)
+        };
+        self.apply_to(node);
+        node
+    }
+
     pub fn into_new<A>(
         self,
         alloc: &PersistentAlloc<A>,
@@ -72,22 +122,9 @@ impl<N: NodeRef> Iterator for Split<N> {
     fn next(&mut self) -> Option<Self::Item> {
         let len = self.chunk_len + (self.extra > 0) as usize;
         self.extra = self.extra.saturating_sub(1);
-        let start = self.node.take()?;
-        let mut node = start.clone();
-        let mut size = node.size();
-
-        for _ in 1..len {
-            node = node.next_sibling().unwrap();
-            size += node.size();
-        }
-
-        self.node = node.next_sibling();
-        Some(InternalNodeSetup {
-            start,
-            end: node,
-            len,
-            size,
-        })
+        let setup = InternalNodeSetup::new(self.node.take()?, len);
+        self.node = setup.next_sibling();
+        Some(setup)
     }
 }
 
@@ -106,3 +143,112 @@ pub fn split<N: NodeRef>(node: N, len: usize) -> Split<N> {
         extra: len % num_chunks,
     }
 }
+
+/// Splits the sequence of `len` nodes starting at `N` into as few chunks as
+/// possible, with lengths as close to each other as possible.
+pub fn split_full<N: NodeRef>(node: N, len: usize) -> Split<N> {
+    let max = max_node_length::<N::Leaf>();
+    let num_chunks = (len + max - 1) / max;
+    Split {
+        node: Some(node),
+        chunk_len: len / num_chunks,
+        extra: len % num_chunks,
+    }
+}
+
+/// An iterator that splits a sequence of nodes like [`split_full`], but
+/// prefers to end chunks at the boundaries given by [`LeafRef::chunk_level`].
+pub struct ContentSplit<N: NodeRef> {
+    node: Option<N>,
+    /// Number of nodes not yet passed to `run`.
+    remaining: usize,
+    /// Height of the nodes being split.
+    height: usize,
+    /// Splits the current run of nodes between two boundaries.
+    run: Split<N>,
+    /// Number of chunks left in `run`.
+    run_chunks: usize,
+    /// Whether to look for boundaries at all.
+    enabled: bool,
+    /// The largest chunk level of the nodes examined so far.
+    pub max_level: usize,
+}
+
+impl<N: NodeRef> ContentSplit<N> {
+    /// Gets the chunk level of the last leaf in `node`'s subtree.
+    fn level(node: &N) -> usize {
+        match node.as_down() {
+            Down::Leaf(leaf) => leaf.chunk_level(),
+            Down::Internal(node) => {
+                SkipList::subtree_last(node.down().unwrap()).chunk_level()
+            }
+        }
+    }
+}
+
+impl<N: NodeRef> Iterator for ContentSplit<N> {
+    type Item = InternalNodeSetup<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.run_chunks > 0 {
+            self.run_chunks -= 1;
+            return self.run.next();
+        }
+        let start = self.node.take()?;
+        let min = min_node_length::<N::Leaf>();
+        let mut node = start.clone();
+        let mut len = 1;
+        // A boundary is used only if the nodes on both sides of it can form
+        // chunks of at least the minimum length.
+        while self.enabled && len < self.remaining {
+            let level = Self::level(&node);
+            self.max_level = self.max_level.max(level);
+            if level > self.height && len >= min && self.remaining - len >= min
+            {
+                break;
+            }
+            node = node.next_sibling().unwrap();
+            len += 1;
+        }
+        if !self.enabled {
+            len = self.remaining;
+        }
+        self.remaining -= len;
+        if self.remaining > 0 {
+            self.node = node.next_sibling();
+        }
+        // `split_full` emits this many chunks, but wouldn't stop after the
+        // last one, since the run may be followed by more siblings.
+        let max = max_node_length::<N::Leaf>();
+        self.run_chunks = (len + max - 1) / max - 1;
+        self.run = split_full(start, len);
+        self.run.next()
+    }
+}
+
+impl<N: NodeRef> FusedIterator for ContentSplit<N> {}
+
+/// Splits the sequence of `len` nodes of height `height`, starting at `N`,
+/// into as few chunks as possible between the boundaries given by
+/// [`LeafRef::chunk_level`]. If `enabled` is false, the boundaries are
+/// ignored, which is the same as calling [`split_full`].
+pub fn split_content<N: NodeRef>(
+    node: N,
+    len: usize,
+    height: usize,
+    enabled: bool,
+) -> ContentSplit<N> {
+    ContentSplit {
+        node: Some(node),
+        remaining: len,
+        height,
+        run: Split {
+            node: None,
+            chunk_len: 0,
+            extra: 0,
+        },
+        run_chunks: 0,
+        enabled,
+        max_level: 0,
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::node::{Down, InternalNodeRef, LeafRef, NodeRef};
+use alloc::vec::Vec;
+
+/// Statistics about the structure of a [`SkipList`].
+///
+/// This type is returned by [`SkipList::stats`]. The *fill* of an internal
+/// node is the number of children it has, which is at most the list's
+/// [fanout].
+///
+/// [`SkipList`]: super::SkipList
+/// [`SkipList::stats`]: super::SkipList::stats
+/// [fanout]: crate::ListOptions::Fanout
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct ListStats {
+    /// The number of levels in the list, including the level containing the
+    /// items. This is 0 for an empty list.
+    pub height: usize,
+    /// The number of internal nodes.
+    pub internal_nodes: usize,
+    /// The number of items (leaves).
+    pub leaves: usize,
+    /// The average fill of the internal nodes, or 0 if there are none.
+    pub avg_fill: f64,
+    /// The smallest fill of any internal node, or 0 if there are none.
+    pub min_fill: usize,
+    /// The largest fill of any internal node, or 0 if there are none.
+    pub max_fill: usize,
+}
+
+/// Computes the statistics of the list whose root is `root`.
+pub fn stats<L: LeafRef>(root: Option<Down<L>>) -> ListStats {
+    let mut stats = ListStats::default();
+    let mut stack: Vec<(InternalNodeRef<L>, usize)> = match root {
+        Some(Down::Internal(root)) => Vec::from([(root, 1)]),
+        Some(Down::Leaf(_)) => {
+            stats.height = 1;
+            stats.leaves = 1;
+            return stats;
+        }
+        None => return stats,
+    };
+    let mut total_fill = 0;
+    stats.min_fill = usize::MAX;
+    while let Some((node, depth)) = stack.pop() {
+        let fill = node.len.get();
+        stats.internal_nodes += 1;
+        stats.min_fill = stats.min_fill.min(fill);
+        stats.max_fill = stats.max_fill.max(fill);
+        total_fill += fill;
+        match node.down().unwrap() {
+            Down::Leaf(_) => {
+                stats.height = stats.height.max(depth + 1);
+                stats.leaves += fill;
+            }
+            Down::Internal(mut child) => loop {
+                stack.push((child, depth + 1));
+                child = match child.next_sibling() {
+                    Some(next) => next,
+                    None => break,
+                };
+            },
+        }
+    }
+    stats.avg_fill = total_fill as f64 / stats.internal_nodes as f64;
+    stats
+}
@@ -0,0 +1,171 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, SkipList};
+use crate::allocator::Allocator;
+use core::ops::Deref;
+
+/// A [`LeafRef`] that can be used with [`SyncSkipList`].
+///
+/// This trait is implemented by <code>[Arc]\<[ArcLeaf]\<T>></code>,
+/// <code>[&][r][RefLeaf]\<T></code>, and <code>[BoxLeaf]\<T></code> when
+/// `T` is [`Send`].
+///
+/// [Arc]: alloc::sync::Arc
+/// [ArcLeaf]: crate::basic::ArcLeaf
+/// [r]: reference
+/// [RefLeaf]: crate::basic::RefLeaf
+/// [BoxLeaf]: crate::basic::BoxLeaf
+///
+/// # Safety
+///
+/// Provided that no two threads ever use a leaf (or any of its clones) at the
+/// same time, and that each use on one thread *happens before* the next use
+/// on another (as is the case when the list is guarded by a mutex), it must
+/// be safe to use clones of `Self` from different threads. For example, the
+/// data a leaf refers to must be safe to access from another thread, and the
+/// leaf must not rely on thread-local state.
+///
+/// The requirement in [`LeafRef`] that `Self` must not be [`Send`] or
+/// [`Sync`] still applies.
+pub unsafe trait SyncLeafRef: LeafRef {}
+
+/// A [`SkipList`] that can be sent to another thread.
+///
+/// [`SkipList`] is neither [`Send`] nor [`Sync`], so wrapping it in a
+/// [`Mutex`] isn't enough to share it between threads. This type is [`Send`]
+/// (but not [`Sync`]) when `L` implements [`SyncLeafRef`] and `A` is
+/// [`Send`], so <code>[Mutex]\<[SyncSkipList]\<L, A>></code> can be shared
+/// between threads. Because this requires guarantees about how the items in
+/// the list are used, it can be created only with the unsafe function
+/// [`Self::new`].
+///
+/// This type dereferences to the inner [`SkipList`], so it can be read
+/// from directly. Methods that rearrange or remove items are provided
+/// directly; other changes require the unsafe method [`Self::get_mut`], as
+/// they could add items that aren't covered by the requirements of
+/// [`Self::new`].
+///
+/// [Mutex]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+/// [`Mutex`]: https://doc.rust-lang.org/std/sync/struct.Mutex.html
+pub struct SyncSkipList<L, A = crate::allocator::Global>
+where
+    L: SyncLeafRef,
+    A: Allocator,
+{
+    list: SkipList<L, A>,
+}
+
+impl<L, A> SyncSkipList<L, A>
+where
+    L: SyncLeafRef,
+    A: Allocator,
+{
+    /// Wraps `list` so that it can be sent to another thread.
+    ///
+    /// # Safety
+    ///
+    /// Until the returned [`SyncSkipList`] is dropped or unwrapped with
+    /// [`Self::into_inner`], the caller must ensure that the following are
+    /// never used on a thread other than the one that currently owns (or has
+    /// exclusive access to) the [`SyncSkipList`]:
+    ///
+    /// * The items in the list, and any clones of them.
+    /// * Any other list that shares memory with this list: a list created
+    ///   from this list (such as with [`SkipList::split_off`] or
+    ///   [`SkipList::subtree_builder`]), the list this list was created from,
+    ///   and any list created from that one. Such lists allocate and free
+    ///   internal nodes in the same slabs, whose bookkeeping isn't
+    ///   thread-safe.
+    ///
+    /// Additionally, any [observer] attached to the list, including one
+    /// attached after this function is called, must be safe to use and drop
+    /// on whichever thread owns the [`SyncSkipList`], as if it were [`Send`].
+    ///
+    /// [observer]: SkipList::set_observer
+    pub unsafe fn new(list: SkipList<L, A>) -> Self {
+        Self {
+            list,
+        }
+    }
+
+    /// Returns the inner [`SkipList`].
+    pub fn into_inner(self) -> SkipList<L, A> {
+        self.list
+    }
+
+    /// Gets a mutable reference to the inner [`SkipList`].
+    ///
+    /// # Safety
+    ///
+    /// The requirements of [`Self::new`] must hold for everything the
+    /// returned reference is used to place in the list: any items that are
+    /// inserted, any items in a list that is swapped in or grafted, and any
+    /// observer that is attached.
+    pub unsafe fn get_mut(&mut self) -> &mut SkipList<L, A> {
+        &mut self.list
+    }
+
+    /// See [`SkipList::move_after`].
+    pub fn move_after(&mut self, item: L, pos: L) {
+        self.list.move_after(item, pos);
+    }
+
+    /// See [`SkipList::move_before`].
+    pub fn move_before(&mut self, item: L, pos: L) {
+        self.list.move_before(item, pos);
+    }
+
+    /// See [`SkipList::remove`].
+    pub fn remove(&mut self, item: L) {
+        self.list.remove(item);
+    }
+
+    /// See [`SkipList::pop_front`].
+    pub fn pop_front(&mut self) -> Option<L> {
+        self.list.pop_front()
+    }
+
+    /// See [`SkipList::pop_back`].
+    pub fn pop_back(&mut self) -> Option<L> {
+        self.list.pop_back()
+    }
+}
+
+impl<L, A> Deref for SyncSkipList<L, A>
+where
+    L: SyncLeafRef,
+    A: Allocator,
+{
+    type Target = SkipList<L, A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+// SAFETY: `L` implements `SyncLeafRef` and `A` is `Send`, and the caller of
+// `Self::new` guaranteed that the list's items, and any lists that share
+// memory with it, are used only by the thread that owns this list.
+unsafe impl<L, A> Send for SyncSkipList<L, A>
+where
+    L: SyncLeafRef,
+    A: Allocator + Send,
+{
+}
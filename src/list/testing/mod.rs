@@ -0,0 +1,202 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Utilities for testing implementations of [`LeafRef`].
+//!
+//! The reference model `ListModel` is available when the crate feature
+//! `testing` is enabled.
+//!
+//! See also [`assert_not_send_sync`](crate::assert_not_send_sync), which
+//! checks that a leaf type isn't [`Send`] or [`Sync`].
+
+use super::node::{AllocItem, LeafExt, LeafNext, LeafRef};
+use core::mem::MaybeUninit;
+use core::ptr::NonNull;
+
+#[cfg(feature = "testing")]
+mod model;
+#[cfg(feature = "testing")]
+pub use model::{ListModel, Op};
+
+/// Gets the data pointer stored in `leaf`, panicking if `leaf` stores
+/// something else.
+fn data<L: LeafRef>(leaf: &L, context: &str) -> NonNull<AllocItem<L>> {
+    match leaf.next() {
+        Some(LeafNext::Data(ptr)) => ptr,
+        Some(LeafNext::Leaf(_)) => {
+            panic!("{context}: `next` returned `LeafNext::Leaf`")
+        }
+        None => panic!("{context}: `next` returned `None`"),
+    }
+}
+
+/// Exercises an implementation of [`LeafRef`] against the requirements
+/// documented in its safety section, panicking if any are violated.
+///
+/// `make_leaf` should return a new leaf that isn't in any list each time it
+/// is called. This function calls it four times and unlinks every leaf
+/// before returning.
+///
+/// The following are checked:
+///
+/// * New leaves return [`None`] from [`LeafRef::next`].
+/// * Values passed to [`LeafRef::set_next`] are returned unchanged from
+///   [`LeafRef::next`], including [`LeafNext::Data`] pointers at every
+///   alignment the list may use and leaves that are themselves linked.
+/// * Clones of a leaf observe changes made through the original, and vice
+///   versa.
+/// * [`LeafRef::size`] returns the same value when called repeatedly.
+///
+/// These checks can't prove that an implementation is sound, but they catch
+/// common mistakes, such as discarding the low bits of data pointers.
+///
+/// # Example
+///
+/// ```
+/// use skippy::basic::{BasicLeaf, RefLeaf};
+///
+/// struct Item(u32);
+///
+/// impl BasicLeaf for Item {
+///     type Options = skippy::basic::Options;
+/// }
+///
+/// let items: Vec<_> = (0..4).map(|n| RefLeaf::new(Item(n))).collect();
+/// let mut iter = items.iter();
+/// skippy::testing::leafref_conformance(|| iter.next().unwrap());
+/// ```
+pub fn leafref_conformance<L, F>(mut make_leaf: F)
+where
+    L: LeafRef,
+    F: FnMut() -> L,
+{
+    let a = make_leaf();
+    let b = make_leaf();
+    let c = make_leaf();
+    let d = make_leaf();
+    for leaf in [&a, &b, &c, &d] {
+        assert!(leaf.next().is_none(), "new leaf has a next item");
+        assert!(leaf.size() == leaf.size(), "`size` is inconsistent");
+    }
+
+    // Data pointers at consecutive `AllocItem`s cover every alignment that
+    // the list may provide.
+    let blocks: [MaybeUninit<AllocItem<L>>; 4] =
+        [(); 4].map(|_| MaybeUninit::uninit());
+    for block in &blocks {
+        let ptr = NonNull::from(block).cast::<AllocItem<L>>();
+        a.set_next_leaf(Some(LeafNext::Data(ptr)));
+        assert!(data(&a, "data round-trip") == ptr, "data pointer changed");
+    }
+
+    // Leaves stored as the next item must remain usable.
+    let ptr = NonNull::from(&blocks[1]).cast();
+    c.set_next_leaf(Some(LeafNext::Data(ptr)));
+    b.set_next_leaf(Some(LeafNext::Leaf(c.clone())));
+    let next = match b.next() {
+        Some(LeafNext::Leaf(next)) => next,
+        _ => panic!("leaf round-trip: `next` didn't return `LeafNext::Leaf`"),
+    };
+    assert!(data(&next, "stored leaf") == ptr, "stored leaf is different");
+
+    // Clones must behave like the original.
+    let clone = d.clone();
+    let ptr = NonNull::from(&blocks[2]).cast();
+    d.set_next_leaf(Some(LeafNext::Data(ptr)));
+    assert!(data(&clone, "clone") == ptr, "clone didn't observe change");
+    let ptr = NonNull::from(&blocks[3]).cast();
+    clone.set_next_leaf(Some(LeafNext::Data(ptr)));
+    assert!(data(&d, "original") == ptr, "original didn't observe change");
+    let next_clone = next.clone();
+    next.set_next_leaf(None);
+    assert!(next_clone.next().is_none(), "clone didn't observe `None`");
+    assert!(c.next().is_none(), "stored leaf isn't the original");
+
+    for leaf in [&a, &b, &c, &d] {
+        leaf.set_next_leaf(None);
+        assert!(leaf.next().is_none(), "`set_next(None)` was ignored");
+    }
+}
+
+/// Asserts at compile time that each of the given types implements neither
+/// [`Send`] nor [`Sync`].
+///
+/// Types that implement [`LeafRef`] must not be [`Send`] or [`Sync`] (see its
+/// safety section). Because auto traits are implemented implicitly, a change
+/// to an unrelated field can silently make a leaf type [`Send`] or [`Sync`];
+/// placing this macro next to the [`LeafRef`] implementation (or in a test)
+/// turns that into a compile error.
+///
+/// The macro can be used anywhere an item is allowed. Types must be fully
+/// specified; use `'static` for any lifetime parameters.
+///
+/// # Example
+///
+/// ```
+/// use skippy::basic::{BasicLeaf, RcLeaf, RefLeaf};
+///
+/// struct Item(u32);
+///
+/// impl BasicLeaf for Item {
+///     type Options = skippy::basic::Options;
+/// }
+///
+/// skippy::assert_not_send_sync!(
+///     &'static RefLeaf<'static, Item>,
+///     RcLeaf<Item>,
+/// );
+/// ```
+///
+/// Types that are [`Send`] or [`Sync`] are rejected:
+///
+/// ```compile_fail
+/// skippy::assert_not_send_sync!(&'static u8);
+/// ```
+///
+/// ```compile_fail
+/// skippy::assert_not_send_sync!(std::sync::MutexGuard<'static, u8>);
+/// ```
+///
+/// ```compile_fail
+/// skippy::assert_not_send_sync!(std::cell::Cell<u8>);
+/// ```
+#[macro_export]
+macro_rules! assert_not_send_sync {
+    ($($ty:ty),+ $(,)?) => {
+        const _: fn() = || {
+            // If `T` implements the auto trait, both impls apply and the
+            // type parameter of `some_item` can't be inferred.
+            trait AmbiguousIfSend<A> {
+                fn some_item() {}
+            }
+            trait AmbiguousIfSync<A> {
+                fn some_item() {}
+            }
+            struct Invalid;
+            impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+            impl<T: ?Sized + Send> AmbiguousIfSend<Invalid> for T {}
+            impl<T: ?Sized> AmbiguousIfSync<()> for T {}
+            impl<T: ?Sized + Sync> AmbiguousIfSync<Invalid> for T {}
+            $(
+                let _ = <$ty as AmbiguousIfSend<_>>::some_item;
+                let _ = <$ty as AmbiguousIfSync<_>>::some_item;
+            )+
+        };
+    };
+}
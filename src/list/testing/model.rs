@@ -0,0 +1,268 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! A reference model for testing lists.
+
+use super::super::node::LeafRef;
+use super::super::{SkipList, same_leaf};
+use crate::allocator::Allocator;
+use crate::options::LeafSize;
+use alloc::vec::Vec;
+
+/// An operation that [`ListModel::apply`] performs on both a [`SkipList`] and
+/// its model.
+///
+/// Items are identified by their position in the list, which is taken modulo
+/// the length of the list, so every operation is valid regardless of the
+/// values it contains. This makes it easy to generate operations randomly
+/// and to shrink failing sequences of them. Operations that need an item
+/// do nothing on an empty list, and operations that insert an item do
+/// nothing if the model has no unused leaves left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Op {
+    /// Inserts an unused leaf at the front of the list.
+    PushFront,
+    /// Inserts an unused leaf at the back of the list.
+    PushBack,
+    /// Inserts an unused leaf after the item at the given position, or at
+    /// the front of the list if it is empty.
+    InsertAfter(usize),
+    /// Inserts an unused leaf before the item at the given position, or at
+    /// the front of the list if it is empty.
+    InsertBefore(usize),
+    /// Removes the item at the given position, making its leaf unused.
+    Remove(usize),
+    /// Updates the item at the given position with the function passed to
+    /// [`ListModel::apply`].
+    Update(usize),
+    /// Swaps the items at the given positions.
+    Swap(usize, usize),
+    /// Moves the item at the first position after the item at the second
+    /// position, where both positions are determined before the move.
+    MoveAfter(usize, usize),
+    /// Reverses the list.
+    Reverse,
+}
+
+/// A simple model of a [`SkipList`], backed by a [`Vec`], used to check that
+/// the list behaves correctly.
+///
+/// The model is given a set of leaves that aren't in any list. Operations
+/// applied with [`Self::apply`] insert these leaves into the list, remove
+/// them, and so on, while [`Self::check`] verifies that the list matches the
+/// model. This can be used to test a [`LeafRef`] implementation under
+/// arbitrary sequences of operations, such as those produced by a fuzzer or
+/// property-testing library.
+///
+/// This type is available only when the crate feature `testing` is enabled.
+///
+/// # Example
+///
+/// ```
+/// use skippy::SkipList;
+/// use skippy::basic::{BasicLeaf, RefLeaf};
+/// use skippy::testing::{ListModel, Op};
+/// use std::cell::Cell;
+///
+/// struct Item(Cell<usize>);
+///
+/// impl BasicLeaf for Item {
+///     type Options = skippy::basic::options::Options<usize>;
+///
+///     fn size(&self) -> usize {
+///         self.0.get()
+///     }
+/// }
+///
+/// let items: Vec<_> =
+///     (0..8).map(|n| RefLeaf::new(Item(Cell::new(n)))).collect();
+/// let mut list = SkipList::new();
+/// let mut model = ListModel::new(&items);
+/// for op in [Op::PushBack, Op::PushBack, Op::InsertAfter(0), Op::Update(1)] {
+///     model.apply(&mut list, op, |item| item.0.set(item.0.get() + 1));
+///     model.check(&list);
+/// }
+/// assert_eq!(model.items().len(), 3);
+/// ```
+pub struct ListModel<L> {
+    items: Vec<L>,
+    unused: Vec<L>,
+}
+
+impl<L: LeafRef> ListModel<L> {
+    /// Creates a new model of an empty list.
+    ///
+    /// `leaves` are the leaves that operations may insert. They must not be
+    /// in any list.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any leaf is already in a list.
+    pub fn new<I>(leaves: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+    {
+        let mut unused: Vec<_> = leaves.into_iter().collect();
+        assert!(
+            unused.iter().all(|leaf| leaf.next().is_none()),
+            "leaf is already in a list",
+        );
+        // Use the leaves in the order they were given.
+        unused.reverse();
+        Self {
+            items: Vec::new(),
+            unused,
+        }
+    }
+
+    /// The items that the list should contain, in order.
+    pub fn items(&self) -> &[L] {
+        &self.items
+    }
+
+    /// The leaves that aren't currently in the list.
+    pub fn unused(&self) -> &[L] {
+        &self.unused
+    }
+
+    fn position(&self, position: usize) -> Option<usize> {
+        let len = self.items.len();
+        (len > 0).then(|| position % len)
+    }
+
+    /// Performs `op` on both `list` and the model.
+    ///
+    /// `list` should contain exactly the items in the model, which is the
+    /// case if it was empty when the model was created and has been modified
+    /// only through this method. `update` is used for [`Op::Update`]; it may
+    /// change the item's size.
+    pub fn apply<A, F>(&mut self, list: &mut SkipList<L, A>, op: Op, update: F)
+    where
+        A: Allocator,
+        F: FnOnce(&L),
+    {
+        let len = self.items.len();
+        match op {
+            Op::PushFront
+            | Op::PushBack
+            | Op::InsertAfter(_)
+            | Op::InsertBefore(_) => {
+                let index = match op {
+                    _ if len == 0 => 0,
+                    Op::PushFront => 0,
+                    Op::InsertAfter(i) => i % len + 1,
+                    Op::InsertBefore(i) => i % len,
+                    _ => len,
+                };
+                let item = match self.unused.pop() {
+                    Some(item) => item,
+                    None => return,
+                };
+                match index.checked_sub(1) {
+                    Some(prev) => list
+                        .insert_after(self.items[prev].clone(), item.clone()),
+                    None => list.push_front(item.clone()),
+                }
+                self.items.insert(index, item);
+            }
+            Op::Remove(i) => {
+                if let Some(i) = self.position(i) {
+                    let item = self.items.remove(i);
+                    list.remove(item.clone());
+                    self.unused.push(item);
+                }
+            }
+            Op::Update(i) => {
+                if let Some(i) = self.position(i) {
+                    let item = self.items[i].clone();
+                    list.update(item.clone(), || update(&item));
+                }
+            }
+            Op::Swap(a, b) => {
+                if let (Some(a), Some(b)) =
+                    (self.position(a), self.position(b))
+                {
+                    let (x, y) =
+                        (self.items[a].clone(), self.items[b].clone());
+                    list.swap(x, y);
+                    self.items.swap(a, b);
+                }
+            }
+            Op::MoveAfter(a, b) => {
+                if let (Some(a), Some(b)) =
+                    (self.position(a), self.position(b))
+                {
+                    let (x, y) =
+                        (self.items[a].clone(), self.items[b].clone());
+                    list.move_after(x, y);
+                    if a != b {
+                        let item = self.items.remove(a);
+                        let b = if a < b {
+                            b
+                        } else {
+                            b + 1
+                        };
+                        self.items.insert(b, item);
+                    }
+                }
+            }
+            Op::Reverse => {
+                list.reverse();
+                self.items.reverse();
+            }
+        }
+    }
+
+    /// Checks that `list` matches the model, panicking if it doesn't.
+    ///
+    /// This checks the structure of the list with [`SkipList::validate`],
+    /// the order and positions of its items, and its size.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `list` doesn't match the model.
+    pub fn check<A: Allocator>(&self, list: &SkipList<L, A>) {
+        if let Err(e) = list.validate() {
+            panic!("list is invalid: {e}");
+        }
+        assert!(list.len() == self.items.len(), "list has the wrong length");
+        let mut size = LeafSize::<L>::default();
+        let mut iter = list.iter();
+        for (i, item) in self.items.iter().enumerate() {
+            let actual = match iter.next() {
+                Some(actual) => actual,
+                None => panic!("list has too few items"),
+            };
+            assert!(
+                same_leaf(&actual, item),
+                "item at position {i} is different",
+            );
+            assert!(
+                SkipList::index(item.clone()) == size,
+                "item at position {i} has the wrong index",
+            );
+            size += item.size();
+        }
+        assert!(iter.next().is_none(), "list has too many items");
+        assert!(list.size() == size, "list has the wrong size");
+        for leaf in &self.unused {
+            assert!(leaf.next().is_none(), "unused leaf is in a list");
+        }
+    }
+}
@@ -17,7 +17,8 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::node::{InternalNodeRef, Next, NodeRef};
+use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
+use alloc::vec::Vec;
 
 pub fn get_parent<N: NodeRef>(node: N) -> Option<InternalNodeRef<N::Leaf>> {
     get_parent_info(node).parent
@@ -61,6 +62,17 @@ pub fn get_parent_info<N: NodeRef>(node: N) -> ParentInfo<N> {
     }
 }
 
+pub fn get_root<N: NodeRef>(node: N) -> Down<N::Leaf> {
+    let mut root = match get_parent(node.clone()) {
+        Some(parent) => parent,
+        None => return node.as_down(),
+    };
+    while let Some(parent) = get_parent(root) {
+        root = parent;
+    }
+    Down::Internal(root)
+}
+
 pub fn get_nth_sibling<N: NodeRef>(mut node: N, n: usize) -> Option<N> {
     for _ in 0..n {
         node = node.next()?.into_sibling()?;
@@ -124,6 +136,143 @@ pub fn get_previous_info<N: NodeRef>(node: N) -> PreviousInfo<N> {
     }
 }
 
+/// One level of a [`Path`].
+pub struct PathLevel<L: LeafRef> {
+    /// The parent of the node at this level.
+    pub parent: InternalNodeRef<L>,
+    /// The index of the node at this level within `parent`.
+    pub index: usize,
+}
+
+/// The ancestors of a node, recorded in a single upward traversal.
+///
+/// Compound operations can fill a path once and then use it instead of
+/// climbing the list again for each step (e.g., checking that an item is in
+/// a list and then finding its parent). A path can be refilled for a
+/// different node without allocating again, so lists keep one around as
+/// scratch space.
+pub struct Path<L: LeafRef> {
+    /// The parent of each node on the path, starting with the parent of the
+    /// node the path was filled from.
+    levels: Vec<PathLevel<L>>,
+    root: Option<Down<L>>,
+}
+
+impl<L: LeafRef> Path<L> {
+    pub const fn new() -> Self {
+        Self {
+            levels: Vec::new(),
+            root: None,
+        }
+    }
+
+    /// Records the ancestors of `node`, replacing the current contents.
+    pub fn fill<N: NodeRef<Leaf = L>>(&mut self, node: N) {
+        self.levels.clear();
+        let info = get_parent_info(node.clone());
+        let mut parent = match info.parent {
+            Some(parent) => parent,
+            None => {
+                self.root = Some(node.as_down());
+                return;
+            }
+        };
+        self.levels.push(PathLevel {
+            parent,
+            index: info.index,
+        });
+        loop {
+            let info = get_parent_info(parent);
+            parent = match info.parent {
+                Some(next) => next,
+                None => break,
+            };
+            self.levels.push(PathLevel {
+                parent,
+                index: info.index,
+            });
+        }
+        self.root = Some(Down::Internal(parent));
+    }
+
+    /// Starts recording the path to a node found by descending from `root`.
+    ///
+    /// The levels passed through must then be added from the top down with
+    /// [`Self::push_descent`], followed by a call to [`Self::finish_descent`].
+    pub fn start_descent(&mut self, root: Down<L>) {
+        self.levels.clear();
+        self.root = Some(root);
+    }
+
+    /// Records that the descent passed through the child at `index` within
+    /// `parent`.
+    pub fn push_descent(&mut self, parent: InternalNodeRef<L>, index: usize) {
+        self.levels.push(PathLevel {
+            parent,
+            index,
+        });
+    }
+
+    /// Finishes a path started with [`Self::start_descent`], so that it is
+    /// ordered like a path recorded with [`Self::fill`].
+    pub fn finish_descent(&mut self) {
+        self.levels.reverse();
+    }
+
+    /// Clears the path without releasing its memory.
+    pub fn clear(&mut self) {
+        self.levels.clear();
+        self.root = None;
+    }
+
+    /// The root of the list containing the node the path was filled from.
+    pub fn root(&self) -> Option<&Down<L>> {
+        self.root.as_ref()
+    }
+
+    /// The parent of the node the path was filled from, and the node's
+    /// index within it, or [`None`] if the node is the root.
+    pub fn first(&self) -> Option<&PathLevel<L>> {
+        self.levels.first()
+    }
+
+    /// The parent of the ancestor `n` levels above the node the path was
+    /// filled from, and the ancestor's index within it. Level 0 is the same
+    /// as [`Self::first`].
+    pub fn level(&self, n: usize) -> Option<&PathLevel<L>> {
+        self.levels.get(n)
+    }
+
+    /// Gets the node directly before the node at the first level of the path,
+    /// like [`get_previous`]. `N` must be the type of that node.
+    pub fn previous<N: NodeRef<Leaf = L>>(&self) -> Option<Next<N>> {
+        self.previous_at(0)
+    }
+
+    /// Gets the node directly before the ancestor `n` levels above the node
+    /// the path was filled from. `N` must be the type of that ancestor.
+    pub fn previous_at<N: NodeRef<Leaf = L>>(
+        &self,
+        n: usize,
+    ) -> Option<Next<N>> {
+        let level = self.level(n)?;
+        let parent = level.parent;
+        Some(match level.index.checked_sub(1) {
+            Some(index) => {
+                let first: N = parent.down_as().unwrap();
+                Next::Sibling(get_nth_sibling(first, index).unwrap())
+            }
+            None => Next::Parent(parent),
+        })
+    }
+}
+
+impl<L: LeafRef> Default for Path<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<N: NodeRef> From<PreviousInfo<N>> for ParentInfo<N> {
     fn from(info: PreviousInfo<N>) -> Self {
         Self {
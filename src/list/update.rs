@@ -0,0 +1,144 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{LeafRef, SkipList, propagate_update_diff};
+use crate::allocator::{Allocator, Global};
+use crate::options::LeafSize;
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::Deref;
+
+/// A guard that updates the size of an item in a [`SkipList`] when dropped.
+///
+/// This type is returned by [`SkipList::update_guard`]. It records the size
+/// of the item when it is created, and when it is dropped, the list is
+/// updated with the difference between that size and the item's current
+/// size. While the guard exists, the list is mutably borrowed, so the item
+/// can be modified across arbitrary control flow (e.g., an `await`) before
+/// the list is used again. The list itself updates sizes eagerly, so the
+/// guard's is the only pending update; [`Self::get_consistent`] applies it
+/// before reading from the list, and [`Self::flush`] applies it without
+/// ending the guard.
+///
+/// The guard dereferences to the item.
+#[must_use = "the list is updated when the guard is dropped"]
+pub struct UpdateGuard<'a, L, A = Global>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    list: &'a mut SkipList<L, A>,
+    item: L,
+    old_size: Option<LeafSize<L>>,
+}
+
+impl<'a, L, A> UpdateGuard<'a, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    pub(super) fn new(list: &'a mut SkipList<L, A>, item: L) -> Self {
+        Self {
+            list,
+            old_size: Some(item.size()),
+            item,
+        }
+    }
+
+    /// Gets the size the item had when the guard was created.
+    pub fn old_size(&self) -> &LeafSize<L> {
+        self.old_size.as_ref().unwrap()
+    }
+
+    /// Updates the list with the item's current size without dropping the
+    /// guard. Afterward, [`Self::old_size`] returns the item's current size.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn flush(&mut self) {
+        let new_size = self.item.size();
+        let old_size = self.old_size.replace(new_size.clone()).unwrap();
+        propagate_update_diff(self.item.clone(), None, old_size, new_size);
+        let item = &self.item;
+        self.list.notify(|observer| observer.on_update(item));
+    }
+
+    /// Gets an item by index, as with [`SkipList::get`], after updating the
+    /// list with the item's current size (see [`Self::flush`]), so the result
+    /// reflects any changes made to the item through the guard.
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(log *n*).
+    pub fn get_consistent<S>(&mut self, index: &S) -> Option<L>
+    where
+        S: Ord + ?Sized,
+        LeafSize<L>: Borrow<S>,
+    {
+        self.flush();
+        self.list.get(index)
+    }
+
+    /// Updates the list and returns the item. This is equivalent to dropping
+    /// the guard.
+    pub fn finish(self) -> L {
+        self.item.clone()
+    }
+}
+
+impl<L, A> Deref for UpdateGuard<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    type Target = L;
+
+    fn deref(&self) -> &L {
+        &self.item
+    }
+}
+
+impl<L, A> Drop for UpdateGuard<'_, L, A>
+where
+    L: LeafRef,
+    A: Allocator,
+{
+    fn drop(&mut self) {
+        let old_size = self.old_size.take().unwrap();
+        let new_size = self.item.size();
+        propagate_update_diff(self.item.clone(), None, old_size, new_size);
+        let item = &self.item;
+        self.list.notify(|observer| observer.on_update(item));
+    }
+}
+
+impl<L, A> fmt::Debug for UpdateGuard<'_, L, A>
+where
+    L: LeafRef + fmt::Debug,
+    A: Allocator,
+    LeafSize<L>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UpdateGuard")
+            .field("item", &self.item)
+            .field("old_size", &self.old_size)
+            .finish()
+    }
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+//! Structural validation of lists.
+
+use super::node::{Down, InternalNodeRef, LeafRef, Next, NodeRef};
+use super::{max_node_length, min_node_length};
+use crate::ValidationError;
+use crate::options::LeafSize;
+
+/// The properties of a valid subtree.
+struct Subtree<L: LeafRef> {
+    height: usize,
+    size: LeafSize<L>,
+    /// The parent of the first leaf in the subtree, if the subtree isn't
+    /// itself a leaf.
+    bottom: Option<InternalNodeRef<L>>,
+}
+
+/// Checks whether `leaf` is the first child of `parent`. At most `parent.len`
+/// links are followed, so a corrupted list can't cause an infinite loop.
+fn is_first_child<L: LeafRef>(leaf: &L, parent: InternalNodeRef<L>) -> bool {
+    let mut node = leaf.clone();
+    for _ in 1..parent.len.get() {
+        node = match NodeRef::next(&node) {
+            Some(Next::Sibling(next)) => next,
+            _ => return false,
+        };
+    }
+    matches!(NodeRef::next(&node), Some(Next::Parent(p)) if p == parent)
+}
+
+fn check_children<N: NodeRef>(
+    parent: InternalNodeRef<N::Leaf>,
+    first: N,
+) -> Result<Subtree<N::Leaf>, ValidationError> {
+    let mut len = 0;
+    let mut leaves = 0;
+    let mut size = LeafSize::<N::Leaf>::default();
+    let mut height = None;
+    let mut bottom = None;
+    let mut child = first;
+    loop {
+        len += 1;
+        // Splitting a node with one more child than the maximum can produce
+        // a single node of that length (see `split::split`). Checking this
+        // here also ensures that a cycle in the links can't cause an infinite
+        // loop.
+        if len > max_node_length::<N::Leaf>() + 1 {
+            return Err(ValidationError::Fanout);
+        }
+        let subtree = check(child.as_down(), false)?;
+        if *height.get_or_insert(subtree.height) != subtree.height {
+            return Err(ValidationError::Depth);
+        }
+        if len == 1 {
+            bottom = subtree.bottom.or(Some(parent));
+        }
+        size += subtree.size;
+        leaves += child.leaf_count();
+        child = match child.next() {
+            Some(Next::Sibling(next)) => next,
+            Some(Next::Parent(p)) if p == parent => break,
+            _ => return Err(ValidationError::Link),
+        };
+    }
+    if len != parent.len.get() || leaves != parent.leaf_count() {
+        return Err(ValidationError::Length);
+    }
+    if size != parent.size() {
+        return Err(ValidationError::Size);
+    }
+    Ok(Subtree {
+        height: height.unwrap_or_default() + 1,
+        size,
+        bottom,
+    })
+}
+
+fn check<L: LeafRef>(
+    node: Down<L>,
+    is_root: bool,
+) -> Result<Subtree<L>, ValidationError> {
+    let node = match node {
+        Down::Leaf(leaf) => {
+            return Ok(Subtree {
+                height: 0,
+                size: leaf.size(),
+                bottom: None,
+            });
+        }
+        Down::Internal(node) => node,
+    };
+    let min_len = if is_root {
+        2
+    } else {
+        min_node_length::<L>()
+    };
+    if node.len.get() < min_len {
+        return Err(ValidationError::Fanout);
+    }
+    let subtree = match node.down() {
+        Some(Down::Leaf(first)) => check_children(node, first),
+        Some(Down::Internal(first)) => check_children(node, first),
+        None => Err(ValidationError::Length),
+    }?;
+    // Children are checked first, so if the first child has no key, keys
+    // aren't stored.
+    let bottom = subtree.bottom.unwrap();
+    match node.key_as_leaf() {
+        Some(key) if is_first_child(&key, bottom) => Ok(subtree),
+        None if node.down().and_then(|d| d.key()).is_none() => Ok(subtree),
+        _ => Err(ValidationError::Key),
+    }
+}
+
+/// Checks the structure of the list whose root is `root`.
+pub fn validate<L: LeafRef>(
+    root: Option<Down<L>>,
+) -> Result<(), ValidationError> {
+    let root = match root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+    let is_linked = match &root {
+        Down::Leaf(leaf) => NodeRef::next(leaf).is_some(),
+        Down::Internal(node) => node.next().is_some(),
+    };
+    if is_linked {
+        return Err(ValidationError::Link);
+    }
+    check(root, true).map(|_| ())
+}
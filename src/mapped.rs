@@ -0,0 +1,460 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A compact, read-only list format that can be memory-mapped.
+//!
+//! [`encode`] writes a sequence of items (e.g., from a [`SkipList`]) to a
+//! byte buffer, which can be saved to a file. [`MappedList`] then answers
+//! queries directly over those bytes---for example, the contents of a
+//! memory-mapped file---without deserializing or allocating anything, so even
+//! huge lists can be opened instantly.
+//!
+//! The bytes of each item are produced by an [`Encode`] implementation and
+//! interpreted by a [`Decode`] implementation. Each item also has a size,
+//! which is stored as a [`u64`], so that items can be looked up by index as
+//! with [`SkipList::get`].
+//!
+//! # Format
+//!
+//! All integers are stored as little-endian [`u64`]s, and no alignment is
+//! required. For a list of *n* items, the format is:
+//!
+//! 1. `magic`: the bytes `SKIPPYM1` (8 bytes).
+//! 2. `len`: *n*, the number of items (8 bytes).
+//! 3. `offsets`: the offset of each item's bytes within `data`, followed by
+//!    the length of `data` (8 × (*n* + 1) bytes).
+//! 4. `indices`: the index of each item (the sum of the sizes of the items
+//!    before it), followed by the total size (8 × (*n* + 1) bytes).
+//! 5. `data`: the bytes of every item, in order.
+//!
+//! All reads are bounds-checked, so malformed data can cause panics or
+//! incorrect results, but never undefined behavior. [`MappedList::new`]
+//! checks only the header and the overall length, so it takes constant time.
+//!
+//! # Example
+//!
+//! ```
+//! use skippy::SkipList;
+//! use skippy::basic::{BasicLeaf, RefLeaf};
+//! use skippy::mapped::{Decode, Encode, MappedList};
+//!
+//! struct Word(&'static str);
+//!
+//! impl BasicLeaf for Word {
+//!     type Options = skippy::basic::options::Options<usize>;
+//!
+//!     fn size(&self) -> usize {
+//!         self.0.len()
+//!     }
+//! }
+//!
+//! struct Codec;
+//!
+//! impl Encode<&RefLeaf<'_, Word>> for Codec {
+//!     fn encode(&self, item: &&RefLeaf<'_, Word>, out: &mut Vec<u8>) {
+//!         out.extend_from_slice(item.0.as_bytes());
+//!     }
+//!
+//!     fn size(&self, item: &&RefLeaf<'_, Word>) -> u64 {
+//!         item.0.len() as u64
+//!     }
+//! }
+//!
+//! impl Decode for Codec {
+//!     type Value<'a> = &'a str;
+//!
+//!     fn decode<'a>(&self, bytes: &'a [u8]) -> &'a str {
+//!         core::str::from_utf8(bytes).unwrap()
+//!     }
+//! }
+//!
+//! let words = ["skip", "lists", "are", "neat"];
+//! let words = words.map(|w| RefLeaf::new(Word(w)));
+//! let list: SkipList<_> = words.iter().collect();
+//! let mut bytes = Vec::new();
+//! skippy::mapped::encode(&list, &Codec, &mut bytes);
+//!
+//! let mapped = MappedList::new(&bytes, Codec).unwrap();
+//! assert_eq!(mapped.len(), 4);
+//! assert_eq!(mapped.size(), 16);
+//! assert_eq!(mapped.get(5), Some("lists"));
+//! assert!(mapped.iter().eq(["skip", "lists", "are", "neat"]));
+//! ```
+
+use crate::Error;
+#[cfg(doc)]
+use crate::SkipList;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+
+const MAGIC: &[u8; 8] = b"SKIPPYM1";
+const HEADER_LEN: usize = 16;
+
+/// Converts items to bytes for [`encode`].
+pub trait Encode<L> {
+    /// Appends the bytes that represent `item` to `out`.
+    fn encode(&self, item: &L, out: &mut Vec<u8>);
+
+    /// Gets the size of `item`; see [`LeafRef::size`](crate::LeafRef::size).
+    fn size(&self, item: &L) -> u64;
+}
+
+/// Interprets the bytes of items in a [`MappedList`].
+pub trait Decode {
+    /// The type of the decoded items, which may borrow from the bytes.
+    type Value<'a>;
+
+    /// Decodes the bytes that were written by [`Encode::encode`] for a
+    /// single item.
+    fn decode<'a>(&self, bytes: &'a [u8]) -> Self::Value<'a>;
+}
+
+/// A [`Decode`] implementation that returns the bytes of each item
+/// unchanged.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawBytes;
+
+impl Decode for RawBytes {
+    type Value<'a> = &'a [u8];
+
+    fn decode<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        bytes
+    }
+}
+
+fn push_u64(out: &mut Vec<u8>, n: u64) {
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+fn read_u64(bytes: &[u8], i: usize) -> u64 {
+    let start = i * 8;
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[start..start + 8]);
+    u64::from_le_bytes(buf)
+}
+
+fn to_usize(n: u64) -> usize {
+    usize::try_from(n).expect("malformed mapped list")
+}
+
+/// Writes `items` to `out` in the format read by [`MappedList`].
+///
+/// # Panics
+///
+/// This function panics if the total size of the items or the total length
+/// of their bytes overflows a [`u64`].
+pub fn encode<I, E>(items: I, encoder: &E, out: &mut Vec<u8>)
+where
+    I: IntoIterator,
+    E: Encode<I::Item>,
+{
+    let mut data = Vec::new();
+    let mut offsets = Vec::new();
+    let mut indices = Vec::new();
+    let mut index = 0_u64;
+    for item in items {
+        offsets.push(data.len() as u64);
+        indices.push(index);
+        index = index.checked_add(encoder.size(&item)).expect("overflow");
+        encoder.encode(&item, &mut data);
+    }
+    offsets.push(data.len() as u64);
+    indices.push(index);
+
+    out.reserve(HEADER_LEN + (offsets.len() + indices.len()) * 8 + data.len());
+    out.extend_from_slice(MAGIC);
+    push_u64(out, offsets.len() as u64 - 1);
+    offsets.into_iter().for_each(|n| push_u64(out, n));
+    indices.into_iter().for_each(|n| push_u64(out, n));
+    out.extend_from_slice(&data);
+}
+
+/// A read-only list stored in the format written by [`encode`].
+///
+/// Queries are answered directly over the underlying bytes; see the
+/// [module documentation](self).
+pub struct MappedList<'a, D = RawBytes> {
+    len: usize,
+    offsets: &'a [u8],
+    indices: &'a [u8],
+    data: &'a [u8],
+    decoder: D,
+}
+
+impl<'a, D: Decode> MappedList<'a, D> {
+    /// Opens a list stored in `bytes`, using `decoder` to decode its items.
+    ///
+    /// Returns [`Error::InvalidFormat`] if `bytes` doesn't start with a valid
+    /// header or is too short to contain the list it describes.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn new(bytes: &'a [u8], decoder: D) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+            return Err(Error::InvalidFormat);
+        }
+        let len = usize::try_from(read_u64(&bytes[8..], 0))
+            .map_err(|_| Error::InvalidFormat)?;
+        let table_len = len
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(8))
+            .ok_or(Error::InvalidFormat)?;
+        let rest = &bytes[HEADER_LEN..];
+        if rest.len() / 2 < table_len {
+            return Err(Error::InvalidFormat);
+        }
+        let (offsets, rest) = rest.split_at(table_len);
+        let (indices, data) = rest.split_at(table_len);
+        if read_u64(offsets, len) != data.len() as u64 {
+            return Err(Error::InvalidFormat);
+        }
+        Ok(Self {
+            len,
+            offsets,
+            indices,
+            data,
+            decoder,
+        })
+    }
+
+    /// Gets the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the total size of the list.
+    pub fn size(&self) -> u64 {
+        read_u64(self.indices, self.len)
+    }
+
+    /// Gets the bytes of the item at `position`, which counts items rather
+    /// than sizes.
+    fn bytes(&self, position: usize) -> &'a [u8] {
+        let start = to_usize(read_u64(self.offsets, position));
+        let end = to_usize(read_u64(self.offsets, position + 1));
+        self.data.get(start..end).expect("malformed mapped list")
+    }
+
+    /// Gets the item at `position`, which counts items (starting at 0 for
+    /// the first item) rather than sizes.
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn nth(&self, position: usize) -> Option<D::Value<'a>> {
+        (position < self.len)
+            .then(|| self.decoder.decode(self.bytes(position)))
+    }
+
+    /// Gets the index of the item at `position` (the sum of the sizes of the
+    /// items before it).
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `position` is greater than or equal to
+    /// [`self.len()`](Self::len).
+    ///
+    /// # Time complexity
+    ///
+    /// Constant.
+    pub fn index(&self, position: usize) -> u64 {
+        assert!(position < self.len, "position out of bounds");
+        read_u64(self.indices, position)
+    }
+
+    /// Gets the position of the item at `index`, determined in the same way
+    /// as [`Self::get`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn position(&self, index: u64) -> Option<usize> {
+        // The first item that ends after `index`.
+        let position = partition_point(self.len, |i| {
+            read_u64(self.indices, i + 1) <= index
+        });
+        if position < self.len {
+            return Some(position);
+        }
+        let last = self.len.checked_sub(1)?;
+        (index == self.size() && read_u64(self.indices, last) == index)
+            .then_some(last)
+    }
+
+    /// Gets an item by index.
+    ///
+    /// As with [`SkipList::get`], if there are items with a size of 0, this
+    /// method returns the first non–zero-sized item at `index`, or the last
+    /// item in the list if `index` is [`self.size()`](Self::size) and the
+    /// list ends with a zero-sized item.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn get(&self, index: u64) -> Option<D::Value<'a>> {
+        self.nth(self.position(index)?)
+    }
+
+    /// Finds an item in a sorted list.
+    ///
+    /// The return value is the same as for [`SkipList::find`]: if the item
+    /// is not in the list, the item that would immediately precede it is
+    /// returned in [`Err`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn find<K>(
+        &self,
+        key: &K,
+    ) -> Result<D::Value<'a>, Option<D::Value<'a>>>
+    where
+        K: Ord + ?Sized,
+        D::Value<'a>: Borrow<K>,
+    {
+        self.find_with_cmp(|value| value.borrow().cmp(key))
+    }
+
+    /// Finds an item in a sorted list using the given comparison function.
+    ///
+    /// `cmp` compares an item with the desired item, in the same way as for
+    /// [`SkipList::find_with_cmp`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn find_with_cmp<F>(
+        &self,
+        mut cmp: F,
+    ) -> Result<D::Value<'a>, Option<D::Value<'a>>>
+    where
+        F: FnMut(&D::Value<'a>) -> Ordering,
+    {
+        let position = partition_point(self.len, |i| {
+            cmp(&self.decoder.decode(self.bytes(i))).is_lt()
+        });
+        match self.nth(position) {
+            Some(value) if cmp(&value).is_eq() => Ok(value),
+            _ => Err(position.checked_sub(1).and_then(|i| self.nth(i))),
+        }
+    }
+
+    /// Gets an iterator over the items in the list.
+    pub fn iter(&self) -> Iter<'_, 'a, D> {
+        Iter {
+            list: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+/// Returns the first index in `0..len` for which `pred` returns false,
+/// assuming `pred` returns true for every index before it.
+fn partition_point<F>(len: usize, mut pred: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+impl<D: Decode + fmt::Debug> fmt::Debug for MappedList<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedList")
+            .field("len", &self.len)
+            .field("data_len", &self.data.len())
+            .field("decoder", &self.decoder)
+            .finish()
+    }
+}
+
+impl<'b, 'a, D: Decode> IntoIterator for &'b MappedList<'a, D> {
+    type Item = D::Value<'a>;
+    type IntoIter = Iter<'b, 'a, D>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items in a [`MappedList`].
+pub struct Iter<'b, 'a, D> {
+    list: &'b MappedList<'a, D>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, D: Decode> Iterator for Iter<'_, 'a, D> {
+    type Item = D::Value<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.list.nth(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<D: Decode> DoubleEndedIterator for Iter<'_, '_, D> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.list.nth(self.back)
+    }
+}
+
+impl<D: Decode> ExactSizeIterator for Iter<'_, '_, D> {}
+
+impl<D: Decode> FusedIterator for Iter<'_, '_, D> {}
+
+impl<D> Clone for Iter<'_, '_, D> {
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
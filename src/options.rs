@@ -22,6 +22,7 @@
 use crate::LeafRef;
 #[cfg(doc)]
 use crate::{LeafNext, SkipList};
+use core::borrow::Borrow;
 use core::convert::Infallible;
 use core::fmt;
 use core::marker::PhantomData;
@@ -44,6 +45,41 @@ mod detail {
     }
 
     pub trait FanoutPriv: Constant<usize> {}
+
+    pub trait SlabSizePriv: Constant<usize> {}
+
+    pub trait EmptyPolicyTypePriv {}
+
+    pub trait StoreHashesPriv {
+        type Hash: Copy;
+        const ENABLED: bool = false;
+
+        fn as_hash(_hash: u64) -> Option<Self::Hash> {
+            None
+        }
+
+        fn to_u64(_hash: Option<Self::Hash>) -> Option<u64> {
+            None
+        }
+    }
+
+    pub trait StoreMaxEndPriv {
+        type MaxEnd<T: Clone>: Clone;
+        const ENABLED: bool = false;
+
+        fn as_max_end<T: Clone>(_leaf: T) -> Option<Self::MaxEnd<T>> {
+            None
+        }
+
+        fn to_leaf<T: Clone>(_max_end: Option<Self::MaxEnd<T>>) -> Option<T> {
+            None
+        }
+    }
+
+    pub trait AggregatePriv {
+        type Value: Clone;
+        const ENABLED: bool = false;
+    }
 }
 
 pub(crate) use detail::*;
@@ -75,6 +111,150 @@ pub trait Fanout: FanoutPriv {}
 impl<const N: usize> Fanout for Usize<N> {}
 impl<const N: usize> FanoutPriv for Usize<N> {}
 
+/// Trait bound on [`ListOptions::StoreHashes`].
+pub trait StoreHashes: StoreHashesPriv {}
+
+impl StoreHashes for Bool<false> {}
+impl StoreHashesPriv for Bool<false> {
+    type Hash = Infallible;
+}
+
+impl StoreHashes for Bool<true> {}
+impl StoreHashesPriv for Bool<true> {
+    type Hash = u64;
+    const ENABLED: bool = true;
+
+    fn as_hash(hash: u64) -> Option<u64> {
+        Some(hash)
+    }
+
+    fn to_u64(hash: Option<u64>) -> Option<u64> {
+        hash
+    }
+}
+
+/// Trait bound on [`ListOptions::StoreMaxEnd`].
+pub trait StoreMaxEnd: StoreMaxEndPriv {}
+
+impl StoreMaxEnd for Bool<false> {}
+impl StoreMaxEndPriv for Bool<false> {
+    type MaxEnd<T: Clone> = Infallible;
+}
+
+impl StoreMaxEnd for Bool<true> {}
+impl StoreMaxEndPriv for Bool<true> {
+    type MaxEnd<T: Clone> = T;
+    const ENABLED: bool = true;
+
+    fn as_max_end<T: Clone>(leaf: T) -> Option<T> {
+        Some(leaf)
+    }
+
+    fn to_leaf<T: Clone>(max_end: Option<T>) -> Option<T> {
+        max_end
+    }
+}
+
+/// An associative operation with an identity, used to summarize ranges of
+/// items; see [`ListOptions::Aggregate`].
+///
+/// [`Self::combine`] must be associative, and [`Self::identity`] must be its
+/// identity, but the operation doesn't need to be commutative or invertible:
+/// the value for a range of items is always combined in list order, and
+/// values are recomputed rather than updated when items change. This allows
+/// aggregates like the maximum of a value, which can't be used as a
+/// [`ListOptions::SizeType`].
+///
+/// # Example
+///
+/// ```
+/// use skippy::options::Monoid;
+///
+/// struct Max;
+///
+/// impl Monoid for Max {
+///     type Value = u32;
+///
+///     fn identity() -> u32 {
+///         0
+///     }
+///
+///     fn combine(left: &u32, right: &u32) -> u32 {
+///         *left.max(right)
+///     }
+/// }
+/// ```
+pub trait Monoid {
+    /// The type of the summary of a range of items.
+    type Value: Clone;
+
+    /// Gets the summary of an empty range.
+    fn identity() -> Self::Value;
+
+    /// Combines the summaries of two adjacent ranges.
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+}
+
+/// Trait bound on [`ListOptions::Aggregate`].
+pub trait AggregateType: AggregatePriv {}
+
+/// Disables [`ListOptions::Aggregate`]. This is the default.
+pub struct NoAggregate(());
+
+impl AggregateType for NoAggregate {}
+impl AggregatePriv for NoAggregate {
+    type Value = Infallible;
+}
+
+/// Selects the [`Monoid`] `M` in [`ListOptions::Aggregate`].
+pub struct Aggregate<M>(PhantomData<fn() -> M>);
+
+impl<M: Monoid> AggregateType for Aggregate<M> {}
+impl<M: Monoid> AggregatePriv for Aggregate<M> {
+    type Value = M::Value;
+    const ENABLED: bool = true;
+}
+
+/// Trait bound on [`ListOptions::SlabSize`].
+pub trait SlabSize: SlabSizePriv {}
+
+impl<const N: usize> SlabSize for Usize<N> {}
+impl<const N: usize> SlabSizePriv for Usize<N> {}
+
+/// How index queries like [`SkipList::get`] treat zero-sized items at the end
+/// of a list; see [`ListOptions::EmptyPolicy`].
+///
+/// This matters when querying the index equal to the [size] of the list,
+/// including index 0 in a list whose items all have a size of 0. Queries on
+/// an empty list always return [`None`].
+///
+/// [size]: SkipList::size
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EmptyPolicy {
+    /// The last item in the list is returned if it has a size of 0.
+    ReturnLast,
+    /// [`None`] is returned, so zero-sized items at the end of the list can
+    /// be reached only through other means (e.g., [`SkipList::last`]).
+    ReturnNone,
+}
+
+/// Trait bound on [`ListOptions::EmptyPolicy`].
+pub trait EmptyPolicyType: EmptyPolicyTypePriv {
+    /// The policy this type represents.
+    const POLICY: EmptyPolicy;
+}
+
+impl EmptyPolicyType for Bool<true> {
+    const POLICY: EmptyPolicy = EmptyPolicy::ReturnLast;
+}
+
+impl EmptyPolicyType for Bool<false> {
+    const POLICY: EmptyPolicy = EmptyPolicy::ReturnNone;
+}
+
+impl EmptyPolicyTypePriv for Bool<true> {}
+impl EmptyPolicyTypePriv for Bool<false> {}
+
 /// A no-op, zero-sized size type for lists whose items don't need a notion of
 /// size.
 ///
@@ -97,6 +277,122 @@ impl SubAssign for NoSize {
     fn sub_assign(&mut self, _rhs: Self) {}
 }
 
+/// A size type that panics on overflow instead of wrapping.
+///
+/// This wraps an unsigned integer type `T` and can be used as the `SizeType`
+/// parameter in [`Options`]. The list adds and subtracts sizes with checked
+/// arithmetic, so if an implementation of [`LeafRef::size`] returns
+/// inconsistent values (for example, a different size than the item had when
+/// it was inserted), the list panics where the corruption occurs, rather
+/// than silently storing wrapped totals. This applies even when overflow
+/// checks are disabled.
+///
+/// [`LeafSize<L>`] can be borrowed as `T`, so methods like
+/// [`SkipList::get`] can be called with plain integers.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CheckedSize<T>(pub T);
+
+impl<T> CheckedSize<T> {
+    /// Returns the wrapped value.
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for CheckedSize<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Borrow<T> for CheckedSize<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CheckedSize<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+macro_rules! impl_checked_size {
+    ($($t:ty),*) => {$(
+        impl AddAssign for CheckedSize<$t> {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 = self.0.checked_add(rhs.0).unwrap_or_else(|| {
+                    panic!("size overflow: {} + {}", self.0, rhs.0)
+                });
+            }
+        }
+
+        impl SubAssign for CheckedSize<$t> {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 = self.0.checked_sub(rhs.0).unwrap_or_else(|| {
+                    panic!("size underflow: {} - {}", self.0, rhs.0)
+                });
+            }
+        }
+    )*};
+}
+
+impl_checked_size!(u8, u16, u32, u64, u128, usize);
+
+/// One component of a multi-dimensional size type.
+///
+/// A [`ListOptions::SizeType`] can be made up of several independent
+/// components, like the number of bytes and the number of characters in a
+/// piece of text. Implementing this trait on a marker type allows the list to
+/// be queried by a single component with methods like [`SkipList::get_by`],
+/// rather than by the entire size.
+///
+/// For correct results, each component should conceptually be an unsigned
+/// integer: [`Self::get`] applied to the sum of two sizes should never be
+/// less than it is for either of them.
+///
+/// # Example
+///
+/// ```
+/// use skippy::options::Dimension;
+/// # use core::ops::{AddAssign, SubAssign};
+///
+/// #[derive(Clone, Default, PartialEq, Eq)]
+/// struct TextSize {
+///     bytes: usize,
+///     chars: usize,
+/// }
+///
+/// # impl AddAssign for TextSize {
+/// #     fn add_assign(&mut self, rhs: Self) {
+/// #         self.bytes += rhs.bytes;
+/// #         self.chars += rhs.chars;
+/// #     }
+/// # }
+/// # impl SubAssign for TextSize {
+/// #     fn sub_assign(&mut self, rhs: Self) {
+/// #         self.bytes -= rhs.bytes;
+/// #         self.chars -= rhs.chars;
+/// #     }
+/// # }
+/// struct Chars;
+///
+/// impl Dimension<TextSize> for Chars {
+///     type Value = usize;
+///
+///     fn get(size: &TextSize) -> usize {
+///         size.chars
+///     }
+/// }
+/// ```
+pub trait Dimension<S> {
+    /// The type of this component.
+    type Value: Ord;
+
+    /// Gets this component of `size`.
+    fn get(size: &S) -> Self::Value;
+}
+
 mod sealed {
     pub trait Sealed {}
 }
@@ -113,6 +409,24 @@ pub trait ListOptions: sealed::Sealed {
     /// integer or collection of unsigned integers---returning negative values
     /// from [`LeafRef::size`] will produce incorrect results.
     ///
+    /// Wide and arbitrary-precision integers (like [`u128`] or
+    /// `num_bigint::BigUint`) can be used directly. Lookups by index update
+    /// sizes in place and avoid cloning them where possible, so sizes that
+    /// are expensive to clone remain efficient.
+    ///
+    /// Narrow integers (like [`u32`] or [`u8`]) can also be used. The total
+    /// size of the list must fit in the type, so with [`u32`], the combined
+    /// size of all items can be at most [`u32::MAX`]. Operations on the list
+    /// never compute a size larger than the list's total size before or
+    /// after the operation, so as long as that total fits, no overflow
+    /// occurs. (The exception is [`SkipList::update_many`], which may
+    /// temporarily count each updated item with the larger of its old and
+    /// new sizes.) If the total doesn't fit, arithmetic overflows: this
+    /// panics when overflow checks are enabled (as in debug builds), but
+    /// wraps silently otherwise, which produces incorrect results. Users who
+    /// can't rule out this possibility should enable `overflow-checks` in
+    /// their release profile, use a wider type, or use [`CheckedSize`].
+    ///
     /// *Default:* [`NoSize`]
     type SizeType: Clone + Default + Eq + AddAssign + SubAssign;
 
@@ -145,6 +459,72 @@ pub trait ListOptions: sealed::Sealed {
     ///
     /// [tagged pointers]: tagged_pointer
     type Align;
+
+    /// The number of internal nodes allocated together in a single block of
+    /// memory (a *slab*).
+    ///
+    /// If this is greater than 1, the list allocates slabs of this many
+    /// contiguous nodes and keeps track of unused nodes in each slab with an
+    /// intrusive free list. This improves locality and reduces the number of
+    /// calls to the allocator, at the cost of memory that stays allocated
+    /// until every node in a slab is freed.
+    ///
+    /// If this is 0 or 1, each node is allocated separately.
+    ///
+    /// *Default:* 1
+    type SlabSize: SlabSize;
+
+    /// Whether or not to cache a hash of the items in each internal node of
+    /// the list.
+    ///
+    /// This enables [`SkipList::content_hash`] and [`SkipList::subtree_hash`]
+    /// to be computed incrementally, which is useful for comparing replicas
+    /// of a list.
+    ///
+    /// *Default:* false
+    type StoreHashes: StoreHashes;
+
+    /// Whether index queries for the [size] of the list return its last
+    /// item when that item has a size of 0; see [`EmptyPolicy`].
+    ///
+    /// If true ([`EmptyPolicy::ReturnLast`]), the last item is returned;
+    /// otherwise ([`EmptyPolicy::ReturnNone`]), [`None`] is returned. This
+    /// also applies to index 0 in a list whose items all have a size of 0.
+    /// Queries on an empty list always return [`None`].
+    ///
+    /// *Default:* true
+    ///
+    /// [size]: SkipList::size
+    type EmptyPolicy: EmptyPolicyType;
+
+    /// Whether or not to cache, in each internal node of the list, the item
+    /// in the node's subtree whose [`Interval`] ends last.
+    ///
+    /// This enables [`SkipList::find_overlapping`] and
+    /// [`SkipList::find_all_overlapping`], which use the cached items to skip
+    /// subtrees that can't contain any matching intervals. Like hashes (see
+    /// [`Self::StoreHashes`]), the cached items are recomputed lazily after
+    /// the list changes.
+    ///
+    /// [`Interval`]: crate::Interval
+    ///
+    /// *Default:* false
+    type StoreMaxEnd: StoreMaxEnd;
+
+    /// A user-defined summary of the items in each internal node's subtree.
+    ///
+    /// This is [`NoAggregate`] or <code>[Aggregate]\<M></code>, where `M` is
+    /// a [`Monoid`]. In the latter case, each internal node caches the
+    /// combined value of the items in its subtree (see [`Summarize`]), which
+    /// allows [`SkipList::query_aggregate`] to combine the values of any
+    /// range of items in Θ(log *n*) time. Like hashes (see
+    /// [`Self::StoreHashes`]), the cached values are recomputed lazily after
+    /// the list changes.
+    ///
+    /// [`Summarize`]: crate::Summarize
+    ///
+    /// *Default:* [`NoAggregate`]
+    type Aggregate: AggregateType;
 }
 
 /// Alias of <code>[LeafRef::Options]::[SizeType]</code>.
@@ -158,23 +538,38 @@ pub type LeafSize<L> = <<L as LeafRef>::Options as ListOptions>::SizeType;
 /// to associated types in [`ListOptions`] as follows; see those associated
 /// types for documentation:
 ///
-/// Parameter    | Associated type
-/// ------------ | --------------------------
-/// `SizeType`   | [`ListOptions::SizeType`]
-/// `STORE_KEYS` | [`ListOptions::StoreKeys`]
-/// `FANOUT`     | [`ListOptions::Fanout`]
-/// `Align`      | [`ListOptions::Align`]
+/// Parameter       | Associated type
+/// --------------- | ----------------------------
+/// `SizeType`      | [`ListOptions::SizeType`]
+/// `STORE_KEYS`    | [`ListOptions::StoreKeys`]
+/// `FANOUT`        | [`ListOptions::Fanout`]
+/// `Align`         | [`ListOptions::Align`]
+/// `SLAB_SIZE`     | [`ListOptions::SlabSize`]
+/// `STORE_HASHES`  | [`ListOptions::StoreHashes`]
+/// `RETURN_LAST`   | [`ListOptions::EmptyPolicy`]
+/// `STORE_MAX_END` | [`ListOptions::StoreMaxEnd`]
+/// `Aggregate`     | [`ListOptions::Aggregate`]
 #[rustfmt::skip]
 pub type Options<
     SizeType = NoSize,
     const STORE_KEYS: bool = false,
     const FANOUT: usize = 8,
     Align = (),
+    const SLAB_SIZE: usize = 1,
+    const STORE_HASHES: bool = false,
+    const RETURN_LAST: bool = true,
+    const STORE_MAX_END: bool = false,
+    Aggregate = NoAggregate,
 > = TypedOptions<
     SizeType,
     Bool<STORE_KEYS>,
     Usize<FANOUT>,
     Align,
+    Usize<SLAB_SIZE>,
+    Bool<STORE_HASHES>,
+    Bool<RETURN_LAST>,
+    Bool<STORE_MAX_END>,
+    Aggregate,
 >;
 
 /// Like [`Options`], but uses types instead of const parameters.
@@ -187,11 +582,21 @@ pub struct TypedOptions<
     StoreKeys = Bool<false>,
     Fanout = Usize<8>,
     Align = (),
+    SlabSize = Usize<1>,
+    StoreHashes = Bool<false>,
+    EmptyPolicy = Bool<true>,
+    StoreMaxEnd = Bool<false>,
+    Aggregate = NoAggregate,
 >(PhantomData<fn() -> (
     SizeType,
     StoreKeys,
     Fanout,
     Align,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 )>);
 
 #[rustfmt::skip]
@@ -200,11 +605,21 @@ impl<
     StoreKeys,
     Fanout,
     Align,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > sealed::Sealed for TypedOptions<
     SizeType,
     StoreKeys,
     Fanout,
     Align,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > {}
 
 #[rustfmt::skip]
@@ -213,14 +628,29 @@ impl<
     StoreKeys: self::StoreKeys,
     Fanout: self::Fanout,
     Align,
+    SlabSize: self::SlabSize,
+    StoreHashes: self::StoreHashes,
+    EmptyPolicy: EmptyPolicyType,
+    StoreMaxEnd: self::StoreMaxEnd,
+    Aggregate: AggregateType,
 > ListOptions for TypedOptions<
     SizeType,
     StoreKeys,
     Fanout,
     Align,
+    SlabSize,
+    StoreHashes,
+    EmptyPolicy,
+    StoreMaxEnd,
+    Aggregate,
 > {
     type SizeType = SizeType;
     type StoreKeys = StoreKeys;
     type Fanout = Fanout;
     type Align = Align;
+    type SlabSize = SlabSize;
+    type StoreHashes = StoreHashes;
+    type EmptyPolicy = EmptyPolicy;
+    type StoreMaxEnd = StoreMaxEnd;
+    type Aggregate = Aggregate;
 }
@@ -19,11 +19,125 @@
 
 use crate::allocator::{AllocError, Allocator};
 use alloc::alloc::Layout;
-use core::mem::ManuallyDrop;
+use core::cell::Cell;
+use core::mem::{ManuallyDrop, align_of, size_of};
 use core::ops::Deref;
 use core::ptr::NonNull;
 
-pub struct PersistentAlloc<A>(ManuallyDrop<A>);
+/// What to do after an allocation fails; returned by the hook passed to
+/// [`SkipList::set_alloc_error_hook`].
+///
+/// [`SkipList::set_alloc_error_hook`]: crate::SkipList::set_alloc_error_hook
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllocFallback {
+    /// Try the allocation again, once. If it fails again, the failure is
+    /// handled as with [`Self::Abort`], without calling the hook again.
+    Retry,
+    /// Give up and call [`handle_alloc_error`], which typically aborts.
+    ///
+    /// [`handle_alloc_error`]: alloc::alloc::handle_alloc_error
+    Abort,
+}
+
+/// A function called when an allocation fails.
+pub type AllocErrorHook = fn(Layout) -> AllocFallback;
+
+/// A link in a slab's free list, stored in the memory of an unused block.
+type FreeLink = Option<NonNull<u8>>;
+
+/// The header at the start of every slab.
+///
+/// Each slab consists of this header followed by a number of slots, each of
+/// which contains a pointer back to this header followed by a block of
+/// memory that can be handed out by [`PersistentAlloc::allocate_in_slab`].
+struct SlabHeader {
+    /// The number of blocks in use, plus one if the slab is in the
+    /// [`SlabList`] of some [`PersistentAlloc`]. The slab is deallocated when
+    /// this becomes zero.
+    refs: Cell<usize>,
+    /// The first unused block.
+    free: Cell<FreeLink>,
+    /// The list this slab is in, if any.
+    owner: Cell<Option<NonNull<SlabList>>>,
+    /// The previous slab in [`Self::owner`].
+    prev: Cell<Option<NonNull<SlabHeader>>>,
+    /// The next slab in [`Self::owner`].
+    next: Cell<Option<NonNull<SlabHeader>>>,
+    /// The layout of the entire slab.
+    layout: Layout,
+}
+
+/// The slabs with unused blocks from which a [`PersistentAlloc`] allocates.
+///
+/// This is allocated separately from the [`PersistentAlloc`] so that slabs
+/// can refer to it (to remove themselves from it) even as the allocator is
+/// moved. A slab is in at most one list at a time, and every slab in a list
+/// has at least one unused block.
+struct SlabList {
+    head: Cell<Option<NonNull<SlabHeader>>>,
+}
+
+impl SlabList {
+    /// Adds `slab`, which must not be in any list, to the front of the list.
+    /// The list takes over a reference to the slab held by the caller.
+    fn push(&self, slab: NonNull<SlabHeader>) {
+        // SAFETY: The caller holds a reference to `slab`, so it is live.
+        let header = unsafe { slab.as_ref() };
+        debug_assert!(header.owner.get().is_none());
+        if let Some(head) = self.head.get() {
+            // SAFETY: Slabs in a list are live.
+            unsafe { head.as_ref() }.prev.set(Some(slab));
+        }
+        header.owner.set(Some(NonNull::from(self)));
+        header.next.set(self.head.replace(Some(slab)));
+    }
+}
+
+/// Removes `slab` from the list it's in. The reference the list held to the
+/// slab now belongs to the caller.
+///
+/// # Safety
+///
+/// `slab` must be in a list.
+unsafe fn unlink_slab(slab: NonNull<SlabHeader>) {
+    // SAFETY: Slabs in a list are live.
+    let header = unsafe { slab.as_ref() };
+    let owner = header.owner.take().unwrap();
+    let prev = header.prev.take();
+    let next = header.next.take();
+    match prev {
+        // SAFETY: Slabs in a list are live.
+        Some(prev) => unsafe { prev.as_ref() }.next.set(next),
+        // SAFETY: A list outlives the slabs in it; see
+        // `PersistentAlloc::drop`.
+        None => unsafe { owner.as_ref() }.head.set(next),
+    }
+    if let Some(next) = next {
+        // SAFETY: Slabs in a list are live.
+        unsafe { next.as_ref() }.prev.set(prev);
+    }
+}
+
+/// Gets the layout of a slot holding a block with the given layout, and the
+/// offset of the block within the slot.
+fn slot_layout(layout: Layout) -> (Layout, usize) {
+    let block = Layout::from_size_align(
+        layout.size().max(size_of::<FreeLink>()),
+        layout.align().max(align_of::<FreeLink>()),
+    )
+    .unwrap();
+    let (slot, offset) =
+        Layout::new::<NonNull<SlabHeader>>().extend(block).unwrap();
+    (slot.pad_to_align(), offset)
+}
+
+pub struct PersistentAlloc<A> {
+    alloc: ManuallyDrop<A>,
+    hook: Option<AllocErrorHook>,
+    /// The slabs from which blocks are allocated. This is allocated when
+    /// first needed.
+    slabs: Cell<Option<NonNull<SlabList>>>,
+}
 
 impl<A: Allocator> PersistentAlloc<A> {
     /// Creates a new [`PersistentAlloc`] with `alloc` as the inner allocator.
@@ -34,7 +148,237 @@ impl<A: Allocator> PersistentAlloc<A> {
     where
         A: 'static,
     {
-        Self(ManuallyDrop::new(alloc))
+        // SAFETY: `A` is `'static`.
+        unsafe { Self::new_unchecked(alloc) }
+    }
+
+    /// Like [`Self::new`], but without requiring `A: 'static`.
+    ///
+    /// # Safety
+    ///
+    /// Memory allocated by `alloc` must remain valid for as long as any leaf
+    /// that was in a list using this allocator may be used; see
+    /// [`SkipList::new_in_unchecked`].
+    ///
+    /// [`SkipList::new_in_unchecked`]: crate::SkipList::new_in_unchecked
+    pub unsafe fn new_unchecked(alloc: A) -> Self {
+        Self {
+            alloc: ManuallyDrop::new(alloc),
+            hook: None,
+            slabs: Cell::new(None),
+        }
+    }
+
+    /// Sets the function called when the inner allocator fails.
+    pub fn set_hook(&mut self, hook: Option<AllocErrorHook>) {
+        self.hook = hook;
+    }
+
+    /// Gets the function called when the inner allocator fails.
+    pub fn hook(&self) -> Option<AllocErrorHook> {
+        self.hook
+    }
+
+    /// Allocates a block of memory for `layout`, taking it from a slab of
+    /// `slab_size` blocks.
+    ///
+    /// If `slab_size` is 0 or 1, this simply calls [`Allocator::allocate`].
+    /// Otherwise, the block is taken from a slab that has unused blocks, if
+    /// there is one, and a new slab is allocated only if there isn't. The
+    /// block must be deallocated with [`Self::deallocate_in_slab`], called
+    /// with the same `layout` and `slab_size`, on this allocator or a clone
+    /// of it.
+    pub fn allocate_in_slab(
+        &self,
+        layout: Layout,
+        slab_size: usize,
+    ) -> Result<NonNull<u8>, AllocError> {
+        if slab_size <= 1 {
+            return self.allocate(layout).map(NonNull::cast);
+        }
+        let list = self.slab_list(|layout| self.allocate(layout))?;
+        // SAFETY: `self.slabs` always points to a live list.
+        let list = unsafe { list.as_ref() };
+        let slab = match list.head.get() {
+            Some(slab) => slab,
+            None => {
+                let slab = self.allocate_slab(layout, slab_size)?;
+                list.push(slab);
+                slab
+            }
+        };
+        // SAFETY: Slabs in a list are live.
+        let header = unsafe { slab.as_ref() };
+        let block = header.free.get().unwrap();
+        // SAFETY: Unused blocks always store the next link in the free list.
+        header.free.set(unsafe { block.cast::<FreeLink>().as_ptr().read() });
+        header.refs.set(header.refs.get() + 1);
+        if header.free.get().is_none() {
+            // SAFETY: `slab` is in `list`, and the block just allocated
+            // holds another reference to it.
+            unsafe {
+                unlink_slab(slab);
+                self.release_slab(slab);
+            }
+        }
+        Ok(block)
+    }
+
+    /// Deallocates a block of memory allocated by
+    /// [`Self::allocate_in_slab`].
+    ///
+    /// The block's slab becomes available for allocation from this
+    /// allocator if it wasn't already available from some allocator, and it
+    /// is deallocated once none of its blocks are in use, unless it is the
+    /// only slab available.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must have been returned by [`Self::allocate_in_slab`], called
+    ///   on this allocator or a clone of it with the same `layout` and
+    ///   `slab_size`.
+    /// * `ptr` must not have already been deallocated.
+    pub unsafe fn deallocate_in_slab(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        slab_size: usize,
+    ) {
+        if slab_size <= 1 {
+            // SAFETY: Checked by caller.
+            unsafe {
+                self.deallocate(ptr, layout);
+            }
+            return;
+        }
+        let offset = slot_layout(layout).1;
+        // SAFETY: Checked by caller. Every block is preceded by a pointer to
+        // its slab's header, which is live while the block is in use.
+        let slab = unsafe {
+            ptr.as_ptr().sub(offset).cast::<NonNull<SlabHeader>>().read()
+        };
+        // SAFETY: See above.
+        let header = unsafe { slab.as_ref() };
+        // SAFETY: The block is large enough and sufficiently aligned to store
+        // a `FreeLink`; see `slot_layout`.
+        unsafe {
+            ptr.cast::<FreeLink>().as_ptr().write(header.free.get());
+        }
+        header.free.set(Some(ptr));
+        if header.owner.get().is_none() {
+            // Avoid calling the hook while deallocating; if the list can't be
+            // allocated, the slab is simply deallocated once it's unused.
+            if let Ok(list) =
+                self.slab_list(|layout| self.alloc.allocate(layout))
+            {
+                header.refs.set(header.refs.get() + 1);
+                // SAFETY: `self.slabs` always points to a live list.
+                unsafe { list.as_ref() }.push(slab);
+            }
+        }
+        let only = header.prev.get().is_none() && header.next.get().is_none();
+        if header.refs.get() == 2 && header.owner.get().is_some() && !only {
+            // The block being deallocated holds the only other reference.
+            // SAFETY: `slab` is in a list.
+            unsafe {
+                unlink_slab(slab);
+                self.release_slab(slab);
+            }
+        }
+        // SAFETY: The block being deallocated held a reference to the slab.
+        unsafe {
+            self.release_slab(slab);
+        }
+    }
+
+    /// Gets this allocator's [`SlabList`], allocating it with `allocate` if
+    /// it doesn't exist yet.
+    fn slab_list<F>(
+        &self,
+        allocate: F,
+    ) -> Result<NonNull<SlabList>, AllocError>
+    where
+        F: FnOnce(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    {
+        if let Some(list) = self.slabs.get() {
+            return Ok(list);
+        }
+        let list = allocate(Layout::new::<SlabList>())?.cast::<SlabList>();
+        // SAFETY: `list` was allocated with room for a `SlabList`.
+        unsafe {
+            list.as_ptr().write(SlabList {
+                head: Cell::new(None),
+            });
+        }
+        self.slabs.set(Some(list));
+        Ok(list)
+    }
+
+    /// Allocates a new slab with all of its blocks unused. The slab starts
+    /// with one reference, which belongs to the caller.
+    fn allocate_slab(
+        &self,
+        layout: Layout,
+        slab_size: usize,
+    ) -> Result<NonNull<SlabHeader>, AllocError> {
+        let (slot, offset) = slot_layout(layout);
+        let slots = slot
+            .size()
+            .checked_mul(slab_size)
+            .and_then(|size| Layout::from_size_align(size, slot.align()).ok())
+            .ok_or(AllocError)?;
+        let (slab_layout, start) = Layout::new::<SlabHeader>()
+            .extend(slots)
+            .map_err(|_| AllocError)?;
+        let slab = self.allocate(slab_layout)?.cast::<SlabHeader>();
+        let base = slab.as_ptr().cast::<u8>();
+        let mut free = None;
+        for i in (0..slab_size).rev() {
+            // SAFETY: Every slot is within the slab's allocation, and the
+            // slab's layout ensures each slot and block is properly aligned.
+            unsafe {
+                let slot_ptr = base.add(start + slot.size() * i);
+                slot_ptr.cast::<NonNull<SlabHeader>>().write(slab);
+                let block = slot_ptr.add(offset);
+                block.cast::<FreeLink>().write(free);
+                free = Some(NonNull::new_unchecked(block));
+            }
+        }
+        // SAFETY: `slab` was allocated with room for a `SlabHeader`.
+        unsafe {
+            slab.as_ptr().write(SlabHeader {
+                refs: Cell::new(1),
+                free: Cell::new(free),
+                owner: Cell::new(None),
+                prev: Cell::new(None),
+                next: Cell::new(None),
+                layout: slab_layout,
+            });
+        }
+        Ok(slab)
+    }
+
+    /// Releases a reference to `slab`, deallocating it if no references
+    /// remain.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a reference to `slab`, which may not be used
+    /// after this method is called.
+    unsafe fn release_slab(&self, slab: NonNull<SlabHeader>) {
+        // SAFETY: Checked by caller.
+        let header = unsafe { slab.as_ref() };
+        let refs = header.refs.get() - 1;
+        header.refs.set(refs);
+        if refs == 0 {
+            debug_assert!(header.owner.get().is_none());
+            let layout = header.layout;
+            // SAFETY: Every slab is allocated by this allocator or a clone
+            // of it, and no references to it remain.
+            unsafe {
+                self.deallocate(slab.cast(), layout);
+            }
+        }
     }
 
     /// Drops the inner allocator.
@@ -42,13 +386,31 @@ impl<A: Allocator> PersistentAlloc<A> {
     /// # Safety
     ///
     /// * Every block of memory allocated by the inner allocator must have been
-    ///   deallocated with [`Allocator::deallocate`].
+    ///   deallocated with [`Allocator::deallocate`] (or
+    ///   [`Self::deallocate_in_slab`]).
     /// * This [`PersistentAlloc`] must not be used (including being
     ///   dereferenced) after this method is called.
     pub unsafe fn drop(&mut self) {
+        if let Some(list) = self.slabs.take() {
+            // SAFETY: `self.slabs` always points to a live list.
+            while let Some(slab) = unsafe { list.as_ref() }.head.get() {
+                // SAFETY: `slab` is in `list`, which holds a reference to it.
+                // Slabs that still have blocks in use are no longer in any
+                // list, so they don't refer to `list` after it's deallocated.
+                unsafe {
+                    unlink_slab(slab);
+                    self.release_slab(slab);
+                }
+            }
+            // SAFETY: `list` was allocated by `self.alloc` or `self` (which
+            // forwards to it) in `Self::slab_list`.
+            unsafe {
+                self.alloc.deallocate(list.cast(), Layout::new::<SlabList>());
+            }
+        }
         // SAFETY: Checked by caller.
         unsafe {
-            ManuallyDrop::drop(&mut self.0);
+            ManuallyDrop::drop(&mut self.alloc);
         }
     }
 }
@@ -57,21 +419,33 @@ impl<A> Deref for PersistentAlloc<A> {
     type Target = A;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.alloc
     }
 }
 
-// SAFETY: Simply forwards to the inner allocator's implementation.
-/// Forwards to the inner allocator.
+/// Forwards to the inner allocator, calling the allocation error hook (if
+/// any) when an allocation fails.
+// SAFETY: Every block is allocated and deallocated by the inner allocator,
+// which upholds the requirements of `Allocator`. Retrying a failed allocation
+// doesn't affect those requirements.
 unsafe impl<A: Allocator> Allocator for PersistentAlloc<A> {
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        self.0.allocate(layout)
+        let err = match self.alloc.allocate(layout) {
+            Ok(ptr) => return Ok(ptr),
+            Err(err) => err,
+        };
+        match self.hook.map(|hook| hook(layout)) {
+            // Retry only once, so a hook that can't free any memory doesn't
+            // cause an infinite loop.
+            Some(AllocFallback::Retry) => self.alloc.allocate(layout),
+            _ => Err(err),
+        }
     }
 
     unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
         // SAFETY: Checked by caller.
         unsafe {
-            self.0.deallocate(ptr, layout);
+            self.alloc.deallocate(ptr, layout);
         }
     }
 }
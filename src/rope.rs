@@ -0,0 +1,472 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A rope: a string that supports efficient editing at any position.
+//!
+//! [`Rope`] stores its text in chunks of up to a few hundred bytes, each in
+//! its own leaf of a [`SkipList`]. The size of each leaf has two components,
+//! bytes and [`char`]s, so the list can be indexed by either one (see
+//! [`Dimension`]); this lets a [`Rope`] convert between byte and character
+//! indices in Θ(log *n*) time without scanning the text.
+//!
+//! [`Dimension`]: crate::options::Dimension
+
+use crate::SkipList;
+use crate::basic::{self, BasicLeaf, BoxLeaf};
+use crate::options::Dimension;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{AddAssign, Bound, RangeBounds, SubAssign};
+
+/// The target maximum length of a chunk in bytes. Chunks may be up to three
+/// bytes longer so that they end on a [`char`] boundary.
+const MAX_CHUNK: usize = 512;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct TextSize {
+    bytes: usize,
+    chars: usize,
+}
+
+impl AddAssign for TextSize {
+    fn add_assign(&mut self, rhs: Self) {
+        self.bytes += rhs.bytes;
+        self.chars += rhs.chars;
+    }
+}
+
+impl SubAssign for TextSize {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.bytes -= rhs.bytes;
+        self.chars -= rhs.chars;
+    }
+}
+
+struct Bytes;
+struct Chars;
+
+impl Dimension<TextSize> for Bytes {
+    type Value = usize;
+
+    fn get(size: &TextSize) -> usize {
+        size.bytes
+    }
+}
+
+impl Dimension<TextSize> for Chars {
+    type Value = usize;
+
+    fn get(size: &TextSize) -> usize {
+        size.chars
+    }
+}
+
+/// A chunk of text. Chunks are never modified; edits replace them.
+struct Chunk {
+    text: Box<str>,
+    chars: usize,
+}
+
+impl BasicLeaf for Chunk {
+    type Options = basic::Options<TextSize>;
+
+    fn size(&self) -> TextSize {
+        TextSize {
+            bytes: self.text.len(),
+            chars: self.chars,
+        }
+    }
+}
+
+type Leaf = BoxLeaf<Chunk>;
+
+/// Gets the text of a leaf.
+///
+/// # Safety
+///
+/// The leaf must be owned by a [`Rope`] that is borrowed for `'a`.
+unsafe fn text<'a>(leaf: Leaf) -> &'a str {
+    let text: *const str = &*leaf.text;
+    // SAFETY: Leaves owned by a rope are freed only when they are removed
+    // from its list, which requires mutable access to the rope.
+    unsafe { &*text }
+}
+
+/// Allocates a leaf containing `text`.
+fn new_leaf(text: &str) -> Leaf {
+    let chunk = Chunk {
+        text: text.into(),
+        chars: text.chars().count(),
+    };
+    // SAFETY: Leaves are never accessible outside of `Rope`, which doesn't
+    // use them after they are freed.
+    unsafe { Leaf::new(chunk) }
+}
+
+/// Gets the byte offset of the `n`th [`char`] in `text`, or the length of
+/// `text` if it has exactly `n` chars.
+fn char_offset(text: &str, n: usize) -> usize {
+    text.char_indices().nth(n).map_or(text.len(), |(i, _)| i)
+}
+
+/// Splits `text` into chunks of roughly equal length, using as few chunks as
+/// [`MAX_CHUNK`] allows.
+fn make_chunks(text: &str) -> impl Iterator<Item = Leaf> + '_ {
+    let count = ((text.len() + MAX_CHUNK - 1) / MAX_CHUNK).max(1);
+    let target = (text.len() + count - 1) / count;
+    let mut rest = text;
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = target.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        let (chunk, next) = rest.split_at(end);
+        rest = next;
+        Some(new_leaf(chunk))
+    })
+}
+
+/// A string that supports Θ(log *n*) insertion and removal at any position,
+/// indexed by [`char`].
+///
+/// Positions passed to and returned by most methods are `char` indices;
+/// [`Self::char_to_byte`] and [`Self::byte_to_char`] convert between those
+/// and byte indices.
+pub struct Rope {
+    list: SkipList<Leaf>,
+}
+
+impl Rope {
+    /// Creates a new, empty rope.
+    pub fn new() -> Self {
+        Self {
+            list: SkipList::new(),
+        }
+    }
+
+    /// Gets the length of the text in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.list.size().bytes
+    }
+
+    /// Gets the length of the text in [`char`]s.
+    pub fn len_chars(&self) -> usize {
+        self.list.size().chars
+    }
+
+    /// Checks whether the rope is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Converts a [`char`] index to a byte index.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `char_idx` is greater than
+    /// [`self.len_chars()`](Self::len_chars).
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn char_to_byte(&self, char_idx: usize) -> usize {
+        assert!(char_idx <= self.len_chars(), "index out of bounds");
+        let leaf = match self.list.get_by::<Chars>(&char_idx) {
+            Some(leaf) => leaf,
+            None => return self.len_bytes(),
+        };
+        let start = SkipList::index(leaf.clone());
+        start.bytes + char_offset(&leaf.text, char_idx - start.chars)
+    }
+
+    /// Converts a byte index to a [`char`] index.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `byte_idx` is greater than
+    /// [`self.len_bytes()`](Self::len_bytes) or is not on a `char` boundary.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn byte_to_char(&self, byte_idx: usize) -> usize {
+        assert!(byte_idx <= self.len_bytes(), "index out of bounds");
+        let leaf = match self.list.get_by::<Bytes>(&byte_idx) {
+            Some(leaf) => leaf,
+            None => return self.len_chars(),
+        };
+        let start = SkipList::index(leaf.clone());
+        let offset = byte_idx - start.bytes;
+        assert!(leaf.text.is_char_boundary(offset), "not a char boundary");
+        start.chars + leaf.text[..offset].chars().count()
+    }
+
+    /// Gets the [`char`] at `char_idx`, or returns [`None`] if `char_idx` is
+    /// out of bounds.
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*).
+    pub fn char(&self, char_idx: usize) -> Option<char> {
+        let leaf = self.list.get_by::<Chars>(&char_idx)?;
+        let start = SkipList::index(leaf.clone());
+        leaf.text.chars().nth(char_idx - start.chars)
+    }
+
+    /// Inserts `text` at [`char`] index `char_idx`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `char_idx` is greater than
+    /// [`self.len_chars()`](Self::len_chars).
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(*m* + log *n*), where *m* is the length of `text`.
+    pub fn insert_str(&mut self, char_idx: usize, text: &str) {
+        assert!(char_idx <= self.len_chars(), "index out of bounds");
+        if text.is_empty() {
+            return;
+        }
+        // The chunk that will contain the start of `text`.
+        let target = self.list.get_by::<Chars>(&char_idx).or_else(|| {
+            self.list.last().filter(|l| l.text.len() + text.len() <= MAX_CHUNK)
+        });
+        let target = match target {
+            Some(target) => target,
+            None => {
+                self.list.push_back_from(make_chunks(text));
+                return;
+            }
+        };
+        let start = SkipList::index(target.clone());
+        let offset = char_offset(&target.text, char_idx - start.chars);
+        let mut joined = String::with_capacity(target.text.len() + text.len());
+        joined.push_str(&target.text[..offset]);
+        joined.push_str(text);
+        joined.push_str(&target.text[offset..]);
+        self.replace_chunks(target.clone(), target, &joined);
+    }
+
+    /// Removes the [`char`]s in `range`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the start of `range` is greater than its end,
+    /// or if its end is greater than [`self.len_chars()`](Self::len_chars).
+    ///
+    /// # Time complexity
+    ///
+    /// Worst-case Θ(*k* + log *n*), where *k* is the number of chunks that
+    /// overlap `range`.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = self.char_range(range);
+        if start == end {
+            return;
+        }
+        let first = self.list.get_by::<Chars>(&start).unwrap();
+        let last = self.list.get_by::<Chars>(&(end - 1)).unwrap();
+        let first_start = SkipList::index(first.clone());
+        let last_start = SkipList::index(last.clone());
+        let head = char_offset(&first.text, start - first_start.chars);
+        let tail = char_offset(&last.text, end - last_start.chars);
+        let mut joined = String::new();
+        joined.push_str(&first.text[..head]);
+        joined.push_str(&last.text[tail..]);
+        self.replace_chunks(first, last, &joined);
+    }
+
+    /// Replaces the chunks from `first` to `last` (inclusive) with the
+    /// contents of `text`, merging it into the following chunk if the
+    /// result fits in one chunk.
+    fn replace_chunks(&mut self, first: Leaf, mut last: Leaf, text: &str) {
+        let mut joined;
+        let mut text = text;
+        if let Some(next) = SkipList::next(last.clone()) {
+            if !text.is_empty() && text.len() + next.text.len() <= MAX_CHUNK {
+                joined = String::with_capacity(MAX_CHUNK);
+                joined.push_str(text);
+                joined.push_str(&next.text);
+                text = &joined;
+                last = next;
+            }
+        }
+        let prev = SkipList::previous(first.clone());
+        for leaf in self.list.remove_range(first, last) {
+            // SAFETY: The leaf has been removed from the list, and no clones
+            // of it exist outside of the list.
+            drop(unsafe { BoxLeaf::into_inner(leaf) });
+        }
+        self.list.insert_after_opt_from(prev, make_chunks(text));
+    }
+
+    fn char_range<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&i) => i,
+            Bound::Excluded(&i) => i + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => i + 1,
+            Bound::Excluded(&i) => i,
+            Bound::Unbounded => self.len_chars(),
+        };
+        assert!(start <= end, "range start is greater than range end");
+        assert!(end <= self.len_chars(), "range end out of bounds");
+        (start, end)
+    }
+
+    /// Returns an iterator over the chunks of text in the rope.
+    pub fn chunks(&self) -> Chunks<'_> {
+        self.slice(..)
+    }
+
+    /// Returns an iterator over the chunks of text that make up the [`char`]s
+    /// in `range`. The first and last chunks are trimmed to fit `range`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same conditions as [`Self::remove`].
+    ///
+    /// # Time complexity
+    ///
+    /// Θ(log *n*) to create the iterator.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Chunks<'_> {
+        let (start, end) = self.char_range(range);
+        let start = self.char_to_byte(start);
+        let end = self.char_to_byte(end);
+        let first = self.list.get_by::<Bytes>(&start).filter(|_| start < end);
+        let skip = first
+            .as_ref()
+            .map_or(0, |f| start - SkipList::index(f.clone()).bytes);
+        Chunks {
+            iter: first.map(SkipList::iter_at),
+            skip,
+            remaining: end - start,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the [`char`]s in the rope.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut rope = Self::new();
+        rope.list.push_back_from(make_chunks(text));
+        rope
+    }
+}
+
+impl Clone for Rope {
+    fn clone(&self) -> Self {
+        let mut rope = Self::new();
+        rope.list.push_back_from(self.chunks().map(new_leaf));
+        rope
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+        f.write_char('"')?;
+        for chunk in self.chunks() {
+            write!(f, "{}", chunk.escape_debug())?;
+        }
+        f.write_char('"')
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len_bytes() == other.len_bytes() && self.chars().eq(other.chars())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        let mut rest = other.as_bytes();
+        self.len_bytes() == other.len()
+            && self.chunks().all(|chunk| {
+                let (head, tail) = rest.split_at(chunk.len());
+                rest = tail;
+                head == chunk.as_bytes()
+            })
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+/// An iterator over chunks of text in a [`Rope`].
+///
+/// This type is returned by [`Rope::chunks`] and [`Rope::slice`].
+pub struct Chunks<'a> {
+    iter: Option<crate::iter::Iter<Leaf>>,
+    /// The number of bytes to skip at the start of the next chunk.
+    skip: usize,
+    /// The number of bytes left to yield.
+    remaining: usize,
+    phantom: PhantomData<&'a Rope>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let leaf = self.iter.as_mut()?.next()?;
+        // SAFETY: The leaf is owned by the rope, which is borrowed for `'a`.
+        let text = &unsafe { text(leaf) }[self.skip..];
+        let text = &text[..text.len().min(self.remaining)];
+        self.skip = 0;
+        self.remaining -= text.len();
+        Some(text)
+    }
+}
+
+impl FusedIterator for Chunks<'_> {}
@@ -0,0 +1,279 @@
+/*
+ * Copyright (C) 2025 taylor.fish <contact@taylor.fish>
+ *
+ * This file is part of Skippy.
+ *
+ * Skippy is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published
+ * by the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Skippy is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Differential tests that mirror randomized workloads on a [`SkipList`]
+//! against `BTreeMap` (for keyed operations) and `Vec` (for positional
+//! operations), checking that they agree after every operation.
+
+use skippy::SkipList;
+use skippy::basic::options::{BasicOptions, Options};
+use skippy::basic::{BasicLeaf, LeafArena, RefLeaf};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// A small xorshift generator, so the tests are deterministic and need no
+/// extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+struct Item<O> {
+    key: u32,
+    size: Cell<usize>,
+    phantom: PhantomData<O>,
+}
+
+impl<O> Item<O> {
+    fn new(key: u32, size: usize) -> Self {
+        Self {
+            key,
+            size: Cell::new(size),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<O> PartialEq for Item<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<O> Eq for Item<O> {}
+
+impl<O> PartialOrd for Item<O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<O> Ord for Item<O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<O: BasicOptions<SizeType = usize>> BasicLeaf for Item<O> {
+    type Options = O;
+
+    fn size(&self) -> usize {
+        self.size.get()
+    }
+}
+
+type KeysStored = <Options<usize, true> as BasicOptions>::StoreKeys;
+type Leaf<'a, O> = &'a RefLeaf<'a, Item<O>>;
+
+fn check_positional<'a, O>(list: &SkipList<Leaf<'a, O>>, vec: &[Leaf<'a, O>])
+where
+    O: BasicOptions<SizeType = usize>,
+{
+    assert_eq!(list.len(), vec.len());
+    assert_eq!(list.size(), vec.iter().map(|i| i.size.get()).sum());
+    assert!(list.iter().zip(vec).all(|(a, b)| std::ptr::eq(a, *b)));
+    assert_eq!(list.iter().count(), vec.len());
+    assert_eq!(list.first().map(|i| i.key), vec.first().map(|i| i.key));
+    assert_eq!(list.last().map(|i| i.key), vec.last().map(|i| i.key));
+}
+
+/// Mirrors positional operations against a `Vec`.
+fn positional<O>(seed: u64, ops: usize)
+where
+    O: BasicOptions<SizeType = usize>,
+{
+    let arena = LeafArena::new();
+    let mut list = SkipList::new();
+    let mut vec: Vec<Leaf<'_, O>> = Vec::new();
+    let mut rng = Rng(seed);
+    let mut next_key = 0;
+    for _ in 0..ops {
+        match rng.below(10) {
+            0..=3 => {
+                let item = arena.alloc(Item::new(next_key, rng.below(5) + 1));
+                next_key += 1;
+                let i = rng.below(vec.len() + 1);
+                if i == vec.len() {
+                    list.push_back(item);
+                } else if rng.below(2) == 0 {
+                    list.insert_before(vec[i], item);
+                } else if i == 0 {
+                    list.push_front(item);
+                } else {
+                    list.insert_after(vec[i - 1], item);
+                }
+                vec.insert(i, item);
+            }
+            4 | 5 if !vec.is_empty() => {
+                let i = rng.below(vec.len());
+                list.remove(vec.remove(i));
+            }
+            6 if !vec.is_empty() => {
+                let item = vec[rng.below(vec.len())];
+                let size = rng.below(5) + 1;
+                list.update(item, || item.size.set(size));
+            }
+            7 if vec.len() > 1 => {
+                let i = rng.below(vec.len());
+                let j = rng.below(vec.len());
+                if i != j {
+                    list.swap(vec[i], vec[j]);
+                    vec.swap(i, j);
+                }
+            }
+            8 if !vec.is_empty() => {
+                let size = list.size();
+                let index = rng.below(size);
+                let mut start = 0;
+                let expected = vec.iter().position(|i| {
+                    start += i.size.get();
+                    start > index
+                });
+                let item = list.get(&index).unwrap();
+                assert!(std::ptr::eq(item, vec[expected.unwrap()]));
+                assert_eq!(SkipList::index(item), start - item.size.get());
+            }
+            9 if !vec.is_empty() => {
+                let at = rng.below(vec.len());
+                let mut other = list.split_off(vec[at]);
+                let tail = vec.split_off(at);
+                check_positional(&list, &vec);
+                check_positional(&other, &tail);
+                list.push_back_from(other.drain());
+                vec.extend(tail);
+            }
+            _ => {}
+        }
+        check_positional(&list, &vec);
+    }
+}
+
+/// Mirrors keyed operations against a `BTreeMap`.
+fn keyed<O>(seed: u64, ops: usize)
+where
+    O: BasicOptions<SizeType = usize, StoreKeys = KeysStored>,
+{
+    let arena = LeafArena::new();
+    let mut list: SkipList<Leaf<'_, O>> = SkipList::new();
+    let mut map: BTreeMap<u32, Leaf<'_, O>> = BTreeMap::new();
+    let mut rng = Rng(seed);
+    for _ in 0..ops {
+        let key = rng.below(500) as u32;
+        match rng.below(8) {
+            0..=3 => {
+                // `RefLeaf`'s `Ord` impl also compares the next pointer, so
+                // `SkipList::insert` wouldn't detect duplicate keys.
+                match list.find_with_cmp(|i| i.key.cmp(&key)) {
+                    Ok(_) => assert!(map.contains_key(&key)),
+                    Err(prev) => {
+                        let item = arena.alloc(Item::new(key, rng.below(5)));
+                        list.insert_after_opt(prev, item);
+                        assert!(map.insert(key, item).is_none());
+                    }
+                }
+            }
+            4 | 5 => {
+                let found = list.find_with_cmp(|i| i.key.cmp(&key));
+                match (found, map.remove(&key)) {
+                    (Ok(item), Some(expected)) => {
+                        assert!(std::ptr::eq(item, expected));
+                        list.remove(item);
+                    }
+                    (Err(_), None) => {}
+                    (found, expected) => panic!(
+                        "find returned {:?}, expected {:?}",
+                        found.map(|i| i.key).map_err(|p| p.map(|i| i.key)),
+                        expected.map(|i| i.key),
+                    ),
+                }
+            }
+            6 => {
+                let found = list.find_with_cmp(|i| i.key.cmp(&key));
+                let before = map.range(..key).next_back().map(|(k, _)| *k);
+                match found {
+                    Ok(item) => assert_eq!(item.key, key),
+                    Err(prev) => {
+                        assert!(!map.contains_key(&key));
+                        assert_eq!(prev.map(|i| i.key), before);
+                    }
+                }
+            }
+            _ => {
+                if let Some((_, item)) = map.range(key..).next() {
+                    let size = rng.below(5);
+                    list.update(*item, || item.size.set(size));
+                }
+            }
+        }
+        assert_eq!(list.len(), map.len());
+        assert!(list.iter().map(|i| i.key).eq(map.keys().copied()));
+        assert_eq!(list.size(), map.values().map(|i| i.size.get()).sum());
+    }
+}
+
+macro_rules! oracle_tests {
+    ($($name:ident: $options:ty,)*) => {
+        $(mod $name {
+            use super::*;
+
+            #[test]
+            fn positional() {
+                for seed in 1..=4 {
+                    super::positional::<$options>(seed, 1500);
+                }
+            }
+
+            #[test]
+            fn keyed() {
+                for seed in 1..=4 {
+                    super::keyed::<$options>(seed, 1500);
+                }
+            }
+        })*
+    };
+}
+
+oracle_tests! {
+    fanout_3: Options<usize, true, 3>,
+    fanout_4: Options<usize, true, 4>,
+    fanout_8: Options<usize, true, 8>,
+    fanout_16: Options<usize, true, 16>,
+    slab: Options<usize, true, 5, 8>,
+    hashes: Options<usize, true, 4, 1, true>,
+}
+
+#[test]
+fn positional_unkeyed() {
+    for seed in 1..=4 {
+        positional::<Options<usize, false, 3>>(seed, 1500);
+        positional::<Options<usize, false, 8, 4>>(seed, 1500);
+    }
+}
@@ -17,11 +17,21 @@
  * along with Skippy. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use skippy::SkipList;
+use allocator_fallback::{AllocError, Allocator, Global};
 use skippy::basic::{self, BasicLeaf, RefLeaf};
+use skippy::facade::{Deque, PriorityQueue};
+use skippy::rope::Rope;
+use skippy::{BatchOp, CheckedSize, Cursor, DropPolicy, DuplicatePolicy};
+use skippy::{Entry, This, ValidationError};
+use skippy::{Error, LeafNext, LeafRef, ListStats, NextHint, SkipList};
+use skippy::{SharedSkipList, SpillCheckpoint, SubtreeBuilder, SyncSkipList};
+use std::alloc::Layout;
+use std::borrow::Borrow;
 use std::cell::Cell;
 use std::cmp::Ordering;
 use std::fmt;
+use std::ops::ControlFlow;
+use std::ptr::{self, NonNull};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct Data {
@@ -58,6 +68,94 @@ impl BasicLeaf for Data {
 
 type Leaf<'a> = RefLeaf<'a, Data>;
 
+/// Counts the total number of allocations in a local variable.
+struct Counting<'a>(&'a Cell<usize>);
+
+// SAFETY: Forwards to `Global`.
+unsafe impl Allocator for Counting<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // SAFETY: Checked by caller.
+        unsafe {
+            Global.deallocate(ptr, layout);
+        }
+    }
+}
+
+// Unlike `RefLeaf`, this compares only `key`, so distinct nodes can be
+// equal.
+struct KeyNode<'a> {
+    key: usize,
+    next: Cell<Option<LeafNext<&'a KeyNode<'a>>>>,
+}
+
+impl KeyNode<'_> {
+    pub fn new(key: usize) -> Self {
+        Self {
+            key,
+            next: Cell::new(None),
+        }
+    }
+}
+
+impl fmt::Debug for KeyNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key)
+    }
+}
+
+impl PartialEq for KeyNode<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for KeyNode<'_> {}
+
+impl PartialOrd for KeyNode<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyNode<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+// SAFETY: `next` returns the value most recently passed to `set_next`,
+// and `&KeyNode` is neither `Send` nor `Sync` because of the `Cell`.
+unsafe impl<'a> LeafRef for &'a KeyNode<'a> {
+    type Options = skippy::Options<usize, true, 3>;
+
+    fn next(&self) -> Option<LeafNext<Self>> {
+        let next = self.next.take();
+        self.next.set(next.clone());
+        next
+    }
+
+    fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
+        this.next.set(next);
+    }
+
+    fn size(&self) -> usize {
+        1
+    }
+}
+
+skippy::assert_not_send_sync!(&KeyNode<'static>, &Leaf<'static>);
+
+impl Borrow<usize> for &KeyNode<'_> {
+    fn borrow(&self) -> &usize {
+        &self.key
+    }
+}
+
 struct Value<F> {
     value: usize,
     transformation: F,
@@ -200,6 +298,532 @@ fn remove() {
     assert!(list.iter().eq(refs.iter().copied()));
 }
 
+#[test]
+fn remove_range() {
+    let items: Vec<_> = (0..250).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut refs = Vec::from_iter(&items);
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    for (start, end) in [(10, 20), (0, 5), (100, 180), (30, 31), (0, 1)] {
+        let removed = list.remove_range(refs[start], refs[end - 1]);
+        assert!(removed.eq(refs.drain(start..end)));
+        assert!(list.iter().eq(refs.iter().copied()));
+        assert_eq!(list.size(), refs.len());
+    }
+    for (i, item) in refs.iter().enumerate() {
+        assert_eq!(list.get(&i), Some(*item));
+        assert_eq!(list.find(*item), Ok(*item));
+    }
+
+    let last = refs.len() - 1;
+    list.remove_range(refs[0], refs[last]).for_each(drop);
+    assert_eq!(list.first(), None);
+    list.push_back_from(&items[..10]);
+    assert!(list.iter().eq(&items[..10]));
+}
+
+#[test]
+fn reverse() {
+    let items: Vec<_> =
+        (0..250).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    list.reverse();
+    assert!(list.iter().eq(items.iter().rev()));
+    assert_eq!(list.size(), items.iter().map(|i| i.size.get()).sum());
+    let mut index = 0;
+    for item in items.iter().rev() {
+        assert_eq!(SkipList::index(item), index);
+        index += item.size.get();
+    }
+    list.reverse();
+    assert!(list.iter().eq(&items));
+}
+
+#[test]
+fn rotate() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut refs = Vec::from_iter(&items);
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for k in [0, 1, 2, 51, 198, 199, 70] {
+        list.rotate_left_by_size(&k);
+        refs.rotate_left(k / 2);
+        assert!(list.iter().eq(refs.iter().copied()));
+    }
+    list.rotate_left_by_size(&200);
+    assert!(list.iter().eq(refs.iter().copied()));
+    for (i, item) in refs.iter().enumerate() {
+        assert_eq!(SkipList::index(*item), i * 2);
+    }
+}
+
+#[test]
+fn split_off() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut rest = list.split_off(&items[60]);
+    assert!(list.iter().eq(&items[..60]));
+    assert!(rest.iter().eq(&items[60..]));
+    assert_eq!(list.size(), 120);
+    assert_eq!(rest.size(), 80);
+    assert_eq!(SkipList::index(&items[70]), 20);
+
+    let tail = rest.split_off(&items[60]);
+    assert!(rest.iter().next().is_none());
+    assert!(tail.iter().eq(&items[60..]));
+}
+
+#[test]
+fn retain() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let removed: Vec<_> =
+        list.extract_if(|item| item.value % 3 == 0).collect();
+    assert!(removed.iter().map(|i| i.value).eq((0..100).step_by(3)));
+    assert!(removed.iter().all(|i| SkipList::next(*i).is_none()));
+    list.retain(|item| item.value < 50);
+    let expected = (0..50).filter(|n| n % 3 != 0);
+    assert!(list.iter().map(|i| i.value).eq(expected.clone()));
+    assert_eq!(list.size(), expected.count() * 2);
+
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.retain(|item| {
+                assert!(item.value < 20);
+                item.value % 2 == 0
+            })
+        }));
+    assert!(result.is_err());
+    let expected = (0..50).filter(|n| n % 3 != 0 && (n % 2 == 0 || *n >= 20));
+    assert!(list.iter().map(|i| i.value).eq(expected));
+    list.retain(|_| false);
+    assert!(list.is_empty());
+}
+
+#[test]
+fn apply_sorted_batch() {
+    let items: Vec<_> = (0..300).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items[..100]);
+
+    // Small batch.
+    let results = list.apply_sorted_batch([
+        BatchOp::Insert {
+            after: None,
+            item: &items[100],
+        },
+        BatchOp::Update(
+            &items[10],
+            Box::new(|| items[10].size.set(5)) as Box<dyn FnOnce()>,
+        ),
+        BatchOp::Remove(&items[10]),
+        BatchOp::Insert {
+            after: Some(&items[10]),
+            item: &items[101],
+        },
+        BatchOp::Insert {
+            after: Some(&items[10]),
+            item: &items[102],
+        },
+        BatchOp::Remove(&items[5]),
+        BatchOp::Remove(&items[10]),
+        BatchOp::Insert {
+            after: Some(&items[11]),
+            item: &items[101],
+        },
+        BatchOp::Update(&items[20], Box::new(|| items[20].size.set(3))),
+    ]);
+    assert_eq!(results, [
+        Ok(()),
+        Ok(()),
+        Ok(()),
+        Ok(()),
+        Ok(()),
+        Err(Error::NotSorted),
+        Err(Error::NotInList),
+        Err(Error::AlreadyInList),
+        Ok(()),
+    ],);
+    let expected: Vec<_> = [100]
+        .into_iter()
+        .chain(0..10)
+        .chain([101, 102])
+        .chain(11..100)
+        .collect();
+    assert!(list.iter().map(|i| i.value).eq(expected.iter().copied()));
+    assert_eq!(list.size(), 102 + 2);
+
+    // Large batch, which rebuilds the list.
+    let ops = (0..100).step_by(2).filter(|n| *n != 10).flat_map(|n| {
+        [BatchOp::<_, fn()>::Remove(&items[n]), BatchOp::Insert {
+            after: Some(&items[n + 1]),
+            item: &items[200 + n],
+        }]
+    });
+    let results = list.apply_sorted_batch(ops);
+    assert!(results.iter().all(|r| r.is_ok()));
+    let mut new_expected = Vec::new();
+    for n in expected {
+        if n < 100 && n % 2 == 0 {
+            continue;
+        }
+        new_expected.push(n);
+        if n < 100 && n != 11 {
+            new_expected.push(199 + n);
+        }
+    }
+    assert!(list.iter().map(|i| i.value).eq(new_expected.iter().copied()));
+    assert_eq!(list.len(), new_expected.len());
+}
+
+#[test]
+fn apply_sorted_batch_validation() {
+    let items: Vec<_> =
+        (0..1200).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items[..1000]);
+    let mut other = SkipList::new();
+    other.push_back_from(&items[1000..1010]);
+
+    let mut ops = Vec::new();
+    let mut expected = Vec::new();
+    for n in (0..1000).step_by(7) {
+        ops.push(BatchOp::<_, fn()>::Remove(&items[n]));
+        expected.push(Ok(()));
+        if n % 5 == 0 {
+            // Items in another list, and items already inserted, are
+            // rejected without affecting later operations.
+            ops.push(BatchOp::Remove(&items[1000 + n % 10]));
+            expected.push(Err(Error::WrongList));
+            ops.push(BatchOp::Insert {
+                after: Some(&items[n + 1]),
+                item: &items[1000 + n % 10],
+            });
+            expected.push(Err(Error::AlreadyInList));
+        }
+        ops.push(BatchOp::Insert {
+            after: Some(&items[n + 1]),
+            item: &items[1010 + n / 7],
+        });
+        expected.push(Ok(()));
+        if n % 3 == 0 {
+            ops.push(BatchOp::Insert {
+                after: Some(&items[n + 2]),
+                item: &items[1010 + n / 7],
+            });
+            expected.push(Err(Error::AlreadyInList));
+        }
+    }
+    assert_eq!(list.apply_sorted_batch(ops), expected);
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(other.validate(), Ok(()));
+    assert!(other.iter().eq(&items[1000..1010]));
+
+    let mut values = Vec::new();
+    for n in 0..1000 {
+        if n % 7 == 0 {
+            continue;
+        }
+        values.push(n);
+        if n % 7 == 1 {
+            values.push(1010 + n / 7);
+        }
+    }
+    assert!(list.iter().map(|i| i.value).eq(values));
+}
+
+#[test]
+fn next_hint() {
+    let items: Vec<_> = (0..250).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut climbs = 0;
+    for pair in items.windows(2) {
+        match SkipList::next_hint(&pair[0]) {
+            NextHint::Sibling(next) => assert!(ptr::eq(next, &pair[1])),
+            NextHint::Climb => climbs += 1,
+        }
+    }
+    assert!(climbs > 0 && climbs < items.len() / 2);
+    let last = items.last().unwrap();
+    assert!(matches!(SkipList::next_hint(last), NextHint::Climb));
+}
+
+#[test]
+fn double_ended() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert!(list.iter().rev().eq(items.iter().rev()));
+
+    let mut iter = SkipList::iter_at(&items[10]);
+    let mut front = 10..100;
+    for i in 0..90 {
+        if i % 3 == 0 {
+            assert!(ptr::eq(
+                iter.next_back().unwrap(),
+                &items[front.next_back().unwrap()]
+            ));
+        } else {
+            assert!(ptr::eq(
+                iter.next().unwrap(),
+                &items[front.next().unwrap()]
+            ));
+        }
+    }
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+
+    let mut into_iter = list.into_iter();
+    assert!(ptr::eq(into_iter.next_back().unwrap(), &items[99]));
+    assert!(ptr::eq(into_iter.next().unwrap(), &items[0]));
+    assert!(into_iter.by_ref().rev().take(50).eq(items[49..99].iter().rev()));
+    assert!(into_iter.eq(&items[1..49]));
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert!(list.drain().rev().eq(items.iter().rev()));
+}
+
+#[test]
+fn iter_range() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert!(list.iter_range(&3, &9).eq(&items[1..5]));
+    assert!(list.iter_range(&4, &10).eq(&items[2..5]));
+    assert!(list.iter_range(&0, &1).eq(&items[..1]));
+    assert!(list.iter_range(&150, &1000).eq(&items[75..]));
+    assert!(list.iter_range(&5, &5).next().is_none());
+    assert!(list.iter_range(&200, &300).next().is_none());
+}
+
+#[test]
+fn len() {
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n, n % 3))).collect();
+    let mut list = SkipList::new();
+    assert_eq!(list.len(), 0);
+    assert!(list.is_empty());
+    list.push_back_from(&items);
+    assert_eq!(list.len(), 100);
+    for item in &items[20..40] {
+        list.remove(item);
+    }
+    assert_eq!(list.len(), 80);
+    assert_eq!(list.iter_exact().len(), 80);
+
+    let mut iter = list.iter_exact();
+    iter.next_back();
+    iter.next();
+    assert_eq!(iter.size_hint(), (78, Some(78)));
+
+    // `Iter` counts the remaining items once iteration from the back starts.
+    let mut iter = list.iter();
+    let mut count = 0;
+    while iter.next().is_some() {
+        count += 1 + usize::from(iter.next_back().is_some());
+    }
+    assert_eq!(count, 80);
+    let slice = list.slice(&items[50], &items[59]);
+    let mut iter = slice.iter();
+    assert_eq!(iter.len(), 10);
+    assert!(ptr::eq(iter.next_back().unwrap(), &items[59]));
+    assert_eq!(iter.len(), 9);
+    assert_eq!(list.remove_range(&items[40], &items[59]).len(), 20);
+    assert_eq!(list.len(), 60);
+    assert_eq!(list.into_iter().len(), 60);
+}
+
+#[test]
+fn iter_at_during_changes() {
+    let items: Vec<_> = (0..50).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let extra: Vec<_> = (50..60).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    // Iterators that don't borrow the list keep working when it changes.
+    let mut values = Vec::new();
+    for (i, item) in SkipList::iter_at(&items[0]).enumerate() {
+        values.push(item.value);
+        if i == 5 {
+            list.remove(&items[30]);
+            list.insert_after(&items[40], &extra[0]);
+            list.push_back_from(&extra[1..]);
+        }
+    }
+    let expected = (0..50)
+        .filter(|&n| n != 30)
+        .flat_map(|n| {
+            if n == 40 {
+                vec![40, 50]
+            } else {
+                vec![n]
+            }
+        })
+        .chain(51..60);
+    assert!(values.into_iter().eq(expected));
+
+    let mut iter = SkipList::iter_at(&items[10]);
+    list.remove(&items[49]);
+    assert!(ptr::eq(iter.next_back().unwrap(), &extra[9]));
+    assert!(ptr::eq(iter.next().unwrap(), &items[10]));
+}
+
+#[test]
+fn from_iter() {
+    let items: Vec<_> = (0..250).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list: SkipList<_> = items[..200].iter().collect();
+    assert!(list.iter().eq(&items[..200]));
+    assert_eq!(list.size(), 200);
+    assert_eq!(list.len(), 200);
+    for (i, item) in items[..200].iter().enumerate() {
+        assert_eq!(SkipList::index(item), i);
+        assert_eq!(list.find_with(&Value::new(i)).ok(), Some(item));
+    }
+    list.push_back_from(&items[200..]);
+    for item in &items[..100] {
+        list.remove(item);
+    }
+    assert!(list.iter().eq(&items[100..]));
+    drop(list);
+
+    let list: SkipList<_> = items[..1].iter().collect();
+    assert!(list.iter().eq(&items[..1]));
+    let list: SkipList<_> = items[..0].iter().collect();
+    assert!(list.is_empty());
+}
+
+#[test]
+fn large_sizes() {
+    use num_bigint::BigUint;
+    use std::ops::{AddAssign, SubAssign};
+
+    struct Sized<S>(S);
+
+    impl<S> BasicLeaf for Sized<S>
+    where
+        S: Clone + Default + Eq + AddAssign + SubAssign,
+    {
+        type Options = basic::options::Options<
+            /* SizeType */ S,
+            /* STORE_KEYS */ false,
+            /* FANOUT */ 4,
+        >;
+
+        fn size(&self) -> S {
+            self.0.clone()
+        }
+    }
+
+    fn check<S>(sizes: impl Iterator<Item = S>)
+    where
+        S: Clone + Default + Ord + AddAssign + SubAssign,
+    {
+        let items: Vec<_> = sizes.map(|s| RefLeaf::new(Sized(s))).collect();
+        let mut list = SkipList::new();
+        list.push_back_from(&items);
+        let mut offset = S::default();
+        for item in &items {
+            assert!(ptr::eq(list.get(&offset).unwrap(), item));
+            assert!(SkipList::index(item) == offset);
+            let start = list.first().unwrap();
+            let after = SkipList::get_after(start, &offset).unwrap();
+            assert!(ptr::eq(after, item));
+            offset += item.0.clone();
+        }
+        assert!(list.size() == offset);
+        assert!(list.get(&offset).is_none());
+    }
+
+    check((0..100_u32).map(|n| (u128::from(n) + 1) << 100));
+    check((0..100_u32).map(|n| (BigUint::from(n) + 1_u32) << 200));
+}
+
+#[test]
+fn get_by() {
+    use skippy::options::Dimension;
+    use std::ops::{AddAssign, SubAssign};
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    struct TextSize {
+        bytes: usize,
+        chars: usize,
+    }
+
+    impl AddAssign for TextSize {
+        fn add_assign(&mut self, rhs: Self) {
+            self.bytes += rhs.bytes;
+            self.chars += rhs.chars;
+        }
+    }
+
+    impl SubAssign for TextSize {
+        fn sub_assign(&mut self, rhs: Self) {
+            self.bytes -= rhs.bytes;
+            self.chars -= rhs.chars;
+        }
+    }
+
+    struct Bytes;
+    struct Chars;
+
+    impl Dimension<TextSize> for Bytes {
+        type Value = usize;
+
+        fn get(size: &TextSize) -> usize {
+            size.bytes
+        }
+    }
+
+    impl Dimension<TextSize> for Chars {
+        type Value = usize;
+
+        fn get(size: &TextSize) -> usize {
+            size.chars
+        }
+    }
+
+    struct Chunk(&'static str);
+
+    impl BasicLeaf for Chunk {
+        type Options = basic::options::Options<TextSize>;
+
+        fn size(&self) -> TextSize {
+            TextSize {
+                bytes: self.0.len(),
+                chars: self.0.chars().count(),
+            }
+        }
+    }
+
+    let chunks: Vec<_> = ["héllo", " ", "wörld", "!", "€€€"]
+        .into_iter()
+        .cycle()
+        .take(50)
+        .map(|s| RefLeaf::new(Chunk(s)))
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&chunks);
+    let text: String = chunks.iter().map(|c| c.0).collect();
+    for (char_index, (byte_index, _)) in text.char_indices().enumerate() {
+        let by_chars = list.get_by::<Chars>(&char_index).unwrap();
+        let by_bytes = list.get_by::<Bytes>(&byte_index).unwrap();
+        assert!(ptr::eq(by_chars, by_bytes));
+        let start = SkipList::index(by_chars);
+        assert!(start.chars <= char_index && start.bytes <= byte_index);
+        assert!(char_index < start.chars + by_chars.size().chars);
+    }
+    assert!(list.get_by::<Chars>(&list.size().chars).is_none());
+    let next = SkipList::get_after_by::<Bytes>(&chunks[0], &6).unwrap();
+    assert!(ptr::eq(next, &chunks[1]));
+    let next = SkipList::get_after_by::<Chars>(&chunks[0], &6).unwrap();
+    assert!(ptr::eq(next, &chunks[2]));
+}
+
 #[test]
 fn get_after() {
     let items: Vec<_> = (0..250).map(|n| Leaf::new(Data::new(n, 1))).collect();
@@ -239,6 +863,84 @@ fn find_after() {
     assert_eq!(find(250).unwrap_err().unwrap().value, 249);
 }
 
+#[test]
+fn remove_first_keys() {
+    let items: Vec<_> = (0..200).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    // Removing the first child of a node, and merging a node into its right
+    // sibling, must leave each node's cached key equal to its first item's.
+    let leading = items.iter().take(60).filter(|i| i.value % 3 != 0);
+    for item in items.iter().step_by(3).chain(leading) {
+        list.remove(item);
+        assert_eq!(list.validate(), Ok(()));
+    }
+    for v in 0..200 {
+        let found = list.find_with(&Value::new(v)).ok().map(|i| i.value);
+        assert_eq!(found, Some(v).filter(|v| *v >= 60 && v % 3 != 0));
+    }
+}
+
+#[test]
+fn rank() {
+    let items: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    // Removing leading items changes the first item of many nodes.
+    for item in items.iter().step_by(2).take(40) {
+        list.remove(item);
+    }
+    for v in 0..=200 {
+        let expected: usize = items
+            .iter()
+            .filter(|i| i.value < v && (i.value >= 80 || i.value % 2 == 1))
+            .map(|i| i.size())
+            .sum();
+        assert_eq!(list.rank_with_cmp(|item| item.value.cmp(&v)), expected);
+        if let Ok(item) = list.find_with(&Value::new(v)) {
+            assert_eq!(SkipList::index(item), expected);
+        }
+    }
+    for item in items.iter().step_by(2).take(40) {
+        list.insert(item).unwrap();
+    }
+    assert!(list.iter().eq(&items));
+}
+
+#[test]
+fn count_between() {
+    let nodes: Vec<_> = (0..90).map(|n| KeyNode::new(n / 3 * 2)).collect();
+    let mut list = SkipList::new();
+    assert_eq!(list.count_between(&0, &10), 0);
+    list.push_back_from(&nodes);
+    for lo in 0..=62 {
+        for hi in 0..=62 {
+            let expected =
+                nodes.iter().filter(|n| (lo..hi).contains(&n.key)).count();
+            assert_eq!(list.count_between(&lo, &hi), expected);
+        }
+    }
+}
+
+#[test]
+fn nearest_by_key() {
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n * 2, 1))).collect();
+    let mut list = SkipList::<&Leaf>::new();
+    let nearest = |list: &SkipList<&Leaf>, v: usize| {
+        let (prev, next) = list.nearest_with_cmp(|item| item.value.cmp(&v));
+        (prev.map(|i| i.value), next.map(|i| i.value))
+    };
+    assert_eq!(nearest(&list, 0), (None, None));
+    list.push_back_from(&items);
+    for v in 0_usize..=200 {
+        let prev = v.checked_sub(1).map(|v| v / 2 * 2);
+        let next = Some(v / 2 * 2 + 2).filter(|&n| n < 200);
+        assert_eq!(nearest(&list, v), (prev, next));
+    }
+}
+
 #[test]
 fn zero_sized() {
     let mut items = Vec::new();
@@ -300,7 +1002,102 @@ fn large_items() {
     assert_eq!(list.get(&300), None);
 }
 
-#[cfg(skippy_debug)]
+#[test]
+fn into_iter() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut iter = list.into_iter();
+    let mut other = SkipList::new();
+    other.push_back_from(iter.by_ref().take(40));
+    drop(iter);
+    assert!(other.iter().eq(&items[..40]));
+
+    let mut list = SkipList::new();
+    list.push_back_from(&items[40..]);
+    assert!(list.iter().eq(&items[40..]));
+    assert!(list.drain().eq(&items[40..]));
+    assert_eq!(list.first(), None);
+    list.push_back_from(items[40..].iter().rev());
+    assert!(list.iter().eq(items[40..].iter().rev()));
+}
+
+#[test]
+fn find_prefix() {
+    struct Word(&'static str);
+
+    impl BasicLeaf for Word {
+        type Options = basic::options::Options<
+            /* SizeType */ usize,
+            /* STORE_KEYS */ true,
+            /* FANOUT */ 4,
+        >;
+    }
+
+    let mut words = vec![
+        "a",
+        "ab",
+        "abc",
+        "abd",
+        "abz",
+        "b",
+        "ba",
+        "bab",
+        "c",
+        "ca",
+        "cab",
+    ];
+    words.extend(
+        (0..50).map(|n| &*Box::leak(format!("ab{n:02}").into_boxed_str())),
+    );
+    words.sort();
+    let items: Vec<_> = words.iter().map(|w| RefLeaf::new(Word(w))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    let find = |prefix: &str| {
+        list.find_prefix(prefix.as_bytes(), |w| w.0.as_bytes())
+            .map(|w| w.0)
+            .collect::<Vec<_>>()
+    };
+    let expected = |prefix: &str| -> Vec<_> {
+        words.iter().copied().filter(|w| w.starts_with(prefix)).collect()
+    };
+    for prefix in ["", "a", "ab", "ab1", "ab4", "abc", "b", "ba", "c", "d"] {
+        assert_eq!(find(prefix), expected(prefix));
+    }
+}
+
+#[test]
+fn cursor() {
+    let items: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n, n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut cursor = Cursor::new(list.first().unwrap());
+    for item in &items[1..] {
+        assert!(cursor.move_next());
+        assert_eq!(cursor.item().value, item.value);
+        assert_eq!(cursor.position(), SkipList::index(item));
+    }
+    assert!(!cursor.move_next());
+    for item in items[..199].iter().rev() {
+        assert!(cursor.move_prev());
+        assert_eq!(cursor.item().value, item.value);
+        assert_eq!(cursor.position(), SkipList::index(item));
+    }
+    assert!(!cursor.move_prev());
+    for i in [0, 5, 100, 50, 198, 197, 1, 150] {
+        assert!(cursor.seek(&list, &i));
+        let item = list.get(&i).unwrap();
+        assert_eq!(cursor.item().value, item.value);
+        assert_eq!(cursor.position(), SkipList::index(item));
+    }
+    assert!(!cursor.seek(&list, &(list.size() + 1)));
+    assert_eq!(cursor.item().value, list.get(&150).unwrap().value);
+}
+
+#[cfg(feature = "debug-graphviz")]
 #[allow(dead_code)]
 fn make_graph<L>(
     list: &SkipList<L>,
@@ -325,3 +1122,3133 @@ where
         .status()?;
     Ok(())
 }
+
+#[test]
+fn slab() {
+    struct Item(usize);
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<
+            /* SizeType */ usize,
+            /* STORE_KEYS */ false,
+            /* FANOUT */ 3,
+            /* SLAB_SIZE */ 4,
+        >;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    let items: Vec<_> = (0..200).map(|n| RefLeaf::new(Item(n))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for item in items.iter().step_by(3) {
+        list.remove(item);
+    }
+    let mut rest = list.split_off(list.get(&60).unwrap());
+    drop(list);
+    rest.reverse();
+    assert_eq!(rest.size(), 200 - 67 - 60);
+    let mut expected: Vec<_> = (0..200).filter(|n| n % 3 != 0).collect();
+    expected.drain(..60);
+    expected.reverse();
+    assert!(rest.iter().map(|i| i.0).eq(expected));
+
+    rest.remove_range(rest.first().unwrap(), rest.last().unwrap());
+    assert!(rest.iter().next().is_none());
+
+    // Blocks freed in any slab are reused before a new slab is allocated.
+    let allocs = Cell::new(0);
+    // SAFETY: The list is dropped before `allocs`.
+    let mut list = unsafe { SkipList::new_in_unchecked(Counting(&allocs)) };
+    list.push_back_from(&items);
+    let count = allocs.get();
+    let nodes = list.stats().internal_nodes;
+    for item in items[..100].iter().step_by(2) {
+        list.remove(item);
+    }
+    list.push_back_from(items[..100].iter().step_by(2));
+    let added = list.stats().internal_nodes.saturating_sub(nodes);
+    assert!(allocs.get() - count <= (added + 3) / 4);
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn content_hash() {
+    struct Item(Cell<usize>);
+
+    impl std::hash::Hash for Item {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.get().hash(state);
+        }
+    }
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<
+            /* SizeType */ usize,
+            /* STORE_KEYS */ false,
+            /* FANOUT */ 3,
+            /* SLAB_SIZE */ 1,
+            /* STORE_HASHES */ true,
+        >;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    let new = |n| RefLeaf::new(Item(Cell::new(n)));
+    let items1: Vec<_> = (0..100).map(new).collect();
+    let items2: Vec<_> = (0..100).map(new).collect();
+    let items3: Vec<_> = (0..100).map(new).collect();
+    let mut list1 = SkipList::new();
+    let mut list2 = SkipList::new();
+    let mut list3 = SkipList::new();
+    assert_eq!(list1.content_hash(), 0);
+    list1.push_back_from(&items1);
+    for item in items2.iter().rev() {
+        list2.push_front(item);
+    }
+    list3.push_back_from(&items3[50..]);
+    list3.push_front_from(&items3[..50]);
+
+    // Equal items have equal hashes, regardless of tree shape.
+    let hash = list1.content_hash();
+    assert_eq!(list2.content_hash(), hash);
+    assert_eq!(list3.content_hash(), hash);
+    assert_eq!(list1.subtree_hash(&[]), Some(hash));
+    assert_eq!(list1.subtree_hash(&[usize::MAX]), None);
+
+    // Find a changed item by comparing subtree hashes of replicas with the
+    // same shape.
+    let items4: Vec<_> = (0..100).map(new).collect();
+    let mut list4 = SkipList::new();
+    list4.push_back_from(&items4);
+    list4.update(&items4[37], || items4[37].0.set(1000));
+    assert_ne!(list4.content_hash(), hash);
+    let mut path = Vec::new();
+    while list1.subtree_hash(&[&path[..], &[0]].concat()).is_some() {
+        path.push(0);
+        while list1.subtree_hash(&path) == list4.subtree_hash(&path) {
+            *path.last_mut().unwrap() += 1;
+        }
+    }
+    let single = new(37);
+    let mut list5 = SkipList::new();
+    list5.push_back(&single);
+    assert_eq!(list1.subtree_hash(&path), Some(list5.content_hash()));
+    single.0.set(1000);
+    list5.update(&single, || ());
+    assert_eq!(list4.subtree_hash(&path), Some(list5.content_hash()));
+
+    list2.update(&items2[37], || items2[37].0.set(37));
+    assert_eq!(list2.content_hash(), hash);
+    list1.remove(&items1[50]);
+    list1.insert_before(&items1[51], &items1[50]);
+    assert_eq!(list1.content_hash(), hash);
+    list1.reverse();
+    assert_ne!(list1.content_hash(), hash);
+}
+
+#[test]
+fn leaf_align() {
+    #[repr(align(64))]
+    struct CacheLine;
+
+    let items: Vec<_> = (0..50)
+        .map(|n| RefLeaf::<_, CacheLine>::with_align(Data::new(n, 1)))
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for item in list.iter() {
+        assert_eq!(item as *const _ as usize % 64, 0);
+        assert!(ptr::eq(
+            &**item as *const Data as *const u8,
+            item as *const _ as _
+        ));
+    }
+    assert_eq!(
+        std::mem::align_of::<skippy::AllocItem<&RefLeaf<Data, CacheLine>>>(),
+        64
+    );
+    assert!(list.iter().map(|i| i.value).eq(0..50));
+
+    let rc = std::rc::Rc::new(basic::RcLeaf::<_, CacheLine>::with_align(
+        Data::new(0, 1),
+    ));
+    let mut list = SkipList::new();
+    list.push_back(rc.clone());
+    assert_eq!(std::rc::Rc::as_ptr(&rc) as usize % 64, 0);
+    assert_eq!(list.size(), 1);
+}
+
+#[test]
+fn error() {
+    fn fails() -> Result<(), Box<dyn std::error::Error>> {
+        Err(Error::NotInList)?;
+        Ok(())
+    }
+    assert_eq!(fails().unwrap_err().to_string(), "item is not in a list");
+    assert_eq!(Error::Alloc.to_string(), "memory allocation failed");
+}
+
+#[test]
+fn try_mutation() {
+    let items: Vec<_> = (0..20).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list1 = SkipList::new();
+    let mut list2 = SkipList::new();
+    assert_eq!(list1.try_remove(&items[0]), Err(Error::NotInList));
+    list1.push_back(&items[0]);
+    assert_eq!(list1.try_remove(&items[1]), Err(Error::NotInList));
+    assert_eq!(
+        list1.try_insert_after(&items[0], &items[0]),
+        Err(Error::AlreadyInList),
+    );
+    assert_eq!(
+        list1.try_insert_after(&items[1], &items[2]),
+        Err(Error::NotInList)
+    );
+    list1.try_insert_after(&items[0], &items[1]).unwrap();
+    list2.push_back_from(&items[10..]);
+    assert_eq!(list1.try_remove(&items[15]), Err(Error::WrongList));
+    assert_eq!(
+        list2.try_insert_after(&items[12], &items[1]),
+        Err(Error::AlreadyInList),
+    );
+    assert_eq!(
+        list1.try_replace(&items[12], &items[3]),
+        Err(Error::WrongList)
+    );
+    list1.try_replace(&items[1], &items[3]).unwrap();
+    assert!(list1.iter().map(|i| i.value).eq([0, 3]));
+    list2.try_remove(&items[15]).unwrap();
+    list1.try_remove(&items[0]).unwrap();
+    assert_eq!(list1.try_remove(&items[0]), Err(Error::NotInList));
+    assert_eq!(list1.try_remove(&items[12]), Err(Error::WrongList));
+    list1.try_remove(&items[3]).unwrap();
+    assert!(list1.is_empty());
+    assert_eq!(list2.len(), 9);
+}
+
+#[test]
+fn spill() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut checkpoint = SpillCheckpoint::new();
+    let mut spilled = Vec::new();
+    let result = list.spill(&mut checkpoint, 10, |_| Err("write failed"));
+    assert_eq!(result, Err("write failed"));
+    assert_eq!(checkpoint.index(), 0);
+    while list.spill(&mut checkpoint, 10, |buf| {
+        assert!(!buf.is_empty() && buf.len() <= 10);
+        spilled.push(buf.iter().map(|i| i.value).collect::<Vec<_>>());
+        Ok::<_, ()>(())
+    }) == Ok(true)
+    {}
+    assert_eq!(checkpoint.index(), 100);
+    assert!(spilled.iter().flatten().copied().eq(0..100));
+    assert!(spilled.len() < 100);
+
+    let mut checkpoint = SpillCheckpoint::at_index(95);
+    list.spill(&mut checkpoint, 0, |buf| {
+        assert_eq!(buf.len(), 1);
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+    assert_eq!(checkpoint.index(), 96);
+}
+
+#[test]
+fn replace_size() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let new: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n, 1 + n % 4))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut size = 100;
+    // Replace items both at the start of their parent nodes and after it.
+    for i in (0..100).step_by(3).chain([1, 98]) {
+        list.replace(&items[i], &new[i]);
+        size += new[i].size() - 1;
+        assert_eq!(list.size(), size);
+        let mut offset = 0;
+        for item in list.iter() {
+            assert_eq!(SkipList::index(item), offset);
+            assert_eq!(list.get(&offset), Some(item));
+            offset += item.size();
+        }
+    }
+}
+
+#[test]
+fn swap() {
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n, 1 + n % 3))).collect();
+    let new = Leaf::new(Data::new(100, 10));
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut expected: Vec<_> = items.iter().collect();
+    for (a, b) in [(0, 99), (10, 11), (11, 10), (40, 40), (0, 57), (3, 5)] {
+        list.swap(&items[a], &items[b]);
+        let i = expected.iter().position(|i| ptr::eq(*i, &items[a]));
+        let j = expected.iter().position(|i| ptr::eq(*i, &items[b]));
+        expected.swap(i.unwrap(), j.unwrap());
+        assert!(list.iter().eq(expected.iter().copied()));
+    }
+    list.replace(&items[50], &new);
+    let i = expected.iter().position(|i| ptr::eq(*i, &items[50]));
+    expected[i.unwrap()] = &new;
+    let mut offset = 0;
+    for item in &expected {
+        assert_eq!(list.get(&offset), Some(*item));
+        offset += item.size();
+    }
+}
+
+#[test]
+fn move_item() {
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n, 1 + n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut expected: Vec<_> = items.iter().collect();
+    for (item, pos, after) in [
+        (0, 1, true),
+        (5, 3, true),
+        (3, 4, true),
+        (7, 7, false),
+        (99, 0, false),
+        (1, 98, true),
+        (50, 20, false),
+    ] {
+        let (item, pos) = (&items[item], &items[pos]);
+        if after {
+            list.move_after(item, pos);
+        } else {
+            list.move_before(item, pos);
+        }
+        if !ptr::eq(item, pos) {
+            expected.retain(|i| !ptr::eq(*i, item));
+            let i = expected.iter().position(|i| ptr::eq(*i, pos)).unwrap();
+            expected.insert(i + after as usize, item);
+        }
+        assert!(list.iter().eq(expected.iter().copied()));
+    }
+    let mut offset = 0;
+    for item in &expected {
+        assert_eq!(list.get(&offset), Some(*item));
+        offset += item.size();
+    }
+}
+
+#[test]
+fn observer() {
+    use skippy::ListObserver;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Event {
+        Insert(usize),
+        Remove(usize),
+        Update(usize),
+        Reorder,
+    }
+
+    struct Recorder(Rc<RefCell<Vec<Event>>>);
+
+    impl ListObserver<&Leaf<'_>> for Recorder {
+        fn on_insert(&mut self, item: &&Leaf) {
+            self.0.borrow_mut().push(Event::Insert(item.value));
+        }
+
+        fn on_remove(&mut self, item: &&Leaf) {
+            self.0.borrow_mut().push(Event::Remove(item.value));
+        }
+
+        fn on_update(&mut self, item: &&Leaf) {
+            // The list is consistent when observers are called.
+            assert_eq!(SkipList::index(*item), item.value);
+            self.0.borrow_mut().push(Event::Update(item.value));
+        }
+
+        fn on_reorder(&mut self) {
+            self.0.borrow_mut().push(Event::Reorder);
+        }
+    }
+
+    use Event::*;
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let take = || events.borrow_mut().drain(..).collect::<Vec<_>>();
+    let items: Vec<_> = (0..10).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    let old = list.set_observer(Some(Box::new(Recorder(events.clone()))));
+    assert!(old.is_none());
+
+    list.push_back_from(&items[1..4]);
+    list.push_front(&items[0]);
+    assert_eq!(take(), [Insert(1), Insert(2), Insert(3), Insert(0)]);
+    list.insert_before(&items[3], &items[9]);
+    list.remove(&items[9]);
+    assert_eq!(take(), [Insert(9), Remove(9)]);
+    list.update(&items[2], || {});
+    assert_eq!(take(), [Update(2)]);
+    list.move_before(&items[3], &items[0]);
+    list.move_after(&items[3], &items[2]);
+    assert_eq!(take(), [Reorder, Reorder]);
+    list.push_back_from(&items[4..8]);
+    take();
+    assert!(
+        list.remove_range(&items[5], &items[6]).eq([&items[5], &items[6]])
+    );
+    assert_eq!(take(), [Remove(5), Remove(6)]);
+    list.replace(&items[7], &items[5]);
+    assert_eq!(take(), [Remove(7), Insert(5)]);
+    let other = list.split_off(&items[4]);
+    assert_eq!(take(), [Remove(4), Remove(5)]);
+    list.merge_sorted(other);
+    assert_eq!(take(), [Insert(4), Insert(5)]);
+    list.apply_sorted_batch::<_, fn()>([
+        BatchOp::Remove(&items[0]),
+        BatchOp::Insert {
+            after: Some(&items[5]),
+            item: &items[6],
+        },
+    ]);
+    assert_eq!(take(), [Remove(0), Insert(6)]);
+    assert!(list.drain().eq(&items[1..7]));
+    assert_eq!(take(), (1..7).map(Remove).collect::<Vec<_>>());
+
+    assert!(list.set_observer(None).is_some());
+    list.push_back(&items[0]);
+    assert!(take().is_empty());
+}
+
+#[test]
+fn handles() {
+    use skippy::basic::RcLeaf;
+    use std::rc::Rc;
+
+    let items: Vec<_> = (0..20).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let handles: Vec<_> = items.iter().map(|i| list.handle(i)).collect();
+    assert_eq!(list.handle(&items[3]), handles[3]);
+    for (item, handle) in items.iter().zip(&handles) {
+        assert!(ptr::eq(list.resolve(*handle).unwrap(), item));
+    }
+
+    // Moving items doesn't invalidate their handles.
+    list.reverse();
+    list.move_after(&items[0], &items[10]);
+    list.swap(&items[1], &items[2]);
+    assert!(ptr::eq(list.resolve(handles[0]).unwrap(), &items[0]));
+
+    // Removing them does, even if they're inserted again.
+    list.remove(&items[5]);
+    list.take_front(2).for_each(drop);
+    assert!(list.resolve(handles[5]).is_none());
+    assert!(list.resolve(handles[19]).is_none());
+    assert!(list.resolve(handles[18]).is_none());
+    list.push_back(&items[5]);
+    assert!(list.resolve(handles[5]).is_none());
+    let handle = list.handle(&items[5]);
+    assert_ne!(handle, handles[5]);
+    assert!(ptr::eq(list.resolve(handle).unwrap(), &items[5]));
+    let other = list.split_off(&items[5]);
+    assert!(list.resolve(handle).is_none());
+    assert!(other.resolve(handle).is_none());
+    assert!(ptr::eq(list.resolve(handles[17]).unwrap(), &items[17]));
+
+    // Freed leaves can't be reached through their handles.
+    let mut list = SkipList::new();
+    let item = Rc::new(RcLeaf::new(Data::new(0, 1)));
+    list.push_back(item.clone());
+    let handle = list.handle(item.clone());
+    list.replace(item.clone(), Rc::new(RcLeaf::new(Data::new(1, 1))));
+    assert!(list.resolve(handle).is_none());
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn purge_dead() {
+    use skippy::basic::RcLeaf;
+    use std::rc::Rc;
+
+    let mut items: Vec<_> =
+        (0..20).map(|n| Some(Rc::new(RcLeaf::new(Data::new(n, n))))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(items.iter().flatten().cloned());
+    assert_eq!(list.purge_dead(), 0);
+
+    let weak = Rc::downgrade(items[4].as_ref().unwrap());
+    let handle = list.handle(items[4].clone().unwrap());
+    let kept = list.handle(items[5].clone().unwrap());
+    for i in (0..20).filter(|i| i % 4 == 0) {
+        items[i] = None;
+    }
+    assert_eq!(list.len(), 20);
+    assert_eq!(list.purge_dead(), 5);
+    assert_eq!(weak.strong_count(), 0);
+    assert!(list.resolve(handle).is_none());
+    assert!(Rc::ptr_eq(
+        &list.resolve(kept).unwrap(),
+        items[5].as_ref().unwrap(),
+    ));
+    assert!(list.iter().map(|i| i.value).eq((0..20).filter(|i| i % 4 != 0)));
+    assert_eq!(list.size(), (0..20).filter(|i| i % 4 != 0).sum());
+    assert_eq!(list.purge_dead(), 0);
+
+    drop(items);
+    assert_eq!(list.purge_dead(), 15);
+    assert!(list.is_empty());
+}
+
+#[test]
+fn purge_dead_no_rebuild() {
+    use skippy::Interval;
+    use skippy::basic::RcLeaf;
+    use std::rc::Rc;
+
+    struct Span(usize);
+
+    impl BasicLeaf for Span {
+        type Options = basic::Options<usize, true, 4, 1, false, true, true>;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    impl Interval for Span {
+        type Point = usize;
+
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.0 + 100 - self.0 % 100
+        }
+    }
+
+    let mut items: Vec<_> =
+        (0..200).map(|n| Rc::new(RcLeaf::new(Span(n)))).collect();
+    let allocs = Cell::new(0);
+    // SAFETY: The list is dropped before `allocs`.
+    let mut list = unsafe { SkipList::new_in_unchecked(Counting(&allocs)) };
+    list.push_back_from(items.iter().cloned());
+    let handle = list.handle(items[1].clone());
+    // Populates the cached items in internal nodes.
+    assert_eq!(list.find_overlapping(&150).count(), 51);
+    let count = allocs.get();
+    assert_eq!(list.purge_dead(), 0);
+    assert_eq!(allocs.get(), count);
+    assert_eq!(list.find_overlapping(&150).count(), 51);
+
+    // The first item is referenced by the most internal nodes.
+    items.remove(0);
+    assert_eq!(list.purge_dead(), 1);
+    assert!(Rc::ptr_eq(&list.resolve(handle).unwrap(), &items[0]));
+    assert_eq!(list.len(), 199);
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.find_overlapping(&150).count(), 51);
+
+    // Dead items are unlinked in place rather than by rebuilding the list.
+    items.retain(|item| item.0 % 3 != 0);
+    assert_eq!(list.purge_dead(), 66);
+    assert_eq!(allocs.get(), count);
+    assert!(list.iter().map(|i| i.0).eq(items.iter().map(|i| i.0)));
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn sync_skip_list() {
+    use std::sync::Mutex;
+    use std::thread;
+
+    struct Item(usize);
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<usize>;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    let items: Vec<_> = (0..100).map(|n| RefLeaf::new(Item(n))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    // SAFETY: Until the list is unwrapped, the items are used only through
+    // the list, by the thread holding the mutex.
+    let list = Mutex::new(unsafe { SyncSkipList::new(list) });
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..10 {
+                    let mut list = list.lock().unwrap();
+                    let first = list.first().unwrap();
+                    let last = list.last().unwrap();
+                    list.move_after(first, last);
+                }
+            });
+        }
+    });
+    let list = list.into_inner().unwrap().into_inner();
+    assert!(list.iter().map(|i| i.0).eq((40..100).chain(0..40)));
+}
+
+#[test]
+// `Arc<ArcLeaf>` is intentionally not `Send` or `Sync`.
+#[allow(clippy::arc_with_non_send_sync)]
+fn arc_leaf() {
+    use skippy::basic::ArcLeaf;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    struct Item(usize);
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<usize>;
+
+        fn size(&self) -> usize {
+            self.0
+        }
+    }
+
+    // SAFETY: The list's items are only ever accessed through the list.
+    let list = Mutex::new(unsafe { SyncSkipList::new(SkipList::new()) });
+    thread::scope(|s| {
+        for t in 0..4 {
+            let list = &list;
+            s.spawn(move || {
+                for n in 0..25 {
+                    let item = Arc::new(ArcLeaf::new(Item(t * 25 + n)));
+                    let mut list = list.lock().unwrap();
+                    // SAFETY: No other clones of `item` exist.
+                    unsafe { list.get_mut() }.push_back(item);
+                }
+            });
+        }
+    });
+    let list = list.into_inner().unwrap();
+    assert_eq!(list.size(), (0..100).sum());
+    let mut values: Vec<_> = list.iter().map(|i| i.0).collect();
+    values.sort_unstable();
+    assert!(values.into_iter().eq(0..100));
+    // The last references to the leaves are dropped on another thread.
+    thread::spawn(move || drop(list)).join().unwrap();
+}
+
+#[test]
+fn freeze() {
+    use std::thread;
+
+    struct Item(usize);
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<usize>;
+
+        fn size(&self) -> usize {
+            self.0
+        }
+    }
+
+    let items: Vec<_> = (0..100).map(|n| RefLeaf::new(Item(n))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let list = list.freeze();
+    thread::scope(|s| {
+        for t in 0..4 {
+            let list = &list;
+            s.spawn(move || {
+                assert_eq!(list.len(), 100);
+                for n in (t + 1..100).step_by(4) {
+                    let index = n * (n - 1) / 2;
+                    assert_eq!(list.get(&index).unwrap().0, n);
+                }
+            });
+        }
+    });
+    let mut list = list.thaw();
+    list.remove(&items[0]);
+    assert!(list.iter().map(|i| i.0).eq(1..100));
+}
+
+#[test]
+fn freeze_dense() {
+    let new_items = || -> Vec<_> {
+        (0..150).map(|n| Leaf::new(Data::new(n * 2, n % 3))).collect()
+    };
+    let items = new_items();
+    let other = new_items();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let frozen = SkipList::from_iter(&other).freeze();
+    let dense = list.freeze_dense();
+    assert_eq!(dense.len(), 150);
+    assert_eq!(dense.size(), frozen.size());
+    assert!(dense.iter().eq(&items));
+    assert!(dense.as_slice().iter().copied().eq(&items));
+    for index in 0..=dense.size() + 1 {
+        let value = |item: Option<&Leaf>| item.map(|i| i.value);
+        assert_eq!(value(dense.get(&index)), value(frozen.get(&index)));
+    }
+    for v in 0..=300 {
+        let cmp = |item: &&Leaf| item.value.cmp(&v);
+        let value = |r: Result<&Leaf, Option<&Leaf>>| match r {
+            Ok(item) => Ok(item.value),
+            Err(prev) => Err(prev.map(|i| i.value)),
+        };
+        assert_eq!(
+            value(dense.find_with_cmp(cmp)),
+            value(frozen.find_with_cmp(cmp)),
+        );
+        assert_eq!(dense.rank_with_cmp(cmp), frozen.rank_with_cmp(cmp));
+    }
+
+    let empty = SkipList::<&Leaf>::new().freeze_dense();
+    assert!(empty.is_empty());
+    assert_eq!(empty.size(), 0);
+    assert!(empty.get(&0).is_none());
+    assert_eq!(empty.rank_with_cmp(|_| Ordering::Less), 0);
+
+    let mut list = dense.thaw();
+    list.remove(&items[0]);
+    assert_eq!(list.len(), 149);
+}
+
+#[test]
+fn box_leaf() {
+    use skippy::basic::BoxLeaf;
+
+    thread_local! {
+        static DROPPED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Item(usize);
+
+    impl Drop for Item {
+        fn drop(&mut self) {
+            DROPPED.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<usize>;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    // SAFETY: No clones of the leaves are used after they are freed.
+    let new_leaf = |n| unsafe { BoxLeaf::new(Item(n)) };
+    let mut list = SkipList::new();
+    list.push_back_from((0..100).map(new_leaf));
+    let removed = list.get(&10).unwrap();
+    list.remove(removed.clone());
+    // SAFETY: `removed` is no longer in the list and isn't used again.
+    assert_eq!(unsafe { BoxLeaf::into_inner(removed) }.0, 10);
+    assert_eq!(DROPPED.with(Cell::get), 1);
+
+    let mut values = list.into_values();
+    assert!(values.by_ref().take(20).map(|i| i.0).eq((0..10).chain(11..21)));
+    assert_eq!(DROPPED.with(Cell::get), 21);
+    drop(values);
+    assert_eq!(DROPPED.with(Cell::get), 100);
+
+    // Lists free their leaves when dropped.
+    let mut list = SkipList::new();
+    list.push_back_from((0..100).map(new_leaf));
+    assert_eq!(list.drop_policy(), DropPolicy::Release);
+    let tail = list.split_off(list.get(&50).unwrap());
+    assert_eq!(tail.drop_policy(), DropPolicy::Release);
+    drop(list);
+    assert_eq!(DROPPED.with(Cell::get), 150);
+    let mut iter = tail.into_iter();
+    let item = iter.next().unwrap();
+    drop(iter);
+    assert_eq!(DROPPED.with(Cell::get), 199);
+    // SAFETY: `item` was yielded by the iterator, so it wasn't freed.
+    assert_eq!(unsafe { BoxLeaf::into_inner(item) }.0, 50);
+    assert_eq!(DROPPED.with(Cell::get), 200);
+}
+
+#[test]
+fn resume() {
+    let items: Vec<_> = (0..50).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut iter = list.iter();
+    iter.by_ref().take(10).for_each(drop);
+    let token = iter.resume_token();
+    assert_eq!(token.index(), 10);
+    list.remove(&items[3]);
+    assert!(list.resume(token.clone()).map(|i| i.value).eq(10..50));
+
+    list.remove(&items[10]);
+    assert!(list.resume(token.clone()).map(|i| i.value).eq(12..50));
+    assert!(list.resume_sorted(token).map(|i| i.value).eq(11..50));
+
+    let mut iter = list.iter();
+    iter.by_ref().for_each(drop);
+    assert!(list.resume(iter.resume_token()).next().is_none());
+}
+
+#[test]
+fn leafref_conformance() {
+    let items: Vec<_> = (0..4).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut iter = items.iter();
+    skippy::testing::leafref_conformance(|| iter.next().unwrap());
+    skippy::testing::leafref_conformance(|| {
+        std::rc::Rc::new(basic::RcLeaf::new(Data::new(0, 1)))
+    });
+    #[allow(clippy::arc_with_non_send_sync)]
+    skippy::testing::leafref_conformance(|| {
+        std::sync::Arc::new(basic::ArcLeaf::new(Data::new(0, 1)))
+    });
+}
+
+#[test]
+fn rc_leaf_ownership() {
+    use std::rc::Rc;
+    let items: Vec<_> = (0..100)
+        .map(|n| Rc::new(basic::RcLeaf::new(Data::new(n, 1))))
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(items.iter().cloned());
+    for item in items.iter().step_by(2) {
+        list.remove(item.clone());
+    }
+    for _ in 0..3 {
+        assert!(list.iter().map(|i| i.value).eq((1..100).step_by(2)));
+    }
+    for item in items.iter().step_by(2) {
+        assert_eq!(Rc::strong_count(item), 1);
+    }
+    drop(list);
+    assert!(items.iter().all(|i| Rc::strong_count(i) == 1));
+}
+
+#[test]
+fn rc_leaf_next_refcount() {
+    use std::rc::Rc;
+    let items: Vec<_> = (0..20)
+        .map(|n| Rc::new(basic::RcLeaf::new(Data::new(n, 1))))
+        .collect();
+    let counts = || items.iter().map(Rc::strong_count).collect::<Vec<_>>();
+    let mut list = SkipList::new();
+    list.push_back_from(items.iter().cloned());
+
+    // Reading a leaf's successor must not release the reference the leaf
+    // holds to it.
+    let before = counts();
+    for _ in 0..5 {
+        assert!(list.iter().map(|i| i.value).eq(0..20));
+        assert!(list.iter().rev().map(|i| i.value).eq((0..20).rev()));
+    }
+    assert_eq!(counts(), before);
+
+    // Relinking a leaf must release the reference to its old successor.
+    for item in items.iter().skip(1) {
+        list.move_after(item.clone(), items[0].clone());
+    }
+    assert!(list.iter().map(|i| i.value).eq((0..1).chain((1..20).rev())));
+    list.remove(items[5].clone());
+    assert_eq!(Rc::strong_count(&items[5]), 1);
+    drop(list);
+    assert!(items.iter().all(|i| Rc::strong_count(i) == 1));
+}
+
+#[test]
+fn rc_leaf_list_drop() {
+    use skippy::basic::RcLeaf;
+    use std::rc::{Rc, Weak};
+
+    // Leaves hold strong references to their successors, which the list
+    // must release when it is dropped, even if nothing else refers to the
+    // leaves.
+    let new_list = |weak: &mut Vec<Weak<_>>| {
+        let mut list = SkipList::new();
+        list.push_back_from((0..100).map(|n| {
+            let item = Rc::new(RcLeaf::new(Data::new(n, 1)));
+            weak.push(Rc::downgrade(&item));
+            item
+        }));
+        list
+    };
+    let all_freed =
+        |weak: &[Weak<_>]| weak.iter().all(|w| w.strong_count() == 0);
+
+    let mut weak = Vec::new();
+    drop(new_list(&mut weak));
+    assert!(all_freed(&weak));
+
+    weak.clear();
+    let mut list = new_list(&mut weak);
+    // SAFETY: `Rc<RcLeaf>` uses the default `on_list_drop`, which does
+    // nothing.
+    unsafe {
+        list.set_drop_policy(DropPolicy::Release);
+    }
+    drop(list);
+    assert!(all_freed(&weak));
+
+    weak.clear();
+    let mut iter = new_list(&mut weak).into_iter();
+    iter.by_ref().take(10).for_each(drop);
+    drop(iter);
+    assert!(all_freed(&weak));
+
+    weak.clear();
+    let mut list = new_list(&mut weak);
+    list.drain().take(10).for_each(drop);
+    assert!(all_freed(&weak));
+}
+
+#[test]
+fn list_slice() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let slice = list.slice(&items[30], &items[59]);
+    assert_eq!(slice.start(), 60);
+    assert_eq!(slice.size(), 60);
+    assert_eq!(slice.len(), 30);
+    assert_eq!(slice.get(&0).unwrap().value, 30);
+    assert_eq!(slice.get(&5).unwrap().value, 32);
+    assert_eq!(slice.get(&59).unwrap().value, 59);
+    assert!(slice.get(&60).is_none());
+    assert_eq!(slice.index(&items[40]), 20);
+    assert!(slice.iter().map(|i| i.value).eq(30..60));
+
+    let slice = list.slice(&items[99], &items[99]);
+    assert_eq!(slice.len(), 1);
+    assert!(slice.get(&1).unwrap().value == 99);
+    assert!(slice.get(&2).is_none());
+}
+
+#[test]
+fn leaf_arena() {
+    let arena = basic::LeafArena::new();
+    let mut list =
+        SkipList::with_arena(&arena, (0..100).map(|n| Data::new(n, 1)));
+    assert_eq!(arena.len(), 100);
+    let more = arena.alloc_from((100..1000).map(|n| Data::new(n, 1)));
+    list.push_back_from(more.iter().copied());
+    assert_eq!(list.size(), 1000);
+    assert!(list.iter().map(|i| i.value).eq(0..1000));
+    let item = list.get(&500).unwrap();
+    assert!(std::ptr::eq(item, more[400]));
+    list.remove(item);
+    assert_eq!(list.len(), 999);
+    assert_eq!(arena.len(), 1000);
+}
+
+#[test]
+fn user_flag() {
+    use basic::{LeafExt, UserFlag};
+    use std::mem::align_of;
+
+    // The flag is opt-in, so the default layout is unchanged.
+    let ptr_align = align_of::<*const ()>();
+    assert_eq!(align_of::<RefLeaf<u8>>(), ptr_align.max(2));
+    assert_eq!(align_of::<RefLeaf<u8, UserFlag>>(), ptr_align.max(4));
+
+    let items: Vec<_> = (0..200)
+        .map(|n| RefLeaf::<_, UserFlag>::with_align(Data::new(n, 1)))
+        .collect();
+    for item in items.iter().step_by(3) {
+        item.set_user_flag(true);
+    }
+    let mut list = SkipList::new();
+    list.push_back_from(items.iter().step_by(2));
+    list.insert_after_from(&items[0], items.iter().skip(1).step_by(2));
+    let expected =
+        [0].into_iter().chain((1..200).step_by(2)).chain((2..200).step_by(2));
+    assert!(list.iter().map(|i| i.value).eq(expected));
+    for (i, item) in items.iter().enumerate() {
+        assert_eq!(item.user_flag(), i % 3 == 0);
+        item.set_user_flag(i % 5 == 0);
+    }
+    assert_eq!(list.len(), 200);
+    assert_eq!(list.size(), 200);
+    for item in items.iter().take(100) {
+        list.remove(item);
+    }
+    assert!(
+        list.iter()
+            .map(|i| i.value)
+            .eq((101..200).step_by(2).chain((100..200).step_by(2)))
+    );
+    assert!(list.iter().all(|i| i.user_flag() == (i.value % 5 == 0)));
+    assert!(items.iter().all(|i| i.user_flag() == (i.value % 5 == 0)));
+
+    let new_rc = |n| {
+        let leaf = basic::RcLeaf::<_, UserFlag>::with_align(Data::new(n, 1));
+        std::rc::Rc::new(leaf)
+    };
+    let leaf = new_rc(0);
+    leaf.set_user_flag(true);
+    let mut list = SkipList::new();
+    list.push_back(leaf.clone());
+    list.push_back(new_rc(1));
+    assert!(leaf.user_flag());
+    assert!(list.iter().map(|i| i.value).eq(0..2));
+}
+
+#[test]
+fn update_guard() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    let guard = list.update_guard(&items[40]);
+    assert_eq!(*guard.old_size(), 1);
+    guard.size.set(5);
+    let old = guard.size.replace(10);
+    assert_eq!(old, 5);
+    drop(guard);
+    assert_eq!(list.size(), 109);
+    assert_eq!(list.get(&49).unwrap().value, 40);
+    assert_eq!(list.get(&50).unwrap().value, 41);
+
+    for item in &items[..10] {
+        let guard = list.update_guard(item);
+        guard.size.set(0);
+        assert_eq!(guard.finish().value, item.value);
+    }
+    assert_eq!(list.size(), 99);
+    assert_eq!(list.get(&0).unwrap().value, 10);
+
+    let mut guard = list.update_guard(&items[50]);
+    guard.size.set(20);
+    assert_eq!(guard.get_consistent(&49).unwrap().value, 50);
+    assert_eq!(guard.get_consistent(&68).unwrap().value, 50);
+    assert_eq!(guard.get_consistent(&69).unwrap().value, 51);
+    assert_eq!(*guard.old_size(), 20);
+    guard.size.set(2);
+    guard.flush();
+    assert_eq!(*guard.old_size(), 2);
+    drop(guard);
+    assert_eq!(list.size(), 100);
+    assert_eq!(list.get(&51).unwrap().value, 51);
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn update_many() {
+    let items: Vec<_> = (0..500).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    list.update_many(&items[100..300], |item| item.size.set(2));
+    assert_eq!(list.size(), 700);
+    list.update_many(
+        items.iter().step_by(7).chain(items.iter().rev().step_by(11)),
+        |item| item.size.set(item.size.get() + 3),
+    );
+    let mut index = 0;
+    for item in &items {
+        assert_eq!(SkipList::index(item), index);
+        assert!(ptr::eq(list.get(&index).unwrap(), item));
+        index += item.size.get();
+    }
+    assert_eq!(list.size(), index);
+    list.update_many(&items, |item| item.size.set(0));
+    assert_eq!(list.size(), 0);
+}
+
+#[test]
+fn rebuild() {
+    struct Node<'a> {
+        value: usize,
+        next: Cell<Option<LeafNext<&'a Node<'a>>>>,
+    }
+
+    // SAFETY: `next` returns the value most recently passed to `set_next`,
+    // and `&Node` is neither `Send` nor `Sync` because of the `Cell`.
+    unsafe impl<'a> LeafRef for &'a Node<'a> {
+        type Options = skippy::Options<usize, false, 3>;
+
+        fn next(&self) -> Option<LeafNext<Self>> {
+            let next = self.next.take();
+            self.next.set(next.clone());
+            next
+        }
+
+        fn set_next(this: This<&'_ Self>, next: Option<LeafNext<Self>>) {
+            this.next.set(next);
+        }
+
+        fn size(&self) -> usize {
+            self.value
+        }
+    }
+
+    let nodes: Vec<_> = (0..100)
+        .map(|value| Node {
+            value,
+            next: Cell::new(None),
+        })
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&nodes[..60]);
+
+    // Relink every node in reverse order.
+    for pair in nodes.windows(2) {
+        pair[1].next.set(Some(LeafNext::Leaf(&pair[0])));
+    }
+    nodes[0].next.set(None);
+    // SAFETY: The nodes form a chain of 100 leaves.
+    unsafe {
+        list.rebuild(&nodes[99], None);
+    }
+    assert_eq!(list.len(), 100);
+    assert_eq!(list.size(), 4950);
+    assert!(list.iter().map(|n| n.value).eq((0..100).rev()));
+    let mut index = 0;
+    for node in nodes.iter().rev() {
+        assert_eq!(SkipList::index(node), index);
+        assert!(ptr::eq(list.get(&index).unwrap(), node));
+        index += node.value;
+    }
+
+    drop(list);
+    let mut list = SkipList::new();
+    for pair in nodes.windows(2) {
+        pair[0].next.set(Some(LeafNext::Leaf(&pair[1])));
+    }
+    nodes[99].next.set(None);
+    // SAFETY: The nodes form a chain of 100 leaves.
+    unsafe {
+        list.rebuild(&nodes[0], Some(100));
+    }
+    assert!(list.iter().map(|n| n.value).eq(0..100));
+    list.remove(&nodes[50]);
+    assert_eq!(list.size(), 4900);
+}
+
+#[test]
+fn insert_at() {
+    let sizes = [2, 0, 3, 0, 0, 1, 4, 0, 1, 1, 0, 2, 0, 0, 3];
+    let total: usize = sizes.iter().sum();
+    for index in 0..=total {
+        let items: Vec<_> = sizes
+            .iter()
+            .enumerate()
+            .map(|(n, size)| Leaf::new(Data::new(n, *size)))
+            .collect();
+        let new = Leaf::new(Data::new(100, 1));
+        let mut list = SkipList::new();
+        list.push_back_from(&items);
+        list.insert_at(&index, &new);
+
+        let mut start = 0;
+        let expected = sizes
+            .iter()
+            .take_while(|size| {
+                let before = start < index;
+                start += *size;
+                before
+            })
+            .count();
+        let values: Vec<_> = list.iter().map(|i| i.value).collect();
+        assert_eq!(values.iter().position(|v| *v == 100), Some(expected));
+        assert!(SkipList::index(&new) >= index);
+        assert_eq!(list.size(), total + 1);
+    }
+
+    let items: Vec<_> = (0..3).map(|n| Leaf::new(Data::new(n, 0))).collect();
+    let mut list = SkipList::new();
+    list.insert_at(&0, &items[0]);
+    list.insert_at(&0, &items[1]);
+    list.insert_at(&0, &items[2]);
+    assert!(list.iter().map(|i| i.value).eq([2, 1, 0]));
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn insert_at_out_of_bounds() {
+    let item = Leaf::new(Data::new(0, 1));
+    let new = Leaf::new(Data::new(1, 1));
+    let mut list = SkipList::new();
+    list.push_back(&item);
+    list.insert_at(&2, &new);
+}
+
+#[test]
+fn stable_partition_in_place() {
+    let items: Vec<_> = (0..200).map(|n| Leaf::new(Data::new(n, n))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let count = list.stable_partition_in_place(|i| i.value % 3 == 0);
+    assert_eq!(count, 67);
+    let expected: Vec<_> = (0..200)
+        .filter(|n| n % 3 == 0)
+        .chain((0..200).filter(|n| n % 3 != 0))
+        .collect();
+    assert!(list.iter().map(|i| i.value).eq(expected.iter().copied()));
+    let mut index = 0;
+    for n in &expected {
+        assert_eq!(SkipList::index(&items[*n]), index);
+        index += n;
+    }
+    assert_eq!(list.size(), index);
+
+    assert_eq!(list.stable_partition_in_place(|_| false), 0);
+    assert!(list.iter().map(|i| i.value).eq(expected.iter().copied()));
+
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.stable_partition_in_place(|i| {
+                assert!(i.value != 7, "panicked");
+                i.value % 2 == 0
+            })
+        }));
+    assert!(result.is_err());
+    let tested = &expected[..expected.iter().position(|n| *n == 7).unwrap()];
+    let expected: Vec<_> = tested
+        .iter()
+        .filter(|n| *n % 2 == 0)
+        .chain(tested.iter().filter(|n| *n % 2 != 0))
+        .chain(&expected[tested.len()..])
+        .copied()
+        .collect();
+    assert!(list.iter().map(|i| i.value).eq(expected));
+    assert_eq!(list.len(), 200);
+}
+
+#[test]
+fn remove_at() {
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n, n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut expected: Vec<_> = (0..100).collect();
+    let mut offset = 17;
+    while list.size() > 0 {
+        offset = (offset * 7 + 3) % list.size();
+        let get = list.get(&offset).unwrap();
+        let removed = list.remove_at(&offset).unwrap();
+        assert!(ptr::eq(get, removed));
+        expected.retain(|n| *n != removed.value);
+        assert!(list.iter().map(|i| i.value).eq(expected.iter().copied()));
+        list.validate().unwrap();
+    }
+    assert!(list.remove_at(&1).is_none());
+    assert_eq!(list.len(), 34);
+    assert!(list.iter().all(|i| i.size.get() == 0));
+    // With only zero-sized items left, index 0 refers to the last item.
+    let last = list.last().unwrap();
+    assert!(ptr::eq(list.remove_at(&0).unwrap(), last));
+    assert_eq!(list.len(), 33);
+}
+
+#[test]
+fn mapped_list() {
+    use skippy::mapped::{self, Decode, Encode, MappedList};
+
+    struct Codec;
+
+    impl Encode<&Leaf<'_>> for Codec {
+        fn encode(&self, item: &&Leaf<'_>, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(item.value as u32).to_le_bytes());
+        }
+
+        fn size(&self, item: &&Leaf<'_>) -> u64 {
+            item.size.get() as u64
+        }
+    }
+
+    impl Decode for Codec {
+        type Value<'a> = usize;
+
+        fn decode(&self, bytes: &[u8]) -> usize {
+            u32::from_le_bytes(bytes.try_into().unwrap()) as usize
+        }
+    }
+
+    let items: Vec<_> =
+        (0..100).map(|n| Leaf::new(Data::new(n * 2, n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut bytes = Vec::new();
+    mapped::encode(&list, &Codec, &mut bytes);
+
+    let mapped = MappedList::new(&bytes, Codec).unwrap();
+    assert_eq!(mapped.len(), 100);
+    assert_eq!(mapped.size(), list.size() as u64);
+    assert!(mapped.iter().eq(list.iter().map(|i| i.value)));
+    assert!(mapped.iter().rev().eq(list.iter().rev().map(|i| i.value)));
+    for index in 0..=list.size() + 1 {
+        let expected = list.get(&index).map(|i| i.value);
+        assert_eq!(mapped.get(index as u64), expected);
+    }
+    for (position, item) in list.iter().enumerate() {
+        assert_eq!(mapped.index(position), SkipList::index(item) as u64);
+    }
+    assert_eq!(mapped.find(&84), Ok(84));
+    assert_eq!(mapped.find(&85), Err(Some(84)));
+    assert_eq!(mapped.find(&0), Ok(0));
+    assert_eq!(mapped.find_with_cmp(|v| v.cmp(&usize::MAX)), Err(Some(198)));
+    assert_eq!(mapped.find_with_cmp(|_| Ordering::Greater), Err(None));
+
+    let raw = MappedList::new(&bytes, mapped::RawBytes).unwrap();
+    assert_eq!(raw.nth(3), Some(&6_u32.to_le_bytes()[..]));
+    assert_eq!(raw.nth(100), None);
+
+    let empty: [&Leaf<'_>; 0] = [];
+    let mut empty_bytes = Vec::new();
+    mapped::encode(empty, &Codec, &mut empty_bytes);
+    let empty = MappedList::new(&empty_bytes, Codec).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.get(0), None);
+    assert_eq!(empty.find(&0), Err(None));
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert_eq!(
+        MappedList::new(truncated, Codec).err(),
+        Some(Error::InvalidFormat),
+    );
+    assert_eq!(
+        MappedList::new(&bytes[1..], Codec).err(),
+        Some(Error::InvalidFormat),
+    );
+}
+
+#[test]
+fn persist() {
+    use skippy::basic::LeafArena;
+    use skippy::persist::{self, LeafCodec};
+
+    struct Codec<'a>(&'a LeafArena<'a, Data>);
+
+    impl<'a> LeafCodec<&'a Leaf<'a>> for Codec<'a> {
+        fn encode(&self, item: &&'a Leaf<'a>, out: &mut Vec<u8>) {
+            out.extend_from_slice(&(item.value as u32).to_le_bytes());
+            out.push(item.size.get() as u8);
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<&'a Leaf<'a>, Error> {
+            let (value, size) = match bytes {
+                &[a, b, c, d, size] => {
+                    (u32::from_le_bytes([a, b, c, d]), size)
+                }
+                _ => return Err(Error::InvalidFormat),
+            };
+            if value == 999 {
+                return Err(Error::NotSorted);
+            }
+            Ok(self.0.alloc(Data::new(value as usize, size as usize)))
+        }
+    }
+
+    let arena = LeafArena::new();
+    let codec = Codec(&arena);
+    let items: Vec<_> =
+        (0..500).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for item in items.iter().step_by(3) {
+        list.remove(item);
+    }
+    for item in items.iter().step_by(6) {
+        list.insert_at(&(item.value / 2), item);
+    }
+
+    let mut bytes = Vec::new();
+    persist::save(&list, &codec, &mut bytes);
+    let loaded = persist::load(&bytes, &codec).unwrap();
+    assert_eq!(loaded.validate(), Ok(()));
+    assert_eq!(loaded.size(), list.size());
+    assert_eq!(loaded.stats(), list.stats());
+    assert!(loaded.iter().map(|i| &**i).eq(list.iter().map(|i| &**i)));
+    // Saving the loaded list again produces the same shape.
+    let mut resaved = Vec::new();
+    persist::save(&loaded, &codec, &mut resaved);
+    assert_eq!(resaved, bytes);
+
+    for len in [0, 1] {
+        let small =
+            SkipList::with_arena(&arena, (0..len).map(|n| Data::new(n, 1)));
+        let mut bytes = Vec::new();
+        persist::save(&small, &codec, &mut bytes);
+        let loaded = persist::load(&bytes, &codec).unwrap();
+        assert_eq!(loaded.validate(), Ok(()));
+        assert_eq!(loaded.len(), len);
+    }
+
+    let load = |bytes: &[u8]| persist::load(bytes, &codec).err();
+    assert_eq!(load(&bytes[..bytes.len() - 1]), Some(Error::InvalidFormat));
+    assert_eq!(
+        load(&[bytes.as_slice(), &[0]].concat()),
+        Some(Error::InvalidFormat)
+    );
+    assert_eq!(load(b"SKIPPYM1"), Some(Error::InvalidFormat));
+    let mut bad = bytes.clone();
+    bad[8] = 8;
+    assert_eq!(load(&bad), Some(Error::InvalidFormat));
+    let mut bad = bytes.clone();
+    bad[24] = 1;
+    assert_eq!(load(&bad), Some(Error::InvalidFormat));
+
+    // Errors from the codec are returned unchanged.
+    let bad = SkipList::with_arena(&arena, [Data::new(1, 1), Data::new(2, 2)]);
+    let mut bytes = Vec::new();
+    persist::save(&bad, &codec, &mut bytes);
+    let before = arena.len();
+    let marker = bytes.len() - 5;
+    bytes[marker..marker + 4].copy_from_slice(&999_u32.to_le_bytes());
+    assert_eq!(load(&bytes), Some(Error::NotSorted));
+    assert_eq!(arena.len(), before + 1);
+}
+
+#[test]
+fn insert_with_policy() {
+    let nodes: Vec<_> = (0..60).map(|n| KeyNode::new((n * 7) % 5)).collect();
+    let extra = KeyNode::new(3);
+    let mut list = SkipList::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let policy = if i % 2 == 0 {
+            DuplicatePolicy::After
+        } else {
+            DuplicatePolicy::Before
+        };
+        list.insert_with_policy(node, policy).unwrap();
+    }
+    assert_eq!(list.len(), 60);
+    assert!(list.iter().map(|n| n.key).eq((0..5).flat_map(|k| [k; 12])));
+
+    for key in 0..5 {
+        // Nodes inserted with `Before` appear in reverse insertion order,
+        // followed by nodes inserted with `After` in insertion order.
+        let positions: Vec<_> =
+            (0..60).filter(|n| (n * 7) % 5 == key).collect();
+        let expected: Vec<_> = positions
+            .iter()
+            .rev()
+            .filter(|n| *n % 2 == 1)
+            .chain(positions.iter().filter(|n| *n % 2 == 0))
+            .map(|n| &nodes[*n])
+            .collect();
+        let run: Vec<_> = list.iter().filter(|n| n.key == key).collect();
+        assert_eq!(run.len(), expected.len());
+        assert!(run.iter().zip(&expected).all(|(a, b)| ptr::eq(*a, *b)));
+
+        let first = list.find_first(expected[0]).unwrap();
+        let last = list.find_last(expected[0]).unwrap();
+        assert!(ptr::eq(first, expected[0]));
+        assert!(ptr::eq(last, *expected.last().unwrap()));
+    }
+
+    let cmp = |key| move |n: &&KeyNode| n.key.cmp(&key);
+    assert_eq!(list.find_first_with_cmp(cmp(0)).unwrap().key, 0);
+    assert!(list.find_first_with_cmp(|_| Ordering::Greater) == Err(None));
+    let prev = list.find_last_with_cmp(|_| Ordering::Less).unwrap_err();
+    assert!(ptr::eq(prev.unwrap(), list.last().unwrap()));
+    assert!(list.find_first_with_cmp(cmp(5)).is_err());
+
+    list.remove(&nodes[1]);
+    list.remove(&nodes[6]);
+    let prev =
+        list.find_first_with_cmp(|n| n.key.cmp(&2).then(Ordering::Greater));
+    assert_eq!(prev.unwrap_err().unwrap().key, 1);
+    assert!(list.insert_with_policy(&extra, DuplicatePolicy::Reject).is_err());
+    assert_eq!(list.len(), 58);
+}
+
+#[test]
+fn lower_upper_bound() {
+    let values: Vec<_> = (0..80).map(|n| n / 3 * 2).collect();
+    let items: Vec<_> =
+        values.iter().map(|n| Leaf::new(Data::new(*n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for key in 0..=values.last().unwrap() + 2 {
+        let lower = list.lower_bound_with_cmp(|i| i.value.cmp(&key));
+        let upper = list.upper_bound_with_cmp(|i| i.value.cmp(&key));
+        let expected_lower = values.iter().position(|v| *v >= key);
+        let expected_upper = values.iter().position(|v| *v > key);
+        assert_eq!(
+            lower.map(|i| i as *const _),
+            expected_lower.map(|p| &items[p] as *const _)
+        );
+        assert_eq!(
+            upper.map(|i| i as *const _),
+            expected_upper.map(|p| &items[p] as *const _)
+        );
+    }
+    let empty = SkipList::<&Leaf>::new();
+    assert!(empty.lower_bound_with_cmp(|_| Ordering::Less).is_none());
+    assert!(empty.upper_bound_with_cmp(|_| Ordering::Less).is_none());
+}
+
+#[test]
+fn budgeted_search() {
+    let items: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n * 2, n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    // Aborts every `budget` comparisons.
+    let limited = |budget: usize| {
+        let calls = Cell::new(0);
+        move |ord: Ordering| {
+            calls.set(calls.get() + 1);
+            if calls.get() % budget == 0 {
+                return ControlFlow::Break(calls.get());
+            }
+            ControlFlow::Continue(ord)
+        }
+    };
+
+    for budget in [2, 3, 7, 1000] {
+        for index in 0..=list.size() + 1 {
+            let step = limited(budget);
+            let cmp = |size: &usize| step(size.cmp(&index));
+            let mut result = list.get_with_cmp_budgeted(cmp);
+            let mut aborts = 0;
+            let found = loop {
+                match result {
+                    ControlFlow::Continue(found) => break found,
+                    ControlFlow::Break(partial) => {
+                        assert_eq!(*partial.reason(), (aborts + 1) * budget);
+                        aborts += 1;
+                        result = partial.resume(cmp);
+                    }
+                }
+            };
+            let expected = list.get(&index);
+            assert_eq!(
+                found.map(|i| i as *const _),
+                expected.map(|i| i as *const _)
+            );
+        }
+
+        for key in 0..=400 {
+            let step = limited(budget);
+            let cmp = |item: &&Leaf| step(item.value.cmp(&key));
+            let mut result = list.find_with_cmp_budgeted(cmp);
+            let found = loop {
+                match result {
+                    ControlFlow::Continue(found) => break found,
+                    ControlFlow::Break(partial) => {
+                        result = partial.resume(cmp);
+                    }
+                }
+            };
+            let expected = list.find_with_cmp(|i| i.value.cmp(&key));
+            assert_eq!(found, expected);
+        }
+    }
+
+    let partial = match list
+        .find_with_cmp_budgeted(|_| ControlFlow::Break("deadline"))
+    {
+        ControlFlow::Break(partial) => partial,
+        ControlFlow::Continue(_) => panic!("search was not aborted"),
+    };
+    assert!(ptr::eq(partial.list(), &list));
+    assert_eq!(partial.into_reason(), "deadline");
+
+    let empty = SkipList::<&Leaf>::new();
+    let result = empty.get_with_cmp_budgeted(|_| ControlFlow::Break(()));
+    assert!(matches!(result, ControlFlow::Continue(None)));
+}
+
+#[test]
+fn finger() {
+    let items: Vec<_> =
+        (0..500).map(|n| Leaf::new(Data::new(n, n % 3))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+
+    let mut finger = list.finger();
+    assert!(ptr::eq(finger.list(), &list));
+    let indices = (0..=list.size() + 1)
+        .chain((0..list.size()).rev())
+        .chain((0..200).map(|n| n * 37 % (list.size() + 2)));
+    for index in indices {
+        let found = finger.get(&index).map(|i| i as *const _);
+        assert_eq!(found, list.get(&index).map(|i| i as *const _));
+    }
+
+    // Searches near the previous one take fewer comparisons.
+    let calls = Cell::new(0);
+    let counted = |index: usize| {
+        let calls = &calls;
+        move |size: &usize| {
+            calls.set(calls.get() + 1);
+            size.cmp(&index)
+        }
+    };
+    let mut finger = list.finger();
+    for index in 0..list.size() {
+        finger.get_with_cmp(counted(index));
+    }
+    let near = calls.replace(0);
+    for index in 0..list.size() {
+        list.get_with_cmp(counted(index));
+    }
+    assert!(near * 2 < calls.get());
+
+    let empty = SkipList::<&Leaf>::new();
+    assert!(empty.finger().get(&0).is_none());
+}
+
+#[test]
+fn empty_policy() {
+    use basic::options::BasicOptions;
+    use skippy::options::{EmptyPolicy, EmptyPolicyType};
+
+    struct Item(usize);
+
+    impl BasicLeaf for Item {
+        type Options =
+            basic::options::Options<usize, false, 3, 1, false, false>;
+
+        fn size(&self) -> usize {
+            self.0
+        }
+    }
+
+    let policy =
+        <<Item as BasicLeaf>::Options as BasicOptions>::EmptyPolicy::POLICY;
+    assert_eq!(policy, EmptyPolicy::ReturnNone);
+    let empty = SkipList::<&RefLeaf<Item>>::new();
+    assert!(empty.get(&0).is_none());
+
+    let zeros: Vec<_> = (0..20).map(|_| RefLeaf::new(Item(0))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&zeros);
+    assert!(list.get(&0).is_none());
+    assert!(SkipList::get_after(list.first().unwrap(), &0).is_none());
+    assert!(list.remove_at(&0).is_none());
+    assert_eq!(list.len(), 20);
+
+    // With the default policy, the last zero-sized item is returned.
+    let data: Vec<_> = (0..20).map(|n| Leaf::new(Data::new(n, 0))).collect();
+    let mut default = SkipList::new();
+    assert!(default.get(&0).is_none());
+    default.push_back_from(&data);
+    assert!(ptr::eq(default.get(&0).unwrap(), &data[19]));
+    let first = default.first().unwrap();
+    assert!(ptr::eq(SkipList::get_after(first, &0).unwrap(), &data[19]));
+
+    // Other indices are unaffected.
+    let items: Vec<_> =
+        (0..50).map(|n| RefLeaf::new(Item((n % 3 == 0) as usize))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for index in 0..list.size() {
+        let item = list.get(&index).unwrap();
+        assert_eq!(item.0, 1);
+        assert_eq!(SkipList::index(item), index);
+    }
+    assert!(list.get(&list.size()).is_none());
+    let first = list.first().unwrap();
+    assert!(SkipList::get_after(first, &list.size()).is_none());
+}
+
+#[test]
+fn range_by_key() {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+    use std::ops::RangeBounds;
+
+    let keys: Vec<_> = (0..90).map(|n| n / 3 * 2).collect();
+    let nodes: Vec<_> = keys.iter().map(|k| KeyNode::new(*k)).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&nodes);
+
+    let bounds = |k: &usize| [Included(*k), Excluded(*k), Unbounded];
+    for start in 0..=60 {
+        for end in 0..=60 {
+            for (start, end) in bounds(&start)
+                .into_iter()
+                .flat_map(|s| bounds(&end).map(move |e| (s, e)))
+            {
+                let range = (start, end);
+                let expected = nodes.iter().filter(|n| range.contains(&n.key));
+                let actual = list.range_by_key(start.as_ref(), end.as_ref());
+                assert!(
+                    actual
+                        .map(|n| n as *const _)
+                        .eq(expected.map(|n| n as *const _))
+                );
+            }
+        }
+    }
+
+    let empty = SkipList::<&KeyNode>::new();
+    assert!(
+        empty.range_by_key::<usize>(Unbounded, Unbounded).next().is_none()
+    );
+}
+
+#[test]
+fn insert_slice_after() {
+    let values: Vec<_> = (0..40).collect();
+    let items: Vec<_> =
+        (0..60).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let extra: Vec<_> = (60..70).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items[..20]);
+    let mut made = 0;
+    list.insert_slice_after(&items[9], &values, |v| {
+        assert_eq!(*v, made);
+        made += 1;
+        &items[20 + v]
+    });
+    assert_eq!(made, 40);
+    let expected = (0..10).chain(20..60).chain(10..20);
+    assert!(list.iter().map(|i| i.value).eq(expected));
+    let size: usize = items.iter().map(|i| i.size.get()).sum();
+    assert_eq!(list.size(), size);
+    assert_eq!(list.validate(), Ok(()));
+    list.insert_slice_after(&items[0], &[] as &[usize], |_| unreachable!());
+    assert_eq!(list.len(), 60);
+
+    // Inserting after the last item, and into a list with one item.
+    list.insert_slice_after(&items[19], &[0, 1, 2], |v| &extra[*v]);
+    assert!(list.iter().skip(60).map(|i| i.value).eq(60..63));
+    let mut single = SkipList::new();
+    single.push_back(&extra[3]);
+    single.insert_slice_after(&extra[3], &[4, 5, 6, 7, 8, 9], |v| &extra[*v]);
+    assert!(single.iter().map(|i| i.value).eq(63..70));
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(single.validate(), Ok(()));
+}
+
+#[test]
+fn merge_sorted() {
+    let left: Vec<_> = (0..70).map(|n| KeyNode::new(n / 2 * 3)).collect();
+    let right: Vec<_> = (0..50).map(|n| KeyNode::new(n / 2 * 4)).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&left);
+    let mut other = SkipList::new();
+    other.push_back_from(&right);
+    list.merge_sorted(other);
+    assert_eq!(list.len(), 120);
+    assert_eq!(list.size(), 120);
+
+    let mut expected: Vec<_> = left.iter().chain(&right).collect();
+    // Stable sort, so items from `left` precede equal items from `right`.
+    expected.sort_by_key(|n| n.key);
+    assert!(list.iter().zip(&expected).all(|(a, b)| ptr::eq(a, *b)));
+    for node in &right {
+        assert_eq!(list.find_first(&node.key).unwrap().key, node.key);
+    }
+
+    let extra = [KeyNode::new(1), KeyNode::new(500)];
+    let mut empty = SkipList::new();
+    empty.merge_sorted(list);
+    assert_eq!(empty.len(), 120);
+    let mut list = empty;
+    list.merge_sorted(SkipList::new());
+    assert_eq!(list.len(), 120);
+    let mut other = SkipList::new();
+    other.push_back_from(&extra);
+    list.merge_sorted(other);
+    assert_eq!(list.len(), 122);
+    assert!(ptr::eq(list.last().unwrap(), &extra[1]));
+    assert_eq!(list.find_first(&1).unwrap().key, 1);
+    let keys: Vec<_> = list.iter().map(|n| n.key).collect();
+    assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn stats() {
+    let items: Vec<_> = (0..500).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    assert_eq!(list.stats(), ListStats::default());
+    list.push_back(&items[0]);
+    let stats = list.stats();
+    assert_eq!((stats.height, stats.leaves, stats.internal_nodes), (1, 1, 0));
+
+    list.push_back_from(&items[1..]);
+    let stats = list.stats();
+    assert_eq!(stats.leaves, 500);
+    // `Data` uses a fanout of 4.
+    assert!(stats.max_fill <= 4);
+    assert!(stats.min_fill >= 1 && stats.min_fill <= stats.max_fill);
+    assert!(stats.avg_fill >= stats.min_fill as f64);
+    assert!(stats.avg_fill <= stats.max_fill as f64);
+    // Every node except the root has a parent, and the fills of all nodes
+    // add up to the number of non-root nodes.
+    let total_fill = stats.avg_fill * stats.internal_nodes as f64;
+    let expected = (stats.internal_nodes - 1 + stats.leaves) as f64;
+    assert!((total_fill - expected).abs() < 1e-6);
+    // With a fanout of 4, at least five levels of internal nodes are needed
+    // to hold 500 items.
+    assert!(stats.height >= 6);
+    let height = stats.height;
+    for item in &items[..450] {
+        list.remove(item);
+    }
+    assert!(list.stats().height <= height);
+    assert_eq!(list.stats().leaves, 50);
+}
+
+#[test]
+fn narrow_size_type() {
+    struct Item(Cell<u8>);
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<u8, false, 4>;
+
+        fn size(&self) -> u8 {
+            self.0.get()
+        }
+    }
+
+    let items: Vec<_> =
+        (0..51).map(|_| RefLeaf::new(Item(Cell::new(5)))).collect();
+    let extra = RefLeaf::new(Item(Cell::new(5)));
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert_eq!(list.size(), u8::MAX);
+
+    // Each update below keeps the total at or under `u8::MAX`, but adding
+    // the new size before subtracting the old one would overflow.
+    list.update(&items[1], || items[1].0.set(0));
+    list.update(&items[0], || items[0].0.set(10));
+    assert_eq!(list.size(), u8::MAX);
+    list.update_many(&items[..2], |i| i.0.set(5));
+    assert_eq!(list.size(), u8::MAX);
+    list.replace(&items[50], &extra);
+    assert_eq!(list.size(), u8::MAX);
+
+    assert!(ptr::eq(list.get(&0).unwrap(), &items[0]));
+    assert!(ptr::eq(list.get(&10).unwrap(), &items[2]));
+    assert!(ptr::eq(list.get(&254).unwrap(), &extra));
+    assert_eq!(SkipList::index(&extra), 250);
+    assert_eq!(SkipList::index(&items[49]), 245);
+}
+
+#[test]
+fn validate() {
+    let items: Vec<_> = (0..300).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    assert_eq!(list.validate(), Ok(()));
+    list.push_back(&items[0]);
+    assert_eq!(list.validate(), Ok(()));
+    list.push_back_from(&items[1..]);
+    assert_eq!(list.validate(), Ok(()));
+    for item in items.iter().step_by(3) {
+        list.remove(item);
+        assert_eq!(list.validate(), Ok(()));
+    }
+    list.update_many(items.iter().skip(1).step_by(5), |item| {
+        item.size.set(4);
+    });
+    assert_eq!(list.validate(), Ok(()));
+
+    // Changing a size without notifying the list is detected.
+    items[1].size.set(9);
+    assert_eq!(list.validate(), Err(ValidationError::Size));
+    items[1].size.set(4);
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn dump() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let mut list = SkipList::new();
+    assert_eq!(list.dump().to_string(), "");
+    list.push_back(&items[0]);
+    assert_eq!(list.dump().to_string(), "0: (2)\n");
+    list.push_back_from(&items[1..3]);
+    assert_eq!(
+        list.dump().to_string(),
+        "0: [len 3, size 6]\n1: (2) (2) (2)\n"
+    );
+
+    list.push_back_from(&items[3..]);
+    let dump = list.dump().to_string();
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(lines.len(), list.stats().height);
+    assert!(lines[0].starts_with("0: [len "));
+    assert!(lines[0].ends_with(", size 200]"));
+    let last = lines.last().unwrap();
+    assert_eq!(last.matches("(2)").count(), 100);
+}
+
+#[cfg(feature = "debug-graphviz")]
+#[test]
+fn debug_graphviz() {
+    let items: Vec<_> = (0..20).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let mut state = skippy::debug::State::new();
+    let graph = list.debug(&mut state).to_string();
+    assert!(graph.starts_with("digraph {\n"));
+    assert!(graph.ends_with("}\n"));
+    assert_eq!(
+        graph.matches("shape=rectangle").count(),
+        20 + list.stats().internal_nodes
+    );
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn list_model() {
+    use skippy::testing::{ListModel, Op};
+
+    let items: Vec<_> = (0..64).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    let mut model = ListModel::new(&items);
+    // A simple linear congruential generator keeps the sequence of
+    // operations deterministic.
+    let mut state = 1_u64;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) as usize
+    };
+    for _ in 0..2000 {
+        let op = match next() % 9 {
+            0 => Op::PushFront,
+            1 => Op::PushBack,
+            2 | 3 => Op::InsertAfter(next()),
+            4 => Op::InsertBefore(next()),
+            5 => Op::Remove(next()),
+            6 => Op::Update(next()),
+            7 => Op::Swap(next(), next()),
+            _ => Op::MoveAfter(next(), next()),
+        };
+        let size = next() % 4;
+        model.apply(&mut list, op, |item| item.size.set(size));
+        model.check(&list);
+    }
+    model.apply(&mut list, Op::Reverse, |_| {});
+    model.check(&list);
+    assert_eq!(model.items().len() + model.unused().len(), 64);
+}
+
+#[cfg(feature = "chaos")]
+#[test]
+fn chaos() {
+    let items: Vec<_> = (0..400).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    for seed in 1..5 {
+        skippy::chaos::set_seed(seed);
+        let mut list = SkipList::new();
+        for item in &items {
+            list.push_back(item);
+            assert_eq!(list.validate(), Ok(()));
+        }
+        for (i, item) in items.iter().enumerate().step_by(2) {
+            assert_eq!(SkipList::index(item), i / 2);
+            list.remove(item);
+            assert_eq!(list.validate(), Ok(()));
+        }
+        assert!(list.iter().map(|i| i.value).eq((1..400).step_by(2)));
+    }
+}
+
+#[test]
+fn shared_skip_list() {
+    let items: Vec<_> = (0..10).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let shared = SharedSkipList::new();
+    let other = shared.clone();
+    assert!(shared.ptr_eq(&other));
+    for item in &items[..5] {
+        shared.push_back(item).unwrap();
+    }
+    other.push_front(&items[5]).unwrap();
+    other.insert_after(&items[0], &items[6]).unwrap();
+    other.insert_before(&items[0], &items[7]).unwrap();
+    assert_eq!(shared.len(), Ok(8));
+    assert_eq!(shared.push_back(&items[0]), Err(Error::AlreadyInList));
+    assert_eq!(shared.remove(&items[8]), Err(Error::NotInList));
+    shared.remove(&items[1]).unwrap();
+
+    // Using the list while it is borrowed returns an error.
+    let result = shared.update(&items[2], || {
+        assert_eq!(other.len(), Err(Error::Borrowed));
+        assert_eq!(other.push_back(&items[9]), Err(Error::Borrowed));
+        items[2].size.set(3);
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(shared.size(), Ok(9));
+    let borrow = shared.try_borrow().unwrap();
+    assert!(other.try_borrow().is_ok());
+    assert_eq!(other.remove(&items[0]), Err(Error::Borrowed));
+    drop(borrow);
+
+    let shared = match shared.try_into_inner() {
+        Ok(_) => panic!("list is still shared"),
+        Err(shared) => shared,
+    };
+    drop(other);
+    let list = shared.try_into_inner().ok().unwrap();
+    let values = [5, 7, 0, 6, 2, 3, 4];
+    assert!(list.iter().map(|i| i.value).eq(values));
+}
+
+#[test]
+fn try_insert_alloc_failure() {
+    use allocator_fallback::{AllocError, Allocator, Global};
+    use std::alloc::Layout;
+    use std::ptr::NonNull;
+    use std::rc::Rc;
+
+    /// Fails once the number of allowed allocations runs out.
+    struct FailingAlloc(Rc<Cell<usize>>);
+
+    // SAFETY: Forwards to `Global`.
+    unsafe impl Allocator for FailingAlloc {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let remaining = self.0.get().checked_sub(1).ok_or(AllocError)?;
+            self.0.set(remaining);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            // SAFETY: Checked by caller.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    let items: Vec<_> = (0..200).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let remaining = Rc::new(Cell::new(usize::MAX));
+    let mut list = SkipList::new_in(FailingAlloc(remaining.clone()));
+    list.push_back_from(&items[..100]);
+
+    remaining.set(0);
+    let mut failures = 0;
+    for (i, item) in items.iter().enumerate().skip(100) {
+        // Allow just enough allocations for some insertions to succeed.
+        remaining.set(i % 8);
+        let len = list.len();
+        match list.try_push_back(item) {
+            Ok(()) => assert_eq!(list.len(), len + 1),
+            Err(e) => {
+                assert_eq!(e, Error::Alloc);
+                assert_eq!(list.len(), len);
+                assert!(item.next().is_none());
+                failures += 1;
+            }
+        }
+        assert_eq!(list.validate(), Ok(()));
+    }
+    assert!(failures > 0 && failures < 100);
+    remaining.set(usize::MAX);
+    let first = list.first().unwrap();
+    assert_eq!(
+        list.try_insert_after(first, &items[0]),
+        Err(Error::AlreadyInList)
+    );
+    let values: Vec<_> = list.iter().map(|i| i.value).collect();
+    assert!(values[..100].iter().copied().eq(0..100));
+    assert!(values.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(values.len(), 200 - failures);
+}
+
+#[test]
+fn alloc_error_hook() {
+    use allocator_fallback::{AllocError, Allocator, Global};
+    use skippy::AllocFallback;
+    use std::alloc::Layout;
+    use std::ptr::NonNull;
+
+    thread_local! {
+        /// The number of allocations that will fail before one succeeds.
+        static FAILURES: Cell<usize> = const { Cell::new(0) };
+        /// The number of times the hook has been called.
+        static HOOK_CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct FailingAlloc;
+
+    // SAFETY: Forwards to `Global`.
+    unsafe impl Allocator for FailingAlloc {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            let failures = FAILURES.with(Cell::get);
+            if failures > 0 {
+                FAILURES.with(|f| f.set(failures - 1));
+                return Err(AllocError);
+            }
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            // SAFETY: Checked by caller.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    fn hook(_: Layout) -> AllocFallback {
+        HOOK_CALLS.with(|c| c.set(c.get() + 1));
+        AllocFallback::Retry
+    }
+
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new_in(FailingAlloc);
+    list.set_alloc_error_hook(hook);
+    list.push_back_from(&items[..50]);
+
+    // A single failure is recovered from by retrying.
+    FAILURES.with(|f| f.set(1));
+    assert_eq!(list.try_push_back(&items[50]), Ok(()));
+    assert_eq!(HOOK_CALLS.with(Cell::get), 1);
+
+    // The allocation is retried only once, even if the hook keeps returning
+    // `Retry`.
+    HOOK_CALLS.with(|c| c.set(0));
+    FAILURES.with(|f| f.set(usize::MAX));
+    assert_eq!(list.try_push_back(&items[51]), Err(Error::Alloc));
+    assert_eq!(HOOK_CALLS.with(Cell::get), 1);
+    assert_eq!(FAILURES.with(Cell::get), usize::MAX - 2);
+
+    FAILURES.with(|f| f.set(0));
+    list.push_back_from(&items[51..]);
+    assert!(list.iter().eq(&items));
+}
+
+#[test]
+fn non_static_allocator() {
+    use allocator_fallback::{AllocError, Allocator, Global};
+    use std::alloc::Layout;
+    use std::ptr::NonNull;
+
+    /// Counts the number of live allocations in a local variable.
+    #[derive(Clone)]
+    struct Counting<'a>(&'a Cell<usize>);
+
+    // SAFETY: Forwards to `Global`.
+    unsafe impl Allocator for Counting<'_> {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.set(self.0.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.set(self.0.get() - 1);
+            // SAFETY: Checked by caller.
+            unsafe {
+                Global.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let live = Cell::new(0);
+    // SAFETY: The list is dropped normally, and the items aren't used after
+    // `live` goes out of scope.
+    let mut list = unsafe { SkipList::new_in_unchecked(Counting(&live)) };
+    list.push_back_from(&items);
+    assert!(live.get() > 0);
+    for item in items.iter().step_by(2) {
+        list.remove(item);
+    }
+    let other = list.split_off(&items[51]);
+    assert_eq!(other.len(), 25);
+    drop(other);
+    drop(list);
+    assert_eq!(live.get(), 0);
+}
+
+#[test]
+fn find_key_in_range() {
+    let keys = [1, 1, 2, 2, 2, 2, 3, 5, 5, 5, 5, 5, 5, 5, 6, 8, 8, 9];
+    let nodes: Vec<_> = keys.iter().map(|&k| KeyNode::new(k)).collect();
+    let mut list = SkipList::new();
+    for node in &nodes {
+        list.push_back(node);
+    }
+    for key in 0..10 {
+        for start in 0..=keys.len() {
+            for end in start..=keys.len() + 1 {
+                let expected =
+                    (start..end.min(keys.len())).find(|&i| keys[i] == key);
+                let actual = list
+                    .find_key_in_range(&key, &start, &end)
+                    .map(SkipList::index);
+                assert_eq!(actual, expected, "{key} in {start}..{end}");
+            }
+        }
+    }
+}
+
+#[test]
+fn compact() {
+    let items: Vec<_> = (0..400).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.compact();
+    assert!(list.is_empty());
+    for item in &items {
+        list.push_front(item);
+    }
+    for item in items.iter().filter(|item| item.value % 4 != 0) {
+        list.remove(item);
+    }
+    let before = list.stats();
+    list.compact();
+    assert_eq!(list.validate(), Ok(()));
+    let after = list.stats();
+    assert_eq!(after.leaves, 100);
+    assert!(after.internal_nodes < before.internal_nodes);
+    assert!(after.height <= before.height);
+    assert!(after.avg_fill > before.avg_fill);
+    assert!(list.iter().map(|item| item.value).eq((0..400).step_by(4).rev()));
+    assert!(ptr::eq(list.get(&50).unwrap(), &items[196]));
+
+    let nodes: Vec<_> = (0..50).map(KeyNode::new).collect();
+    let mut list = SkipList::new();
+    for node in nodes.iter().rev() {
+        list.insert(node).unwrap();
+    }
+    list.compact();
+    assert_eq!(list.validate(), Ok(()));
+    assert!(ptr::eq(list.find(&20).unwrap(), &nodes[20]));
+}
+
+#[test]
+fn get_many() {
+    let items: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let size = list.size();
+    let get = |i: &usize| list.get(i).map(|item| item.value);
+
+    let indices: Vec<_> = (0..=size + 2).collect();
+    let many = list.get_many(&indices).map(|item| item.map(|i| i.value));
+    assert!(many.eq(indices.iter().map(get)));
+
+    let indices = [3, 3, 50, 51, 120, 7, 8, size, size + 1, 0];
+    let many = list.get_many(&indices).map(|item| item.map(|i| i.value));
+    assert!(many.eq(indices.iter().map(get)));
+    let list: SkipList<&Leaf> = SkipList::new();
+    assert!(list.get_many(&[0, 1]).eq([None, None]));
+}
+
+#[test]
+fn take_front_back() {
+    let items: Vec<_> = (0..100).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert_eq!(list.take_front(0).count(), 0);
+    assert_eq!(list.take_back(0).count(), 0);
+    assert!(list.take_front(10).map(|item| item.value).eq(0..10));
+    assert!(list.take_back(15).map(|item| item.value).eq(85..100));
+    assert_eq!(list.validate(), Ok(()));
+    assert!(list.iter().map(|item| item.value).eq(10..85));
+    assert_eq!(list.size(), 75);
+
+    // Removed items can be inserted again.
+    list.push_front_from(&items[..10]);
+    assert!(list.take_back(100).map(|item| item.value).eq(0..85));
+    assert!(list.is_empty());
+    assert_eq!(list.take_front(1).count(), 0);
+}
+
+#[test]
+fn chunk_level() {
+    struct Chunk(usize);
+
+    impl BasicLeaf for Chunk {
+        type Options = basic::Options<usize, false, 6>;
+
+        fn size(&self) -> usize {
+            self.0
+        }
+
+        fn chunk_level(&self) -> usize {
+            // A boundary follows about one in four items.
+            self.0.wrapping_mul(0x9e3779b97f4a7c15).trailing_zeros() as usize
+                / 2
+        }
+    }
+
+    // Gets the groups of leaves with the same parent, as lists of sizes.
+    fn groups(list: &SkipList<&RefLeaf<Chunk>>) -> Vec<Vec<usize>> {
+        let dump = list.dump().to_string();
+        let leaves = dump.lines().last().unwrap();
+        let leaves = leaves.split_once(": ").unwrap().1;
+        leaves
+            .split(" | ")
+            .map(|group| {
+                group
+                    .split(' ')
+                    .map(|s| s.trim_matches(&['(', ')'][..]).parse().unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    let common = 1..400;
+    let a: Vec<_> = (1000..1010).chain(common.clone()).map(Chunk).collect();
+    let b: Vec<_> = (2000..2003).chain(common.clone()).map(Chunk).collect();
+    let a: Vec<_> = a.into_iter().map(RefLeaf::new).collect();
+    let b: Vec<_> = b.into_iter().map(RefLeaf::new).collect();
+    let mut list_a = SkipList::new();
+    let mut list_b = SkipList::new();
+    list_a.push_back_from(&a);
+    list_b.push_back_from(&b);
+    assert_eq!(list_a.validate(), Ok(()));
+    assert_eq!(list_b.validate(), Ok(()));
+
+    // Apart from near the ends of the lists, the common items are grouped
+    // identically.
+    let groups_a = groups(&list_a);
+    let groups_b = groups(&list_b);
+    let inner = |groups: &[Vec<usize>]| -> Vec<Vec<usize>> {
+        groups
+            .iter()
+            .filter(|g| g.iter().all(|n| (20..380).contains(n)))
+            .cloned()
+            .collect()
+    };
+    assert!(inner(&groups_a).len() > 30);
+    assert_eq!(inner(&groups_a), inner(&groups_b));
+
+    // Inserting items individually doesn't use the boundaries, but
+    // `compact` restores them.
+    list_b.drain();
+    for item in &b {
+        list_b.push_back(item);
+    }
+    assert_ne!(inner(&groups(&list_b)), inner(&groups_a));
+    list_b.compact();
+    assert_eq!(list_b.validate(), Ok(()));
+    assert_eq!(inner(&groups(&list_b)), inner(&groups_a));
+}
+
+#[test]
+fn pop_front_back() {
+    let items: Vec<_> = (0..50).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    assert!(list.pop_front().is_none());
+    assert!(list.pop_back().is_none());
+    list.push_back_from(&items);
+    for i in 0..25 {
+        assert!(ptr::eq(list.pop_front().unwrap(), &items[i]));
+        assert!(ptr::eq(list.pop_back().unwrap(), &items[49 - i]));
+        assert_eq!(list.validate(), Ok(()));
+        assert_eq!(list.size(), 48 - i * 2);
+    }
+    assert!(list.is_empty());
+    assert!(list.pop_front().is_none());
+    list.push_back(&items[0]);
+    assert!(ptr::eq(list.pop_back().unwrap(), &items[0]));
+}
+
+#[test]
+fn dispose_incremental() {
+    use skippy::basic::BoxLeaf;
+
+    thread_local! {
+        static DROPPED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Item;
+
+    impl Drop for Item {
+        fn drop(&mut self) {
+            DROPPED.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    impl BasicLeaf for Item {
+        type Options = basic::options::Options<usize, false, 4>;
+    }
+
+    let items: Vec<_> = (0..500).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let internal_nodes = list.stats().internal_nodes;
+    let mut disposer = list.dispose_incremental();
+    let mut steps = 1;
+    while !disposer.step(10) {
+        steps += 1;
+    }
+    assert_eq!(steps, (internal_nodes + 9) / 10);
+    assert!(disposer.is_done());
+    // The items were unlinked, so they can be inserted into another list.
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert_eq!(list.len(), 500);
+    drop(list);
+
+    // SAFETY: No clones of the leaves are used after they are freed.
+    let new_leaf = || unsafe { BoxLeaf::new(Item) };
+    let mut list = SkipList::new();
+    list.push_back_from((0..300).map(|_| new_leaf()));
+    let mut disposer = list.dispose_incremental();
+    assert!(!disposer.step(5));
+    let dropped = DROPPED.with(Cell::get);
+    assert!(dropped > 0 && dropped < 300);
+    drop(disposer);
+    assert_eq!(DROPPED.with(Cell::get), 300);
+
+    let mut list = SkipList::new();
+    list.push_back(new_leaf());
+    assert!(list.dispose_incremental().is_done());
+    assert_eq!(DROPPED.with(Cell::get), 301);
+}
+
+#[test]
+fn replace_at() {
+    let items: Vec<_> = (0..30).map(|n| Leaf::new(Data::new(n, 2))).collect();
+    let extra: Vec<_> = (30..33).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let old = list.replace_at(&21, &extra[0]).unwrap();
+    assert!(ptr::eq(old, &items[10]));
+    assert!(old.next().is_none());
+    assert_eq!(list.size(), 59);
+    assert!(ptr::eq(list.get(&20).unwrap(), &extra[0]));
+    assert!(ptr::eq(list.get(&21).unwrap(), &items[11]));
+    assert!(list.replace_at(&59, &extra[1]).is_none());
+    assert!((&extra[1]).next().is_none());
+    let old = list.replace_at(&0, &extra[1]).unwrap();
+    assert!(ptr::eq(old, &items[0]));
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.size(), 58);
+
+    // Sizes and keys are updated along the recorded path.
+    let items: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n, n % 4))).collect();
+    let fresh: Vec<_> =
+        (0..200).map(|n| Leaf::new(Data::new(n + 200, 5))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    for (i, new) in fresh.iter().enumerate().step_by(3) {
+        let index = SkipList::index(&items[i]);
+        if items[i].size.get() == 0 {
+            continue;
+        }
+        assert!(ptr::eq(list.replace_at(&index, new).unwrap(), &items[i]));
+        assert_eq!(list.validate(), Ok(()));
+    }
+}
+
+#[test]
+fn graft() {
+    let items: Vec<_> = (0..400).map(|n| Leaf::new(Data::new(n, 1))).collect();
+    let values = |list: &SkipList<_>| -> Vec<usize> {
+        list.iter().map(|item: &Leaf| item.value).collect()
+    };
+
+    let mut list = SkipList::new();
+    let mut builder = list.subtree_builder();
+    builder.push_from(&items[100..200]);
+    assert_eq!(builder.len(), 100);
+    list.graft_after_opt(None, builder);
+    assert_eq!(list.validate(), Ok(()));
+
+    let mut builder = SubtreeBuilder::new();
+    builder.push(&items[0]);
+    list.graft_after_opt(None, builder);
+    let mut builder = SubtreeBuilder::new();
+    builder.extend(&items[1..100]);
+    list.graft_after(&items[0], builder);
+    let mut builder = SubtreeBuilder::new();
+    builder.push_from(&items[300..400]);
+    list.graft_after(&items[199], builder);
+    let mut builder = SubtreeBuilder::new();
+    builder.push_from(&items[200..300]);
+    assert_eq!(builder.size(), 100);
+    list.graft_after(&items[199], builder);
+    list.graft_after(&items[50], SubtreeBuilder::new());
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(values(&list), (0..400).collect::<Vec<_>>());
+    assert_eq!(list.size(), 400);
+    assert!(ptr::eq(list.get(&250).unwrap(), &items[250]));
+
+    let nodes: Vec<_> = (0..60).map(KeyNode::new).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(nodes[..10].iter().chain(&nodes[40..]));
+    let mut builder = SubtreeBuilder::new();
+    builder.push_from(&nodes[10..40]);
+    list.graft_after(&nodes[9], builder);
+    assert_eq!(list.validate(), Ok(()));
+    assert!(ptr::eq(list.find(&5).unwrap(), &nodes[5]));
+    assert!(ptr::eq(list.find(&25).unwrap(), &nodes[25]));
+    assert!(ptr::eq(list.find(&45).unwrap(), &nodes[45]));
+}
+
+#[test]
+fn update_key() {
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    struct Item {
+        key: Cell<usize>,
+        id: usize,
+    }
+
+    impl BasicLeaf for Item {
+        type Options = basic::Options<usize, true, 4>;
+
+        fn size(&self) -> usize {
+            self.key.get()
+        }
+    }
+
+    let items: Vec<_> = (0..100)
+        .map(|n| {
+            RefLeaf::new(Item {
+                key: Cell::new(n * 2),
+                id: n,
+            })
+        })
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let keys = |list: &SkipList<_>| -> Vec<usize> {
+        list.iter().map(|item: &RefLeaf<Item>| item.key.get()).collect()
+    };
+
+    // Still in order, so the item isn't moved.
+    list.update_key(&items[10], || items[10].key.set(21));
+    assert!(ptr::eq(list.iter().nth(10).unwrap(), &items[10]));
+    assert_eq!(list.size(), 9901);
+
+    list.update_key(&items[0], || items[0].key.set(101));
+    list.update_key(&items[99], || items[99].key.set(1));
+    list.update_key(&items[50], || items[50].key.set(500));
+    assert_eq!(list.validate(), Ok(()));
+    let mut expected: Vec<_> = items.iter().map(|i| i.key.get()).collect();
+    expected.sort();
+    assert_eq!(keys(&list), expected);
+    assert_eq!(list.size(), expected.iter().sum::<usize>());
+    assert!(ptr::eq(list.first().unwrap(), &items[99]));
+    assert!(ptr::eq(list.last().unwrap(), &items[50]));
+    assert!(ptr::eq(list.iter().nth(50).unwrap(), &items[0]));
+}
+
+#[test]
+fn entry() {
+    let nodes: Vec<_> = (0..100).map(|n| KeyNode::new(n * 2)).collect();
+    let extra = KeyNode::new(10);
+    let first = KeyNode::new(0);
+    let mut list = SkipList::new();
+    for node in nodes.iter().step_by(2) {
+        list.insert(node).unwrap();
+    }
+    for (i, node) in nodes.iter().enumerate() {
+        let key = i * 2;
+        match list.entry(&key) {
+            Entry::Occupied(entry) => {
+                assert_eq!(i % 2, 0);
+                assert!(ptr::eq(entry.get(), node));
+            }
+            Entry::Vacant(entry) => {
+                assert_eq!(i % 2, 1);
+                assert!(ptr::eq(entry.prev().unwrap(), &nodes[i - 1]));
+                entry.insert(node);
+            }
+        }
+    }
+    assert_eq!(list.validate(), Ok(()));
+    assert!(list.iter().eq(&nodes));
+
+    let item = list.entry(&10).or_insert_with(|| &extra);
+    assert!(ptr::eq(item, &nodes[5]));
+    let Entry::Occupied(entry) = list.entry(&10) else {
+        panic!("expected occupied entry");
+    };
+    assert!(ptr::eq(entry.replace(&extra), &nodes[5]));
+    assert!(ptr::eq(list.find(&10).unwrap(), &extra));
+    let Entry::Occupied(entry) = list.entry(&10) else {
+        panic!("expected occupied entry");
+    };
+    assert!(ptr::eq(entry.remove(), &extra));
+    assert!(matches!(list.entry(&10), Entry::Vacant(_)));
+
+    let item = list.entry(&10).or_insert_with(|| &nodes[5]);
+    assert!(ptr::eq(item, &nodes[5]));
+    let Entry::Occupied(_) = list.entry(&0) else {
+        panic!("expected occupied entry");
+    };
+    list.remove(&nodes[0]);
+    let Entry::Vacant(entry) = list.entry(&0) else {
+        panic!("expected vacant entry");
+    };
+    assert!(entry.prev().is_none());
+    entry.insert(&first);
+    assert!(ptr::eq(list.first().unwrap(), &first));
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.len(), 100);
+}
+
+#[test]
+fn get_fraction() {
+    struct Sample(u32);
+
+    impl BasicLeaf for Sample {
+        type Options = basic::Options<u32>;
+
+        fn size(&self) -> u32 {
+            self.0
+        }
+    }
+
+    // The total size is close to `u32::MAX`, so `num * size` would overflow.
+    let items: Vec<_> = (0..41)
+        .map(|n| {
+            RefLeaf::new(Sample(if n == 40 {
+                0
+            } else {
+                100_000_000
+            }))
+        })
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert_eq!(list.size(), 4_000_000_000);
+    for (num, denom) in [(0, 1), (3, 4), (1, 3), (2, 3), (999, 1000), (1, 1)] {
+        let index = (4_000_000_000 * num / denom) as u32;
+        let expected = list.get(&index).unwrap();
+        assert!(ptr::eq(list.get_fraction(num, denom).unwrap(), expected));
+    }
+    assert!(ptr::eq(list.get_fraction(3, 4).unwrap(), &items[30]));
+    assert!(ptr::eq(list.get_fraction(1, 1).unwrap(), &items[40]));
+    assert!(list.get_fraction(2, 1).is_none());
+    assert!(list.get_fraction(u64::MAX, 1).is_none());
+}
+
+struct Checked(Cell<u8>);
+
+impl BasicLeaf for Checked {
+    type Options = basic::Options<CheckedSize<u8>>;
+
+    fn size(&self) -> CheckedSize<u8> {
+        CheckedSize(self.0.get())
+    }
+}
+
+#[test]
+fn checked_size() {
+    let items: Vec<_> =
+        (0..50).map(|_| RefLeaf::new(Checked(Cell::new(5)))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    assert_eq!(list.size(), CheckedSize(250));
+    assert!(ptr::eq(list.get(&7).unwrap(), &items[1]));
+    list.update(&items[0], || items[0].0.set(0));
+    assert_eq!(list.size().get(), 245);
+}
+
+#[test]
+#[should_panic(expected = "size underflow")]
+fn checked_size_underflow() {
+    let items: Vec<_> =
+        (0..50).map(|_| RefLeaf::new(Checked(Cell::new(1)))).collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    // Changing the size without calling `update` corrupts the list.
+    for item in &items {
+        item.0.set(2);
+    }
+    for item in &items {
+        list.remove(item);
+    }
+}
+
+#[test]
+fn deque() {
+    let mut deque = Deque::new();
+    let mut expected = std::collections::VecDeque::new();
+    for i in 0..200 {
+        match i % 5 {
+            0 | 1 => {
+                deque.push_back(i);
+                expected.push_back(i);
+            }
+            2 => {
+                deque.push_front(i);
+                expected.push_front(i);
+            }
+            3 => {
+                let index = i * 7 % (expected.len() + 1);
+                deque.insert(index, i);
+                expected.insert(index, i);
+            }
+            _ => {
+                let index = i * 3 % expected.len();
+                assert_eq!(deque.remove(index), expected.remove(index));
+            }
+        }
+    }
+    assert_eq!(deque.len(), expected.len());
+    assert!(deque.iter().eq(&expected));
+    assert!(deque.iter().rev().eq(expected.iter().rev()));
+    assert_eq!(deque.get(deque.len()), None);
+    assert_eq!(deque.remove(deque.len()), None);
+    *deque.get_mut(10).unwrap() = 1000;
+    expected[10] = 1000;
+    assert_eq!(deque.get(10), Some(&1000));
+    assert_eq!(deque.front(), expected.front());
+    assert_eq!(deque.back(), expected.back());
+    assert_eq!(deque.pop_front(), expected.pop_front());
+    assert_eq!(deque.pop_back(), expected.pop_back());
+    assert_eq!(deque.clone(), deque);
+    deque.clear();
+    assert!(deque.is_empty());
+    assert_eq!(deque.pop_front(), None);
+
+    // Elements still in the deque are dropped with it.
+    let rc = std::rc::Rc::new(());
+    let deque: Deque<_> = (0..50).map(|_| rc.clone()).collect();
+    assert_eq!(std::rc::Rc::strong_count(&rc), 51);
+    drop(deque);
+    assert_eq!(std::rc::Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn chunk_leaf() {
+    use skippy::basic::{ArcLeaf, ChunkLeaf, RcLeaf};
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let mut list = SkipList::<Rc<RcLeaf<ChunkLeaf<u32, 8>>>>::new();
+    let mut model = Vec::new();
+    let mut state = 1_u64;
+    let mut next = |bound: usize| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) as usize % bound
+    };
+    for i in 0..3000 {
+        if model.is_empty() || next(3) != 0 {
+            let index = next(model.len() + 1);
+            list.insert_in_chunk(index, i);
+            model.insert(index, i);
+        } else {
+            let index = next(model.len());
+            assert_eq!(list.remove_in_chunk(index), model.remove(index));
+        }
+    }
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.size(), model.len());
+    assert!(list.iter().all(|leaf| !leaf.is_empty() && leaf.len() <= 8));
+    let values: Vec<_> =
+        list.iter().flat_map(|leaf| leaf.items().to_vec()).collect();
+    assert_eq!(values, model);
+    for index in (0..model.len()).step_by(17) {
+        let (leaf, offset) = list.get_in_chunk(index).unwrap();
+        assert_eq!(leaf.items()[offset], model[index]);
+    }
+    assert!(list.get_in_chunk(model.len()).is_none());
+
+    let (leaf, offset) = list.get_in_chunk(5).unwrap();
+    leaf.items_mut()[offset] = 1000;
+    model[5] = 1000;
+    while !model.is_empty() {
+        assert_eq!(list.remove_in_chunk(0), model.remove(0));
+    }
+    assert!(list.is_empty());
+
+    let mut list = SkipList::<Arc<ArcLeaf<ChunkLeaf<char, 2>>>>::new();
+    for (i, c) in "skipy".chars().enumerate() {
+        list.insert_in_chunk(i, c);
+    }
+    list.insert_in_chunk(3, 'p');
+    assert_eq!(list.validate(), Ok(()));
+    let text: String =
+        list.iter().flat_map(|leaf| leaf.items().to_vec()).collect();
+    assert_eq!(text, "skippy");
+    assert_eq!(list.len(), 4);
+}
+
+#[test]
+fn rope() {
+    let pieces = ["héllo", " wörld", "€€", "\n", "abc", "𝄞x", ""];
+    let mut rope = Rope::new();
+    let mut expected = String::new();
+    let chars = |s: &String| s.chars().count();
+    let byte =
+        |s: &String, i| s.char_indices().nth(i).map_or(s.len(), |c| c.0);
+    for i in 0..3000 {
+        let len = chars(&expected);
+        if i % 4 == 3 && len > 0 {
+            let start = i * 13 % len;
+            let end = (start + i % 40).min(len);
+            rope.remove(start..end);
+            expected.replace_range(
+                byte(&expected, start)..byte(&expected, end),
+                "",
+            );
+        } else {
+            let at = i * 7 % (len + 1);
+            let text = pieces[i % pieces.len()].repeat(i % 23);
+            rope.insert_str(at, &text);
+            expected.insert_str(byte(&expected, at), &text);
+        }
+    }
+    assert_eq!(rope, *expected);
+    assert_eq!(rope.to_string(), expected);
+    assert_eq!(rope.len_bytes(), expected.len());
+    assert_eq!(rope.len_chars(), chars(&expected));
+    assert!(rope.chunks().count() > 1);
+    assert!(rope.chars().eq(expected.chars()));
+    for (c, (b, ch)) in expected.char_indices().enumerate().step_by(7) {
+        assert_eq!(rope.char_to_byte(c), b);
+        assert_eq!(rope.byte_to_char(b), c);
+        assert_eq!(rope.char(c), Some(ch));
+    }
+    let len = rope.len_chars();
+    assert_eq!(rope.char_to_byte(len), expected.len());
+    assert_eq!(rope.byte_to_char(expected.len()), len);
+    assert_eq!(rope.char(len), None);
+    for (start, end) in [(0, 0), (3, 900), (100, 101), (len - 50, len)] {
+        let slice: String = rope.slice(start..end).collect();
+        let range = byte(&expected, start)..byte(&expected, end);
+        assert_eq!(slice, expected[range]);
+    }
+    assert_eq!(rope.clone(), rope);
+    rope.remove(..);
+    assert!(rope.is_empty());
+    assert_eq!(rope, "");
+    assert_eq!(Rope::from("a€b").byte_to_char(4), 2);
+}
+
+#[test]
+fn priority_queue() {
+    let mut queue = PriorityQueue::new();
+    let handles: Vec<_> =
+        (0..200).map(|n| (n, queue.push((n * 37 % 50, n)))).collect();
+    assert_eq!(queue.len(), 200);
+    assert_eq!(queue.peek_min(), Some(&(0, 0)));
+    for (n, handle) in handles.iter().step_by(3) {
+        assert_eq!(queue.remove(handle), Some((n * 37 % 50, *n)));
+        assert!(!queue.contains(handle));
+        assert_eq!(queue.remove(handle), None);
+    }
+    for (n, handle) in handles.iter().skip(1).step_by(3) {
+        let new = (n * 7 % 60, *n);
+        assert_eq!(queue.replace(handle, new), Ok((n * 37 % 50, *n)));
+        assert_eq!(queue.get(handle), Some(&new));
+    }
+    assert_eq!(queue.replace(&handles[0].1, (0, 0)), Err((0, 0)));
+
+    let mut expected: Vec<_> = queue.iter().copied().collect();
+    assert!(expected.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(expected.len(), 133);
+    expected.reverse();
+    while let Some(min) = queue.pop_min() {
+        assert_eq!(Some(min), expected.pop());
+    }
+    assert!(expected.is_empty());
+    assert!(handles.iter().all(|(_, h)| queue.get(h).is_none()));
+
+    // Equal elements are popped in the order they were pushed, and handles
+    // from another queue are ignored.
+    #[derive(Debug)]
+    struct Job(u8, usize);
+
+    impl PartialEq for Job {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Job {}
+
+    impl PartialOrd for Job {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Job {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    let mut queue = PriorityQueue::new();
+    let mut other = PriorityQueue::new();
+    let jobs: Vec<_> =
+        (0..20).map(|n| queue.push(Job(n % 3, n.into()))).collect();
+    let foreign = other.push(Job(0, 100));
+    assert!(!queue.contains(&foreign));
+    assert_eq!(queue.remove(&foreign).map(|j| j.1), None);
+    assert_eq!(queue.replace(&jobs[4], Job(0, 4)).map(|j| j.1), Ok(4));
+    let order: Vec<_> =
+        std::iter::from_fn(|| queue.pop_min().map(|j| j.1)).collect();
+    let mut expected: Vec<_> = (0..20).collect();
+    expected.sort_by_key(|&n| (n % 3, n));
+    expected.retain(|&n| n != 4);
+    expected.insert(7, 4);
+    assert_eq!(order, expected);
+    assert_eq!(other.pop_min().map(|j| j.1), Some(100));
+}
+
+#[test]
+fn intervals() {
+    use skippy::Interval;
+    use skippy::options::NoSize;
+
+    struct Span {
+        start: usize,
+        len: Cell<usize>,
+    }
+
+    impl BasicLeaf for Span {
+        type Options = basic::Options<NoSize, false, 4, 1, false, true, true>;
+    }
+
+    impl Interval for Span {
+        type Point = usize;
+
+        fn start(&self) -> usize {
+            self.start
+        }
+
+        fn end(&self) -> usize {
+            self.start + self.len.get()
+        }
+    }
+
+    let spans: Vec<_> = (0..300)
+        .map(|n| {
+            RefLeaf::new(Span {
+                start: n * 3,
+                len: Cell::new(n * 37 % 50),
+            })
+        })
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(spans.iter().step_by(2));
+    let check = |list: &SkipList<&RefLeaf<Span>>| {
+        let starts = |iter: &mut dyn Iterator<Item = &RefLeaf<Span>>| {
+            iter.map(|s| s.start).collect::<Vec<_>>()
+        };
+        for point in (0..1000).step_by(7) {
+            let expected = starts(
+                &mut list
+                    .iter()
+                    .filter(|s| s.start <= point && point < s.end()),
+            );
+            assert_eq!(starts(&mut list.find_overlapping(&point)), expected);
+            let end = point + 1 + point % 30;
+            let expected = starts(
+                &mut list.iter().filter(|s| s.start < end && point < s.end()),
+            );
+            let mut actual = list.find_all_overlapping(point..end);
+            assert_eq!(starts(&mut actual), expected, "{point}..{end}");
+        }
+    };
+    check(&list);
+
+    list.update(&spans[10], || spans[10].len.set(600));
+    list.update(&spans[200], || spans[200].len.set(0));
+    check(&list);
+    for span in spans.iter().skip(20).step_by(6) {
+        list.remove(span);
+    }
+    check(&list);
+    for span in spans.iter().skip(1).step_by(4) {
+        let prev = list.iter().take_while(|s| s.start < span.start).last();
+        list.insert_after_opt(prev, span);
+    }
+    assert_eq!(list.validate(), Ok(()));
+    check(&list);
+    assert_eq!(list.find_all_overlapping(50..50).count(), 0);
+}
+
+#[test]
+fn aggregate() {
+    use skippy::Summarize;
+    use skippy::options::{Aggregate, Monoid};
+
+    /// The maximum value and the first and last values of a range. The
+    /// latter two check that values are combined in list order.
+    struct Summary;
+
+    type Value = Option<(u32, u32, u32)>;
+
+    impl Monoid for Summary {
+        type Value = Value;
+
+        fn identity() -> Value {
+            None
+        }
+
+        fn combine(left: &Value, right: &Value) -> Value {
+            match (left, right) {
+                (Some(l), Some(r)) => Some((l.0.max(r.0), l.1, r.2)),
+                (l, None) => *l,
+                (None, r) => *r,
+            }
+        }
+    }
+
+    struct Item(Cell<u32>);
+
+    impl BasicLeaf for Item {
+        type Options = basic::Options<
+            usize,
+            false,
+            4,
+            1,
+            false,
+            true,
+            false,
+            Aggregate<Summary>,
+        >;
+
+        fn size(&self) -> usize {
+            1
+        }
+    }
+
+    impl Summarize<Summary> for Item {
+        fn summarize(&self) -> Value {
+            let value = self.0.get();
+            Some((value, value, value))
+        }
+    }
+
+    let items: Vec<_> = (0..200)
+        .map(|n| RefLeaf::new(Item(Cell::new(n * 37 % 101))))
+        .collect();
+    let mut list = SkipList::new();
+    list.push_back_from(&items);
+    let check = |list: &SkipList<&RefLeaf<Item>>| {
+        let values: Vec<_> = list.iter().map(|item| item.0.get()).collect();
+        for start in (0..=values.len()).step_by(7) {
+            for end in (start..=values.len()).step_by(5) {
+                let range = &values[start..end];
+                let expected = range
+                    .iter()
+                    .max()
+                    .map(|&max| (max, range[0], range[range.len() - 1]));
+                assert_eq!(list.query_aggregate(start..end), expected);
+            }
+        }
+    };
+    check(&list);
+
+    list.update(&items[50], || items[50].0.set(1000));
+    list.update(&items[199], || items[199].0.set(0));
+    check(&list);
+    for item in items.iter().step_by(3) {
+        list.remove(item);
+    }
+    check(&list);
+    list.insert_at(&10, &items[0]);
+    list.push_front(&items[3]);
+    list.reverse();
+    assert_eq!(list.validate(), Ok(()));
+    check(&list);
+    assert_eq!(list.query_aggregate(5..5), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde::de::value::{Error as ValueError, SeqDeserializer};
+    use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+    use serde::ser::{Serialize, Serializer};
+    use serde_test::{Token, assert_ser_tokens};
+    use skippy::basic::{ArcLeaf, LeafArena, RcLeaf};
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    struct Num(u32);
+
+    impl BasicLeaf for Num {
+        type Options = basic::Options<usize, false, 4>;
+
+        fn size(&self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    impl Serialize for Num {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize(s)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Num {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            u32::deserialize(d).map(Num)
+        }
+    }
+
+    let values: Vec<u32> = (0..100).map(|n| n * 7 % 13).collect();
+    let tokens: Vec<_> = [Token::Seq {
+        len: Some(values.len()),
+    }]
+    .into_iter()
+    .chain(values.iter().map(|&n| Token::U32(n)))
+    .chain([Token::SeqEnd])
+    .collect();
+    let seq = || SeqDeserializer::<_, ValueError>::new(values.iter().copied());
+
+    let mut list = SkipList::new();
+    list.push_back_from(values.iter().map(|&n| Rc::new(RcLeaf::new(Num(n)))));
+    assert_ser_tokens(&list, &tokens);
+
+    let list = SkipList::<Rc<RcLeaf<Num>>>::deserialize(seq()).unwrap();
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.size(), values.iter().sum::<u32>() as usize);
+    assert!(list.iter().map(|leaf| leaf.0).eq(values.iter().copied()));
+    assert_ser_tokens(&list, &tokens);
+
+    let list = SkipList::<Arc<ArcLeaf<Num>>>::deserialize(seq()).unwrap();
+    assert_eq!(list.validate(), Ok(()));
+    assert!(list.iter().map(|leaf| leaf.0).eq(values.iter().copied()));
+
+    let arena = LeafArena::<Num>::new();
+    let list = (&arena).deserialize(seq()).unwrap();
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(arena.len(), values.len());
+    assert!(list.iter().map(|leaf| leaf.0).eq(values.iter().copied()));
+    assert_ser_tokens(&list, &tokens);
+
+    let none =
+        SeqDeserializer::<_, ValueError>::new(std::iter::empty::<u32>());
+    let empty = SkipList::<Rc<RcLeaf<Num>>>::deserialize(none).unwrap();
+    assert!(empty.is_empty());
+    let bad = SeqDeserializer::<_, ValueError>::new([1, -2].into_iter());
+    assert!(SkipList::<Rc<RcLeaf<Num>>>::deserialize(bad).is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn from_par_iter() {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    use skippy::basic::{ArcLeaf, BoxLeaf};
+    use std::sync::Arc;
+
+    let data = || (0..20_000).into_par_iter().map(|n| Data::new(n, n % 3));
+    let list = SkipList::<Arc<ArcLeaf<Data>>>::from_par_iter(data());
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.len(), 20_000);
+    assert_eq!(list.size(), (0..20_000).map(|n| n % 3).sum());
+    assert!(list.iter().map(|leaf| leaf.value).eq(0..20_000));
+
+    // SAFETY: No clones of the leaves are used after they are freed.
+    let list = unsafe { SkipList::<BoxLeaf<Data>>::from_par_iter(data()) };
+    assert_eq!(list.validate(), Ok(()));
+    let values = list.into_values();
+    assert!(values.map(|data| data.value).eq(0..20_000));
+
+    let empty = SkipList::<Arc<ArcLeaf<Data>>>::from_par_iter(Vec::new());
+    assert!(empty.is_empty());
+
+    let deque: Deque<_> = (0..5_000).into_par_iter().map(|n| n * 2).collect();
+    assert_eq!(deque.len(), 5_000);
+    assert!(deque.iter().copied().eq((0..5_000).map(|n| n * 2)));
+}